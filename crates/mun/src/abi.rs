@@ -0,0 +1,389 @@
+//! Reflection of the ABI of compiled Mun libraries, which enables comparing the ABI of different
+//! builds of a package.
+
+use mun_abi::StructMemoryKind;
+use mun_runtime::{Runtime, RuntimeBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The extension of the metadata file that `build --emit-metadata` writes next to every library,
+/// e.g. `main.metadata.json` for `main.munlib`.
+pub const METADATA_EXTENSION: &str = "metadata.json";
+
+/// The ABI of a single library, as written by `build --emit-metadata` so tools can inspect a
+/// library without loading it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LibraryMetadata {
+    /// The version of the ABI that the library was compiled against
+    pub abi_version: u32,
+    #[serde(flatten)]
+    pub abi: AbiSnapshot,
+}
+
+impl LibraryMetadata {
+    /// Reflects the metadata that the compiler embedded in the library at `library_path`.
+    pub fn from_library(library_path: &Path) -> Result<Self, anyhow::Error> {
+        let runtime = RuntimeBuilder::new(library_path).set_watch(false).spawn()?;
+        let abi = AbiSnapshot::from_runtime(&runtime.borrow());
+        Ok(LibraryMetadata {
+            abi_version: mun_abi::ABI_VERSION,
+            abi,
+        })
+    }
+
+    /// Writes the metadata of the library at `library_path` to a JSON file next to it and returns
+    /// the path of that file.
+    pub fn write_for_library(library_path: &Path) -> Result<PathBuf, anyhow::Error> {
+        let metadata = Self::from_library(library_path)?;
+        let metadata_path = library_path.with_extension(METADATA_EXTENSION);
+        std::fs::write(
+            &metadata_path,
+            serde_json::to_string_pretty(&metadata)? + "\n",
+        )?;
+        Ok(metadata_path)
+    }
+}
+
+/// A snapshot of all functions and structs exported by one or more Mun libraries.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbiSnapshot {
+    pub functions: BTreeMap<String, FunctionAbi>,
+    pub structs: BTreeMap<String, StructAbi>,
+}
+
+/// The signature of an exported function.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionAbi {
+    pub arg_types: Vec<String>,
+    pub return_type: Option<String>,
+}
+
+/// The memory kind and fields of an exported struct.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructAbi {
+    pub memory_kind: String,
+    /// The names and types of the fields, in declaration order
+    pub fields: Vec<(String, String)>,
+}
+
+impl fmt::Display for FunctionAbi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({})", self.arg_types.join(", "))?;
+        if let Some(return_type) = &self.return_type {
+            write!(f, " -> {}", return_type)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for StructAbi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|(name, ty)| format!("{}: {}", name, ty))
+            .collect();
+        write!(f, "({}) {{ {} }}", self.memory_kind, fields.join(", "))
+    }
+}
+
+impl AbiSnapshot {
+    /// Reflects the ABI of all assemblies loaded by the `runtime`.
+    pub fn from_runtime(runtime: &Runtime) -> Self {
+        let mut snapshot = AbiSnapshot::default();
+        for assembly in runtime.assemblies() {
+            let symbols = &assembly.info().symbols;
+            for function in symbols.functions() {
+                let signature = &function.prototype.signature;
+                snapshot.functions.insert(
+                    function.prototype.name().to_owned(),
+                    FunctionAbi {
+                        arg_types: signature
+                            .arg_types()
+                            .iter()
+                            .map(|ty| ty.name().to_owned())
+                            .collect(),
+                        return_type: signature.return_type().map(|ty| ty.name().to_owned()),
+                    },
+                );
+            }
+            for type_info in symbols.types() {
+                if let Some(struct_info) = type_info.as_struct() {
+                    let memory_kind = match struct_info.memory_kind {
+                        StructMemoryKind::GC => "gc",
+                        StructMemoryKind::Value => "value",
+                    };
+                    snapshot.structs.insert(
+                        type_info.name().to_owned(),
+                        StructAbi {
+                            memory_kind: memory_kind.to_owned(),
+                            fields: struct_info
+                                .field_names()
+                                .zip(struct_info.field_types().iter())
+                                .map(|(name, ty)| (name.to_owned(), ty.name().to_owned()))
+                                .collect(),
+                        },
+                    );
+                }
+            }
+        }
+        snapshot
+    }
+
+    /// Loads all Mun libraries in `dir` and its subdirectories and reflects their combined ABI.
+    pub fn from_directory(dir: &Path) -> Result<Self, anyhow::Error> {
+        let mut snapshot = AbiSnapshot::default();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                snapshot.extend(Self::from_directory(&path)?);
+            } else if path
+                .extension()
+                .map_or(false, |ext| ext == crate::LIBRARY_EXTENSION)
+            {
+                let runtime = RuntimeBuilder::new(&path).set_watch(false).spawn()?;
+                let library_snapshot = Self::from_runtime(&runtime.borrow());
+                snapshot.extend(library_snapshot);
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Reads a snapshot from a JSON file.
+    pub fn from_file(path: &Path) -> Result<Self, anyhow::Error> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            anyhow::anyhow!("could not read ABI snapshot '{}': {}", path.display(), e)
+        })?;
+        serde_json::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("invalid ABI snapshot '{}': {}", path.display(), e))
+    }
+
+    /// Writes the snapshot to a JSON file.
+    pub fn write_to_file(&self, path: &Path) -> Result<(), anyhow::Error> {
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+
+    /// Adds all functions and structs of `other` to this snapshot.
+    fn extend(&mut self, other: AbiSnapshot) {
+        self.functions.extend(other.functions);
+        self.structs.extend(other.structs);
+    }
+}
+
+/// The kind of an `AbiChange`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AbiChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A single difference between two ABI snapshots.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AbiChange {
+    pub kind: AbiChangeKind,
+    /// A description of the changed item, e.g. `fn main`
+    pub item: String,
+    pub description: String,
+    /// Whether existing users of the ABI may be broken by this change
+    pub breaking: bool,
+}
+
+impl fmt::Display for AbiChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            AbiChangeKind::Added => "added",
+            AbiChangeKind::Removed => "removed",
+            AbiChangeKind::Changed => "changed",
+        };
+        write!(f, "{} {}: {}", kind, self.item, self.description)
+    }
+}
+
+/// Computes the changes required to go from the `old` to the `new` ABI. Removed or modified
+/// exports are considered breaking, additions are not.
+pub fn diff(old: &AbiSnapshot, new: &AbiSnapshot) -> Vec<AbiChange> {
+    let mut changes = Vec::new();
+    diff_items(
+        &old.functions,
+        &new.functions,
+        "fn",
+        &mut changes,
+        |_, _| true,
+    );
+    diff_items(
+        &old.structs,
+        &new.structs,
+        "struct",
+        &mut changes,
+        |old, new| {
+            // Only appending fields is backwards compatible
+            old.memory_kind != new.memory_kind || !new.fields.starts_with(&old.fields)
+        },
+    );
+    changes
+}
+
+/// Renders the `changes` as a changelog for release notes: a paragraph per kind of change that lists
+/// the affected exports, marking the ones that break existing users.
+pub fn changelog(changes: &[AbiChange]) -> String {
+    if changes.is_empty() {
+        return "The ABI is unchanged.\n".to_owned();
+    }
+
+    let mut changelog = String::new();
+    for (kind, verb) in &[
+        (AbiChangeKind::Added, "added"),
+        (AbiChangeKind::Removed, "removed"),
+        (AbiChangeKind::Changed, "changed"),
+    ] {
+        let changes: Vec<&AbiChange> = changes.iter().filter(|c| c.kind == *kind).collect();
+        if changes.is_empty() {
+            continue;
+        }
+
+        if !changelog.is_empty() {
+            changelog.push('\n');
+        }
+        let num_breaking = changes.iter().filter(|c| c.breaking).count();
+        changelog.push_str(&match (changes.len(), num_breaking) {
+            (1, 0) => format!("1 export was {}:\n", verb),
+            (1, _) => format!("1 export was {}, which breaks existing users:\n", verb),
+            (n, 0) => format!("{} exports were {}:\n", n, verb),
+            (n, b) => format!(
+                "{} exports were {}, {} of which break existing users:\n",
+                n, verb, b
+            ),
+        });
+        for change in changes {
+            changelog.push_str(&format!("  - `{}`: {}", change.item, change.description));
+            if change.breaking && change.kind == AbiChangeKind::Changed {
+                changelog.push_str(" (breaking)");
+            }
+            changelog.push('\n');
+        }
+    }
+    changelog
+}
+
+/// Computes the changes between two maps of items. `is_breaking_change` determines whether a
+/// modification of an item is breaking.
+fn diff_items<T: PartialEq + fmt::Display>(
+    old: &BTreeMap<String, T>,
+    new: &BTreeMap<String, T>,
+    item_kind: &str,
+    changes: &mut Vec<AbiChange>,
+    is_breaking_change: impl Fn(&T, &T) -> bool,
+) {
+    for (name, old_item) in old.iter() {
+        match new.get(name) {
+            None => changes.push(AbiChange {
+                kind: AbiChangeKind::Removed,
+                item: format!("{} {}", item_kind, name),
+                description: old_item.to_string(),
+                breaking: true,
+            }),
+            Some(new_item) if new_item != old_item => changes.push(AbiChange {
+                kind: AbiChangeKind::Changed,
+                item: format!("{} {}", item_kind, name),
+                description: format!("{} => {}", old_item, new_item),
+                breaking: is_breaking_change(old_item, new_item),
+            }),
+            _ => {}
+        }
+    }
+    for (name, new_item) in new.iter() {
+        if !old.contains_key(name) {
+            changes.push(AbiChange {
+                kind: AbiChangeKind::Added,
+                item: format!("{} {}", item_kind, name),
+                description: new_item.to_string(),
+                breaking: false,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{changelog, diff, AbiChangeKind, AbiSnapshot, FunctionAbi, StructAbi};
+
+    fn function(arg_types: &[&str], return_type: Option<&str>) -> FunctionAbi {
+        FunctionAbi {
+            arg_types: arg_types.iter().map(|ty| ty.to_string()).collect(),
+            return_type: return_type.map(ToOwned::to_owned),
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut old = AbiSnapshot::default();
+        old.functions
+            .insert("main".to_owned(), function(&[], Some("core::i32")));
+        old.functions
+            .insert("removed".to_owned(), function(&["core::f32"], None));
+        old.structs.insert(
+            "Foo".to_owned(),
+            StructAbi {
+                memory_kind: "gc".to_owned(),
+                fields: vec![("a".to_owned(), "core::i32".to_owned())],
+            },
+        );
+
+        let mut new = old.clone();
+        new.functions.remove("removed");
+        new.functions
+            .insert("added".to_owned(), function(&["core::bool"], None));
+        new.structs
+            .get_mut("Foo")
+            .unwrap()
+            .fields
+            .push(("b".to_owned(), "core::f64".to_owned()));
+
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 3);
+        assert!(changes
+            .iter()
+            .any(|c| c.kind == AbiChangeKind::Removed && c.item == "fn removed" && c.breaking));
+        assert!(changes
+            .iter()
+            .any(|c| c.kind == AbiChangeKind::Added && c.item == "fn added" && !c.breaking));
+        assert!(changes
+            .iter()
+            .any(|c| c.kind == AbiChangeKind::Changed && c.item == "struct Foo" && !c.breaking));
+    }
+
+    #[test]
+    fn test_changelog() {
+        let mut old = AbiSnapshot::default();
+        old.functions
+            .insert("main".to_owned(), function(&[], Some("core::i32")));
+        old.functions
+            .insert("removed".to_owned(), function(&["core::f32"], None));
+        assert_eq!(changelog(&diff(&old, &old)), "The ABI is unchanged.\n");
+
+        let mut new = old.clone();
+        new.functions.remove("removed");
+        new.functions
+            .insert("main".to_owned(), function(&[], Some("core::i64")));
+        new.functions
+            .insert("added".to_owned(), function(&["core::bool"], None));
+        new.functions
+            .insert("other".to_owned(), function(&[], None));
+        assert_eq!(
+            changelog(&diff(&old, &new)),
+            "2 exports were added:\n\
+             \x20 - `fn added`: (core::bool)\n\
+             \x20 - `fn other`: ()\n\
+             \n\
+             1 export was removed, which breaks existing users:\n\
+             \x20 - `fn removed`: (core::f32)\n\
+             \n\
+             1 export was changed, which breaks existing users:\n\
+             \x20 - `fn main`: () -> core::i32 => () -> core::i64 (breaking)\n"
+        );
+    }
+}
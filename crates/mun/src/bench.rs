@@ -0,0 +1,120 @@
+//! Benchmarking of repeated calls into a library.
+
+use crate::startup_profile::{millis, serialize_millis};
+use anyhow::anyhow;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Statistics of the wall time of repeated calls to a function.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CallStats {
+    pub iterations: usize,
+    #[serde(rename = "min_ms", serialize_with = "serialize_millis")]
+    pub min: Duration,
+    #[serde(rename = "median_ms", serialize_with = "serialize_millis")]
+    pub median: Duration,
+    #[serde(rename = "mean_ms", serialize_with = "serialize_millis")]
+    pub mean: Duration,
+    #[serde(rename = "max_ms", serialize_with = "serialize_millis")]
+    pub max: Duration,
+}
+
+impl CallStats {
+    /// Computes the statistics of the specified call times, of which there must be at least one.
+    fn from_times(mut times: Vec<Duration>) -> Self {
+        times.sort();
+        let middle = times.len() / 2;
+        let median = if times.len() % 2 == 0 {
+            (times[middle - 1] + times[middle]) / 2
+        } else {
+            times[middle]
+        };
+        CallStats {
+            iterations: times.len(),
+            min: times[0],
+            median,
+            mean: times.iter().sum::<Duration>() / times.len() as u32,
+            max: times[times.len() - 1],
+        }
+    }
+
+    /// Renders the statistics with a line per statistic.
+    pub fn to_text(&self) -> String {
+        format!(
+            "{:<12}{:>12}\n{:<12}{:>12.3} ms\n{:<12}{:>12.3} ms\n{:<12}{:>12.3} ms\n{:<12}{:>12.3} ms\n",
+            "iterations",
+            self.iterations,
+            "min",
+            millis(self.min),
+            "median",
+            millis(self.median),
+            "mean",
+            millis(self.mean),
+            "max",
+            millis(self.max)
+        )
+    }
+}
+
+/// Calls `f` `iterations` times and returns the statistics of the time each call took. Stops at
+/// the first call that fails.
+pub fn bench_calls(
+    iterations: usize,
+    mut f: impl FnMut() -> Result<(), anyhow::Error>,
+) -> Result<CallStats, anyhow::Error> {
+    if iterations == 0 {
+        return Err(anyhow!("at least one iteration is required"));
+    }
+
+    let mut times = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        f()?;
+        times.push(start.elapsed());
+    }
+    Ok(CallStats::from_times(times))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bench_calls, CallStats};
+    use std::time::Duration;
+
+    #[test]
+    fn test_stats() {
+        let stats = CallStats::from_times(vec![
+            Duration::from_millis(40),
+            Duration::from_millis(10),
+            Duration::from_millis(30),
+            Duration::from_millis(20),
+        ]);
+        assert_eq!(
+            stats,
+            CallStats {
+                iterations: 4,
+                min: Duration::from_millis(10),
+                median: Duration::from_millis(25),
+                mean: Duration::from_millis(25),
+                max: Duration::from_millis(40),
+            }
+        );
+        assert_eq!(
+            stats.to_text(),
+            "iterations             4\n\
+             min               10.000 ms\n\
+             median            25.000 ms\n\
+             mean              25.000 ms\n\
+             max               40.000 ms\n"
+        );
+
+        let mut calls = 0;
+        let stats = bench_calls(3, || {
+            calls += 1;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(calls, 3);
+        assert_eq!(stats.iterations, 3);
+        assert!(bench_calls(0, || Ok(())).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! Benchmarking of the time it takes to compile a package at every optimization level.
+
+use crate::startup_profile::{millis, serialize_millis};
+use crate::ExitStatus;
+use anyhow::anyhow;
+use mun_compiler::{Config, Driver, OptimizationLevel};
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// The optimization levels that are benchmarked, with the values of `--opt-level` they
+/// correspond to.
+const LEVELS: &[(u8, OptimizationLevel)] = &[
+    (0, OptimizationLevel::None),
+    (1, OptimizationLevel::Less),
+    (2, OptimizationLevel::Default),
+    (3, OptimizationLevel::Aggressive),
+];
+
+/// Statistics of the compile times of a package at a single optimization level.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct LevelStats {
+    pub opt_level: u8,
+    pub iterations: usize,
+    #[serde(rename = "min_ms", serialize_with = "serialize_millis")]
+    pub min: Duration,
+    #[serde(rename = "mean_ms", serialize_with = "serialize_millis")]
+    pub mean: Duration,
+    #[serde(rename = "max_ms", serialize_with = "serialize_millis")]
+    pub max: Duration,
+}
+
+impl LevelStats {
+    /// Computes the statistics of the specified compile times, of which there must be at least
+    /// one.
+    fn from_times(opt_level: u8, times: &[Duration]) -> Self {
+        LevelStats {
+            opt_level,
+            iterations: times.len(),
+            min: times.iter().min().copied().unwrap_or_default(),
+            mean: times.iter().sum::<Duration>() / times.len() as u32,
+            max: times.iter().max().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// Compiles the package at `manifest_path` from scratch `iterations` times at every optimization
+/// level and returns the statistics per level. Artifacts are written to a temporary directory.
+pub fn bench_compile(
+    manifest_path: &Path,
+    config: Config,
+    iterations: usize,
+) -> Result<Vec<LevelStats>, anyhow::Error> {
+    if iterations == 0 {
+        return Err(anyhow!("at least one iteration is required"));
+    }
+    let out_dir = tempdir::TempDir::new("mun_bench_compile")?;
+
+    let mut stats = Vec::with_capacity(LEVELS.len());
+    for (opt_level, optimization_lvl) in LEVELS {
+        let config = Config {
+            optimization_lvl: *optimization_lvl,
+            out_dir: Some(out_dir.path().to_path_buf()),
+            ..config.clone()
+        };
+
+        let mut times = Vec::with_capacity(iterations);
+        for _ in 0..iterations {
+            let start = Instant::now();
+            let (_package, mut driver) = Driver::with_package_path(manifest_path, config.clone())?;
+            if driver.emit_diagnostics(&mut std::io::stderr())? {
+                return Err(anyhow!(
+                    "the package cannot be benchmarked because it has errors"
+                ));
+            }
+            driver.write_all_assemblies()?;
+            times.push(start.elapsed());
+        }
+        log::info!("benchmarked opt-level {}", opt_level);
+        stats.push(LevelStats::from_times(*opt_level, &times));
+    }
+    Ok(stats)
+}
+
+/// Benchmarks compiling the package at `manifest_path` with `config` the specified number of
+/// `iterations` (5 by default) and prints the statistics in the specified `format` (`text` or
+/// `json`).
+pub fn print_bench_compile(
+    manifest_path: &Path,
+    config: Config,
+    iterations: Option<&str>,
+    format: &str,
+) -> Result<ExitStatus, anyhow::Error> {
+    let iterations = match iterations {
+        Some(iterations) => iterations.parse().map_err(|_| {
+            anyhow!(
+                "invalid value '{}' for --bench-compile, expected a number of iterations",
+                iterations
+            )
+        })?,
+        None => 5,
+    };
+    let stats = bench_compile(manifest_path, config, iterations)?;
+    match format {
+        "json" => println!("{}", serde_json::to_string_pretty(&stats)?),
+        _ => print!("{}", render_text(&stats)),
+    }
+    Ok(ExitStatus::Success)
+}
+
+/// Renders the statistics as a table with a row per optimization level.
+pub fn render_text(stats: &[LevelStats]) -> String {
+    let mut text = format!(
+        "{:>9}  {:>10}  {:>12}  {:>12}  {:>12}\n",
+        "opt-level", "iterations", "min (ms)", "mean (ms)", "max (ms)"
+    );
+    for level in stats {
+        text.push_str(&format!(
+            "{:>9}  {:>10}  {:>12.3}  {:>12.3}  {:>12.3}\n",
+            level.opt_level,
+            level.iterations,
+            millis(level.min),
+            millis(level.mean),
+            millis(level.max)
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_text, LevelStats};
+    use std::time::Duration;
+
+    #[test]
+    fn test_stats() {
+        let stats = LevelStats::from_times(
+            3,
+            &[
+                Duration::from_millis(30),
+                Duration::from_millis(10),
+                Duration::from_millis(20),
+            ],
+        );
+        assert_eq!(
+            stats,
+            LevelStats {
+                opt_level: 3,
+                iterations: 3,
+                min: Duration::from_millis(10),
+                mean: Duration::from_millis(20),
+                max: Duration::from_millis(30),
+            }
+        );
+        assert_eq!(
+            render_text(&[stats]),
+            "opt-level  iterations      min (ms)     mean (ms)      max (ms)\n        \
+             3           3        10.000        20.000        30.000\n"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Collects everything that is needed to reproduce a compiler bug into a single zip archive.
+
+use crate::metadata::Metadata;
+use anyhow::anyhow;
+use mun_compiler::{Config, DisplayColor, Driver};
+use mun_project::Package;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+/// The template of the description that is included in the bundle, to be filled in by the user.
+const DESCRIPTION_TEMPLATE: &str = "# Bug report
+
+## Description
+
+<!-- What did you do, what did you expect to happen, and what happened instead? -->
+
+## Steps to reproduce
+
+1. Extract this archive
+2. Run `mun build --manifest-path mun.toml`
+
+## Contents
+
+- `mun.toml` and `src/`: the sources of the package
+- `metadata.json`: the package metadata, as printed by `mun metadata`
+- `config.txt`: the resolved compiler configuration
+- `version.txt`: the version of Mun and the host
+- `diagnostics.txt`: the diagnostics emitted by the compiler
+";
+
+/// Writes a bug report bundle for the package at `manifest_path` to `output_path`. All paths in
+/// the bundle are relative to the package root, so that it does not reveal the location of the
+/// package on the reporter's machine.
+pub fn write_bug_report(
+    manifest_path: &Path,
+    config: Config,
+    output_path: &Path,
+) -> Result<(), anyhow::Error> {
+    let package = Package::from_file(manifest_path)?;
+    let root = package.root().to_path_buf();
+    let relativize = |text: &str| text.replace(&root.display().to_string(), "<package>");
+
+    let file = File::create(output_path)
+        .map_err(|e| anyhow!("could not create '{}': {}", output_path.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = FileOptions::default();
+
+    // The package sources
+    zip.start_file("mun.toml", options)?;
+    zip.write_all(&std::fs::read(manifest_path)?)?;
+    if let Some(source_dir) = package.source_directory() {
+        for source_path in mun_compiler::iter_package_source_files(package.root(), &source_dir)? {
+            let relative_path = source_path
+                .strip_prefix(&root)
+                .expect("source files must be part of the package");
+            zip.start_file(archive_path(relative_path), options)?;
+            zip.write_all(&std::fs::read(&source_path)?)?;
+        }
+    }
+
+    // The package metadata, with all paths relative to the package root
+    let metadata = Metadata {
+        manifest_path: PathBuf::from("mun.toml"),
+        root: PathBuf::from("."),
+        source_directory: package.source_directory().map(|_| PathBuf::from("src")),
+        ..Metadata::from(&package)
+    };
+    zip.start_file("metadata.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(&metadata)?.as_bytes())?;
+
+    zip.start_file("config.txt", options)?;
+    write!(
+        zip,
+        "target: {}\noptimization level: {:?}\ndata layout: {}\n",
+        config.target.llvm_target,
+        config.optimization_lvl,
+        config
+            .data_layout
+            .as_deref()
+            .unwrap_or(&config.target.data_layout)
+    )?;
+
+    zip.start_file("version.txt", options)?;
+    write!(
+        zip,
+        "mun {}\nhost: {} {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )?;
+
+    // The diagnostics, without colors so they are readable in a text editor
+    let config = Config {
+        display_color: DisplayColor::Disable,
+        ..config
+    };
+    let (_, driver) = Driver::with_package_path(manifest_path, config)?;
+    let mut diagnostics = Vec::new();
+    driver.emit_diagnostics(&mut diagnostics)?;
+    zip.start_file("diagnostics.txt", options)?;
+    zip.write_all(relativize(&String::from_utf8_lossy(&diagnostics)).as_bytes())?;
+
+    zip.start_file("DESCRIPTION.md", options)?;
+    zip.write_all(DESCRIPTION_TEMPLATE.as_bytes())?;
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// Converts a relative path to a path inside the archive, which always uses forward slashes.
+fn archive_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,236 @@
+//! A record of the toolchain, configuration and sources that were used for a build, which can be
+//! used to verify that a later build is reproduced exactly.
+
+use crate::abi::{self, AbiSnapshot};
+use anyhow::anyhow;
+use mun_compiler::Config;
+use mun_project::Package;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The name of the file that a build record is written to in the output directory.
+pub const FILENAME: &str = "build-record.json";
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct BuildRecord {
+    /// The version of the Mun compiler
+    pub compiler_version: String,
+    /// The version of LLVM that the compiler uses
+    pub llvm_version: String,
+    /// The target triple that was compiled for
+    pub target: String,
+    /// The optimization level that was used
+    pub opt_level: String,
+    /// The MD5 hash of every source file, keyed by its path relative to the source directory
+    pub sources: BTreeMap<String, String>,
+    /// The values of the environment variables that were captured for provenance, or `None` for
+    /// variables that were not set. These are not compared when verifying a build.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub environment: BTreeMap<String, Option<String>>,
+    /// The ABI of the libraries that were built, used to describe the changes to the ABI since
+    /// this build. This is not compared when verifying a build.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abi: Option<AbiSnapshot>,
+}
+
+impl BuildRecord {
+    /// Constructs the record of building the package at `manifest_path` with `config`.
+    pub fn new(manifest_path: &Path, config: &Config) -> Result<Self, anyhow::Error> {
+        let package = Package::from_file(manifest_path)?;
+        let source_dir = package
+            .source_directory()
+            .ok_or_else(|| anyhow!("the source directory of package {} does not exist", package))?;
+
+        let mut sources = BTreeMap::new();
+        for source_path in mun_compiler::iter_package_source_files(package.root(), &source_dir)? {
+            let relative_path =
+                mun_compiler::compute_source_relative_path(&source_dir, &source_path)?;
+            let contents = std::fs::read(&source_path)?;
+            sources.insert(
+                relative_path.as_str().to_owned(),
+                format!("{:x}", md5::compute(contents)),
+            );
+        }
+
+        Ok(BuildRecord {
+            compiler_version: env!("CARGO_PKG_VERSION").to_owned(),
+            llvm_version: mun_compiler::LLVM_VERSION.to_owned(),
+            target: config.target.llvm_target.clone(),
+            opt_level: format!("{:?}", config.optimization_lvl),
+            sources,
+            environment: BTreeMap::new(),
+            abi: None,
+        })
+    }
+
+    /// Captures the current values of the environment variables with the specified `names`.
+    pub fn with_environment(mut self, names: &[&str]) -> Self {
+        for name in names {
+            self.environment
+                .insert((*name).to_owned(), std::env::var(name).ok());
+        }
+        self
+    }
+
+    /// Records the ABI of the libraries that were built.
+    pub fn with_abi(self, abi: AbiSnapshot) -> Self {
+        Self {
+            abi: Some(abi),
+            ..self
+        }
+    }
+
+    /// Reads a record from a JSON file.
+    pub fn from_file(path: &Path) -> Result<Self, anyhow::Error> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("could not read build record '{}': {}", path.display(), e))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| anyhow!("invalid build record '{}': {}", path.display(), e))
+    }
+
+    /// Writes the record to a JSON file.
+    pub fn write_to_file(&self, path: &Path) -> Result<(), anyhow::Error> {
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+
+    /// Returns a description of every difference between this record and `other`.
+    pub fn diff(&self, other: &BuildRecord) -> Vec<String> {
+        let mut differences = Vec::new();
+        let mut compare = |what: &str, expected: &str, actual: &str| {
+            if expected != actual {
+                differences.push(format!("{}: expected {}, found {}", what, expected, actual));
+            }
+        };
+        compare(
+            "compiler version",
+            &self.compiler_version,
+            &other.compiler_version,
+        );
+        compare("LLVM version", &self.llvm_version, &other.llvm_version);
+        compare("target", &self.target, &other.target);
+        compare("optimization level", &self.opt_level, &other.opt_level);
+
+        for (path, hash) in &self.sources {
+            match other.sources.get(path) {
+                None => differences.push(format!("source '{}' is missing", path)),
+                Some(other_hash) if other_hash != hash => {
+                    differences.push(format!("source '{}' has changed", path))
+                }
+                _ => {}
+            }
+        }
+        for path in other.sources.keys() {
+            if !self.sources.contains_key(path) {
+                differences.push(format!("source '{}' was added", path));
+            }
+        }
+        differences
+    }
+}
+
+/// Prints the changes between the ABI in the build record at `record_path` and the ABI of the
+/// libraries in `out_dir`.
+pub fn print_abi_changelog(record_path: &Path, out_dir: &Path) -> Result<(), anyhow::Error> {
+    let previous = BuildRecord::from_file(record_path)?.abi.ok_or_else(|| {
+        anyhow!(
+            "build record '{}' does not contain an ABI, it was written by an older version of mun",
+            record_path.display()
+        )
+    })?;
+    let current = AbiSnapshot::from_directory(out_dir)?;
+    print!("{}", abi::changelog(&abi::diff(&previous, &current)));
+    Ok(())
+}
+
+/// Verifies that building the package at `manifest_path` with `config` matches the build record
+/// at `record_path`, printing every difference. Returns true if the build matches.
+pub fn verify(
+    manifest_path: &Path,
+    config: &Config,
+    record_path: &Path,
+) -> Result<bool, anyhow::Error> {
+    let expected = BuildRecord::from_file(record_path)?;
+    let actual = BuildRecord::new(manifest_path, config)?;
+
+    let differences = expected.diff(&actual);
+    if differences.is_empty() {
+        println!("Build matches record {}", record_path.display());
+        return Ok(true);
+    }
+
+    eprintln!(
+        "error: build does not match record {}:",
+        record_path.display()
+    );
+    for difference in differences {
+        eprintln!("    {}", difference);
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BuildRecord;
+
+    fn record(sources: &[(&str, &str)]) -> BuildRecord {
+        BuildRecord {
+            compiler_version: "0.2.0".to_owned(),
+            llvm_version: "7.0.1".to_owned(),
+            target: "x86_64-unknown-linux-gnu".to_owned(),
+            opt_level: "Default".to_owned(),
+            sources: sources
+                .iter()
+                .map(|(path, hash)| ((*path).to_owned(), (*hash).to_owned()))
+                .collect(),
+            environment: Default::default(),
+            abi: None,
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let expected = record(&[("main.mun", "a"), ("foo.mun", "b")]);
+        assert!(expected.diff(&expected).is_empty());
+
+        let actual = BuildRecord {
+            opt_level: "Aggressive".to_owned(),
+            ..record(&[("main.mun", "c"), ("bar.mun", "d")])
+        };
+        assert_eq!(
+            expected.diff(&actual),
+            vec![
+                "optimization level: expected Default, found Aggressive".to_owned(),
+                "source 'foo.mun' is missing".to_owned(),
+                "source 'main.mun' has changed".to_owned(),
+                "source 'bar.mun' was added".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_with_environment() {
+        std::env::set_var("MUN_TEST_CAPTURED", "abc123");
+        std::env::remove_var("MUN_TEST_MISSING");
+        let captured = record(&[]).with_environment(&["MUN_TEST_CAPTURED", "MUN_TEST_MISSING"]);
+        assert_eq!(
+            serde_json::to_value(&captured.environment).unwrap(),
+            serde_json::json!({ "MUN_TEST_CAPTURED": "abc123", "MUN_TEST_MISSING": null })
+        );
+
+        // Captured variables do not affect verification
+        assert!(record(&[]).diff(&captured).is_empty());
+    }
+
+    #[test]
+    fn test_abi_is_optional() {
+        let mut value = serde_json::to_value(&record(&[])).unwrap();
+        assert!(value.get("abi").is_none());
+
+        value["abi"] = serde_json::json!({ "functions": {}, "structs": {} });
+        let with_abi: BuildRecord = serde_json::from_value(value).unwrap();
+        assert_eq!(with_abi.abi, Some(Default::default()));
+        assert!(record(&[]).diff(&with_abi).is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+//! Removal of the artifacts that `mun build` writes to an output directory.
+
+use std::path::{Path, PathBuf};
+
+/// The extensions of generated libraries and of the assembly emitted next to them.
+const ARTIFACT_EXTENSIONS: &[&str] = &[crate::LIBRARY_EXTENSION, crate::WASM_EXTENSION, "ll", "s"];
+
+/// Returns whether the file at `path` was generated by a build.
+pub fn is_artifact(path: &Path) -> bool {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    if ARTIFACT_EXTENSIONS.contains(&extension) {
+        return true;
+    }
+
+    let is_build_record = path
+        .file_name()
+        .map_or(false, |name| name == crate::build_record::FILENAME);
+    let is_size_report = path
+        .file_stem()
+        .map_or(false, |stem| stem == crate::size_report::FILE_STEM)
+        && (extension == "txt" || extension == "json");
+    let is_metadata = path.to_str().map_or(false, |path| {
+        path.ends_with(&format!(".{}", crate::abi::METADATA_EXTENSION))
+    });
+    is_build_record || is_size_report || is_metadata
+}
+
+/// Recursively collects all artifacts in `dir`, sorted by path. Returns no artifacts if `dir` does
+/// not exist.
+pub fn artifacts(dir: &Path) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let mut artifacts = Vec::new();
+    if !dir.is_dir() {
+        return Ok(artifacts);
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            artifacts.extend(self::artifacts(&path)?);
+        } else if is_artifact(&path) {
+            artifacts.push(path);
+        }
+    }
+    artifacts.sort();
+    Ok(artifacts)
+}
+
+/// Removes all artifacts in `dir`, or only prints them if `dry_run` is set. Other files, such as
+/// sources in an output directory that is shared with the package, are left untouched.
+pub fn clean(dir: &Path, dry_run: bool) -> Result<(), anyhow::Error> {
+    let artifacts = artifacts(dir)?;
+    for artifact in artifacts.iter() {
+        if dry_run {
+            println!("Would remove {}", artifact.display());
+        } else {
+            std::fs::remove_file(artifact)
+                .map_err(|e| anyhow::anyhow!("could not remove '{}': {}", artifact.display(), e))?;
+        }
+    }
+    if !dry_run {
+        println!("Removed {} file(s)", artifacts.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_artifact;
+    use std::path::Path;
+
+    #[test]
+    fn test_is_artifact() {
+        assert!(is_artifact(Path::new("target/main.munlib")));
+        assert!(is_artifact(Path::new("target/physics/world.ll")));
+        assert!(is_artifact(Path::new("target/main.s")));
+        assert!(is_artifact(Path::new("target/main.wasm")));
+        assert!(is_artifact(Path::new("target/build-record.json")));
+        assert!(is_artifact(Path::new("target/size-report.txt")));
+        assert!(is_artifact(Path::new("target/main.metadata.json")));
+        assert!(!is_artifact(Path::new("target/main.mun")));
+        assert!(!is_artifact(Path::new("target/mun.toml")));
+        assert!(!is_artifact(Path::new("target/abi.json")));
+    }
+}
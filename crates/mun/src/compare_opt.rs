@@ -0,0 +1,141 @@
+//! Comparing the size of the libraries of a package when it is built at several optimization
+//! levels.
+
+use crate::ExitStatus;
+use anyhow::anyhow;
+use mun_compiler::Config;
+use mun_project::Package;
+use std::io::Write;
+use std::path::Path;
+
+/// Builds the package once for every optimization level in the comma-separated list of `levels`,
+/// each into its own output directory, and writes the total artifact size of every build to `out`.
+pub fn compare_opt_levels(
+    manifest_path: &Path,
+    config: Config,
+    levels: &str,
+    out: &mut dyn Write,
+) -> Result<ExitStatus, anyhow::Error> {
+    let levels: Vec<&str> = levels.split(',').map(str::trim).collect();
+    if levels.len() < 2 {
+        return Err(anyhow!(
+            "--compare-opt requires at least two comma-separated optimization levels"
+        ));
+    }
+
+    let package = Package::from_file(manifest_path)?;
+    let target_dir = config
+        .out_dir
+        .clone()
+        .unwrap_or_else(|| package.root().join("target"));
+
+    let mut sizes = Vec::with_capacity(levels.len());
+    for level in levels {
+        let out_dir = target_dir.join(format!("opt-{}", level));
+        let config = Config {
+            optimization_lvl: crate::parse_opt_level(Some(level))?,
+            out_dir: Some(out_dir.clone()),
+            ..config.clone()
+        };
+
+        log::info!(
+            "building with opt-level {} into {}",
+            level,
+            out_dir.display()
+        );
+        if !mun_compiler::compile_manifest(manifest_path, config)? {
+            return Ok(ExitStatus::Error);
+        }
+        sizes.push((level, artifacts_size(&out_dir)?));
+    }
+
+    let baseline = sizes[0].1;
+    writeln!(
+        out,
+        "{:>9}  {:>12}  {:>8}",
+        "opt-level", "size (bytes)", "delta"
+    )?;
+    for (level, size) in sizes {
+        let delta = if baseline == 0 {
+            0.0
+        } else {
+            (size as f64 - baseline as f64) / baseline as f64 * 100.0
+        };
+        writeln!(out, "{:>9}  {:>12}  {:>+7.1}%", level, size, delta)?;
+    }
+
+    Ok(ExitStatus::Success)
+}
+
+/// Returns the combined size in bytes of all Mun libraries in `dir` and its subdirectories.
+fn artifacts_size(dir: &Path) -> Result<u64, anyhow::Error> {
+    let mut size = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            size += artifacts_size(&path)?;
+        } else if path
+            .extension()
+            .map_or(false, |ext| ext == crate::LIBRARY_EXTENSION)
+        {
+            size += path.metadata()?.len();
+        }
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::compare_opt_levels;
+    use crate::ExitStatus;
+    use mun_project::MANIFEST_FILENAME;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_compare_opt_levels() {
+        let dir = TempDir::new("test_compare_opt_levels").unwrap();
+        std::fs::write(
+            dir.path().join(MANIFEST_FILENAME),
+            "[package]\nname=\"test\"\nversion=\"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/main.mun"),
+            "pub fn main() -> i32 { 1 + 2 }",
+        )
+        .unwrap();
+        let manifest_path = dir.path().join(MANIFEST_FILENAME);
+
+        let mut out = Vec::new();
+        assert_eq!(
+            compare_opt_levels(
+                &manifest_path,
+                mun_compiler::Config::default(),
+                "0, 2",
+                &mut out
+            )
+            .unwrap(),
+            ExitStatus::Success
+        );
+        let table = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", table);
+        assert_eq!(lines[0], "opt-level  size (bytes)     delta");
+        assert!(lines[1].trim_start().starts_with("0 "), "{}", table);
+        assert!(lines[1].ends_with("+0.0%"), "{}", table);
+        assert!(lines[2].trim_start().starts_with("2 "), "{}", table);
+        for level in &["0", "2"] {
+            let out_dir = dir.path().join("target").join(format!("opt-{}", level));
+            assert!(out_dir.join("main.munlib").is_file());
+        }
+
+        assert!(compare_opt_levels(
+            &manifest_path,
+            mun_compiler::Config::default(),
+            "2",
+            &mut Vec::new()
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,84 @@
+//! Confirmation of destructive operations, such as removing files.
+
+use anyhow::anyhow;
+use std::io::{BufRead, Write};
+
+/// Asks the user on stderr whether to continue with an operation described by `prompt`, and reads
+/// the answer from stdin. Returns `true` right away if `assume_yes` is set, e.g. by `--yes`.
+///
+/// Fails if stdin is not a terminal, as there is nobody to answer the question; scripts have to
+/// pass `--yes` instead.
+pub fn confirm(prompt: &str, assume_yes: bool) -> Result<bool, anyhow::Error> {
+    let stdin = std::io::stdin();
+    confirm_with(
+        prompt,
+        assume_yes,
+        atty::is(atty::Stream::Stdin),
+        &mut stdin.lock(),
+        &mut std::io::stderr(),
+    )
+}
+
+/// Asks whether to continue like [`confirm`], by writing `prompt` to `output` and reading the
+/// answer from `input`. Only `y` and `yes` continue; any other answer aborts.
+pub fn confirm_with(
+    prompt: &str,
+    assume_yes: bool,
+    is_terminal: bool,
+    input: &mut dyn BufRead,
+    output: &mut dyn Write,
+) -> Result<bool, anyhow::Error> {
+    if assume_yes {
+        return Ok(true);
+    }
+    if !is_terminal {
+        return Err(anyhow!(
+            "cannot ask for confirmation because stdin is not a terminal: {}\n\
+             note: use --yes to continue without confirmation",
+            prompt
+        ));
+    }
+
+    write!(output, "{} [y/N] ", prompt)?;
+    output.flush()?;
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    let answer = answer.trim().to_lowercase();
+    Ok(answer == "y" || answer == "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::confirm_with;
+
+    #[test]
+    fn test_confirm_with() {
+        let confirm = |assume_yes, is_terminal, input: &str| {
+            let mut output = Vec::new();
+            let result = confirm_with(
+                "This will delete 2 files, continue?",
+                assume_yes,
+                is_terminal,
+                &mut input.as_bytes(),
+                &mut output,
+            );
+            (result, String::from_utf8(output).unwrap())
+        };
+
+        let (result, output) = confirm(false, true, "y\n");
+        assert!(result.unwrap());
+        assert_eq!(output, "This will delete 2 files, continue? [y/N] ");
+        assert!(confirm(false, true, "YES\n").0.unwrap());
+        assert!(!confirm(false, true, "\n").0.unwrap());
+        assert!(!confirm(false, true, "no\n").0.unwrap());
+        assert!(!confirm(false, true, "").0.unwrap());
+
+        // Without a terminal the operation is aborted, unless it is confirmed up front
+        let (result, output) = confirm(false, false, "y\n");
+        assert!(result.is_err());
+        assert!(output.is_empty());
+        let (result, output) = confirm(true, false, "");
+        assert!(result.unwrap());
+        assert!(output.is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+//! A minimal line-based TCP protocol that allows other processes, like `mun build --notify`, to
+//! control a running `mun start` instance.
+
+use anyhow::anyhow;
+use mun_runtime::Runtime;
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+
+/// The command that instructs a running instance to reload its libraries
+const RELOAD_COMMAND: &str = "reload";
+
+/// What to do when handling a `reload` command fails, e.g. because the entry point was invoked
+/// while a library was being replaced.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OnInvokeError {
+    /// Try once more before reporting the error and continuing
+    Retry,
+    /// Report the error and continue listening for commands
+    Skip,
+    /// Report the error and stop listening for commands
+    Abort,
+}
+
+impl FromStr for OnInvokeError {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "retry" => Ok(OnInvokeError::Retry),
+            "skip" => Ok(OnInvokeError::Skip),
+            "abort" => Ok(OnInvokeError::Abort),
+            _ => Err(anyhow!("unknown invocation error policy '{}'", s)),
+        }
+    }
+}
+
+/// Listens for commands on `address` until the process is terminated. Every `reload` command that
+/// is received reloads all libraries of the `runtime` with [`Runtime::reload`] and then invokes
+/// `on_reloaded`, the result of which is reported back to the client. Failures are handled
+/// according to `on_error`; if it is `Abort`, the error is also returned.
+pub fn serve<F>(
+    address: &str,
+    on_error: OnInvokeError,
+    runtime: &RefCell<Runtime>,
+    mut on_reloaded: F,
+) -> Result<(), anyhow::Error>
+where
+    F: FnMut() -> Result<(), anyhow::Error>,
+{
+    let mut on_reload = || {
+        runtime.borrow_mut().reload()?;
+        on_reloaded()
+    };
+    let listener = TcpListener::bind(address)
+        .map_err(|e| anyhow!("could not bind control socket '{}': {}", address, e))?;
+    println!(
+        "Listening for control commands on {}",
+        listener.local_addr()?
+    );
+
+    for stream in listener.incoming() {
+        let mut failure = None;
+        let mut reload = || {
+            reload_with_policy(on_error, &mut on_reload).map_err(|e| {
+                failure = Some(e.to_string());
+                e
+            })
+        };
+        if let Err(e) = stream
+            .map_err(Into::into)
+            .and_then(|stream| handle_connection(stream, &mut reload))
+        {
+            log::warn!("error while handling control connection: {}", e);
+        }
+        if let (Some(failure), OnInvokeError::Abort) = (failure, on_error) {
+            return Err(anyhow!("stopped after a failed reload: {}", failure));
+        }
+    }
+    Ok(())
+}
+
+/// Calls `on_reload`, calling it a second time if it fails and `on_error` is `Retry`.
+fn reload_with_policy<F>(on_error: OnInvokeError, on_reload: &mut F) -> Result<(), anyhow::Error>
+where
+    F: FnMut() -> Result<(), anyhow::Error>,
+{
+    match on_reload() {
+        Err(e) if on_error == OnInvokeError::Retry => {
+            log::warn!("retrying failed reload: {}", e);
+            on_reload()
+        }
+        result => result,
+    }
+}
+
+/// Reads a single command from the `stream` and writes back the response.
+fn handle_connection<F>(mut stream: TcpStream, on_reload: &mut F) -> Result<(), anyhow::Error>
+where
+    F: FnMut() -> Result<(), anyhow::Error>,
+{
+    let mut command = String::new();
+    BufReader::new(&stream).read_line(&mut command)?;
+
+    let response = match command.trim() {
+        RELOAD_COMMAND => match on_reload() {
+            Ok(()) => "ok".to_owned(),
+            Err(e) => format!("error: {}", e),
+        },
+        command => format!("error: unknown command '{}'", command),
+    };
+    writeln!(stream, "{}", response)?;
+    Ok(())
+}
+
+/// Sends a `reload` command to the instance listening on `address` and waits for it to respond.
+pub fn notify(address: &str) -> Result<(), anyhow::Error> {
+    let mut stream = TcpStream::connect(address)
+        .map_err(|e| anyhow!("could not connect to control socket '{}': {}", address, e))?;
+    writeln!(stream, "{}", RELOAD_COMMAND)?;
+
+    let mut response = String::new();
+    BufReader::new(&stream).read_line(&mut response)?;
+    match response.trim() {
+        "ok" => Ok(()),
+        response => Err(anyhow!(
+            "failed to reload running instance: {}",
+            response.trim_start_matches("error: ")
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reload_with_policy, OnInvokeError};
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_reload_with_policy() {
+        let fail_once = |calls: &mut usize| {
+            *calls += 1;
+            if *calls == 1 {
+                Err(anyhow!("reloaded mid-invocation"))
+            } else {
+                Ok(())
+            }
+        };
+
+        let mut calls = 0;
+        assert!(reload_with_policy(OnInvokeError::Retry, &mut || fail_once(&mut calls)).is_ok());
+        assert_eq!(calls, 2);
+
+        for on_error in &[OnInvokeError::Skip, OnInvokeError::Abort] {
+            let mut calls = 0;
+            assert!(reload_with_policy(*on_error, &mut || fail_once(&mut calls)).is_err());
+            assert_eq!(calls, 1);
+        }
+    }
+}
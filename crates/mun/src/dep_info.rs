@@ -0,0 +1,96 @@
+//! Dependency files in Makefile syntax, which let build systems like Make or Ninja determine when
+//! the libraries of a package have to be rebuilt.
+
+use mun_compiler::Driver;
+use mun_project::Package;
+use std::path::{Path, PathBuf};
+
+/// A library and the files it is compiled from.
+#[derive(Debug, PartialEq)]
+pub struct DepRule {
+    /// The path of the library
+    pub target: PathBuf,
+    /// The paths of the source file and the manifest the library is compiled from
+    pub dependencies: Vec<PathBuf>,
+}
+
+/// Determines the source files of every library of `package`, as compiled by `driver`. Every
+/// library also depends on the manifest, which configures the build.
+pub fn dep_rules(package: &Package, driver: &Driver) -> Result<Vec<DepRule>, anyhow::Error> {
+    let source_directory = package
+        .source_directory()
+        .ok_or_else(|| anyhow::anyhow!("the source directory does not exist"))?;
+
+    let mut rules: Vec<DepRule> = driver
+        .source_assembly_paths()
+        .into_iter()
+        .map(|(source_path, assembly_path)| DepRule {
+            target: assembly_path,
+            dependencies: vec![
+                source_path.to_path(&source_directory),
+                package.manifest_path().to_path_buf(),
+            ],
+        })
+        .collect();
+    rules.sort_by(|a, b| a.target.cmp(&b.target));
+    Ok(rules)
+}
+
+/// Renders the rules in Makefile syntax, one line per library.
+pub fn render(rules: &[DepRule]) -> String {
+    let mut dep_info = String::new();
+    for rule in rules {
+        dep_info.push_str(&escape(&rule.target));
+        dep_info.push(':');
+        for dependency in rule.dependencies.iter() {
+            dep_info.push(' ');
+            dep_info.push_str(&escape(dependency));
+        }
+        dep_info.push('\n');
+    }
+    dep_info
+}
+
+/// Escapes the spaces in `path`, which separate the paths of a rule.
+fn escape(path: &Path) -> String {
+    path.display().to_string().replace(' ', "\\ ")
+}
+
+/// Writes the dependency file of `package`, as compiled by `driver`, to `<package name>.d` in
+/// `out_dir`. Returns the path of the dependency file.
+pub fn write_dep_info(
+    package: &Package,
+    driver: &Driver,
+    out_dir: &Path,
+) -> Result<PathBuf, anyhow::Error> {
+    let rules = dep_rules(package, driver)?;
+
+    let dep_info_path = out_dir.join(format!("{}.d", package.name()));
+    std::fs::write(&dep_info_path, render(&rules))?;
+    Ok(dep_info_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, DepRule};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_render() {
+        let rules = vec![
+            DepRule {
+                target: PathBuf::from("target/main.munlib"),
+                dependencies: vec![PathBuf::from("src/main.mun"), PathBuf::from("mun.toml")],
+            },
+            DepRule {
+                target: PathBuf::from("target/my physics.munlib"),
+                dependencies: vec![PathBuf::from("src/my physics.mun")],
+            },
+        ];
+        assert_eq!(
+            render(&rules),
+            "target/main.munlib: src/main.mun mun.toml\n\
+             target/my\\ physics.munlib: src/my\\ physics.mun\n"
+        );
+    }
+}
@@ -0,0 +1,201 @@
+//! Structured representations of the diagnostics emitted by the compiler. These are used to
+//! serialize diagnostics into machine-readable formats.
+
+mod github;
+mod json;
+mod sarif;
+
+pub use self::github::write_github;
+pub use self::json::write_json;
+pub use self::sarif::write_sarif;
+
+use mun_compiler::{AnnotationType, Snippet};
+use std::collections::BTreeMap;
+
+/// The severity of a diagnostic.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    /// Returns the lowercase name of the severity.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        }
+    }
+}
+
+impl From<&AnnotationType> for Severity {
+    fn from(annotation_type: &AnnotationType) -> Self {
+        match annotation_type {
+            AnnotationType::Error => Severity::Error,
+            AnnotationType::Warning => Severity::Warning,
+            _ => Severity::Note,
+        }
+    }
+}
+
+/// A region in a source file. Lines and columns are 1-based.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    /// The path of the file relative to the source directory of the package
+    pub path: String,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+/// A single diagnostic message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// An optional code that identifies the kind of diagnostic
+    pub code: Option<String>,
+    pub message: String,
+    /// The primary location of the diagnostic, if it refers to source code
+    pub location: Option<Location>,
+    /// Additional labels attached to the source code
+    pub labels: Vec<String>,
+}
+
+impl Diagnostic {
+    /// Returns true if this diagnostic represents an error.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl From<&Snippet> for Diagnostic {
+    fn from(snippet: &Snippet) -> Self {
+        let first_annotation = snippet
+            .slices
+            .iter()
+            .flat_map(|slice| slice.annotations.iter().map(move |a| (slice, a)))
+            .next();
+
+        let severity = snippet
+            .title
+            .as_ref()
+            .map(|title| Severity::from(&title.annotation_type))
+            .or_else(|| first_annotation.map(|(_, a)| Severity::from(&a.annotation_type)))
+            .unwrap_or(Severity::Note);
+
+        let message = snippet
+            .title
+            .as_ref()
+            .and_then(|title| title.label.clone())
+            .or_else(|| first_annotation.map(|(_, a)| a.label.clone()))
+            .unwrap_or_default();
+
+        let location = first_annotation.and_then(|(slice, annotation)| {
+            let path = slice.origin.clone()?;
+            let (start_line, start_column) =
+                position(&slice.source, slice.line_start, annotation.range.0);
+            let (end_line, end_column) =
+                position(&slice.source, slice.line_start, annotation.range.1);
+            Some(Location {
+                path,
+                start_line,
+                start_column,
+                end_line,
+                end_column,
+            })
+        });
+
+        let labels = snippet
+            .slices
+            .iter()
+            .flat_map(|slice| slice.annotations.iter())
+            .map(|annotation| annotation.label.clone())
+            .chain(snippet.footer.iter().filter_map(|f| f.label.clone()))
+            .filter(|label| !label.is_empty() && *label != message)
+            .collect();
+
+        Diagnostic {
+            severity,
+            code: snippet.title.as_ref().and_then(|title| title.id.clone()),
+            message,
+            location,
+            labels,
+        }
+    }
+}
+
+/// Converts all snippets into structured diagnostics.
+pub fn from_snippets(snippets: &[Snippet]) -> Vec<Diagnostic> {
+    snippets.iter().map(Diagnostic::from).collect()
+}
+
+/// Emits all diagnostics in a human-readable format, grouped by the file they refer to and sorted
+/// by line.
+pub fn emit_grouped_by_file(
+    writer: &mut dyn std::io::Write,
+    snippets: &[Snippet],
+    colors: bool,
+) -> Result<(), anyhow::Error> {
+    let mut files: BTreeMap<Option<&str>, Vec<&Snippet>> = BTreeMap::new();
+    for snippet in snippets {
+        let origin = snippet
+            .slices
+            .first()
+            .and_then(|slice| slice.origin.as_deref());
+        files.entry(origin).or_default().push(snippet);
+    }
+
+    for (origin, mut snippets) in files {
+        snippets.sort_by_key(|snippet| snippet.slices.first().map_or(0, |slice| slice.line_start));
+        writeln!(
+            writer,
+            "--> {} ({} diagnostic(s))",
+            origin.unwrap_or("<unknown>"),
+            snippets.len()
+        )?;
+        mun_compiler::diagnostics::emit_diagnostics(writer, snippets, colors)?;
+    }
+    Ok(())
+}
+
+/// Converts an annotation offset within the source of a slice into a line and column.
+///
+/// Annotation offsets count an additional character for every line break that precedes them (see
+/// `mun_compiler::SliceBuilder::build`), which is accounted for here.
+fn position(source: &str, line_start: usize, offset: usize) -> (usize, usize) {
+    let mut line = line_start;
+    let mut column = 1;
+    let mut counter = 0;
+    for c in source.chars() {
+        if counter >= offset {
+            break;
+        }
+        if c == '\n' {
+            line += 1;
+            column = 1;
+            counter += 2;
+        } else {
+            column += 1;
+            counter += 1;
+        }
+    }
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::position;
+
+    #[test]
+    fn test_position() {
+        let source = "fn foo() {\n    bar\n}";
+        assert_eq!(position(source, 3, 0), (3, 1));
+        assert_eq!(position(source, 3, 3), (3, 4));
+        // `bar` starts at character 15, offset by one for the preceding line break
+        assert_eq!(position(source, 3, 16), (4, 5));
+    }
+}
@@ -0,0 +1,115 @@
+//! Serialization of diagnostics to [GitHub Actions workflow commands], which GitHub displays as
+//! annotations on the lines they refer to.
+//!
+//! [GitHub Actions workflow commands]: https://docs.github.com/en/actions/reference/workflow-commands-for-github-actions
+
+use super::{Diagnostic, Severity};
+use std::path::Path;
+
+/// Writes every diagnostic as a workflow command to the given stream. File paths are made relative
+/// to `base_dir` (usually the root of the repository) if possible.
+pub fn write_github(
+    writer: &mut dyn std::io::Write,
+    diagnostics: &[Diagnostic],
+    source_dir: &Path,
+    base_dir: &Path,
+) -> Result<(), anyhow::Error> {
+    for diagnostic in diagnostics {
+        writeln!(writer, "{}", command(diagnostic, source_dir, base_dir))?;
+    }
+    Ok(())
+}
+
+/// Constructs the workflow command for a single diagnostic.
+fn command(diagnostic: &Diagnostic, source_dir: &Path, base_dir: &Path) -> String {
+    let name = match diagnostic.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Note => "notice",
+    };
+
+    let mut properties = Vec::new();
+    if let Some(location) = &diagnostic.location {
+        let path = source_dir.join(&location.path);
+        let path = path.strip_prefix(base_dir).unwrap_or(&path);
+        let path = path.display().to_string().replace('\\', "/");
+        properties.push(format!("file={}", escape_property(&path)));
+        properties.push(format!("line={}", location.start_line));
+        properties.push(format!("col={}", location.start_column));
+        properties.push(format!("endLine={}", location.end_line));
+        properties.push(format!("endColumn={}", location.end_column));
+    }
+    if let Some(code) = &diagnostic.code {
+        properties.push(format!("title={}", escape_property(code)));
+    }
+
+    let mut message = diagnostic.message.clone();
+    for label in diagnostic.labels.iter() {
+        message.push('\n');
+        message.push_str(label);
+    }
+
+    if properties.is_empty() {
+        format!("::{}::{}", name, escape_data(&message))
+    } else {
+        format!(
+            "::{} {}::{}",
+            name,
+            properties.join(","),
+            escape_data(&message)
+        )
+    }
+}
+
+/// Escapes the message of a workflow command.
+fn escape_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes the value of a property of a workflow command.
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::command;
+    use crate::diagnostics::{Diagnostic, Location, Severity};
+    use std::path::Path;
+
+    #[test]
+    fn test_command() {
+        let diagnostic = Diagnostic {
+            severity: Severity::Error,
+            code: None,
+            message: "mismatched type".to_owned(),
+            location: Some(Location {
+                path: "main.mun".to_owned(),
+                start_line: 2,
+                start_column: 5,
+                end_line: 2,
+                end_column: 9,
+            }),
+            labels: vec!["expected `i32`, found `bool`".to_owned()],
+        };
+        assert_eq!(
+            command(&diagnostic, Path::new("/repo/game/src"), Path::new("/repo")),
+            "::error file=game/src/main.mun,line=2,col=5,endLine=2,endColumn=9::\
+             mismatched type%0Aexpected `i32`, found `bool`"
+        );
+
+        let diagnostic = Diagnostic {
+            severity: Severity::Warning,
+            location: None,
+            labels: vec![],
+            message: "100% unused".to_owned(),
+            ..diagnostic
+        };
+        assert_eq!(
+            command(&diagnostic, Path::new("/repo/src"), Path::new("/repo")),
+            "::warning::100%25 unused"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! Serialization of diagnostics to JSON lines: one JSON object per diagnostic, similar to Cargo's
+//! `--message-format=json`.
+
+use super::Diagnostic;
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// Writes every diagnostic as a single-line JSON object to the given stream. File paths are
+/// joined to `source_dir`.
+pub fn write_json(
+    writer: &mut dyn std::io::Write,
+    diagnostics: &[Diagnostic],
+    source_dir: &Path,
+) -> Result<(), anyhow::Error> {
+    for diagnostic in diagnostics {
+        serde_json::to_writer(&mut *writer, &message(diagnostic, source_dir))?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Constructs the JSON object for a single diagnostic.
+fn message(diagnostic: &Diagnostic, source_dir: &Path) -> Value {
+    let (file, span) = match &diagnostic.location {
+        Some(location) => (
+            json!(source_dir.join(&location.path).display().to_string()),
+            json!({
+                "start_line": location.start_line,
+                "start_column": location.start_column,
+                "end_line": location.end_line,
+                "end_column": location.end_column,
+            }),
+        ),
+        None => (Value::Null, Value::Null),
+    };
+
+    json!({
+        "severity": diagnostic.severity.as_str(),
+        "code": diagnostic.code,
+        "message": diagnostic.message,
+        "file": file,
+        "span": span,
+        "labels": diagnostic.labels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_json;
+    use crate::diagnostics::{Diagnostic, Location, Severity};
+    use std::path::Path;
+
+    #[test]
+    fn test_write_json() {
+        let diagnostics = vec![
+            Diagnostic {
+                severity: Severity::Error,
+                code: None,
+                message: "mismatched type".to_owned(),
+                location: Some(Location {
+                    path: "main.mun".to_owned(),
+                    start_line: 2,
+                    start_column: 5,
+                    end_line: 2,
+                    end_column: 9,
+                }),
+                labels: vec!["expected `i32`, found `bool`".to_owned()],
+            },
+            Diagnostic {
+                severity: Severity::Warning,
+                code: Some("W001".to_owned()),
+                message: "unused".to_owned(),
+                location: None,
+                labels: vec![],
+            },
+        ];
+
+        let mut output = Vec::new();
+        write_json(&mut output, &diagnostics, Path::new("src")).unwrap();
+        let lines: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                serde_json::json!({
+                    "severity": "error",
+                    "code": null,
+                    "message": "mismatched type",
+                    "file": Path::new("src").join("main.mun").display().to_string(),
+                    "span": { "start_line": 2, "start_column": 5, "end_line": 2, "end_column": 9 },
+                    "labels": ["expected `i32`, found `bool`"],
+                }),
+                serde_json::json!({
+                    "severity": "warning",
+                    "code": "W001",
+                    "message": "unused",
+                    "file": null,
+                    "span": null,
+                    "labels": [],
+                }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Serialization of diagnostics to the [SARIF 2.1.0] format.
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use super::Diagnostic;
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// Writes the specified diagnostics as a SARIF log to the given stream. File locations are relative
+/// to the `source_dir`.
+pub fn write_sarif(
+    writer: &mut dyn std::io::Write,
+    diagnostics: &[Diagnostic],
+    source_dir: &Path,
+) -> Result<(), anyhow::Error> {
+    let results: Vec<Value> = diagnostics.iter().map(result).collect();
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "mun",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://mun-lang.org",
+                }
+            },
+            "originalUriBaseIds": {
+                "SRCROOT": { "uri": file_uri(source_dir) }
+            },
+            "results": results,
+        }]
+    });
+    serde_json::to_writer_pretty(&mut *writer, &log)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Constructs a SARIF `result` object from a diagnostic.
+fn result(diagnostic: &Diagnostic) -> Value {
+    let mut text = diagnostic.message.clone();
+    for label in diagnostic.labels.iter() {
+        text.push('\n');
+        text.push_str(label);
+    }
+
+    let mut result = json!({
+        "level": diagnostic.severity.as_str(),
+        "message": { "text": text },
+    });
+    if let Some(code) = &diagnostic.code {
+        result["ruleId"] = json!(code);
+    }
+    if let Some(location) = &diagnostic.location {
+        result["locations"] = json!([{
+            "physicalLocation": {
+                "artifactLocation": {
+                    "uri": location.path,
+                    "uriBaseId": "SRCROOT",
+                },
+                "region": {
+                    "startLine": location.start_line,
+                    "startColumn": location.start_column,
+                    "endLine": location.end_line,
+                    "endColumn": location.end_column,
+                }
+            }
+        }]);
+    }
+    result
+}
+
+/// Converts a directory path into a `file://` URI with a trailing slash.
+fn file_uri(dir: &Path) -> String {
+    let path = dir.display().to_string().replace('\\', "/");
+    let path = path.trim_end_matches('/');
+    if path.starts_with('/') {
+        format!("file://{}/", path)
+    } else {
+        format!("file:///{}/", path)
+    }
+}
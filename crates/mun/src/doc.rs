@@ -0,0 +1,321 @@
+//! Extracting the documentation of a package from the doc comments of its items, as used by
+//! `mun doc`.
+
+use mun_syntax::ast::{
+    self, AstToken, DocCommentsOwner, ModuleItemOwner, NameOwner, TypeAscriptionOwner,
+    VisibilityOwner,
+};
+use mun_syntax::{
+    AstNode, Direction, SourceFile, SyntaxElement, SyntaxKind, SyntaxNode, TextRange,
+};
+use serde::Serialize;
+
+/// The documentation of a package.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct PackageDoc {
+    pub name: String,
+    pub version: String,
+    pub modules: Vec<ModuleDoc>,
+}
+
+/// The documentation of the items in a single source file.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ModuleDoc {
+    /// The path of the source file relative to the source directory of the package
+    pub path: String,
+    pub functions: Vec<FunctionDoc>,
+    pub structs: Vec<StructDoc>,
+}
+
+/// The documentation of a function.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct FunctionDoc {
+    pub name: String,
+    /// The declaration of the function, e.g. `fn add(a: i32, b: i32) -> i32`
+    pub signature: String,
+    pub public: bool,
+    pub doc: Option<String>,
+}
+
+/// The documentation of a struct and its fields.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct StructDoc {
+    pub name: String,
+    pub public: bool,
+    pub doc: Option<String>,
+    pub fields: Vec<FieldDoc>,
+}
+
+/// The documentation of a field of a struct. The fields of tuple structs are named by their index.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct FieldDoc {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub doc: Option<String>,
+}
+
+impl ModuleDoc {
+    /// Extracts the documentation of the items in the source file at `path` with the contents
+    /// `text`. Items without a name are skipped.
+    pub fn from_source(path: &str, text: &str) -> Self {
+        let tree = SourceFile::parse(text).tree();
+        let mut functions = Vec::new();
+        let mut structs = Vec::new();
+        for item in tree.items() {
+            match item.kind() {
+                ast::ModuleItemKind::FunctionDef(function) => {
+                    if let Some(name) = function.name() {
+                        functions.push(FunctionDoc {
+                            name: name.text().to_string(),
+                            signature: text_of_range(text, function.signature_range()),
+                            public: function.visibility().is_some(),
+                            doc: doc_text(function.syntax()),
+                        });
+                    }
+                }
+                ast::ModuleItemKind::StructDef(def) => {
+                    if let Some(name) = def.name() {
+                        structs.push(StructDoc {
+                            name: name.text().to_string(),
+                            public: def.visibility().is_some(),
+                            doc: doc_text(def.syntax()),
+                            fields: fields(text, &def),
+                        });
+                    }
+                }
+            }
+        }
+
+        ModuleDoc {
+            path: path.to_owned(),
+            functions,
+            structs,
+        }
+    }
+}
+
+fn fields(text: &str, def: &ast::StructDef) -> Vec<FieldDoc> {
+    match def.kind() {
+        ast::StructKind::Record(fields) => fields
+            .fields()
+            .filter_map(|field| {
+                Some(FieldDoc {
+                    name: field.name()?.text().to_string(),
+                    ty: field.ascribed_type().map_or_else(String::new, |ty| {
+                        text_of_range(text, ty.syntax().text_range())
+                    }),
+                    doc: doc_text(field.syntax()),
+                })
+            })
+            .collect(),
+        ast::StructKind::Tuple(fields) => fields
+            .fields()
+            .enumerate()
+            .map(|(idx, field)| FieldDoc {
+                name: idx.to_string(),
+                ty: field.type_ref().map_or_else(String::new, |ty| {
+                    text_of_range(text, ty.syntax().text_range())
+                }),
+                doc: doc_text(field.syntax()),
+            })
+            .collect(),
+        ast::StructKind::Unit => Vec::new(),
+    }
+}
+
+/// Returns the text in `range` with all whitespace collapsed into single spaces.
+fn text_of_range(text: &str, range: TextRange) -> String {
+    text[range.start().to_usize()..range.end().to_usize()]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the text of the outer doc comments (`///`) of an item, or `None` if it has none. Doc
+/// comments are attached to functions by the parser, but precede the nodes of other items.
+fn doc_text(node: &SyntaxNode) -> Option<String> {
+    let mut comments: Vec<ast::Comment> = node
+        .siblings_with_tokens(Direction::Prev)
+        .skip(1)
+        .take_while(|element| match element {
+            SyntaxElement::Token(token) => match token.kind() {
+                SyntaxKind::WHITESPACE => !token.text().contains("\n\n"),
+                SyntaxKind::COMMENT => true,
+                _ => false,
+            },
+            SyntaxElement::Node(_) => false,
+        })
+        .filter_map(|element| element.into_token().and_then(ast::Comment::cast))
+        .collect();
+    comments.reverse();
+    if let Some(owner) = ast::FunctionDef::cast(node.clone()) {
+        comments.extend(owner.doc_comments());
+    } else if let Some(owner) = ast::RecordFieldDef::cast(node.clone()) {
+        comments.extend(owner.doc_comments());
+    }
+
+    let lines: Vec<&str> = comments
+        .iter()
+        .filter(|comment| comment.kind().doc == Some(ast::CommentPlacement::Outer))
+        .map(|comment| {
+            let text = &comment.text()[comment.prefix().len()..];
+            let text = text.trim_end_matches("*/");
+            if text.starts_with(' ') {
+                text[1..].trim_end()
+            } else {
+                text.trim_end()
+            }
+        })
+        .collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+/// Renders the documentation as a single, self-contained HTML page.
+pub fn render_html(doc: &PackageDoc) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{name} {version}</title>\n</head>\n<body>\n<h1>{name} {version}</h1>\n",
+        name = escape_html(&doc.name),
+        version = escape_html(&doc.version)
+    );
+    for module in doc.modules.iter() {
+        if module.functions.is_empty() && module.structs.is_empty() {
+            continue;
+        }
+        html.push_str(&format!("<h2>{}</h2>\n", escape_html(&module.path)));
+        for def in module.structs.iter() {
+            html.push_str(&format!(
+                "<h3 id=\"struct.{name}\">struct {name}</h3>\n",
+                name = escape_html(&def.name)
+            ));
+            push_doc(&mut html, def.doc.as_deref());
+            if !def.fields.is_empty() {
+                html.push_str("<ul>\n");
+                for field in def.fields.iter() {
+                    html.push_str(&format!(
+                        "<li><code>{}: {}</code>",
+                        escape_html(&field.name),
+                        escape_html(&field.ty)
+                    ));
+                    if let Some(doc) = &field.doc {
+                        html.push_str(&format!(" &ndash; {}", escape_html(doc)));
+                    }
+                    html.push_str("</li>\n");
+                }
+                html.push_str("</ul>\n");
+            }
+        }
+        for function in module.functions.iter() {
+            html.push_str(&format!(
+                "<h3 id=\"fn.{}\"><code>{}</code></h3>\n",
+                escape_html(&function.name),
+                escape_html(&function.signature)
+            ));
+            push_doc(&mut html, function.doc.as_deref());
+        }
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn push_doc(html: &mut String, doc: Option<&str>) {
+    match doc {
+        Some(doc) => {
+            for paragraph in doc.split("\n\n") {
+                html.push_str(&format!("<p>{}</p>\n", escape_html(paragraph)));
+            }
+        }
+        None => html.push_str("<p><em>No documentation.</em></p>\n"),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_html, FieldDoc, FunctionDoc, ModuleDoc, StructDoc};
+
+    #[test]
+    fn test_module_doc() {
+        let doc = ModuleDoc::from_source(
+            "main.mun",
+            "/// Adds two numbers.\n///\n/// Wraps on overflow.\npub fn add(a: i32,\n    b: i32) -> i32 { a + b }\n\n\
+             // Not documentation\nfn helper() {}\n\n\
+             /// A point in space\npub struct Point {\n    /// The horizontal position\n    x: f64,\n    y: f64,\n}\n\n\
+             struct(value) Pair(i32, f64);",
+        );
+        assert_eq!(
+            doc,
+            ModuleDoc {
+                path: "main.mun".to_owned(),
+                functions: vec![
+                    FunctionDoc {
+                        name: "add".to_owned(),
+                        signature: "fn add(a: i32, b: i32) -> i32".to_owned(),
+                        public: true,
+                        doc: Some("Adds two numbers.\n\nWraps on overflow.".to_owned()),
+                    },
+                    FunctionDoc {
+                        name: "helper".to_owned(),
+                        signature: "fn helper()".to_owned(),
+                        public: false,
+                        doc: None,
+                    },
+                ],
+                structs: vec![
+                    StructDoc {
+                        name: "Point".to_owned(),
+                        public: true,
+                        doc: Some("A point in space".to_owned()),
+                        fields: vec![
+                            FieldDoc {
+                                name: "x".to_owned(),
+                                ty: "f64".to_owned(),
+                                doc: Some("The horizontal position".to_owned()),
+                            },
+                            FieldDoc {
+                                name: "y".to_owned(),
+                                ty: "f64".to_owned(),
+                                doc: None,
+                            },
+                        ],
+                    },
+                    StructDoc {
+                        name: "Pair".to_owned(),
+                        public: false,
+                        doc: None,
+                        fields: vec![
+                            FieldDoc {
+                                name: "0".to_owned(),
+                                ty: "i32".to_owned(),
+                                doc: None,
+                            },
+                            FieldDoc {
+                                name: "1".to_owned(),
+                                ty: "f64".to_owned(),
+                                doc: None,
+                            },
+                        ],
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html("a < b && \"c\""),
+            "a &lt; b &amp;&amp; &quot;c&quot;"
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! Parsing and validation of command-line arguments that are passed to an entry point.
+
+use anyhow::anyhow;
+use mun_abi::FunctionSignature;
+use std::ffi::OsStr;
+use std::fmt;
+use std::path::Path;
+
+/// A value that is passed as an argument to an entry point.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ArgValue {
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+}
+
+impl fmt::Display for ArgValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgValue::Bool(value) => write!(f, "{}", value),
+            ArgValue::I8(value) => write!(f, "{}", value),
+            ArgValue::I16(value) => write!(f, "{}", value),
+            ArgValue::I32(value) => write!(f, "{}", value),
+            ArgValue::I64(value) => write!(f, "{}", value),
+            ArgValue::I128(value) => write!(f, "{}", value),
+            ArgValue::U8(value) => write!(f, "{}", value),
+            ArgValue::U16(value) => write!(f, "{}", value),
+            ArgValue::U32(value) => write!(f, "{}", value),
+            ArgValue::U64(value) => write!(f, "{}", value),
+            ArgValue::U128(value) => write!(f, "{}", value),
+            ArgValue::F32(value) => write!(f, "{}", value),
+            ArgValue::F64(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl ArgValue {
+    /// Returns the name of the Mun type of the value.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ArgValue::Bool(_) => "core::bool",
+            ArgValue::I8(_) => "core::i8",
+            ArgValue::I16(_) => "core::i16",
+            ArgValue::I32(_) => "core::i32",
+            ArgValue::I64(_) => "core::i64",
+            ArgValue::I128(_) => "core::i128",
+            ArgValue::U8(_) => "core::u8",
+            ArgValue::U16(_) => "core::u16",
+            ArgValue::U32(_) => "core::u32",
+            ArgValue::U64(_) => "core::u64",
+            ArgValue::U128(_) => "core::u128",
+            ArgValue::F32(_) => "core::f32",
+            ArgValue::F64(_) => "core::f64",
+        }
+    }
+
+    /// Returns the value if it is an integer, wrapping around for `u128` values beyond `i128`.
+    pub fn to_integer(&self) -> Option<i128> {
+        match *self {
+            ArgValue::I8(value) => Some(value.into()),
+            ArgValue::I16(value) => Some(value.into()),
+            ArgValue::I32(value) => Some(value.into()),
+            ArgValue::I64(value) => Some(value.into()),
+            ArgValue::I128(value) => Some(value),
+            ArgValue::U8(value) => Some(value.into()),
+            ArgValue::U16(value) => Some(value.into()),
+            ArgValue::U32(value) => Some(value.into()),
+            ArgValue::U64(value) => Some(value.into()),
+            ArgValue::U128(value) => Some(value as i128),
+            ArgValue::Bool(_) | ArgValue::F32(_) | ArgValue::F64(_) => None,
+        }
+    }
+}
+
+/// Parses `value` as an argument of the type with the specified name.
+pub fn parse_arg(type_name: &str, value: &str) -> Result<ArgValue, anyhow::Error> {
+    macro_rules! parse {
+        ($variant:ident) => {
+            value
+                .parse()
+                .map(ArgValue::$variant)
+                .map_err(|e| anyhow!("cannot convert '{}' to `{}`: {}", value, type_name, e))
+        };
+    }
+
+    match type_name {
+        "core::bool" => parse!(Bool),
+        "core::i8" => parse!(I8),
+        "core::i16" => parse!(I16),
+        "core::i32" => parse!(I32),
+        "core::i64" => parse!(I64),
+        "core::i128" => parse!(I128),
+        "core::u8" => parse!(U8),
+        "core::u16" => parse!(U16),
+        "core::u32" => parse!(U32),
+        "core::u64" => parse!(U64),
+        "core::u128" => parse!(U128),
+        "core::f32" => parse!(F32),
+        "core::f64" => parse!(F64),
+        _ => Err(anyhow!(
+            "arguments of type `{}` cannot be passed from the command line, only booleans, integers and floating-point numbers are supported",
+            type_name
+        )),
+    }
+}
+
+/// Converts the raw command-line `values` to strings, failing if any of them is not valid UTF-8.
+pub fn utf8_args<'a>(
+    values: impl Iterator<Item = &'a OsStr>,
+) -> Result<Vec<&'a str>, anyhow::Error> {
+    values
+        .enumerate()
+        .map(|(idx, value)| {
+            value
+                .to_str()
+                .ok_or_else(|| anyhow!("argument {} is not valid UTF-8: {:?}", idx + 1, value))
+        })
+        .collect()
+}
+
+/// Reads the arguments of an entry point from the JSON file at `path`, which must contain an array
+/// of booleans, numbers or strings. The values are returned as text, so that they are converted to
+/// the parameter types of the entry point like arguments passed on the command line.
+pub fn read_args_file(path: &Path) -> Result<Vec<String>, anyhow::Error> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("could not read arguments file '{}': {}", path.display(), e))?;
+    json_args(&contents).map_err(|e| anyhow!("invalid arguments file '{}': {}", path.display(), e))
+}
+
+/// Converts a JSON array of booleans, numbers or strings to the text of each value.
+fn json_args(contents: &str) -> Result<Vec<String>, anyhow::Error> {
+    let values = match serde_json::from_str(contents)? {
+        serde_json::Value::Array(values) => values,
+        _ => return Err(anyhow!("expected an array of arguments")),
+    };
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(idx, value)| match value {
+            serde_json::Value::Bool(value) => Ok(value.to_string()),
+            serde_json::Value::Number(value) => Ok(value.to_string()),
+            serde_json::Value::String(value) => Ok(value),
+            value => Err(anyhow!(
+                "argument {}: expected a boolean, number or string, found `{}`",
+                idx + 1,
+                value
+            )),
+        })
+        .collect()
+}
+
+/// Parses `values` as the arguments of a function with the specified `signature`, verifying that
+/// the number of arguments and their types match.
+pub fn parse_args(
+    signature: &FunctionSignature,
+    values: &[&str],
+) -> Result<Vec<ArgValue>, anyhow::Error> {
+    let arg_types = signature.arg_types();
+    if arg_types.len() != values.len() {
+        return Err(anyhow!(
+            "expected {} argument(s) but {} were provided",
+            arg_types.len(),
+            values.len()
+        ));
+    }
+
+    arg_types
+        .iter()
+        .zip(values.iter())
+        .enumerate()
+        .map(|(idx, (ty, value))| {
+            parse_arg(ty.name(), value).map_err(|e| anyhow!("argument {}: {}", idx + 1, e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{json_args, parse_arg, ArgValue};
+
+    #[test]
+    fn test_parse_arg() {
+        assert_eq!(
+            parse_arg("core::bool", "true").unwrap(),
+            ArgValue::Bool(true)
+        );
+        assert_eq!(parse_arg("core::i32", "-5").unwrap(), ArgValue::I32(-5));
+        assert_eq!(parse_arg("core::f64", "1.5").unwrap(), ArgValue::F64(1.5));
+        assert!(parse_arg("core::u8", "256").is_err());
+        assert!(parse_arg("core::u8", "-1").is_err());
+        assert!(parse_arg("Foo", "1").is_err());
+    }
+
+    #[test]
+    fn test_json_args() {
+        assert_eq!(
+            json_args("[true, -5, 1.5, \"340282366920938463463374607431768211455\"]").unwrap(),
+            vec![
+                "true",
+                "-5",
+                "1.5",
+                "340282366920938463463374607431768211455"
+            ]
+        );
+        assert!(json_args("[]").unwrap().is_empty());
+        assert!(json_args("[1, 2").is_err());
+        assert!(json_args("{\"a\": 1}").is_err());
+        assert!(json_args("[[1]]").is_err());
+    }
+}
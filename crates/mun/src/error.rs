@@ -0,0 +1,121 @@
+//! The errors of the driver that embedders of `run_with_args` may want to handle.
+
+use mun_runtime::InvokeError;
+use std::fmt;
+use std::path::PathBuf;
+
+/// An error of the driver. The errors returned by `run_with_args` can be downcast to this type
+/// to distinguish these failures from all others, e.g.:
+///
+/// ```ignore
+/// match error.downcast_ref::<DriverError>() {
+///     Some(DriverError::EntryPointNotFound(name)) => ..,
+///     _ => ..,
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum DriverError {
+    /// No manifest was found in a directory or any of its parents.
+    ManifestNotFound {
+        manifest_name: String,
+        directory: PathBuf,
+    },
+    /// An explicitly specified manifest path does not exist.
+    InvalidManifestPath(PathBuf),
+    /// The library does not export a function with the name of the entry point.
+    EntryPointNotFound(String),
+    /// The entry point returns a value of a type that cannot be read.
+    UnsupportedReturnType {
+        type_name: String,
+        /// Whether arguments were passed to the entry point, which restricts the supported types
+        with_args: bool,
+    },
+    /// The runtime failed to invoke the entry point, e.g. because the arguments do not match its
+    /// signature.
+    Invocation {
+        entry_point: String,
+        /// The function that could not be invoked
+        function: String,
+        message: String,
+    },
+    /// The invocation of the entry point was aborted because it would have exceeded the memory
+    /// limit.
+    MemoryLimitExceeded {
+        entry_point: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for DriverError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DriverError::ManifestNotFound {
+                manifest_name,
+                directory,
+            } => write!(
+                f,
+                "could not find {} in '{}' or a parent directory",
+                manifest_name,
+                directory.display()
+            ),
+            DriverError::InvalidManifestPath(path) => write!(
+                f,
+                "'{}' does not refer to a valid manifest path",
+                path.display()
+            ),
+            DriverError::EntryPointNotFound(entry_point) => {
+                write!(f, "Failed to obtain entry point '{}'", entry_point)
+            }
+            DriverError::UnsupportedReturnType {
+                type_name,
+                with_args: false,
+            } => write!(
+                f,
+                "Only native Mun return types and structs are supported for entry points. Found: {}",
+                type_name
+            ),
+            DriverError::UnsupportedReturnType {
+                type_name,
+                with_args: true,
+            } => write!(
+                f,
+                "only native Mun return types and structs are supported for entry points with arguments. Found: {}",
+                type_name
+            ),
+            DriverError::Invocation {
+                entry_point,
+                function,
+                message,
+            } => {
+                write!(f, "failed to invoke '{}'", entry_point)?;
+                if function != entry_point {
+                    write!(f, "\n  in function '{}'", function)?;
+                }
+                write!(f, "\n  caused by: {}", message)
+            }
+            DriverError::MemoryLimitExceeded {
+                entry_point,
+                message,
+            } => write!(f, "entry point '{}' {}", entry_point, message),
+        }
+    }
+}
+
+impl std::error::Error for DriverError {}
+
+impl DriverError {
+    /// Constructs an error for a failed invocation of `entry_point` from the error of the runtime.
+    pub(crate) fn invocation(entry_point: &str, error: impl InvokeError) -> Self {
+        if error.exceeded_memory_limit() {
+            return DriverError::MemoryLimitExceeded {
+                entry_point: entry_point.to_owned(),
+                message: error.message().to_owned(),
+            };
+        }
+        DriverError::Invocation {
+            entry_point: entry_point.to_owned(),
+            function: error.function_name().to_owned(),
+            message: error.message().to_owned(),
+        }
+    }
+}
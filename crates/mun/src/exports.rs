@@ -0,0 +1,96 @@
+//! Listing the functions exported by a compiled Mun library.
+
+use serde::Serialize;
+
+/// A function exported by a library, with the names of its parameter and return types.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Export {
+    pub name: String,
+    pub arg_types: Vec<String>,
+    pub return_type: Option<String>,
+}
+
+impl Export {
+    /// Renders the export as a signature, e.g. `add(core::i32, core::i32) -> core::i32`.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}({})", self.name, self.arg_types.join(", "));
+        if let Some(return_type) = &self.return_type {
+            text.push_str(" -> ");
+            text.push_str(return_type);
+        }
+        text
+    }
+}
+
+impl From<&mun_abi::FunctionDefinition> for Export {
+    fn from(function: &mun_abi::FunctionDefinition) -> Self {
+        let signature = &function.prototype.signature;
+        Export {
+            name: function.prototype.name().to_owned(),
+            arg_types: signature
+                .arg_types()
+                .iter()
+                .map(|ty| ty.name().to_owned())
+                .collect(),
+            return_type: signature.return_type().map(|ty| ty.name().to_owned()),
+        }
+    }
+}
+
+/// Converts the function definitions of a library into exports, sorted by name.
+pub fn exports(functions: &[&mun_abi::FunctionDefinition]) -> Vec<Export> {
+    let mut exports: Vec<Export> = functions
+        .iter()
+        .map(|function| Export::from(*function))
+        .collect();
+    exports.sort_by(|a, b| a.name.cmp(&b.name));
+    exports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exports, Export};
+    use crate::multi_file::compile_reader;
+    use mun_compiler::Config;
+    use mun_runtime::RuntimeBuilder;
+
+    #[test]
+    fn test_exports() {
+        let out_dir = tempdir::TempDir::new("test_exports").unwrap();
+        let config = Config {
+            out_dir: Some(out_dir.path().to_path_buf()),
+            ..Config::default()
+        };
+        let mut source = &b"pub fn main() -> i32 { 5 }\npub fn scale(x: f64, enabled: bool) {}"[..];
+        assert!(compile_reader(&mut source, config).unwrap());
+
+        let library_path = out_dir.path().join("stdin.munlib");
+        let runtime = RuntimeBuilder::new(&library_path)
+            .set_watch(false)
+            .spawn()
+            .unwrap();
+        let runtime = runtime.borrow();
+        let functions: Vec<_> = runtime
+            .assemblies()
+            .flat_map(|assembly| assembly.info().symbols.functions())
+            .collect();
+        let exports = exports(&functions);
+        assert_eq!(
+            exports,
+            vec![
+                Export {
+                    name: "main".to_owned(),
+                    arg_types: vec![],
+                    return_type: Some("core::i32".to_owned()),
+                },
+                Export {
+                    name: "scale".to_owned(),
+                    arg_types: vec!["core::f64".to_owned(), "core::bool".to_owned()],
+                    return_type: None,
+                },
+            ]
+        );
+        assert_eq!(exports[0].to_text(), "main() -> core::i32");
+        assert_eq!(exports[1].to_text(), "scale(core::f64, core::bool)");
+    }
+}
@@ -0,0 +1,92 @@
+//! Applies the machine-applicable fixes suggested by the compiler to the sources of a package.
+
+use anyhow::anyhow;
+use mun_compiler::{Config, Driver};
+use std::path::Path;
+use std::process::Command;
+
+/// Applies all fixes for the package at `manifest_path`. If `dry_run` is set the fixes are only
+/// printed. Unless `allow_dirty` is set, the package must be part of a git repository without
+/// uncommitted changes so that the edits can be reviewed and reverted.
+pub fn fix_package(
+    manifest_path: &Path,
+    config: Config,
+    dry_run: bool,
+    allow_dirty: bool,
+) -> Result<(), anyhow::Error> {
+    let (package, driver) = Driver::with_package_path(manifest_path, config)?;
+    // This unwrap is safe because the driver fails if the source directory does not exist.
+    let source_dir = package.source_directory().unwrap();
+
+    if !dry_run && !allow_dirty {
+        ensure_clean_git_tree(package.root())?;
+    }
+
+    let mut num_fixes = 0;
+    for (relative_path, fixes) in driver.fixes() {
+        let path = relative_path.to_path(&source_dir);
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("could not read '{}': {}", path.display(), e))?;
+
+        if dry_run {
+            for fix in &fixes {
+                let (line, column) = line_column(&text, fix.range.start().to_usize());
+                println!("{}:{}:{}: {}", path.display(), line, column, fix.message);
+            }
+            num_fixes += fixes.len();
+        } else {
+            let (fixed_text, applied) = mun_compiler::apply_fixes(&text, &fixes);
+            std::fs::write(&path, fixed_text)
+                .map_err(|e| anyhow!("could not write '{}': {}", path.display(), e))?;
+            println!("Fixed {} ({} fix(es))", path.display(), applied);
+            num_fixes += applied;
+        }
+    }
+
+    if num_fixes == 0 {
+        println!("No fixes to apply");
+    } else if dry_run {
+        println!("{} fix(es) would be applied", num_fixes);
+    }
+    Ok(())
+}
+
+/// Returns an error if `dir` is not part of a git repository or if the repository has uncommitted
+/// changes.
+fn ensure_clean_git_tree(dir: &Path) -> Result<(), anyhow::Error> {
+    let output = Command::new("git")
+        .arg("status")
+        .arg("--porcelain")
+        .current_dir(dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .ok_or_else(|| {
+            anyhow!(
+                "'{}' is not part of a git repository, pass --allow-dirty to apply fixes anyway",
+                dir.display()
+            )
+        })?;
+
+    let changes = String::from_utf8_lossy(&output.stdout);
+    if !changes.trim().is_empty() {
+        return Err(anyhow!(
+            "the working directory has uncommitted changes, commit or stash them first or pass --allow-dirty:\n{}",
+            changes.trim_end()
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the one-based line and column of the byte `offset` in `text`.
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before, |idx| &before[idx + 1..])
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
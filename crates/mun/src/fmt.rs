@@ -0,0 +1,304 @@
+//! The formatter for Mun source files, as used by `mun fmt`. Source files are formatted based on
+//! their syntax tree. Only the whitespace between tokens is changed, so formatting never changes
+//! the meaning of a program, and comments and line breaks are preserved.
+
+use ansi_term::Colour::{Cyan, Green, Red};
+use ansi_term::Style;
+use anyhow::anyhow;
+use mun_syntax::{AstNode, SourceFile, SyntaxKind, SyntaxToken};
+
+/// The number of unchanged lines that are shown around every change in a diff.
+const DIFF_CONTEXT: usize = 3;
+
+/// The string that is used for every level of indentation.
+const INDENT: &str = "    ";
+
+/// Formats `source`. Every line is indented by four spaces per level of nesting, tokens on a line
+/// are separated by a single space where they are separated at all, consecutive blank lines are
+/// collapsed into one, and the file ends with a single newline.
+///
+/// Returns an error if `source` contains syntax errors, as its syntax tree cannot be trusted.
+pub fn format_source(source: &str) -> Result<String, anyhow::Error> {
+    let parse = SourceFile::parse(source);
+    if let Some(error) = parse.errors().first() {
+        let offset = error.location().offset().to_usize();
+        return Err(anyhow!(
+            "cannot format source with syntax errors: {} at line {}",
+            error,
+            source[..offset].matches('\n').count() + 1
+        ));
+    }
+
+    // All tokens other than whitespace, with the number of line breaks that precede them
+    let mut tokens: Vec<(SyntaxToken, usize)> = Vec::new();
+    let mut line_breaks = 0;
+    for token in parse
+        .tree()
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+    {
+        if token.kind() == SyntaxKind::WHITESPACE {
+            line_breaks += token.text().matches('\n').count();
+        } else {
+            tokens.push((token, line_breaks));
+            line_breaks = 0;
+        }
+    }
+
+    let mut result = String::with_capacity(source.len());
+    // The indentation of the lines that contain the currently open delimiters
+    let mut open_delimiters: Vec<usize> = Vec::new();
+    let mut line_indent = 0;
+    for (idx, (token, line_breaks)) in tokens.iter().enumerate() {
+        if idx == 0 || *line_breaks > 0 {
+            // A line that starts with closing delimiters is indented like the line that opened
+            // the outermost of them
+            let leading_closers = tokens[idx..]
+                .iter()
+                .enumerate()
+                .take_while(|(offset, (token, line_breaks))| {
+                    (*offset == 0 || *line_breaks == 0) && is_closing_delimiter(token.kind())
+                })
+                .count()
+                .min(open_delimiters.len());
+            line_indent = if leading_closers > 0 {
+                open_delimiters[open_delimiters.len() - leading_closers]
+            } else {
+                open_delimiters.last().map_or(0, |indent| indent + 1)
+            };
+
+            if idx > 0 {
+                result.push_str(if *line_breaks > 1 { "\n\n" } else { "\n" });
+            }
+            for _ in 0..line_indent {
+                result.push_str(INDENT);
+            }
+        } else if needs_space(&tokens[idx - 1].0, token) {
+            result.push(' ');
+        }
+        result.push_str(token.text());
+
+        if is_closing_delimiter(token.kind()) {
+            // Delimiters that are opened after closing a delimiter that was opened on an earlier,
+            // less indented line, e.g. the body of a function whose parameters span multiple
+            // lines, are indented like that earlier line.
+            if let Some(indent) = open_delimiters.pop() {
+                line_indent = line_indent.min(indent);
+            }
+        } else if is_opening_delimiter(token.kind()) {
+            open_delimiters.push(line_indent);
+        }
+    }
+    if !tokens.is_empty() {
+        result.push('\n');
+    }
+    Ok(result)
+}
+
+fn is_opening_delimiter(kind: SyntaxKind) -> bool {
+    match kind {
+        SyntaxKind::L_PAREN | SyntaxKind::L_CURLY | SyntaxKind::L_BRACKET => true,
+        _ => false,
+    }
+}
+
+fn is_closing_delimiter(kind: SyntaxKind) -> bool {
+    match kind {
+        SyntaxKind::R_PAREN | SyntaxKind::R_CURLY | SyntaxKind::R_BRACKET => true,
+        _ => false,
+    }
+}
+
+/// Returns true if `previous` and `next`, which are on the same line, are separated by a space.
+fn needs_space(previous: &SyntaxToken, next: &SyntaxToken) -> bool {
+    use SyntaxKind::*;
+    match (previous.kind(), next.kind()) {
+        (COMMENT, _) | (_, COMMENT) => true,
+        (L_PAREN, _) | (L_BRACKET, _) | (_, R_PAREN) | (_, R_BRACKET) => false,
+        (L_CURLY, R_CURLY) => false,
+        (_, COMMA) | (_, SEMI) | (_, COLON) | (_, DOT) | (DOT, _) => false,
+        (_, COLONCOLON) | (COLONCOLON, _) => false,
+        // Unary operators, e.g. `-a` and `!a`
+        (MINUS, _) | (EXCLAMATION, _) => previous.parent().kind() != PREFIX_EXPR,
+        // Calls, parameter lists, tuple structs, `pub(package)` and `struct(gc)`
+        (IDENT, L_PAREN) | (R_PAREN, L_PAREN) | (PUB_KW, L_PAREN) | (STRUCT_KW, L_PAREN) => false,
+        _ => true,
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum DiffOp {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Computes the line operations that turn `old` into `new`, based on their longest common
+/// subsequence.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(DiffOp, &'a str)> {
+    // lcs[i * (new.len() + 1) + j] is the length of the longest common subsequence of old[i..]
+    // and new[j..]
+    let width = new.len() + 1;
+    let mut lcs = vec![0usize; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i * width + j] = if old[i] == new[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            ops.push((DiffOp::Equal, old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            ops.push((DiffOp::Delete, old[i]));
+            i += 1;
+        } else {
+            ops.push((DiffOp::Insert, new[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[i..].iter().map(|line| (DiffOp::Delete, *line)));
+    ops.extend(new[j..].iter().map(|line| (DiffOp::Insert, *line)));
+    ops
+}
+
+/// Renders a unified diff between the `old` and `new` contents of the file at `path`, optionally
+/// colored with ANSI escape codes. Returns an empty string if the contents are equal.
+pub fn unified_diff(path: &str, old: &str, new: &str, colors: bool) -> String {
+    let paint = |style: Style, text: String| {
+        if colors {
+            style.paint(text).to_string()
+        } else {
+            text
+        }
+    };
+
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_lines(&old_lines, &new_lines);
+    let changes: Vec<usize> = (0..ops.len())
+        .filter(|idx| ops[*idx].0 != DiffOp::Equal)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut result = paint(
+        Style::new().bold(),
+        format!("--- a/{}\n+++ b/{}", path, path),
+    );
+    result.push('\n');
+
+    let mut change_idx = 0;
+    while change_idx < changes.len() {
+        // Group all changes whose context overlaps into a single hunk
+        let start = changes[change_idx].saturating_sub(DIFF_CONTEXT);
+        let mut last = changes[change_idx];
+        change_idx += 1;
+        while change_idx < changes.len() && changes[change_idx] <= last + 2 * DIFF_CONTEXT {
+            last = changes[change_idx];
+            change_idx += 1;
+        }
+        let end = (last + DIFF_CONTEXT + 1).min(ops.len());
+
+        let count = |range: &[(DiffOp, &str)], excluded: DiffOp| {
+            range.iter().filter(|(op, _)| *op != excluded).count()
+        };
+        let old_start = count(&ops[..start], DiffOp::Insert);
+        let new_start = count(&ops[..start], DiffOp::Delete);
+        let old_len = count(&ops[start..end], DiffOp::Insert);
+        let new_len = count(&ops[start..end], DiffOp::Delete);
+        result.push_str(&paint(
+            Cyan.normal(),
+            format!(
+                "@@ -{},{} +{},{} @@",
+                old_start + 1,
+                old_len,
+                new_start + 1,
+                new_len
+            ),
+        ));
+        result.push('\n');
+
+        for (op, line) in &ops[start..end] {
+            let line = match op {
+                DiffOp::Equal => format!(" {}", line),
+                DiffOp::Delete => paint(Red.normal(), format!("-{}", line)),
+                DiffOp::Insert => paint(Green.normal(), format!("+{}", line)),
+            };
+            result.push_str(&line);
+            result.push('\n');
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_source, unified_diff};
+
+    #[test]
+    fn test_format_source() {
+        assert_eq!(
+            format_source("\n\nfn main() {  \n\tlet a = 1;\n\n\n\ta\n}\n\n").unwrap(),
+            "fn main() {\n    let a = 1;\n\n    a\n}\n"
+        );
+        assert_eq!(format_source("fn main() {}").unwrap(), "fn main() {}\n");
+        assert_eq!(
+            format_source("pub  fn add(a:i32,b :i32)->i32{a+ -b}").unwrap(),
+            "pub fn add(a: i32, b: i32) -> i32 { a + -b }\n"
+        );
+        assert_eq!(
+            format_source(
+                "pub(package) struct(gc) Foo{a:f64,}\nfn main(){\nlet foo=Foo{a:1.0};\nif !(foo.a>0.0){\nbar(foo)\n}\n}"
+            )
+            .unwrap(),
+            "pub(package) struct(gc) Foo { a: f64, }\n\
+             fn main() {\n    let foo = Foo { a: 1.0 };\n    if !(foo.a > 0.0) {\n        bar(foo)\n    }\n}\n"
+        );
+        assert!(format_source("fn main( {").is_err());
+    }
+
+    #[test]
+    fn test_format_source_preserves_comments() {
+        assert_eq!(
+            format_source("// The entry point\nfn main()->i32{\n// The answer\n42// not 41\n/* done */}").unwrap(),
+            "// The entry point\nfn main() -> i32 {\n    // The answer\n    42 // not 41\n    /* done */ }\n"
+        );
+    }
+
+    #[test]
+    fn test_format_source_is_idempotent() {
+        let sources = [
+            "fn main() {  \n\tlet a = 1;\n\n\n\ta\n}",
+            "fn foo(a: i32,\nb: i32) {\nbar(Baz {\nx: a\n})\n}",
+            "struct Foo(i32, f64);\n\n// comment\nfn main() { loop { break; } }",
+        ];
+        for source in sources.iter() {
+            let formatted = format_source(source).unwrap();
+            assert_eq!(format_source(&formatted).unwrap(), formatted);
+        }
+        assert_eq!(
+            format_source(sources[1]).unwrap(),
+            "fn foo(a: i32,\n    b: i32) {\n    bar(Baz {\n        x: a\n    })\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        assert_eq!(unified_diff("main.mun", "a\nb\n", "a\nb\n", false), "");
+        assert_eq!(
+            unified_diff("main.mun", "a\nb \nc\n", "a\nb\nc\n", false),
+            "--- a/main.mun\n+++ b/main.mun\n@@ -1,3 +1,3 @@\n a\n-b \n+b\n c\n"
+        );
+    }
+}
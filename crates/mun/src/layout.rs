@@ -0,0 +1,83 @@
+//! Functionality to print the memory layout of the structs exported by loaded Mun libraries.
+
+use mun_runtime::Runtime;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+/// Prints the memory layout of every struct type in the libraries loaded by the `runtime`. The
+/// `format` is either `text` or `json`.
+pub fn dump_layouts(runtime: &Runtime, format: &str) -> Result<(), anyhow::Error> {
+    // Collect all struct types, sorted by name, deduplicating types used in multiple assemblies
+    let types: BTreeMap<&str, &mun_abi::TypeInfo> = runtime
+        .assemblies()
+        .flat_map(|assembly| assembly.info().symbols.types().iter())
+        .filter(|type_info| type_info.group.is_struct())
+        .map(|type_info| (type_info.name(), *type_info))
+        .collect();
+
+    if format == "json" {
+        let layouts: Vec<_> = types
+            .values()
+            .map(|type_info| json_layout(type_info))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&layouts)?);
+        return Ok(());
+    }
+
+    for type_info in types.values() {
+        println!(
+            "struct {} (size: {}, alignment: {})",
+            type_info.name(),
+            type_info.size_in_bytes(),
+            type_info.alignment()
+        );
+        if let Some(struct_info) = type_info.as_struct() {
+            println!("    {:>6}  {:>6}  {:>5}  field", "offset", "size", "align");
+            for ((name, ty), offset) in struct_info
+                .field_names()
+                .zip(struct_info.field_types().iter())
+                .zip(struct_info.field_offsets().iter())
+            {
+                println!(
+                    "    {:>6}  {:>6}  {:>5}  {}: {}",
+                    offset,
+                    ty.size_in_bytes(),
+                    ty.alignment(),
+                    name,
+                    ty.name()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Constructs a JSON description of the layout of a struct type.
+fn json_layout(type_info: &mun_abi::TypeInfo) -> serde_json::Value {
+    let fields: Vec<_> = type_info
+        .as_struct()
+        .map(|struct_info| {
+            struct_info
+                .field_names()
+                .zip(struct_info.field_types().iter())
+                .zip(struct_info.field_offsets().iter())
+                .map(|((name, ty), offset)| {
+                    json!({
+                        "name": name,
+                        "type": ty.name(),
+                        "offset": offset,
+                        "size": ty.size_in_bytes(),
+                        "alignment": ty.alignment(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    json!({
+        "name": type_info.name(),
+        "size": type_info.size_in_bytes(),
+        "alignment": type_info.alignment(),
+        "fields": fields,
+    })
+}
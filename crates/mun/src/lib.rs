@@ -1,20 +1,87 @@
-use std::cell::RefCell;
+mod abi;
+mod bench;
+mod bench_compile;
+mod bug_report;
+mod build_record;
+mod clean;
+mod compare_opt;
+mod confirm;
+mod control;
+mod dep_info;
+mod diagnostics;
+mod doc;
+mod entry_args;
+mod error;
+mod exports;
+mod fix;
+mod fmt;
+mod layout;
+mod log_file;
+mod manifest_check;
+mod message_format;
+mod metadata;
+mod multi_file;
+mod multi_package;
+mod package_archive;
+mod post_build;
+mod reload_summary;
+mod repl;
+mod return_value;
+mod scaffold;
+mod size_report;
+mod startup_profile;
+mod syntax_tree;
+mod test_runner;
+mod version;
+mod watch_lib;
+
+use std::cell::{Cell, RefCell};
 use std::env;
+use std::io::Write;
 use std::rc::Rc;
-use std::time::Duration;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::Once;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use ansi_term::Colour::Red;
 use anyhow::anyhow;
-use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
-use mun_compiler::{Config, DisplayColor, Target};
-use mun_project::MANIFEST_FILENAME;
-use mun_runtime::{invoke_fn, ReturnTypeReflection, Runtime, RuntimeBuilder};
+use clap::{App, AppSettings, Arg, ArgMatches, Shell, SubCommand};
+use lazy_static::lazy_static;
+use mun_compiler::{
+    Config, DisplayColor, Emit, InputEncoding, LinkCommandHook, OverflowBehavior, Stage,
+    SymbolVisibility, Target,
+};
+use mun_project::{Package, MANIFEST_FILENAME};
+use mun_runtime::{invoke_fn, GcStrategy, ReloadEvent, Runtime, RuntimeBuilder};
+use return_value::OutputFormat;
+
+pub use entry_args::ArgValue;
+pub use error::DriverError;
+pub use return_value::ReturnValue;
 use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 
+/// The file extension of compiled Mun libraries
+const LIBRARY_EXTENSION: &str = "munlib";
+
+/// The file extension of Mun libraries compiled for a WebAssembly target
+const WASM_EXTENSION: &str = "wasm";
+
+/// Returns the file extension of the libraries that are compiled for `target`.
+fn library_extension(target: &Target) -> &'static str {
+    if target.options.is_like_wasm {
+        WASM_EXTENSION
+    } else {
+        LIBRARY_EXTENSION
+    }
+}
+
 #[derive(Copy, Debug, Clone, PartialEq, Eq)]
 pub enum ExitStatus {
     Success,
     Error,
+    /// An explicit exit code, e.g. the integer returned by an entry point
+    Code(i32),
 }
 
 impl Into<ExitStatus> for bool {
@@ -27,23 +94,235 @@ impl Into<ExitStatus> for bool {
     }
 }
 
-pub fn run_with_args<T, I>(args: I) -> Result<ExitStatus, anyhow::Error>
-where
-    I: IntoIterator<Item = T>,
-    T: Into<OsString> + Clone,
-{
-    let matches = App::new("mun")
+/// The outcome of a subcommand, for programs that embed the driver through [`run_with_args`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// A package or a set of source files was built.
+    Build {
+        status: ExitStatus,
+        /// The paths of the assemblies that were written, followed by the files that were written
+        /// next to them (e.g. LLVM IR, a size report or a build record), if the build succeeded
+        artifacts: Vec<PathBuf>,
+    },
+    /// An entry point was invoked by `start` or `run`.
+    Start {
+        status: ExitStatus,
+        /// The value returned by the entry point, if it returned a value that was read
+        return_value: Option<ReturnValue>,
+    },
+    /// Any other subcommand finished.
+    Other(ExitStatus),
+}
+
+/// The environment in which the command line arguments are interpreted, for programs that embed
+/// the driver through [`run_with_context`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Context {
+    /// The directory in which subcommands locate the manifest if none is specified, and against
+    /// which relative manifest paths and the other relative paths passed to `build` are resolved
+    pub working_dir: PathBuf,
+}
+
+impl Context {
+    /// Constructs a context that uses the current working directory of the process.
+    pub fn from_env() -> Result<Self, anyhow::Error> {
+        let working_dir = std::env::current_dir()
+            .map_err(|e| anyhow!("could not determine current working directory: {}", e))?;
+        Ok(Context { working_dir })
+    }
+}
+
+impl Outcome {
+    /// Returns the exit status of the subcommand.
+    pub fn exit_status(&self) -> ExitStatus {
+        match self {
+            Outcome::Build { status, .. } | Outcome::Start { status, .. } => *status,
+            Outcome::Other(status) => *status,
+        }
+    }
+}
+
+impl From<ExitStatus> for Outcome {
+    fn from(status: ExitStatus) -> Self {
+        Outcome::Other(status)
+    }
+}
+
+impl PartialEq<ExitStatus> for Outcome {
+    fn eq(&self, other: &ExitStatus) -> bool {
+        self.exit_status() == *other
+    }
+}
+
+// Help texts that are formatted at runtime. They are stored in statics because the command line
+// definition borrows them for as long as it lives.
+lazy_static! {
+    static ref MANIFEST_PATH_HELP: String = format!("Path to {}", MANIFEST_FILENAME);
+    static ref BUILD_MANIFEST_PATH_HELP: String = format!(
+        "Path to {}; repeat it or pass a glob pattern, e.g. 'packages/*/{}', to build several packages in sequence",
+        MANIFEST_FILENAME, MANIFEST_FILENAME
+    );
+    static ref MANIFEST_NAME_HELP: String = format!(
+        "the filename of the manifest to search for instead of {} (e.g. mun.ci.toml)",
+        MANIFEST_FILENAME
+    );
+    static ref ALL_PACKAGES_HELP: String = format!(
+        "build all packages with a {} in the current directory or its subdirectories",
+        MANIFEST_FILENAME
+    );
+    static ref BUILD_RECORD_HELP: String = format!(
+        "write a record of the toolchain, configuration and source hashes of the build to {} in the output directory",
+        build_record::FILENAME
+    );
+    static ref REQUIRE_FRESH_MANIFEST_HELP: String = format!(
+        "Path to the {} of the library's package, used by --require-fresh",
+        MANIFEST_FILENAME
+    );
+    static ref PACKAGE_VERSION_HELP: String = format!(
+        "the version of the package, defaults to {}",
+        scaffold::INITIAL_VERSION
+    );
+}
+
+/// Constructs the definition of the command line interface, with all subcommands and their
+/// arguments.
+pub fn build_cli() -> App<'static, 'static> {
+    App::new("mun")
         .version(env!("CARGO_PKG_VERSION"))
         .author("The Mun Project Developers")
         .about("The Mun executable enables compiling and running standalone Mun code")
-        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .setting(AppSettings::ArgRequiredElseHelp)
+        .arg(
+            Arg::with_name("explain")
+                .long("explain")
+                .takes_value(true)
+                .value_name("CODE")
+                .help("print an extended description of the diagnostic with the specified code, e.g. E002, and exit"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .long("verbose")
+                .multiple(true)
+                .global(true)
+                .help("log more details; repeat to log even more (-vvv logs everything)"),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .short("q")
+                .long("quiet")
+                .global(true)
+                .conflicts_with("verbose")
+                .help("only log errors, and omit the status messages of `build`; diagnostics are always printed"),
+        )
+        .arg(
+            Arg::with_name("log-file")
+                .long("log-file")
+                .takes_value(true)
+                .value_name("FILE")
+                .global(true)
+                .help("append the log to FILE in addition to printing it"),
+        )
         .subcommand(
             SubCommand::with_name("build")
                 .arg(
                     Arg::with_name("manifest-path")
                         .long("manifest-path")
                         .takes_value(true)
-                        .help(&format!("Path to {}", MANIFEST_FILENAME))
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help(&BUILD_MANIFEST_PATH_HELP)
+                )
+                .arg(
+                    Arg::with_name("manifest-name")
+                        .long("manifest-name")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .conflicts_with("all")
+                        .help(&MANIFEST_NAME_HELP),
+                )
+                .arg(
+                    Arg::with_name("keep-going")
+                        .long("keep-going")
+                        .help("when building several manifests, also continue after a manifest that cannot be loaded, and print a summary of the packages that were built and failed"),
+                )
+                .arg(
+                    Arg::with_name("strict-manifest")
+                        .long("strict-manifest")
+                        .conflicts_with("all")
+                        .help("validate the manifest before compiling: report unknown sections and keys, and invalid package names and versions"),
+                )
+                .arg(
+                    Arg::with_name("all")
+                        .long("all")
+                        .conflicts_with_all(&["manifest-path", "watch", "compare-opt"])
+                        .help(&ALL_PACKAGES_HELP),
+                )
+                .arg(
+                    Arg::with_name("layout")
+                        .long("layout")
+                        .takes_value(true)
+                        .possible_values(&["per-package", "flat"])
+                        .requires("all")
+                        .help("how the artifacts of multiple packages are stored: every package in a directory named after it (default), or all in the same directory"),
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .multiple(true)
+                        .conflicts_with_all(&["manifest-path", "watch", "compare-opt"])
+                        .help("source files to compile into a single module, instead of building a package"),
+                )
+                .arg(
+                    Arg::with_name("stdin")
+                        .long("stdin")
+                        .conflicts_with_all(&["INPUT", "manifest-path", "all", "watch", "compare-opt"])
+                        .help("compile source code read from stdin into stdin.munlib in the output directory (defaults to the current directory), instead of building a package"),
+                )
+                .arg(
+                    Arg::with_name("COMPILER_ARGS")
+                        .multiple(true)
+                        .last(true)
+                        .allow_hyphen_values(true)
+                        .help("raw flags that are passed to the compiler verbatim, e.g. `-- -C opt-level=0`"),
+                )
+                .arg(
+                    Arg::with_name("out")
+                        .short("o")
+                        .long("out")
+                        .takes_value(true)
+                        .requires("INPUT")
+                        .help("the path of the library compiled from the input files (defaults to the name of the first input file)"),
+                )
+                .arg(
+                    Arg::with_name("out-dir")
+                        .long("out-dir")
+                        .takes_value(true)
+                        .value_name("DIR")
+                        .conflicts_with_all(&["INPUT", "temp-out"])
+                        .help("the directory to write build artifacts to, created if it does not exist [default: target directory next to the manifest]"),
+                )
+                .arg(
+                    Arg::with_name("out-name")
+                        .long("out-name")
+                        .takes_value(true)
+                        .value_name("STEM")
+                        .conflicts_with_all(&["INPUT", "stdin"])
+                        .help("the file name, without extension, of the library compiled from main.mun (or the only source file) [default: the name of the source file]"),
+                )
+                .arg(
+                    Arg::with_name("print")
+                        .long("print")
+                        .takes_value(true)
+                        .possible_values(&["sources", "search-paths", "target-features", "config", "manifest-path"])
+                        .value_name("INFO")
+                        .conflicts_with_all(&["INPUT", "stdin", "all", "temp-out", "watch"])
+                        .help("print information about the compilation and exit without compiling: the source files, the library search paths, the features of the target, or the resolved compiler configuration or path of the manifest as `key=value` lines"),
+                )
+                .arg(
+                    Arg::with_name("temp-out")
+                        .long("temp-out")
+                        .conflicts_with("INPUT")
+                        .help("compile into a new temporary directory and print its path (to stderr with --print-artifacts); the directory is not removed afterwards"),
                 )
                 .arg(
                     Arg::with_name("watch")
@@ -51,6 +330,37 @@ where
                         .help("Run the compiler in watch mode.\
                         Watch input files and trigger recompilation on changes.",)
                 )
+                .arg(
+                    Arg::with_name("delay")
+                        .long("delay")
+                        .takes_value(true)
+                        .requires("watch")
+                        .help("how long to wait for more filesystem events before recompiling (in ms). A high delay will make recompilation less responsive. (defaults to 10 ms)"),
+                )
+                .arg(
+                    Arg::with_name("debounce-count")
+                        .long("debounce-count")
+                        .takes_value(true)
+                        .value_name("N")
+                        .requires("watch")
+                        .help("recompile as soon as N filesystem events have accumulated, without waiting for the delay to elapse (defaults to 1)"),
+                )
+                .arg(
+                    Arg::with_name("watch-full")
+                        .long("watch-full")
+                        .requires("watch")
+                        .help("recompile all modules after every change, instead of only the modules whose sources changed"),
+                )
+                .arg(
+                    Arg::with_name("watch-paths")
+                        .long("watch-paths")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("PATH")
+                        .requires("watch")
+                        .help("an additional file or directory to watch, e.g. with generated sources; a change reloads and recompiles the package; may be repeated"),
+                )
                 .arg(
                     Arg::with_name("opt-level")
                         .short("O")
@@ -58,12 +368,178 @@ where
                         .takes_value(true)
                         .help("optimize with possible levels 0-3"),
                 )
+                .arg(
+                    Arg::with_name("release")
+                        .long("release")
+                        .conflicts_with_all(&["opt-level", "compare-opt"])
+                        .help("build with all optimizations, equivalent to -O3"),
+                )
+                .arg(
+                    Arg::with_name("compare-opt")
+                        .long("compare-opt")
+                        .takes_value(true)
+                        .value_name("LEVELS")
+                        .conflicts_with_all(&["watch", "opt-level"])
+                        .help("build with each of the comma-separated optimization levels (e.g. 0,3) into separate directories and compare the resulting artifact sizes"),
+                )
+                .arg(
+                    Arg::with_name("bench-compile")
+                        .long("bench-compile")
+                        .takes_value(true)
+                        .min_values(0)
+                        .require_equals(true)
+                        .value_name("ITERATIONS")
+                        .conflicts_with_all(&["watch", "opt-level", "release", "compare-opt"])
+                        .help("compile the package from scratch ITERATIONS times (defaults to 5) at every optimization level and report the compile times, instead of building it"),
+                )
+                .arg(
+                    Arg::with_name("bench-format")
+                        .long("bench-format")
+                        .takes_value(true)
+                        .possible_values(&["text", "json"])
+                        .requires("bench-compile")
+                        .help("the format of the compile time report (defaults to text)"),
+                )
                 .arg(
                     Arg::with_name("target")
                         .long("target")
                         .takes_value(true)
                         .help("target triple for which code is compiled"),
                 )
+                .arg(
+                    Arg::with_name("data-layout")
+                        .long("data-layout")
+                        .takes_value(true)
+                        .help("override the LLVM data layout of the target (expert option, mismatching layouts result in backend errors)"),
+                )
+                .arg(
+                    Arg::with_name("cpu")
+                        .long("cpu")
+                        .takes_value(true)
+                        .help("the CPU to generate code for, `native` selects the CPU of this machine; defaults to the generic CPU of the target"),
+                )
+                .arg(
+                    Arg::with_name("target-features")
+                        .long("target-features")
+                        .takes_value(true)
+                        .help("LLVM target features to enable or disable, e.g. +avx2,-sse4.1"),
+                )
+                .arg(
+                    Arg::with_name("default-visibility")
+                        .long("default-visibility")
+                        .takes_value(true)
+                        .possible_values(&["public", "hidden"])
+                        .help("the symbol visibility of functions that are not marked `pub` [default: public]"),
+                )
+                .arg(
+                    Arg::with_name("overflow")
+                        .long("overflow")
+                        .takes_value(true)
+                        .possible_values(&["wrap", "panic", "saturate"])
+                        .help("what happens when integer addition, subtraction or multiplication overflows: wrap around, abort or clamp to the bounds of the type [default: wrap]"),
+                )
+                .arg(
+                    Arg::with_name("instrument-calls")
+                        .long("instrument-calls")
+                        .help("report every call between Mun functions to the runtime, so `start --profile` counts them"),
+                )
+                .arg(
+                    Arg::with_name("input-encoding")
+                        .long("input-encoding")
+                        .takes_value(true)
+                        .possible_values(&["utf-8", "latin-1"])
+                        .help("the encoding of the source files, which are transcoded to UTF-8 before they are parsed; a leading UTF-8 byte order mark is always ignored [default: utf-8]"),
+                )
+                .arg(
+                    Arg::with_name("check-target")
+                        .long("check-target")
+                        .help("verify that code can be generated for the target, without compiling anything"),
+                )
+                .arg(
+                    Arg::with_name("package-version")
+                        .long("package-version")
+                        .takes_value(true)
+                        .value_name("VERSION")
+                        .help("override the version of the package specified in the manifest for this build"),
+                )
+                .arg(
+                    Arg::with_name("explain-target")
+                        .long("explain-target")
+                        .takes_value(true)
+                        .value_name("TRIPLE")
+                        .help("print how the target triple is interpreted, without compiling anything"),
+                )
+                .arg(
+                    Arg::with_name("emit-build-record")
+                        .long("emit-build-record")
+                        .conflicts_with("watch")
+                        .help(&BUILD_RECORD_HELP),
+                )
+                .arg(
+                    Arg::with_name("emit-metadata")
+                        .long("emit-metadata")
+                        .conflicts_with_all(&["watch", "all"])
+                        .help("write a JSON file next to every library (<name>.metadata.json) with its ABI version, exported functions and struct layouts"),
+                )
+                .arg(
+                    Arg::with_name("capture-env")
+                        .long("capture-env")
+                        .takes_value(true)
+                        .use_delimiter(true)
+                        .value_name("VARS")
+                        .requires("emit-build-record")
+                        .help("record the values of the comma-separated environment variables in the build record, e.g. CI_COMMIT,CI_BRANCH"),
+                )
+                .arg(
+                    Arg::with_name("emit")
+                        .long("emit")
+                        .takes_value(true)
+                        .require_equals(true)
+                        .possible_values(&["munlib", "llvm-ir", "assembly", "size-report", "dep-info"])
+                        .conflicts_with_all(&["watch", "all"])
+                        .help("what to write in addition to the libraries: llvm-ir (.ll) or assembly (.s) files next to them, a report of the compiled size of every exported function, or a Makefile dependency file (<package>.d) that lists the sources of every library [default: munlib]"),
+                )
+                .arg(
+                    Arg::with_name("size-report-format")
+                        .long("size-report-format")
+                        .takes_value(true)
+                        .possible_values(&["text", "json"])
+                        .requires("emit")
+                        .help("the format of the size report, defaults to text"),
+                )
+                .arg(
+                    Arg::with_name("print-artifacts")
+                        .long("print-artifacts")
+                        .conflicts_with_all(&["watch", "all", "stdin", "temp-out", "print", "print-ast", "compare-opt", "bench-compile", "abi-changelog"])
+                        .help("print the absolute path of every file written by a successful build to stdout, one per line; status messages are printed to stderr instead"),
+                )
+                .arg(
+                    Arg::with_name("fail-on-empty")
+                        .long("fail-on-empty")
+                        .conflicts_with_all(&["watch", "all", "INPUT"])
+                        .help("fail if the build produced no artifacts, or only artifacts that export no functions"),
+                )
+                .arg(
+                    Arg::with_name("abi-changelog")
+                        .long("abi-changelog")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .conflicts_with("watch")
+                        .help("after building, print the changes to the exported ABI since the build of the specified build record"),
+                )
+                .arg(
+                    Arg::with_name("verify-build-record")
+                        .long("verify-build-record")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .conflicts_with("watch")
+                        .help("fail if the build does not match the specified build record"),
+                )
+                .arg(
+                    Arg::with_name("print-commands")
+                        .long("print-commands")
+                        .help("print the linker command equivalent to the internal invocation for every assembly that is written"),
+                )
                 .arg(
                     Arg::with_name("color")
                         .long("color")
@@ -71,185 +547,3715 @@ where
                         .possible_values(&["enable", "auto", "disable"])
                         .help("color text in terminal"),
                 )
+                .arg(
+                    Arg::with_name("notify")
+                        .long("notify")
+                        .takes_value(true)
+                        .value_name("ADDRESS")
+                        .conflicts_with("watch")
+                        .help("after a successful build, instruct the `mun start` instance listening on the control socket at ADDRESS to reload"),
+                )
+                .arg(
+                    Arg::with_name("deny-warnings")
+                        .long("deny-warnings")
+                        .overrides_with("allow-warnings")
+                        .help("fail the build if any warnings are emitted"),
+                )
+                .arg(
+                    Arg::with_name("allow-warnings")
+                        .long("allow-warnings")
+                        .overrides_with("deny-warnings")
+                        .help("only fail the build on errors, warnings are just reported [default]"),
+                )
+                .arg(
+                    Arg::with_name("group-by-file")
+                        .long("group-by-file")
+                        .conflicts_with("watch")
+                        .help("print human-readable diagnostics grouped by file and sorted by line, once the build completes"),
+                )
+                .arg(
+                    Arg::with_name("open")
+                        .long("open")
+                        .conflicts_with("watch")
+                        .help("open the output directory in the file manager after a successful build"),
+                )
+                .arg(
+                    Arg::with_name("message-format")
+                        .long("message-format")
+                        .takes_value(true)
+                        .possible_values(&["human", "json", "sarif", "github"])
+                        .default_value("human")
+                        .help("the output format for diagnostic messages"),
+                )
+                .arg(
+                    Arg::with_name("timings")
+                        .long("timings")
+                        .conflicts_with("group-by-file")
+                        .help("print how long analysis, code generation and writing the artifacts took, after every build"),
+                )
+                .arg(
+                    Arg::with_name("print-ast")
+                        .long("print-ast")
+                        .conflicts_with_all(&["INPUT", "stdin", "all", "watch"])
+                        .hidden(true)
+                        .help("print the syntax tree of every source file after parsing it, for debugging the parser"),
+                )
+                .arg(
+                    Arg::with_name("stop-after")
+                        .long("stop-after")
+                        .takes_value(true)
+                        .possible_values(&["parse", "typecheck", "codegen"])
+                        .value_name("STAGE")
+                        .hidden(true)
+                        .help("stop the build after the specified stage, succeeding if the stages that ran produced no errors [default: codegen]"),
+                )
                 .about("Compiles a local Mun file into a module"),
         )
+        .subcommand(
+            SubCommand::with_name("clean")
+                .arg(
+                    Arg::with_name("manifest-path")
+                        .long("manifest-path")
+                        .takes_value(true)
+                        .help(&MANIFEST_PATH_HELP)
+                )
+                .arg(
+                    Arg::with_name("out-dir")
+                        .long("out-dir")
+                        .takes_value(true)
+                        .value_name("DIR")
+                        .help("the directory that build artifacts were written to [default: target directory next to the manifest]"),
+                )
+                .arg(
+                    Arg::with_name("dry-run")
+                        .long("dry-run")
+                        .help("print the files that would be removed instead of removing them"),
+                )
+                .arg(
+                    Arg::with_name("yes")
+                        .short("y")
+                        .long("yes")
+                        .help("remove the files without asking for confirmation, which is required if stdin is not a terminal"),
+                )
+                .about("Removes the build artifacts of the package"),
+        )
+        .subcommand(
+            SubCommand::with_name("check")
+                .arg(
+                    Arg::with_name("manifest-path")
+                        .long("manifest-path")
+                        .takes_value(true)
+                        .help(&MANIFEST_PATH_HELP)
+                )
+                .arg(
+                    Arg::with_name("color")
+                        .long("color")
+                        .takes_value(true)
+                        .possible_values(&["enable", "auto", "disable"])
+                        .help("color text in terminal"),
+                )
+                .about("Analyzes the package and reports errors without generating code"),
+        )
+        .subcommand(
+            SubCommand::with_name("doc")
+                .arg(
+                    Arg::with_name("manifest-path")
+                        .long("manifest-path")
+                        .takes_value(true)
+                        .help(&MANIFEST_PATH_HELP)
+                )
+                .arg(
+                    Arg::with_name("out-dir")
+                        .long("out-dir")
+                        .takes_value(true)
+                        .value_name("DIR")
+                        .help("the directory to write the documentation to, created if it does not exist [default: doc in the target directory next to the manifest]"),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(&["html", "json"])
+                        .default_value("html")
+                        .help("write a static HTML page, or a JSON file with the documentation of every function and struct"),
+                )
+                .arg(
+                    Arg::with_name("color")
+                        .long("color")
+                        .takes_value(true)
+                        .possible_values(&["enable", "auto", "disable"])
+                        .help("color text in terminal"),
+                )
+                .about("Generates the documentation of the package from the doc comments of its items"),
+        )
         .subcommand(
             SubCommand::with_name("start")
                 .arg(
                     Arg::with_name("LIBRARY")
-                        .help("Sets the library to use")
-                        .required(true)
+                        .help("Sets the libraries to use; the entry point can call functions of every library through extern functions. A directory refers to the only library it contains. Defaults to the library built for the package of the manifest")
+                        .multiple(true)
                         .index(1),
                 )
+                .arg(
+                    Arg::with_name("list-supported-types")
+                        .long("list-supported-types")
+                        .help("print every return type of an entry point that can be printed, and how it is formatted"),
+                )
+                .arg(
+                    Arg::with_name("require-fresh")
+                        .long("require-fresh")
+                        .help("refuse to start if a source file of the package was modified after the library was built"),
+                )
+                .arg(
+                    Arg::with_name("manifest-path")
+                        .long("manifest-path")
+                        .takes_value(true)
+                        .help(&REQUIRE_FRESH_MANIFEST_HELP)
+                )
                 .arg(
                     Arg::with_name("entry")
                         .long("entry")
                         .takes_value(true)
-                        .help("the function entry point to call on startup"),
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("the function entry point to call on startup [default: the `default-entry` of the package, or main]; may be repeated to call several functions without arguments in order, stopping at the first that fails"),
                 )
                 .arg(
-                    Arg::with_name("delay")
-                        .long("delay")
+                    Arg::with_name("entry-index")
+                        .long("entry-index")
                         .takes_value(true)
-                        .help("how much to delay received filesystem events (in ms). This allows bundling of identical events, e.g. when several writes to the same file are detected. A high delay will make hot reloading less responsive. (defaults to 10 ms)"),
-                ),
-        )
-        .subcommand(
-            SubCommand::with_name("language-server")
-        )
-        .get_matches_from_safe(args);
-
-    match matches {
-        Ok(matches) => match matches.subcommand() {
-            ("build", Some(matches)) => build(matches),
-            ("language-server", Some(matches)) => language_server(matches),
-            ("start", Some(matches)) => start(matches).map(|_| ExitStatus::Success),
-            _ => unreachable!(),
-        },
-        Err(e) => {
-            eprint!("{}", e.message);
-            Ok(ExitStatus::Error)
-        }
-    }
-}
-
-/// Find a Mun manifest file in the specified directory or one of its parents.
-fn find_manifest(directory: &Path) -> Option<PathBuf> {
-    let mut current_dir = Some(directory);
-    while let Some(dir) = current_dir {
-        let manifest_path = dir.join(MANIFEST_FILENAME);
-        if manifest_path.exists() {
-            return Some(manifest_path);
-        }
-        current_dir = dir.parent();
-    }
-    None
-}
-
+                        .value_name("INDEX")
+                        .conflicts_with("entry")
+                        .help("call the INDEX-th (zero-based) function exported by the library on startup, instead of a function name"),
+                )
+                .arg(
+                    Arg::with_name("entry-match")
+                        .long("entry-match")
+                        .takes_value(true)
+                        .value_name("REGEX")
+                        .conflicts_with_all(&["entry", "entry-index", "args", "dry-run"])
+                        .help("call every exported function without arguments whose name matches REGEX, in order"),
+                )
+                .arg(
+                    Arg::with_name("interactive")
+                        .long("interactive")
+                        .conflicts_with_all(&["entry", "entry-index", "entry-match"])
+                        .help("list the exported functions without arguments and prompt for the one to call; ignored if stdin is not a terminal"),
+                )
+                .arg(
+                    Arg::with_name("fail-fast")
+                        .long("fail-fast")
+                        .requires("entry-match")
+                        .help("stop at the first function that fails instead of calling all matching functions"),
+                )
+                .arg(
+                    Arg::with_name("backtrace")
+                        .long("backtrace")
+                        .help("print the full backtrace if the entry point panics"),
+                )
+                .arg(
+                    Arg::with_name("color")
+                        .long("color")
+                        .takes_value(true)
+                        .possible_values(&["enable", "auto", "disable"])
+                        .help("color text in terminal"),
+                )
+                .arg(
+                    Arg::with_name("exit-code")
+                        .long("exit-code")
+                        .conflicts_with_all(&["output", "ignore-return", "entry-match", "dry-run"])
+                        .help("exit with the integer returned by the entry point, truncated to 32 bits, instead of printing it"),
+                )
+                .arg(
+                    Arg::with_name("ignore-return")
+                        .long("ignore-return")
+                        .conflicts_with("output")
+                        .help("call the entry point for its side effects only, without printing its return value"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .possible_values(&["text", "table", "json"])
+                        .help("how to print the value returned by the entry point; structs are rendered field by field"),
+                )
+                .arg(
+                    Arg::with_name("out-file")
+                        .long("out-file")
+                        .alias("entry-return-file")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .conflicts_with_all(&["exit-code", "ignore-return", "entry-match"])
+                        .help("write the value returned by the entry point to FILE, in the format selected by --output, instead of printing it; the file is overwritten"),
+                )
+                .arg(
+                    Arg::with_name("args")
+                        .long("args")
+                        .takes_value(true)
+                        .multiple(true)
+                        .allow_hyphen_values(true)
+                        .value_name("ARGS")
+                        .help("the arguments to pass to the entry point"),
+                )
+                .arg(
+                    Arg::with_name("ENTRY_ARGS")
+                        .multiple(true)
+                        .last(true)
+                        .allow_hyphen_values(true)
+                        .conflicts_with_all(&["args", "entry-match"])
+                        .help("the arguments to pass to the entry point, e.g. `--entry add -- 3 4`"),
+                )
+                .arg(
+                    Arg::with_name("args-file")
+                        .long("args-file")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .conflicts_with_all(&["args", "ENTRY_ARGS", "entry-match"])
+                        .help("read the arguments to pass to the entry point from FILE, a JSON array of booleans, numbers or strings, e.g. `[3, 4.5, true]`"),
+                )
+                .arg(
+                    Arg::with_name("dry-run")
+                        .long("dry-run")
+                        .help("verify that the arguments match the signature of the entry point, without invoking it"),
+                )
+                .arg(
+                    Arg::with_name("flush")
+                        .long("flush")
+                        .help("flush stdout after every printed result, for streaming the output to another process"),
+                )
+                .arg(
+                    Arg::with_name("delay")
+                        .long("delay")
+                        .takes_value(true)
+                        .help("how much to delay received filesystem events (in ms). This allows bundling of identical events, e.g. when several writes to the same file are detected. A high delay will make hot reloading less responsive. Overrides the MUN_RELOAD_DELAY environment variable and the `reload-delay` of the manifest. (defaults to 10 ms)"),
+                )
+                .arg(
+                    Arg::with_name("watch")
+                        .long("watch")
+                        .overrides_with("no-watch")
+                        .help("watch the library for changes and hot reload it (default)"),
+                )
+                .arg(
+                    Arg::with_name("no-watch")
+                        .long("no-watch")
+                        .overrides_with("watch")
+                        .help("do not watch the library for changes, disabling hot reloading"),
+                )
+                .arg(
+                    Arg::with_name("watch-retries")
+                        .long("watch-retries")
+                        .takes_value(true)
+                        .value_name("N")
+                        .conflicts_with("no-watch")
+                        .help("how many times to retry watching the library for changes if that fails, with an increasing delay (defaults to 3)"),
+                )
+                .arg(
+                    Arg::with_name("control-socket")
+                        .long("control-socket")
+                        .takes_value(true)
+                        .value_name("ADDRESS")
+                        .help("after invoking the entry point, keep running and listen for control commands (e.g. from `mun build --notify`) on ADDRESS (e.g. 127.0.0.1:4000)"),
+                )
+                .arg(
+                    Arg::with_name("on-invoke-error")
+                        .long("on-invoke-error")
+                        .takes_value(true)
+                        .possible_values(&["retry", "skip", "abort"])
+                        .requires("control-socket")
+                        .help("what to do when reloading and invoking the entry point for a control command fails: retry once, skip to the next command, or stop listening [default: skip]"),
+                )
+                .arg(
+                    Arg::with_name("trace-reloads")
+                        .long("trace-reloads")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("append a timestamped entry to FILE for every filesystem event and reload of the hot reloading system"),
+                )
+                .arg(
+                    Arg::with_name("reload-summary")
+                        .long("reload-summary")
+                        .help("print the number of reloads and the duration of the session when it ends"),
+                )
+                .arg(
+                    Arg::with_name("profile")
+                        .long("profile")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("write how many times every function was invoked and the time spent in it to FILE as JSON when the session ends; calls between Mun functions are only counted if the library was built with --instrument-calls"),
+                )
+                .arg(
+                    Arg::with_name("gc-deterministic")
+                        .long("gc-deterministic")
+                        .help("collect garbage at fixed points only: after every invocation of the entry point and after every reload"),
+                )
+                .arg(
+                    Arg::with_name("gc")
+                        .long("gc")
+                        .takes_value(true)
+                        .value_name("STRATEGY")
+                        .possible_values(GcStrategy::NAMES)
+                        .help("how the runtime reclaims memory; `none` never frees memory and cannot be combined with --mem-limit [default: mark-sweep]"),
+                )
+                .arg(
+                    Arg::with_name("seed")
+                        .long("seed")
+                        .takes_value(true)
+                        .value_name("U64")
+                        .help("the seed of the random number generator of the runtime, for reproducible runs; fails if the runtime has no random number generator"),
+                )
+                .arg(
+                    Arg::with_name("mem-limit")
+                        .long("mem-limit")
+                        .takes_value(true)
+                        .value_name("SIZE")
+                        .help("fail the entry point as soon as it would allocate more than SIZE memory (e.g. 512kb, 64mb); garbage is collected after every invocation"),
+                )
+                .arg(
+                    Arg::with_name("heap-size")
+                        .long("heap-size")
+                        .takes_value(true)
+                        .value_name("SIZE")
+                        .help("preallocate a heap of SIZE memory (e.g. 32mb) for the garbage collector, if it supports a fixed arena"),
+                )
+                .arg(
+                    Arg::with_name("stack-size")
+                        .long("stack-size")
+                        .takes_value(true)
+                        .value_name("SIZE")
+                        .help("run the entry point on a thread with a stack of SIZE (e.g. 16mb), for deeply recursive code"),
+                )
+                .arg(
+                    Arg::with_name("timeout")
+                        .long("timeout")
+                        .takes_value(true)
+                        .value_name("SECONDS")
+                        .help("exit with an error if the session has not finished after SECONDS, e.g. an entry point that runs an event loop"),
+                )
+                .arg(
+                    Arg::with_name("dump-layouts")
+                        .long("dump-layouts")
+                        .takes_value(true)
+                        .min_values(0)
+                        .require_equals(true)
+                        .possible_values(&["text", "json"])
+                        .hidden(true)
+                        .help("print the memory layout of all exported structs instead of invoking the entry point"),
+                )
+                .arg(
+                    Arg::with_name("dump-fn-addresses")
+                        .long("dump-fn-addresses")
+                        .help("print the address of every exported function after loading, before invoking the entry point; addresses are only meaningful within this process, e.g. to a debugger or profiler attached to it"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("language-server")
+                .arg(
+                    Arg::with_name("trace-performance")
+                        .long("trace-performance")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("write the time spent handling every message to FILE as a Chrome trace when the server shuts down"),
+                )
+                .arg(
+                    Arg::with_name("manifest-path")
+                        .long("manifest-path")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("path to the manifest of a project to load on startup, in addition to the workspace folders of the editor; may be repeated"),
+                )
+                .arg(
+                    Arg::with_name("check")
+                        .long("check")
+                        .takes_value(true)
+                        .value_name("MANIFEST_PATH")
+                        .conflicts_with_all(&["trace-performance", "manifest-path"])
+                        .help("load and analyze the project of the manifest once, print the number of diagnostics and exit instead of starting the server; fails if there are errors"),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("repl")
+                .arg(
+                    Arg::with_name("color")
+                        .long("color")
+                        .takes_value(true)
+                        .possible_values(&["enable", "auto", "disable"])
+                        .help("color diagnostics in terminal"),
+                )
+                .about("Evaluates Mun expressions interactively; definitions and `let` statements are kept for the rest of the session"),
+        )
+        .subcommand(
+            SubCommand::with_name("ast")
+                .arg(
+                    Arg::with_name("FILE")
+                        .help("the source file to parse")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("print the tree as JSON, with the kind and byte span of every node and token"),
+                )
+                .about("Prints the syntax tree of a source file"),
+        )
+        .subcommand(
+            SubCommand::with_name("bug-report")
+                .arg(
+                    Arg::with_name("manifest-path")
+                        .long("manifest-path")
+                        .takes_value(true)
+                        .help(&MANIFEST_PATH_HELP)
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("the path of the bundle [default: mun-bug-report.zip]"),
+                )
+                .about("Bundles the sources, configuration and diagnostics of the package into a zip archive for filing a bug report"),
+        )
+        .subcommand(
+            SubCommand::with_name("package")
+                .arg(
+                    Arg::with_name("manifest-path")
+                        .long("manifest-path")
+                        .takes_value(true)
+                        .help(&MANIFEST_PATH_HELP)
+                )
+                .arg(
+                    Arg::with_name("out")
+                        .long("out")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("the path of the archive [default: target/<name>-<version>.munpkg in the package]"),
+                )
+                .arg(
+                    Arg::with_name("prebuilt")
+                        .long("prebuilt")
+                        .takes_value(true)
+                        .value_name("DIR")
+                        .help("package the libraries that were already built into DIR instead of building the package"),
+                )
+                .about("Builds the package and bundles its libraries and their metadata into a single archive for distribution"),
+        )
+        .subcommand(
+            SubCommand::with_name("fix")
+                .arg(
+                    Arg::with_name("manifest-path")
+                        .long("manifest-path")
+                        .takes_value(true)
+                        .help(&MANIFEST_PATH_HELP)
+                )
+                .arg(
+                    Arg::with_name("dry-run")
+                        .long("dry-run")
+                        .help("print the fixes instead of applying them"),
+                )
+                .arg(
+                    Arg::with_name("allow-dirty")
+                        .long("allow-dirty")
+                        .help("apply fixes even if the package is not part of a clean git repository"),
+                )
+                .about("Applies the fixes suggested by the compiler to the source files of the package"),
+        )
+        .subcommand(
+            SubCommand::with_name("fmt")
+                .arg(
+                    Arg::with_name("FILES")
+                        .help("the source files to format, instead of the source files of the package")
+                        .multiple(true)
+                        .conflicts_with("manifest-path")
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("manifest-path")
+                        .long("manifest-path")
+                        .takes_value(true)
+                        .help(&MANIFEST_PATH_HELP)
+                )
+                .arg(
+                    Arg::with_name("check")
+                        .long("check")
+                        .help("fail if a source file is not formatted, instead of formatting it"),
+                )
+                .arg(
+                    Arg::with_name("diff")
+                        .long("diff")
+                        .help("print the changes that formatting would make, instead of formatting"),
+                )
+                .arg(
+                    Arg::with_name("color")
+                        .long("color")
+                        .takes_value(true)
+                        .possible_values(&["enable", "auto", "disable"])
+                        .help("color text in terminal"),
+                )
+                .about("Formats the source files of the package, or the specified source files"),
+        )
+        .subcommand(
+            SubCommand::with_name("new")
+                .arg(
+                    Arg::with_name("PATH")
+                        .help("the directory to create the package in, its name is used as the package name unless --name is specified")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("name")
+                        .long("name")
+                        .takes_value(true)
+                        .help("the name of the package, defaults to the name of the directory"),
+                )
+                .arg(
+                    Arg::with_name("author")
+                        .long("author")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("an author of the package, defaults to the user configured in git"),
+                )
+                .arg(
+                    Arg::with_name("version")
+                        .long("version")
+                        .takes_value(true)
+                        .help(&PACKAGE_VERSION_HELP),
+                )
+                .arg(
+                    Arg::with_name("license")
+                        .long("license")
+                        .takes_value(true)
+                        .help("the SPDX license expression of the package, e.g. MIT"),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .long("force")
+                        .help("create the package even if the directory is not empty, existing source files are kept"),
+                )
+                .arg(
+                    Arg::with_name("bin")
+                        .long("bin")
+                        .conflicts_with("lib")
+                        .help("create a package with a `main` entry point in src/main.mun [default]"),
+                )
+                .arg(
+                    Arg::with_name("lib")
+                        .long("lib")
+                        .help("create a library package that exports functions from src/lib.mun, without an entry point"),
+                )
+                .arg(
+                    Arg::with_name("template")
+                        .long("template")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("use the contents of FILE, which must be valid Mun, for the source file of the package instead of the built-in contents"),
+                )
+                .about("Creates a new package"),
+        )
+        .subcommand(
+            SubCommand::with_name("init")
+                .arg(
+                    Arg::with_name("PATH")
+                        .help("the existing directory to create the package in, defaults to the current directory")
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("name")
+                        .long("name")
+                        .takes_value(true)
+                        .help("the name of the package, defaults to the name of the directory"),
+                )
+                .arg(
+                    Arg::with_name("author")
+                        .long("author")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("an author of the package, defaults to the user configured in git"),
+                )
+                .arg(
+                    Arg::with_name("version")
+                        .long("version")
+                        .takes_value(true)
+                        .help(&PACKAGE_VERSION_HELP),
+                )
+                .arg(
+                    Arg::with_name("license")
+                        .long("license")
+                        .takes_value(true)
+                        .help("the SPDX license expression of the package, e.g. MIT"),
+                )
+                .about("Creates a package in an existing directory"),
+        )
+        .subcommand(
+            SubCommand::with_name("new-workspace")
+                .arg(
+                    Arg::with_name("PATH")
+                        .help("the directory to create the workspace in")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("members")
+                        .long("members")
+                        .takes_value(true)
+                        .multiple(true)
+                        .use_delimiter(true)
+                        .value_name("NAME")
+                        .help("the names of the initial member packages, each created in a subdirectory"),
+                )
+                .about("Creates a workspace of packages that `mun build --all` builds together"),
+        )
+        .subcommand(
+            SubCommand::with_name("run")
+                .arg(
+                    Arg::with_name("manifest-path")
+                        .long("manifest-path")
+                        .takes_value(true)
+                        .help(&MANIFEST_PATH_HELP)
+                )
+                .arg(
+                    Arg::with_name("opt-level")
+                        .short("O")
+                        .long("opt-level")
+                        .takes_value(true)
+                        .help("optimize with possible levels 0-3"),
+                )
+                .arg(
+                    Arg::with_name("target")
+                        .long("target")
+                        .takes_value(true)
+                        .help("target triple for which code is compiled"),
+                )
+                .arg(
+                    Arg::with_name("color")
+                        .long("color")
+                        .takes_value(true)
+                        .possible_values(&["enable", "auto", "disable"])
+                        .help("color text in terminal"),
+                )
+                .arg(
+                    Arg::with_name("entry")
+                        .long("entry")
+                        .takes_value(true)
+                        .help("the function entry point to call, defaults to the `default-entry` of the package, or main"),
+                )
+                .arg(
+                    Arg::with_name("ENTRY_ARGS")
+                        .multiple(true)
+                        .last(true)
+                        .allow_hyphen_values(true)
+                        .help("the arguments to pass to the entry point, e.g. `--entry add -- 3 4`"),
+                )
+                .arg(
+                    Arg::with_name("watch")
+                        .long("watch")
+                        .help("recompile the package whenever a source file changes and call the entry point again"),
+                )
+                .arg(
+                    Arg::with_name("max-runs")
+                        .long("max-runs")
+                        .takes_value(true)
+                        .value_name("N")
+                        .requires("watch")
+                        .help("stop watching after the entry point was called N times"),
+                )
+                .about("Compiles the local package and immediately calls its entry point"),
+        )
+        .subcommand(
+            SubCommand::with_name("profile-startup")
+                .arg(
+                    Arg::with_name("LIBRARY")
+                        .help("the library to profile")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("entry")
+                        .long("entry")
+                        .takes_value(true)
+                        .help("the function to call, defaults to main"),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("print the duration of every phase as JSON"),
+                )
+                .about("Measures the time it takes to load a library and complete the first call into it"),
+        )
+        .subcommand(
+            SubCommand::with_name("bench")
+                .arg(
+                    Arg::with_name("LIBRARY")
+                        .help("the library whose function to benchmark")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("entry")
+                        .long("entry")
+                        .takes_value(true)
+                        .help("the function to call, defaults to main"),
+                )
+                .arg(
+                    Arg::with_name("iterations")
+                        .long("iterations")
+                        .takes_value(true)
+                        .value_name("N")
+                        .help("how many times to call the function, defaults to 1000"),
+                )
+                .arg(
+                    Arg::with_name("args")
+                        .long("args")
+                        .takes_value(true)
+                        .multiple(true)
+                        .allow_hyphen_values(true)
+                        .value_name("ARGS")
+                        .help("the arguments to pass to the function"),
+                )
+                .arg(
+                    Arg::with_name("ENTRY_ARGS")
+                        .multiple(true)
+                        .last(true)
+                        .allow_hyphen_values(true)
+                        .conflicts_with("args")
+                        .help("the arguments to pass to the function, e.g. `--entry add -- 3 4`"),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("print the statistics as JSON"),
+                )
+                .about("Calls a function of a library repeatedly and reports the min, median, mean and max wall time of a call"),
+        )
+        .subcommand(
+            SubCommand::with_name("list")
+                .arg(
+                    Arg::with_name("LIBRARY")
+                        .help("the library whose exported functions to list")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("print the exported functions as JSON"),
+                )
+                .about("Lists the functions exported by a compiled library and their signatures"),
+        )
+        .subcommand(
+            SubCommand::with_name("targets")
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("print the targets as JSON, with whether each one is the host target"),
+                )
+                .about("Lists the target triples that Mun can compile for, the host target is marked with *"),
+        )
+        .subcommand(
+            SubCommand::with_name("version")
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("print the versions as JSON"),
+                )
+                .about("Prints the versions of Mun and LLVM, and the host target; `mun --version --verbose` prints the same"),
+        )
+        .subcommand(
+            SubCommand::with_name("watch-lib")
+                .arg(
+                    Arg::with_name("LIBRARY")
+                        .help("the library to watch")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("delay")
+                        .long("delay")
+                        .takes_value(true)
+                        .help("how long to wait (in ms) for file changes to settle before reloading"),
+                )
+                .about("Prints a line every time the library is reloaded, without invoking any function, until interrupted with Ctrl-C"),
+        )
+        .subcommand(
+            SubCommand::with_name("metadata")
+                .arg(
+                    Arg::with_name("manifest-path")
+                        .long("manifest-path")
+                        .takes_value(true)
+                        .help(&MANIFEST_PATH_HELP)
+                )
+                .about("Prints the metadata of the package as JSON, without building it"),
+        )
+        .subcommand(
+            SubCommand::with_name("test")
+                .arg(
+                    Arg::with_name("manifest-path")
+                        .long("manifest-path")
+                        .takes_value(true)
+                        .help(&MANIFEST_PATH_HELP)
+                )
+                .arg(
+                    Arg::with_name("filter")
+                        .long("filter")
+                        .takes_value(true)
+                        .value_name("PATTERN")
+                        .help("only run the tests whose name contains the pattern"),
+                )
+                .arg(
+                    Arg::with_name("watch")
+                        .long("watch")
+                        .help("recompile and rerun the tests whenever a source file changes"),
+                )
+                .arg(
+                    Arg::with_name("color")
+                        .long("color")
+                        .takes_value(true)
+                        .possible_values(&["enable", "auto", "disable"])
+                        .help("color text in terminal"),
+                )
+                .about("Builds the package and runs its public functions prefixed with `test_`"),
+        )
+        .subcommand(
+            SubCommand::with_name("abi-check")
+                .arg(
+                    Arg::with_name("manifest-path")
+                        .long("manifest-path")
+                        .takes_value(true)
+                        .help(&MANIFEST_PATH_HELP)
+                )
+                .arg(
+                    Arg::with_name("baseline")
+                        .long("baseline")
+                        .takes_value(true)
+                        .required(true)
+                        .value_name("FILE")
+                        .help("the JSON file containing the baseline ABI"),
+                )
+                .arg(
+                    Arg::with_name("update-baseline")
+                        .long("update-baseline")
+                        .help("write the ABI of the current build to the baseline file instead of comparing against it"),
+                )
+                .about("Builds the package and checks its ABI for breaking changes against a baseline"),
+        )
+        .subcommand(
+            SubCommand::with_name("completions")
+                .arg(
+                    Arg::with_name("SHELL")
+                        .help("the shell to generate the completion script for")
+                        .required(true)
+                        .possible_values(&["bash", "zsh", "fish", "powershell"])
+                        .index(1),
+                )
+                .about("Writes a completion script for mun to stdout"),
+        )
+}
+
+pub fn run_with_args<T, I>(args: I) -> Result<Outcome, anyhow::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    run_with_context(args, &Context::from_env()?)
+}
+
+/// Runs the driver with the specified command line arguments in the specified `context`, instead
+/// of the current working directory of the process.
+pub fn run_with_context<T, I>(args: I, context: &Context) -> Result<Outcome, anyhow::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let process_start = Instant::now();
+    install_panic_hook();
+    let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+    let matches = build_cli().get_matches_from_safe(&args);
+
+    match matches {
+        // The explanation of a diagnostic code replaces the subcommand
+        Ok(matches) if matches.is_present("explain") => {
+            explain(matches.value_of("explain").unwrap()).map(Into::into)
+        }
+        Ok(matches) if matches.subcommand_name().is_none() => {
+            eprintln!(
+                "error: a subcommand is required\n\n{}\n\nFor more information try --help",
+                matches.usage()
+            );
+            Ok(ExitStatus::Error.into())
+        }
+        Ok(matches) => {
+            init_logger(&matches)?;
+            let outcome = run_subcommand(&matches, context, process_start);
+            // The logger is never dropped, so flush the log file explicitly, also on errors
+            log::logger().flush();
+            outcome
+        }
+        Err(e) if e.kind == clap::ErrorKind::VersionDisplayed => {
+            // Clap has already printed the first line, `mun <version>`
+            if args.iter().any(|arg| arg == "-v" || arg == "--verbose") {
+                print!("{}", version::VersionInfo::current().details_to_text());
+            }
+            Ok(ExitStatus::Success.into())
+        }
+        Err(e) => {
+            eprint!("{}", e.message);
+            Ok(ExitStatus::Error.into())
+        }
+    }
+}
+
+/// Prints the extended description of the diagnostic with the specified `code`.
+fn explain(code: &str) -> Result<ExitStatus, anyhow::Error> {
+    let diagnostic = mun_compiler::diagnostic_codes::find(code)
+        .ok_or_else(|| anyhow!("'{}' is not a known diagnostic code", code))?;
+    print!("{}", diagnostic.to_text());
+    Ok(ExitStatus::Success)
+}
+
+/// Runs the subcommand selected by the command line arguments in `matches`.
+fn run_subcommand(
+    matches: &ArgMatches<'static>,
+    context: &Context,
+    process_start: Instant,
+) -> Result<Outcome, anyhow::Error> {
+    match matches.subcommand() {
+        ("build", Some(matches)) => {
+            let outcome = match build(matches, context) {
+                Err(e) => match backend_unavailable_message(&e, display_color(matches)) {
+                    Some(message) => {
+                        eprintln!("{}", message);
+                        return Ok(ExitStatus::Error.into());
+                    }
+                    None => return Err(e),
+                },
+                Ok(outcome) => outcome,
+            };
+            if matches.is_present("print-artifacts") {
+                print_artifacts(&outcome, &mut std::io::stdout())?;
+            }
+            return Ok(outcome);
+        }
+        ("start", Some(matches)) => {
+            let stack_size = matches.value_of("stack-size").map(parse_size).transpose()?;
+            let timeout = matches.value_of("timeout").map(parse_timeout).transpose()?;
+            return if stack_size.is_none() && timeout.is_none() {
+                start(matches, context)
+            } else {
+                start_on_thread(stack_size, timeout, matches.clone(), context.clone())
+            };
+        }
+        ("run", Some(matches)) => return run(matches, context),
+        ("abi-check", Some(matches)) => abi_check(matches, context),
+        ("bench", Some(matches)) => bench(matches),
+        ("ast", Some(matches)) => ast(matches),
+        ("bug-report", Some(matches)) => bug_report(matches, context),
+        ("check", Some(matches)) => check(matches, context),
+        ("clean", Some(matches)) => clean(matches, context),
+        ("completions", Some(matches)) => completions(matches),
+        ("doc", Some(matches)) => doc(matches, context),
+        ("fix", Some(matches)) => fix(matches, context),
+        ("fmt", Some(matches)) => fmt(matches, context),
+        ("init", Some(matches)) => init(matches),
+        ("language-server", Some(matches)) => language_server(matches, context),
+        ("list", Some(matches)) => list(matches),
+        ("metadata", Some(matches)) => metadata(matches, context),
+        ("new", Some(matches)) => new(matches),
+        ("new-workspace", Some(matches)) => new_workspace(matches),
+        ("package", Some(matches)) => package(matches, context),
+        ("profile-startup", Some(matches)) => profile_startup(matches, process_start),
+        ("repl", Some(matches)) => repl(matches),
+        ("targets", Some(matches)) => targets(matches),
+        ("test", Some(matches)) => test(matches, context),
+        ("version", Some(matches)) => version(matches),
+        ("watch-lib", Some(matches)) => watch_lib(matches),
+        _ => unreachable!(),
+    }
+    .map(Outcome::from)
+}
+
+/// Returns the maximum level of log messages for the number of `--verbose` flags, or `--quiet`.
+/// By default warnings and errors are logged.
+fn log_level(verbosity: u64, quiet: bool) -> log::LevelFilter {
+    if quiet {
+        return log::LevelFilter::Error;
+    }
+    match verbosity {
+        0 => log::LevelFilter::Warn,
+        1 => log::LevelFilter::Info,
+        2 => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Trace,
+    }
+}
+
+/// Initializes the logger from `--verbose`, `--quiet` and `--log-file`, which may be specified
+/// before or after the subcommand. Without `--verbose` or `--quiet`, the filters in the `RUST_LOG`
+/// environment variable are used if it is set. Nothing happens if a logger was already
+/// initialized.
+fn init_logger(matches: &ArgMatches) -> Result<(), anyhow::Error> {
+    let sub_matches = matches.subcommand().1;
+    let verbosity = matches
+        .occurrences_of("verbose")
+        .max(sub_matches.map_or(0, |m| m.occurrences_of("verbose")));
+    let quiet = matches.is_present("quiet") || sub_matches.map_or(false, |m| m.is_present("quiet"));
+
+    let mut builder = pretty_env_logger::formatted_builder();
+    match env::var("RUST_LOG") {
+        Ok(filters) if verbosity == 0 && !quiet => builder.parse_filters(&filters),
+        _ => builder.filter_level(log_level(verbosity, quiet)),
+    };
+
+    let log_file = matches
+        .value_of_os("log-file")
+        .or_else(|| sub_matches.and_then(|m| m.value_of_os("log-file")));
+    match log_file {
+        Some(path) => {
+            let logger = log_file::TeeLogger::new(builder.build(), Path::new(path))?;
+            let max_level = logger.max_level();
+            if log::set_boxed_logger(Box::new(logger)).is_ok() {
+                log::set_max_level(max_level);
+            }
+        }
+        None => {
+            let _ = builder.try_init();
+        }
+    }
+    Ok(())
+}
+
+/// Find a Mun manifest file called `manifest_name` in the specified directory or one of its
+/// parents.
+fn find_manifest(directory: &Path, manifest_name: &str) -> Option<PathBuf> {
+    let mut current_dir = Some(directory);
+    while let Some(dir) = current_dir {
+        let manifest_path = dir.join(manifest_name);
+        if manifest_path.exists() {
+            return Some(manifest_path);
+        }
+        current_dir = dir.parent();
+    }
+    None
+}
+
+/// Returns the canonicalized `manifest_path`, resolved against `working_dir` if it is relative,
+/// or if none is specified, locates the manifest in `working_dir` or one of its parents. If
+/// `manifest_path` refers to a directory, the manifest called `manifest_name` in that directory is
+/// used.
+fn locate_manifest(
+    working_dir: &Path,
+    manifest_path: Option<&str>,
+    manifest_name: &str,
+) -> Result<PathBuf, anyhow::Error> {
+    let manifest_path = match manifest_path {
+        None => find_manifest(working_dir, manifest_name).ok_or_else(|| {
+            DriverError::ManifestNotFound {
+                manifest_name: manifest_name.to_owned(),
+                directory: working_dir.to_path_buf(),
+            }
+        })?,
+        Some(path) => {
+            let path = working_dir.join(path);
+            let path = if path.is_dir() {
+                path.join(manifest_name)
+            } else {
+                path
+            };
+            std::fs::canonicalize(&path)
+                .map_err(|_| DriverError::InvalidManifestPath(path.clone()))?
+        }
+    };
+
+    log::info!("located build manifest at: {}", manifest_path.display());
+    Ok(manifest_path)
+}
+
 /// This method is invoked when the executable is run with the `build` argument indicating that a
 /// user requested us to build a project in the current directory or one of its parent directories.
 ///
-/// The `bool` return type for this function indicates whether the process should exit with a
-/// success or failure error code.
-fn build(matches: &ArgMatches) -> Result<ExitStatus, anyhow::Error> {
-    log::trace!("starting build");
+/// The `bool` return type for this function indicates whether the process should exit with a
+/// success or failure error code.
+fn build(matches: &ArgMatches, context: &Context) -> Result<Outcome, anyhow::Error> {
+    log::trace!("starting build");
+
+    if matches.is_present("check-target") {
+        return check_target(matches.value_of("target")).map(Into::into);
+    }
+
+    if let Some(triple) = matches.value_of("explain-target") {
+        return explain_target(triple).map(Into::into);
+    }
+
+    let manifest_name = matches
+        .value_of("manifest-name")
+        .unwrap_or(MANIFEST_FILENAME);
+    match matches.value_of("print") {
+        Some("sources") => {
+            return print_sources(&locate_manifest(
+                &context.working_dir,
+                matches.value_of("manifest-path"),
+                manifest_name,
+            )?)
+            .map(Into::into)
+        }
+        Some("search-paths") => {
+            return print_search_paths(
+                &locate_manifest(
+                    &context.working_dir,
+                    matches.value_of("manifest-path"),
+                    manifest_name,
+                )?,
+                matches.value_of("out-dir").map(Path::new),
+            )
+            .map(Into::into)
+        }
+        Some("target-features") => {
+            return print_target_features(matches.value_of("target")).map(Into::into)
+        }
+        _ => {}
+    }
+
+    let mut options = compiler_options(matches)?;
+    options.out_dir = options
+        .out_dir
+        .map(|out_dir| context.working_dir.join(out_dir));
+    ensure_backend(&options)?;
+    if matches.is_present("temp-out") {
+        let temp_dir = tempdir::TempDir::new("mun_build")
+            .map_err(|e| anyhow!("could not create temporary output directory: {}", e))?;
+        let temp_dir = temp_dir.into_path();
+        // With `--print-artifacts` stdout only lists the artifacts
+        if matches.is_present("print-artifacts") {
+            eprintln!("{}", temp_dir.display());
+        } else {
+            println!("{}", temp_dir.display());
+        }
+        options.out_dir = Some(temp_dir);
+    }
+    if let Some(out_dir) = &options.out_dir {
+        std::fs::create_dir_all(out_dir).map_err(|e| {
+            anyhow!(
+                "could not create output directory '{}': {}",
+                out_dir.display(),
+                e
+            )
+        })?;
+    }
+
+    // Compile source code from stdin without a manifest
+    if matches.is_present("stdin") {
+        return multi_file::compile_reader(&mut std::io::stdin(), options)
+            .map(|success| build_outcome(success, Vec::new()));
+    }
+
+    // Compile explicitly listed files without a manifest
+    if let Some(inputs) = matches.values_of("INPUT") {
+        let inputs: Vec<PathBuf> = inputs.map(PathBuf::from).collect();
+        let output_path = matches.value_of("out").map_or_else(
+            || inputs[0].with_extension(library_extension(&options.target)),
+            PathBuf::from,
+        );
+        return multi_file::compile_files(&inputs, &output_path, options)
+            .map(|success| build_outcome(success, vec![output_path]));
+    }
+
+    // Build all packages in the current directory tree
+    if matches.is_present("all") {
+        let current_dir = &context.working_dir;
+        let out_dir = options
+            .out_dir
+            .clone()
+            .unwrap_or_else(|| current_dir.join("target"));
+        let layout = match matches.value_of("layout") {
+            Some("flat") => multi_package::Layout::Flat,
+            _ => multi_package::Layout::PerPackage,
+        };
+        return multi_package::build_all(current_dir, &out_dir, options, layout)
+            .map(|success| build_outcome(success, Vec::new()));
+    }
+
+    // Build several packages in sequence
+    let manifest_paths: Vec<&str> = matches
+        .values_of("manifest-path")
+        .map_or_else(Vec::new, Iterator::collect);
+    if manifest_paths.len() > 1 || manifest_paths.iter().any(|path| is_glob(path)) {
+        return build_manifests(&manifest_paths, manifest_name, options, matches, context);
+    }
+
+    let manifest_path = locate_manifest(
+        &context.working_dir,
+        matches.value_of("manifest-path"),
+        manifest_name,
+    )?;
+    if matches.is_present("strict-manifest")
+        && !manifest_check::check_manifest_file(&manifest_path)?
+    {
+        return Ok(ExitStatus::Error.into());
+    }
+    let manifest = mun_project::Manifest::from_file(&manifest_path)?;
+    apply_build_defaults(&mut options, matches, manifest.build_defaults())?;
+    if manifest.build_defaults().target.is_some() {
+        ensure_backend(&options)?;
+    }
+
+    match matches.value_of("print") {
+        Some("config") => {
+            print!("{}", render_config(&options, &manifest_path));
+            return Ok(ExitStatus::Success.into());
+        }
+        Some("manifest-path") => {
+            println!("manifest-path={}", manifest_path.display());
+            return Ok(ExitStatus::Success.into());
+        }
+        _ => (),
+    }
+
+    if matches.is_present("print-ast") {
+        print_syntax_trees(&manifest_path, &mut std::io::stdout())?;
+    }
+
+    if let Some(levels) = matches.value_of("compare-opt") {
+        return compare_opt::compare_opt_levels(
+            &manifest_path,
+            options,
+            levels,
+            &mut std::io::stdout(),
+        )
+        .map(Into::into);
+    }
+
+    if matches.is_present("bench-compile") {
+        return bench_compile::print_bench_compile(
+            &manifest_path,
+            options,
+            matches.value_of("bench-compile"),
+            matches.value_of("bench-format").unwrap_or("text"),
+        )
+        .map(Into::into);
+    }
+
+    // Path dependencies have to be compiled before the package that depends on them
+    if !mun_compiler::compile_dependencies(&manifest_path, &options)? {
+        return Ok(build_outcome(false, Vec::new()));
+    }
+
+    let out_dir = options.out_dir.clone().unwrap_or_else(|| {
+        manifest_path
+            .parent()
+            .expect("manifest path must have a parent directory")
+            .join("target")
+    });
+
+    let (success, driver) = message_format::compile(
+        matches,
+        &manifest_path,
+        options.clone(),
+        &context.working_dir,
+    )?;
+    if !success {
+        return Ok(build_outcome(false, Vec::new()));
+    }
+    post_build::run(matches, &manifest_path, &out_dir, &options, driver)
+        .map(|(success, artifacts)| build_outcome(success, artifacts))
+}
+
+/// Writes the absolute path of every artifact of a successful build to `out`, one per line.
+fn print_artifacts(outcome: &Outcome, out: &mut dyn Write) -> Result<(), anyhow::Error> {
+    if let Outcome::Build {
+        status: ExitStatus::Success,
+        artifacts,
+    } = outcome
+    {
+        for artifact in artifacts {
+            let path = std::fs::canonicalize(artifact)
+                .map_err(|e| anyhow!("could not resolve '{}': {}", artifact.display(), e))?;
+            writeln!(out, "{}", path.display())?;
+        }
+    }
+    Ok(())
+}
+
+/// Compiles the package at `manifest_path`. If `timings` is set, how long every phase took is
+/// written to `out` afterwards. Returns `false` if errors were emitted, together with the driver
+/// that compiled the package.
+fn compile_manifest_timed(
+    manifest_path: &Path,
+    config: Config,
+    timings: bool,
+    out: &mut dyn Write,
+) -> Result<(bool, mun_compiler::Driver), anyhow::Error> {
+    let (success, driver, build_timings) =
+        mun_compiler::compile_manifest_with_timings(manifest_path, config)?;
+    if timings {
+        write!(out, "{}", build_timings.to_text())?;
+    }
+    Ok((success, driver))
+}
+
+/// Parses every source file of the package at `manifest_path` and writes its syntax tree to `out`,
+/// preceded by the path of the file. Returns `false` if any of the files contains syntax errors.
+fn print_syntax_trees(manifest_path: &Path, out: &mut dyn Write) -> Result<bool, anyhow::Error> {
+    let package = Package::from_file(manifest_path)?;
+    let source_directory = package
+        .source_directory()
+        .ok_or_else(|| anyhow!("the source directory does not exist"))?;
+    let mut source_files: Vec<PathBuf> =
+        mun_compiler::iter_package_source_files(package.root(), &source_directory)?.collect();
+    source_files.sort();
+
+    let mut success = true;
+    for path in source_files {
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("could not read source file '{}': {}", path.display(), e))?;
+        let parse = mun_syntax::SourceFile::parse(&text);
+        writeln!(
+            out,
+            "// {}",
+            path.strip_prefix(&source_directory)
+                .unwrap_or(&path)
+                .display()
+        )?;
+        write!(out, "{}", parse.debug_dump())?;
+        success &= parse.errors().is_empty();
+    }
+    Ok(success)
+}
+
+/// Returns true if `path` contains glob wildcards.
+fn is_glob(path: &str) -> bool {
+    path.contains(|c| c == '*' || c == '?' || c == '[')
+}
+
+/// Builds the packages of all manifests in `manifest_paths`, which may contain glob patterns, one
+/// after the other. Every build is preceded by the name of the package, and failed packages are
+/// listed at the end. Fails if any of the packages fails to build.
+fn build_manifests(
+    manifest_paths: &[&str],
+    manifest_name: &str,
+    options: Config,
+    matches: &ArgMatches,
+    context: &Context,
+) -> Result<Outcome, anyhow::Error> {
+    for flag in &[
+        "watch",
+        "print",
+        "compare-opt",
+        "bench-compile",
+        "message-format",
+        "strict-manifest",
+    ] {
+        // `message-format` has a default value, which makes it present even if not specified
+        if matches.occurrences_of(flag) > 0 {
+            return Err(anyhow!(
+                "--{} is not supported when building multiple manifests",
+                flag
+            ));
+        }
+    }
+
+    let keep_going = matches.is_present("keep-going");
+    // The package, or the manifest path if it could not be loaded, and whether it was built
+    let mut units: Vec<(String, bool)> = Vec::new();
+    let mut expanded: Vec<PathBuf> = Vec::new();
+    for path in manifest_paths {
+        let matched = if is_glob(path) {
+            let pattern = context.working_dir.join(path);
+            let matched = glob::glob(&pattern.to_string_lossy())
+                .map_err(|e| anyhow!("invalid glob pattern '{}': {}", path, e))?
+                .collect::<Result<Vec<_>, _>>()?;
+            if matched.is_empty() {
+                return Err(anyhow!("no manifests match '{}'", path));
+            }
+            matched
+        } else {
+            vec![PathBuf::from(path)]
+        };
+        for path in matched {
+            // A package is built once, even if several paths refer to it
+            match locate_manifest(&context.working_dir, path.to_str(), manifest_name) {
+                Ok(manifest_path) if !expanded.contains(&manifest_path) => {
+                    expanded.push(manifest_path)
+                }
+                Ok(_) => {}
+                Err(e) if keep_going => {
+                    eprintln!("error: {}", e);
+                    units.push((path.display().to_string(), false));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    for manifest_path in expanded.iter() {
+        let manifest = match mun_project::Manifest::from_file(manifest_path) {
+            Ok(manifest) => manifest,
+            Err(e) if keep_going => {
+                eprintln!("error: {}: {}", manifest_path.display(), e);
+                units.push((manifest_path.display().to_string(), false));
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if !matches.is_present("quiet") {
+            eprintln!(
+                "Building {} ({})",
+                manifest.package_id(),
+                manifest_path.display()
+            );
+        }
+        let success = match build_manifest(manifest_path, &manifest, options.clone(), matches) {
+            Ok(success) => success,
+            Err(e) if keep_going => {
+                eprintln!("error: {}", e);
+                false
+            }
+            Err(e) => return Err(e),
+        };
+        units.push((manifest.package_id().to_string(), success));
+    }
+
+    if keep_going {
+        eprint!("{}", render_build_summary(&units));
+    }
+    let failed: Vec<&str> = units
+        .iter()
+        .filter(|(_, success)| !success)
+        .map(|(unit, _)| unit.as_str())
+        .collect();
+    if !failed.is_empty() {
+        eprintln!(
+            "error: {} of {} packages failed to build: {}",
+            failed.len(),
+            units.len(),
+            failed.join(", ")
+        );
+    }
+    Ok(build_outcome(failed.is_empty(), Vec::new()))
+}
+
+/// Builds the dependencies of the package at `manifest_path` and then the package itself, as one of
+/// several manifests. Returns `false` if either failed to compile.
+fn build_manifest(
+    manifest_path: &Path,
+    manifest: &mun_project::Manifest,
+    mut options: Config,
+    matches: &ArgMatches,
+) -> Result<bool, anyhow::Error> {
+    apply_build_defaults(&mut options, matches, manifest.build_defaults())?;
+    Ok(mun_compiler::compile_dependencies(manifest_path, &options)?
+        && compile_manifest_timed(
+            manifest_path,
+            options,
+            matches.is_present("timings"),
+            &mut std::io::stderr(),
+        )?
+        .0)
+}
+
+/// Renders which of the packages built by `build --keep-going` succeeded and which failed, in
+/// the order in which they were built.
+fn render_build_summary(units: &[(String, bool)]) -> String {
+    let mut summary = String::from("\nsummary:\n");
+    for (unit, success) in units {
+        let result = if *success { "ok" } else { "FAILED" };
+        summary.push_str(&format!("    {} ... {}\n", unit, result));
+    }
+    summary
+}
+
+/// Constructs the outcome of a build from whether it succeeded and the assemblies it wrote.
+fn build_outcome(success: bool, artifacts: Vec<PathBuf>) -> Outcome {
+    Outcome::Build {
+        status: success.into(),
+        artifacts,
+    }
+}
+
+/// Prints the absolute paths of the source files of the package at `manifest_path` as a JSON
+/// array, in the order in which they are passed to the compiler.
+fn print_sources(manifest_path: &Path) -> Result<ExitStatus, anyhow::Error> {
+    let package = Package::from_file(manifest_path)?;
+    let source_dir = package
+        .source_directory()
+        .ok_or_else(|| anyhow!("the source directory of package {} does not exist", package))?;
+
+    let sources: Vec<PathBuf> =
+        mun_compiler::iter_package_source_files(package.root(), &source_dir)?.collect();
+    println!("{}", serde_json::to_string_pretty(&sources)?);
+    Ok(ExitStatus::Success)
+}
+
+/// Prints every directory that building the package at `manifest_path` uses, labeled with its
+/// purpose, in the order in which they are used: the package root, the source directory of every
+/// dependency, the source directory of the package and the output directory.
+fn print_search_paths(
+    manifest_path: &Path,
+    out_dir: Option<&Path>,
+) -> Result<ExitStatus, anyhow::Error> {
+    let package = Package::from_file(manifest_path)?;
+    let describe = |path: PathBuf| {
+        if path.is_dir() {
+            path.display().to_string()
+        } else {
+            format!("{} (missing)", path.display())
+        }
+    };
+
+    println!(
+        "{:<24}{}",
+        "package root",
+        describe(package.root().to_path_buf())
+    );
+    for dependency in package.resolve_dependencies()? {
+        println!(
+            "{:<24}{}",
+            format!("dependency {}", dependency.name()),
+            describe(dependency.root().join("src"))
+        );
+    }
+    println!("{:<24}{}", "sources", describe(package.root().join("src")));
+    println!(
+        "{:<24}{}",
+        "output",
+        describe(out_dir.map_or_else(|| package.root().join("target"), Path::to_path_buf))
+    );
+    Ok(ExitStatus::Success)
+}
+
+/// Prints the triples of all supported targets, marking the host target.
+fn targets(matches: &ArgMatches) -> Result<ExitStatus, anyhow::Error> {
+    let triples: Vec<String> = mun_target::spec::get_targets().collect();
+    let host = Target::host_target()
+        .ok()
+        .map(|_| mun_target::host_triple());
+
+    if matches.is_present("json") {
+        let targets: Vec<serde_json::Value> = triples
+            .iter()
+            .map(|triple| {
+                serde_json::json!({
+                    "triple": triple,
+                    "host": Some(triple.as_str()) == host,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&targets)?);
+    } else {
+        print!("{}", render_targets(&triples, host));
+    }
+    Ok(ExitStatus::Success)
+}
+
+/// Prints the versions of Mun and LLVM, and the host target.
+fn version(matches: &ArgMatches) -> Result<ExitStatus, anyhow::Error> {
+    let info = version::VersionInfo::current();
+    if matches.is_present("json") {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else {
+        print!("{}", info.to_text());
+    }
+    Ok(ExitStatus::Success)
+}
+
+/// Renders a line per target triple, with the `host` triple marked with an asterisk.
+fn render_targets(triples: &[String], host: Option<&str>) -> String {
+    triples
+        .iter()
+        .map(|triple| {
+            let marker = if Some(triple.as_str()) == host {
+                '*'
+            } else {
+                ' '
+            };
+            format!("{} {}\n", marker, triple)
+        })
+        .collect()
+}
+
+/// Writes a completion script for the specified shell to stdout.
+fn completions(matches: &ArgMatches) -> Result<ExitStatus, anyhow::Error> {
+    let shell: Shell = matches
+        .value_of("SHELL")
+        .unwrap() // Safe because its a required arg
+        .parse()
+        .map_err(|e| anyhow!("{}", e))?;
+    build_cli().gen_completions_to("mun", shell, &mut std::io::stdout());
+    Ok(ExitStatus::Success)
+}
+
+/// Prints the components and properties of the target with the specified triple.
+fn explain_target(triple: &str) -> Result<ExitStatus, anyhow::Error> {
+    let target = match Target::search(triple) {
+        Ok(target) => target,
+        Err(e) => {
+            eprintln!(
+                "error: {}\nnote: supported targets are: {}",
+                e,
+                mun_target::spec::get_targets()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            return Ok(ExitStatus::Error);
+        }
+    };
+
+    println!("triple: {}", target.llvm_target);
+    println!("arch: {}", target.arch);
+    println!("vendor: {}", target.target_vendor);
+    println!("os: {}", target.target_os);
+    println!("env: {}", target.target_env);
+    println!("endian: {}", target.target_endian);
+    println!("pointer width: {}", target.target_pointer_width);
+    println!("data layout: {}", target.data_layout);
+    Ok(ExitStatus::Success)
+}
+
+/// Prints the default target features that are enabled for the target with the specified triple,
+/// or the host target if no triple is specified.
+fn print_target_features(triple: Option<&str>) -> Result<ExitStatus, anyhow::Error> {
+    let target = triple.map_or_else(Target::host_target, Target::search)?;
+
+    println!("target: {}", target.llvm_target);
+    println!("cpu: {}", target.options.cpu);
+    println!("features:");
+    for feature in target
+        .options
+        .features
+        .split(',')
+        .filter(|feature| !feature.is_empty())
+    {
+        println!("    {}", feature);
+    }
+    Ok(ExitStatus::Success)
+}
+
+/// Fails with [`mun_compiler::BackendUnavailable`] if code has to be generated for a target that
+/// the LLVM backend does not support, before compilation panics on it.
+fn ensure_backend(config: &Config) -> Result<(), anyhow::Error> {
+    if config.stop_after < Stage::Codegen {
+        return Ok(());
+    }
+    mun_compiler::check_target(&config.target)
+}
+
+/// Returns the guidance that is printed instead of `error` if it was caused by an unavailable code
+/// generation backend, or `None` for any other error.
+fn backend_unavailable_message(
+    error: &anyhow::Error,
+    display_color: DisplayColor,
+) -> Option<String> {
+    let unavailable = error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<mun_compiler::BackendUnavailable>())?;
+    Some(format!(
+        "{}
+note: {}
+help: this build of Mun uses LLVM {} without a backend for the target; install a Mun release that supports it, or build Mun against an LLVM with the backend enabled
+help: run `mun build --check-target` to verify a target",
+        error_message(
+            format!(
+                "cannot generate code for target {}, the code generation backend is unavailable",
+                unavailable.triple
+            ),
+            display_color.should_enable()
+        ),
+        unavailable.reason,
+        mun_compiler::LLVM_VERSION
+    ))
+}
+
+/// Verifies that code can be generated for the target with the specified triple, or the host
+/// target if no triple is specified, and reports the result.
+fn check_target(triple: Option<&str>) -> Result<ExitStatus, anyhow::Error> {
+    let target = match triple.map_or_else(Target::host_target, Target::search) {
+        Ok(target) => target,
+        Err(e) => {
+            eprintln!(
+                "error: {}\nnote: Mun does not have a specification for this target",
+                e
+            );
+            return Ok(ExitStatus::Error);
+        }
+    };
+
+    match mun_compiler::check_target(&target) {
+        Ok(()) => {
+            println!("target {} is supported", target.llvm_target);
+            Ok(ExitStatus::Success)
+        }
+        Err(e) => {
+            eprintln!(
+                "error: target {} is not supported by the LLVM backend: {}\nnote: make sure Mun is built against an LLVM with the {} backend enabled",
+                target.llvm_target, e, target.arch
+            );
+            Ok(ExitStatus::Error)
+        }
+    }
+}
+
+/// Builds the package and runs its tests, optionally rerunning them on every change.
+fn test(matches: &ArgMatches, context: &Context) -> Result<ExitStatus, anyhow::Error> {
+    let options = compiler_options(matches)?;
+    let manifest_path = locate_manifest(
+        &context.working_dir,
+        matches.value_of("manifest-path"),
+        MANIFEST_FILENAME,
+    )?;
+    let filter = matches.value_of("filter");
+
+    if matches.is_present("watch") {
+        return mun_compiler_daemon::compile_and_watch_manifest_with(
+            &manifest_path,
+            options,
+            mun_compiler_daemon::WatchConfig::default(),
+            |driver| {
+                println!("\nrunning tests");
+                test_runner::run_tests(&driver.assembly_output_paths(), filter).map(|_| ())
+            },
+        )
+        .map(Into::into);
+    }
+
+    let (_package, mut driver) = mun_compiler::Driver::with_package_path(&manifest_path, options)?;
+    if driver.emit_diagnostics(&mut std::io::stderr())? {
+        return Ok(ExitStatus::Error);
+    }
+    driver.write_all_assemblies()?;
+
+    let summary = test_runner::run_tests(&driver.assembly_output_paths(), filter)?;
+    Ok(summary.is_success().into())
+}
+
+/// Reads lines from stdin and evaluates each of them, printing the value of every expression, until
+/// the end of the input or `:quit`. Errors are reported without ending the session.
+fn repl(matches: &ArgMatches) -> Result<ExitStatus, anyhow::Error> {
+    let config = Config {
+        display_color: display_color(matches),
+        ..Config::default()
+    };
+    let mut session = repl::Repl::new(config)?;
+    println!(
+        "Mun {} - enter an expression, a definition or `let` statement, or :quit to exit",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    let stdin = std::io::stdin();
+    loop {
+        print!("> ");
+        std::io::stdout().flush()?;
+
+        let mut line = String::new();
+        if stdin.read_line(&mut line)? == 0 {
+            println!();
+            break;
+        }
+        match line.trim() {
+            "" => continue,
+            ":quit" | ":q" => break,
+            _ => {}
+        }
+
+        match session.eval(&line, &mut std::io::stderr()) {
+            Ok(repl::Evaluation::Value(value)) => println!("{}", value),
+            Ok(_) => {}
+            Err(e) => eprintln!("error: {}", e),
+        }
+    }
+    Ok(ExitStatus::Success)
+}
+
+/// Prints the syntax tree of a source file, including the errors that occurred while parsing it.
+fn ast(matches: &ArgMatches) -> Result<ExitStatus, anyhow::Error> {
+    let path = matches.value_of("FILE").unwrap(); // Safe because its a required arg
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("could not read source file '{}': {}", path, e))?;
+
+    if matches.is_present("json") {
+        let tree = syntax_tree::SyntaxTree::parse(&text);
+        println!("{}", serde_json::to_string_pretty(&tree)?);
+    } else {
+        print!("{}", mun_syntax::SourceFile::parse(&text).debug_dump());
+    }
+    Ok(ExitStatus::Success)
+}
+
+/// Writes a bug report bundle for the package.
+fn bug_report(matches: &ArgMatches, context: &Context) -> Result<ExitStatus, anyhow::Error> {
+    let options = compiler_options(matches)?;
+    let manifest_path = locate_manifest(
+        &context.working_dir,
+        matches.value_of("manifest-path"),
+        MANIFEST_FILENAME,
+    )?;
+    let output_path = Path::new(matches.value_of("output").unwrap_or("mun-bug-report.zip"));
+
+    bug_report::write_bug_report(&manifest_path, options, output_path)?;
+    println!("Wrote bug report to {}", output_path.display());
+    println!("Attach it to an issue together with a description of the problem, see DESCRIPTION.md in the archive for a template");
+    Ok(ExitStatus::Success)
+}
+
+/// Builds the package, or uses the libraries in the `prebuilt` directory, and writes its libraries
+/// and their metadata to an archive.
+fn package(matches: &ArgMatches, context: &Context) -> Result<ExitStatus, anyhow::Error> {
+    let mut options = compiler_options(matches)?;
+    let manifest_path = locate_manifest(
+        &context.working_dir,
+        matches.value_of("manifest-path"),
+        MANIFEST_FILENAME,
+    )?;
+    if let Some(prebuilt) = matches.value_of("prebuilt") {
+        options.out_dir = Some(PathBuf::from(prebuilt));
+    }
+    let (package, mut driver) = mun_compiler::Driver::with_package_path(&manifest_path, options)?;
+    if !matches.is_present("prebuilt") {
+        if driver.emit_diagnostics(&mut std::io::stderr())? {
+            return Ok(ExitStatus::Error);
+        }
+        driver.write_all_assemblies()?;
+    }
+
+    let mut libraries: Vec<(String, PathBuf)> = driver
+        .source_assembly_paths()
+        .into_iter()
+        .map(|(source_path, library_path)| {
+            let archive_path = source_path.with_extension(LIBRARY_EXTENSION);
+            (archive_path.as_str().to_owned(), library_path)
+        })
+        .collect();
+    libraries.sort();
+    let output_path = match matches.value_of("out") {
+        Some(out) => PathBuf::from(out),
+        None => package.root().join("target").join(format!(
+            "{}-{}.{}",
+            package.name(),
+            package.version(),
+            package_archive::ARCHIVE_EXTENSION
+        )),
+    };
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let contents = package_archive::write_archive(&package, &libraries, &output_path)?;
+    println!(
+        "Packaged {} {} ({} {}) into {}",
+        contents.name,
+        contents.version,
+        contents.libraries.len(),
+        if contents.libraries.len() == 1 {
+            "library"
+        } else {
+            "libraries"
+        },
+        output_path.display()
+    );
+    Ok(ExitStatus::Success)
+}
+
+/// Analyzes the package and emits its diagnostics, without generating code or writing artifacts.
+fn check(matches: &ArgMatches, context: &Context) -> Result<ExitStatus, anyhow::Error> {
+    let options = compiler_options(matches)?;
+    let manifest_path = locate_manifest(
+        &context.working_dir,
+        matches.value_of("manifest-path"),
+        MANIFEST_FILENAME,
+    )?;
+    if mun_compiler::check_manifest(&manifest_path, options)? {
+        Ok(ExitStatus::Success)
+    } else {
+        Ok(ExitStatus::Error)
+    }
+}
+
+/// Analyzes the package and writes the documentation of its items to the output directory.
+fn doc(matches: &ArgMatches, context: &Context) -> Result<ExitStatus, anyhow::Error> {
+    let options = compiler_options(matches)?;
+    let input_encoding = options.input_encoding;
+    let manifest_path = locate_manifest(
+        &context.working_dir,
+        matches.value_of("manifest-path"),
+        MANIFEST_FILENAME,
+    )?;
+    let (package, driver) = mun_compiler::Driver::with_package_path(&manifest_path, options)?;
+    if driver.emit_diagnostics(&mut std::io::stderr())? {
+        return Ok(ExitStatus::Error);
+    }
+
+    let source_dir = package
+        .source_directory()
+        .ok_or_else(|| anyhow!("the source directory of package {} does not exist", package))?;
+    let mut modules = Vec::new();
+    for source_path in mun_compiler::iter_package_source_files(package.root(), &source_dir)? {
+        let relative_path = mun_compiler::compute_source_relative_path(&source_dir, &source_path)?;
+        let text = mun_compiler::read_source_file(&source_path, input_encoding)?;
+        modules.push(doc::ModuleDoc::from_source(relative_path.as_str(), &text));
+    }
+    modules.sort_by(|a, b| a.path.cmp(&b.path));
+    let package_doc = doc::PackageDoc {
+        name: package.name().to_owned(),
+        version: package.version().to_string(),
+        modules,
+    };
+
+    let out_dir = matches
+        .value_of("out-dir")
+        .map_or_else(|| package.root().join("target").join("doc"), PathBuf::from);
+    std::fs::create_dir_all(&out_dir).map_err(|e| {
+        anyhow!(
+            "could not create documentation directory '{}': {}",
+            out_dir.display(),
+            e
+        )
+    })?;
+    let (path, contents) = match matches.value_of("format") {
+        Some("json") => (
+            out_dir.join("doc.json"),
+            serde_json::to_string_pretty(&package_doc)?,
+        ),
+        _ => (out_dir.join("index.html"), doc::render_html(&package_doc)),
+    };
+    std::fs::write(&path, contents)
+        .map_err(|e| anyhow!("could not write '{}': {}", path.display(), e))?;
+    println!("Documented {} in {}", package, path.display());
+    Ok(ExitStatus::Success)
+}
+
+/// Removes the build artifacts from the output directory of the package.
+fn clean(matches: &ArgMatches, context: &Context) -> Result<ExitStatus, anyhow::Error> {
+    let manifest_path = locate_manifest(
+        &context.working_dir,
+        matches.value_of("manifest-path"),
+        MANIFEST_FILENAME,
+    )?;
+    let out_dir = matches.value_of("out-dir").map_or_else(
+        || {
+            manifest_path
+                .parent()
+                .expect("manifest path must have a parent directory")
+                .join("target")
+        },
+        PathBuf::from,
+    );
+    if !matches.is_present("dry-run") {
+        let num_artifacts = clean::artifacts(&out_dir)?.len();
+        if num_artifacts > 0
+            && !confirm::confirm(
+                &format!("This will delete {} file(s), continue?", num_artifacts),
+                matches.is_present("yes"),
+            )?
+        {
+            eprintln!("Aborted, no files were removed");
+            return Ok(ExitStatus::Error);
+        }
+    }
+    clean::clean(&out_dir, matches.is_present("dry-run"))?;
+    Ok(ExitStatus::Success)
+}
+
+/// Applies the fixes suggested by the compiler to the source files of the package.
+fn fix(matches: &ArgMatches, context: &Context) -> Result<ExitStatus, anyhow::Error> {
+    let options = compiler_options(matches)?;
+    let manifest_path = locate_manifest(
+        &context.working_dir,
+        matches.value_of("manifest-path"),
+        MANIFEST_FILENAME,
+    )?;
+
+    fix::fix_package(
+        &manifest_path,
+        options,
+        matches.is_present("dry-run"),
+        matches.is_present("allow-dirty"),
+    )?;
+    Ok(ExitStatus::Success)
+}
+
+/// Formats the specified source files, or those of the package, or checks or shows what
+/// formatting would change. Files that are already formatted are not touched.
+fn fmt(matches: &ArgMatches, context: &Context) -> Result<ExitStatus, anyhow::Error> {
+    let colors = compiler_options(matches)?.display_color.should_enable();
+
+    // The source files to format, with the names by which they are reported
+    let source_files: Vec<(String, PathBuf)> = match matches.values_of("FILES") {
+        Some(files) => files
+            .map(|file| (file.to_owned(), PathBuf::from(file)))
+            .collect(),
+        None => {
+            let manifest_path = locate_manifest(
+                &context.working_dir,
+                matches.value_of("manifest-path"),
+                MANIFEST_FILENAME,
+            )?;
+            let package = Package::from_file(&manifest_path)?;
+            let source_dir = package.source_directory().ok_or_else(|| {
+                anyhow!("the source directory of package {} does not exist", package)
+            })?;
+            mun_compiler::iter_package_source_files(package.root(), &source_dir)?
+                .map(|source_path| {
+                    let relative_path =
+                        mun_compiler::compute_source_relative_path(&source_dir, &source_path)?;
+                    Ok((relative_path.as_str().to_owned(), source_path))
+                })
+                .collect::<Result<_, anyhow::Error>>()?
+        }
+    };
+
+    let check = matches.is_present("check");
+    let diff = matches.is_present("diff");
+    let mut unformatted = 0;
+    for (name, source_path) in source_files {
+        let source = mun_compiler::read_source_file(&source_path, InputEncoding::Utf8)?;
+        let formatted =
+            fmt::format_source(&source).map_err(|e| anyhow!("could not format {}: {}", name, e))?;
+        if formatted == source {
+            continue;
+        }
+
+        unformatted += 1;
+        if diff {
+            print!("{}", fmt::unified_diff(&name, &source, &formatted, colors));
+        } else if check {
+            println!("{} is not formatted", name);
+        } else {
+            std::fs::write(&source_path, formatted)?;
+            println!("Formatted {}", name);
+        }
+    }
+
+    Ok((!check || unformatted == 0).into())
+}
+
+/// Prints the metadata of the package as JSON.
+fn metadata(matches: &ArgMatches, context: &Context) -> Result<ExitStatus, anyhow::Error> {
+    let manifest_path = locate_manifest(
+        &context.working_dir,
+        matches.value_of("manifest-path"),
+        MANIFEST_FILENAME,
+    )?;
+    let package = Package::from_file(&manifest_path).map_err(|e| {
+        anyhow!(
+            "failed to parse manifest '{}': {}",
+            manifest_path.display(),
+            e
+        )
+    })?;
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&metadata::Metadata::from(&package))?
+    );
+    Ok(ExitStatus::Success)
+}
+
+/// Builds the package and compares the ABI of the resulting libraries against a baseline, failing
+/// if breaking changes are detected. If `--update-baseline` is specified, the baseline is
+/// overwritten with the current ABI instead.
+fn abi_check(matches: &ArgMatches, context: &Context) -> Result<ExitStatus, anyhow::Error> {
+    let options = compiler_options(matches)?;
+    let manifest_path = locate_manifest(
+        &context.working_dir,
+        matches.value_of("manifest-path"),
+        MANIFEST_FILENAME,
+    )?;
+    let baseline_path = Path::new(matches.value_of("baseline").unwrap()); // Safe because its a required arg
+
+    let package = Package::from_file(&manifest_path)?;
+    let out_dir = mun_compiler::ensure_package_output_dir(&package, &options)?;
+    if !mun_compiler::compile_manifest(&manifest_path, options)? {
+        return Ok(ExitStatus::Error);
+    }
+    let current = abi::AbiSnapshot::from_directory(&out_dir)?;
+
+    if matches.is_present("update-baseline") {
+        current.write_to_file(baseline_path)?;
+        println!("Updated ABI baseline: {}", baseline_path.display());
+        return Ok(ExitStatus::Success);
+    }
+
+    let baseline = abi::AbiSnapshot::from_file(baseline_path)?;
+    let changes = abi::diff(&baseline, &current);
+    for change in changes.iter() {
+        let severity = if change.breaking { "error" } else { "note" };
+        println!("{}: {}", severity, change);
+    }
+
+    let num_breaking = changes.iter().filter(|change| change.breaking).count();
+    if num_breaking > 0 {
+        eprintln!("found {} breaking ABI change(s)", num_breaking);
+        Ok(ExitStatus::Error)
+    } else {
+        Ok(ExitStatus::Success)
+    }
+}
+
+/// Creates a package with the requested manifest fields. All fields are validated before any file
+/// is created.
+fn new(matches: &ArgMatches) -> Result<ExitStatus, anyhow::Error> {
+    let path = Path::new(matches.value_of("PATH").unwrap()); // Safe because its a required arg
+    let manifest = package_manifest(matches, path)?;
+    if !matches.is_present("force") && scaffold::is_populated(path)? {
+        eprintln!(
+            "error: destination '{}' already exists and is not empty\n\
+             note: use --force to create the package anyway, or `mun init` to turn the directory into a package",
+            path.display()
+        );
+        return Ok(ExitStatus::Error);
+    }
+    let kind = if matches.is_present("lib") {
+        scaffold::PackageKind::Lib
+    } else {
+        scaffold::PackageKind::Bin
+    };
+    let template = matches
+        .value_of("template")
+        .map(|template| scaffold::read_template(Path::new(template)))
+        .transpose()?;
+    scaffold::create_package(path, &manifest, kind, template.as_deref())?;
+
+    println!(
+        "Created {} package `{}` in {}",
+        match kind {
+            scaffold::PackageKind::Bin => "binary",
+            scaffold::PackageKind::Lib => "library",
+        },
+        manifest.name(),
+        path.display()
+    );
+    if let Some(workspace_path) = scaffold::register_in_workspace(path)? {
+        println!(
+            "Added `{}` to the members of the workspace {}",
+            manifest.name(),
+            workspace_path.display()
+        );
+    }
+    Ok(ExitStatus::Success)
+}
+
+/// Turns an existing directory into a package.
+fn init(matches: &ArgMatches) -> Result<ExitStatus, anyhow::Error> {
+    let path = Path::new(matches.value_of("PATH").unwrap_or("."));
+    // The name of the directory is needed for the package name, even if `path` is e.g. `.`
+    let name_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let manifest = package_manifest(matches, &name_path)?;
+    scaffold::init_package(path, &manifest)?;
+
+    println!(
+        "Initialized package `{}` in {}",
+        manifest.name(),
+        path.display()
+    );
+    Ok(ExitStatus::Success)
+}
+
+/// Constructs the manifest of a new package in the directory `path` from the `name`, `author`,
+/// `version` and `license` arguments shared by `new` and `init`.
+fn package_manifest(
+    matches: &ArgMatches,
+    path: &Path,
+) -> Result<mun_project::Manifest, anyhow::Error> {
+    let name = match matches.value_of("name") {
+        Some(name) => name,
+        None => path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| {
+                anyhow!(
+                    "cannot determine a package name from '{}', use --name to specify one",
+                    path.display()
+                )
+            })?,
+    };
+    scaffold::validate_package_name(name)?;
+
+    let version = matches
+        .value_of("version")
+        .unwrap_or(scaffold::INITIAL_VERSION);
+    let version = mun_project::Version::parse(version).map_err(|e| {
+        anyhow!(
+            "invalid value '{}' for --version, expected a semantic version: {}",
+            version,
+            e
+        )
+    })?;
+
+    let license = matches.value_of("license").map(ToOwned::to_owned);
+    if let Some(license) = &license {
+        scaffold::validate_license(license)?;
+    }
+
+    let authors = matches
+        .values_of("author")
+        .map_or_else(scaffold::default_authors, |authors| {
+            authors.map(ToOwned::to_owned).collect()
+        });
+
+    mun_project::Manifest::new(
+        name,
+        version,
+        mun_project::ManifestMetadata { authors, license },
+    )
+}
+
+/// Creates a workspace with the requested initial member packages.
+fn new_workspace(matches: &ArgMatches) -> Result<ExitStatus, anyhow::Error> {
+    let path = Path::new(matches.value_of("PATH").unwrap()); // Safe because its a required arg
+    let members: Vec<&str> = matches
+        .values_of("members")
+        .map_or_else(Vec::new, Iterator::collect);
+    scaffold::create_workspace(path, &members)?;
+
+    println!("Created workspace in {}", path.display());
+    Ok(ExitStatus::Success)
+}
+
+/// Compiles the local package and invokes the entry point of the resulting library like `start`.
+fn run(matches: &ArgMatches, context: &Context) -> Result<Outcome, anyhow::Error> {
+    let manifest_path = locate_manifest(
+        &context.working_dir,
+        matches.value_of("manifest-path"),
+        MANIFEST_FILENAME,
+    )?;
+    let options = compiler_options(matches)?;
+    if options.target.options.is_like_wasm {
+        return Err(anyhow!(
+            "cannot run code compiled for the WebAssembly target '{}'\n\
+             help: remove --target to run the package on this machine, or use `mun build` to only compile it",
+            options.target.llvm_target
+        ));
+    }
+    if matches.is_present("watch") {
+        return run_watch(&manifest_path, matches);
+    }
+
+    let library_path = match compile_library(&manifest_path, options)? {
+        Some(library_path) => library_path,
+        None => return Ok(ExitStatus::Error.into()),
+    };
+
+    let default_entry = manifest_default_entry(&manifest_path)?;
+    let runtime = runtime(&[&library_path], matches, None)?;
+    let (status, return_value) = invoke_entry_point(&runtime, matches, default_entry.as_deref())?;
+    Ok(Outcome::Start {
+        status,
+        return_value,
+    })
+}
+
+/// Compiles the package at `manifest_path` and returns the path of the library to run: the one
+/// compiled from `main.mun`, or the only library of the package. Returns `None` if errors were
+/// emitted.
+fn compile_library(manifest_path: &Path, config: Config) -> Result<Option<PathBuf>, anyhow::Error> {
+    let (_package, mut driver) = mun_compiler::Driver::with_package_path(manifest_path, config)?;
+    if driver.emit_diagnostics(&mut std::io::stderr())? {
+        return Ok(None);
+    }
+    driver.write_all_assemblies()?;
+    library_to_run(&driver).map(Some)
+}
+
+/// Returns the path of the library to run among the assemblies written by `driver`: the one
+/// compiled from `main.mun`, or the only library of the package.
+fn library_to_run(driver: &mun_compiler::Driver) -> Result<PathBuf, anyhow::Error> {
+    let mut library_paths = driver.assembly_output_paths();
+    let main_path = library_paths
+        .iter()
+        .position(|path| path.file_stem().map_or(false, |stem| stem == "main"));
+    match main_path {
+        Some(idx) => Ok(library_paths.swap_remove(idx)),
+        None if library_paths.len() == 1 => Ok(library_paths.pop().unwrap()),
+        None => Err(anyhow!(
+            "cannot determine the library to run, the package has no `main.mun` and {} source files",
+            library_paths.len()
+        )),
+    }
+}
+
+/// Locates the manifest at `manifest_path`, or in the current directory or one of its parents, and
+/// returns the path of the library that `mun build` writes for its package. Returns an error if
+/// the library has not been built yet.
+fn package_library_path(
+    working_dir: &Path,
+    manifest_path: Option<&str>,
+) -> Result<PathBuf, anyhow::Error> {
+    let manifest_path = locate_manifest(working_dir, manifest_path, MANIFEST_FILENAME)?;
+    let (_package, driver) =
+        mun_compiler::Driver::with_package_path(&manifest_path, Config::default())?;
+    let library_path = library_to_run(&driver)?;
+    if !library_path.is_file() {
+        return Err(anyhow!(
+            "the library '{}' has not been built yet\nhelp: run `mun build --manifest-path {}` first",
+            library_path.display(),
+            manifest_path.display()
+        ));
+    }
+    Ok(library_path)
+}
+
+/// Compiles the package at `manifest_path` and calls its entry point after every successful build,
+/// until interrupted or `--max-runs` is reached. The library is loaded once and reloaded into the
+/// same runtime after every rebuild. Returns the outcome of the last call.
+fn run_watch(manifest_path: &Path, matches: &ArgMatches) -> Result<Outcome, anyhow::Error> {
+    let default_entry = manifest_default_entry(manifest_path)?;
+    let watch_config = mun_compiler_daemon::WatchConfig {
+        max_builds: matches
+            .value_of("max-runs")
+            .map(|runs| {
+                runs.parse().map_err(|_| {
+                    anyhow!(
+                        "invalid value '{}' for --max-runs, expected a positive integer",
+                        runs
+                    )
+                })
+            })
+            .transpose()?,
+        ..mun_compiler_daemon::WatchConfig::default()
+    };
+
+    let mut session: Option<Rc<RefCell<Runtime>>> = None;
+    let mut runs = 0;
+    let mut last = (ExitStatus::Success, None);
+    mun_compiler_daemon::compile_and_watch_manifest_with(
+        manifest_path,
+        compiler_options(matches)?,
+        watch_config,
+        |driver| {
+            let runtime = match session.clone() {
+                Some(runtime) => {
+                    // Keep watching with the previous library, the next build may load again
+                    if let Err(e) = runtime.borrow_mut().reload() {
+                        eprintln!("error: {}", e);
+                        return Ok(());
+                    }
+                    runtime
+                }
+                None => {
+                    let runtime = runtime(&[&library_to_run(driver)?], matches, None)?;
+                    session = Some(runtime.clone());
+                    runtime
+                }
+            };
+
+            runs += 1;
+            if runs > 1 {
+                println!("\n--- run {} ---", runs);
+            }
+            last = invoke_entry_point(&runtime, matches, default_entry.as_deref())?;
+            Ok(())
+        },
+    )?;
+
+    let (status, return_value) = last;
+    Ok(Outcome::Start {
+        status,
+        return_value,
+    })
+}
+
+/// Measures and prints the latency from starting `mun` to the return of the first call into the
+/// specified library.
+fn profile_startup(
+    matches: &ArgMatches,
+    process_start: Instant,
+) -> Result<ExitStatus, anyhow::Error> {
+    let profile = startup_profile::profile_startup(
+        process_start,
+        Path::new(matches.value_of("LIBRARY").unwrap()), // Safe because its a required arg
+        matches.value_of("entry").unwrap_or("main"),
+    )?;
+
+    if matches.is_present("json") {
+        println!("{}", serde_json::to_string_pretty(&profile)?);
+    } else {
+        print!("{}", profile.to_text());
+    }
+    Ok(ExitStatus::Success)
+}
+
+/// Loads the specified library, without watching it for changes, and reports how long calls to
+/// its entry point take.
+fn bench(matches: &ArgMatches) -> Result<ExitStatus, anyhow::Error> {
+    let library_path = Path::new(matches.value_of("LIBRARY").unwrap()); // Safe because its a required arg
+    let iterations = match matches.value_of("iterations") {
+        Some(iterations) => iterations.parse().map_err(|_| {
+            anyhow!(
+                "invalid value '{}' for --iterations, expected a positive integer",
+                iterations
+            )
+        })?,
+        None => 1000,
+    };
+    let args = matches
+        .values_of_os("args")
+        .or_else(|| matches.values_of_os("ENTRY_ARGS"))
+        .map_or_else(|| Ok(Vec::new()), entry_args::utf8_args)?;
+
+    let runtime = RuntimeBuilder::new(library_path).set_watch(false).spawn()?;
+    let stats = bench_entry_point(
+        &runtime,
+        matches.value_of("entry").unwrap_or("main"),
+        &args,
+        iterations,
+    )?;
+
+    if matches.is_present("json") {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        print!("{}", stats.to_text());
+    }
+    Ok(ExitStatus::Success)
+}
+
+/// Calls `entry_point` with `args` `iterations` times, discarding its return value, and returns
+/// the statistics of the call times.
+fn bench_entry_point(
+    runtime: &Rc<RefCell<Runtime>>,
+    entry_point: &str,
+    args: &[&str],
+    iterations: usize,
+) -> Result<bench::CallStats, anyhow::Error> {
+    let args = parse_entry_args(&runtime.borrow(), entry_point, args)?;
+    let options = InvokeOptions {
+        flush: false,
+        output: OutputFormat::Text,
+        backtrace: false,
+        ignore_return: true,
+        exit_code: false,
+        colors: false,
+        out_file: None,
+    };
+    bench::bench_calls(iterations, || {
+        invoke_and_print(runtime, entry_point, &args, options).map(|_| ())
+    })
+}
+
+/// Loads the specified library and prints the signature of every function it exports.
+fn list(matches: &ArgMatches) -> Result<ExitStatus, anyhow::Error> {
+    let library_path = Path::new(matches.value_of("LIBRARY").unwrap()); // Safe because its a required arg
+    let runtime = RuntimeBuilder::new(library_path).set_watch(false).spawn()?;
+    let runtime = runtime.borrow();
+    let exports = exports::exports(&library_functions(&runtime, matches)?);
+
+    if matches.is_present("json") {
+        println!("{}", serde_json::to_string_pretty(&exports)?);
+    } else {
+        for export in exports.iter() {
+            println!("{}", export.to_text());
+        }
+    }
+    Ok(ExitStatus::Success)
+}
+
+/// Loads the specified library into a watching runtime and reports every reload, until
+/// interrupted with Ctrl-C.
+fn watch_lib(matches: &ArgMatches) -> Result<ExitStatus, anyhow::Error> {
+    let delay = matches
+        .value_of("delay")
+        .map(|delay| delay.parse().map(Duration::from_millis))
+        .transpose()?;
+    watch_lib::watch_library(
+        Path::new(matches.value_of("LIBRARY").unwrap()), // Safe because its a required arg
+        delay,
+        &watch_lib::ShutdownFlag::on_ctrl_c(),
+    )?;
+    Ok(ExitStatus::Success)
+}
+
+/// Starts the runtime with the specified library and invokes function `entry`.
+fn start(matches: &ArgMatches, context: &Context) -> Result<Outcome, anyhow::Error> {
+    if matches.is_present("list-supported-types") {
+        for (type_name, format) in return_value::SUPPORTED_TYPES {
+            println!("{:<12}{}", type_name, format);
+        }
+        return Ok(ExitStatus::Success.into());
+    }
+
+    let library_paths: Vec<PathBuf> = match matches.values_of("LIBRARY") {
+        Some(library_paths) => library_paths
+            .map(|path| {
+                let path = PathBuf::from(path);
+                if path.is_dir() {
+                    find_library_in_directory(&path)
+                } else {
+                    Ok(path)
+                }
+            })
+            .collect::<Result<_, _>>()?,
+        None => vec![package_library_path(
+            &context.working_dir,
+            matches.value_of("manifest-path"),
+        )?],
+    };
+    let library_paths: Vec<&Path> = library_paths.iter().map(PathBuf::as_path).collect();
+
+    if matches.is_present("require-fresh") {
+        let manifest_path = locate_manifest(
+            &context.working_dir,
+            matches.value_of("manifest-path"),
+            MANIFEST_FILENAME,
+        )?;
+        for library_path in library_paths.iter() {
+            ensure_library_fresh(library_path, &manifest_path)?;
+        }
+    }
+
+    let reload_summary = if matches.is_present("reload-summary") {
+        Some(reload_summary::ReloadSummary::new())
+    } else {
+        None
+    };
+    let runtime = runtime(&library_paths, matches, reload_summary.as_ref())?;
+
+    if matches.is_present("dump-layouts") {
+        let format = matches.value_of("dump-layouts").unwrap_or("text");
+        layout::dump_layouts(&runtime.borrow(), format)?;
+        return Ok(ExitStatus::Success.into());
+    }
+
+    if matches.is_present("dump-fn-addresses") {
+        dump_fn_addresses(&runtime.borrow(), matches)?;
+    }
+
+    let default_entry = if matches.is_present("entry") {
+        None
+    } else {
+        let manifest_path = match matches.values_of("LIBRARY") {
+            Some(_) => library_paths[0]
+                .canonicalize()
+                .ok()
+                .and_then(|path| find_manifest(path.parent()?, MANIFEST_FILENAME)),
+            None => Some(locate_manifest(
+                &context.working_dir,
+                matches.value_of("manifest-path"),
+                MANIFEST_FILENAME,
+            )?),
+        };
+        match manifest_path {
+            Some(manifest_path) => manifest_default_entry(&manifest_path)?,
+            None => None,
+        }
+    };
+    let result = invoke_entry_point(&runtime, matches, default_entry.as_deref());
+    if let Some(path) = matches.value_of("profile") {
+        write_call_profile(&runtime.borrow(), Path::new(path))?;
+    }
+    let (status, return_value) = result?;
+    if let Some(reload_summary) = reload_summary {
+        eprintln!("{}", reload_summary.to_text());
+    }
+    Ok(Outcome::Start {
+        status,
+        return_value,
+    })
+}
+
+/// Writes the functions that were invoked through `runtime`, with their number of calls and
+/// cumulative duration, to the file at `path` as JSON.
+fn write_call_profile(runtime: &Runtime, path: &Path) -> Result<(), anyhow::Error> {
+    let profile = runtime
+        .call_profile()
+        .expect("profiling must be enabled to write a call profile");
+    let functions: Vec<serde_json::Value> = profile
+        .functions()
+        .iter()
+        .map(|(name, function)| {
+            serde_json::json!({
+                "name": name,
+                "calls": function.calls,
+                "total_ms": function.total.as_secs_f64() * 1000.0,
+            })
+        })
+        .collect();
+    let report = serde_json::json!({ "functions": functions });
+    std::fs::write(path, serde_json::to_string_pretty(&report)?)
+        .map_err(|e| anyhow!("could not write profile '{}': {}", path.display(), e))
+}
+
+/// Returns the path of the only library in `directory`, which is not searched recursively. Fails if
+/// the directory contains no libraries or more than one.
+fn find_library_in_directory(directory: &Path) -> Result<PathBuf, anyhow::Error> {
+    let mut libraries = Vec::new();
+    for entry in std::fs::read_dir(directory)
+        .map_err(|e| anyhow!("could not read directory '{}': {}", directory.display(), e))?
+    {
+        let path = entry?.path();
+        if path.is_file()
+            && path
+                .extension()
+                .map_or(false, |ext| ext == LIBRARY_EXTENSION)
+        {
+            libraries.push(path);
+        }
+    }
+    libraries.sort();
+
+    match libraries.len() {
+        0 => Err(anyhow!(
+            "no .{} library found in directory '{}'",
+            LIBRARY_EXTENSION,
+            directory.display()
+        )),
+        1 => Ok(libraries.remove(0)),
+        _ => Err(anyhow!(
+            "directory '{}' contains multiple libraries, specify one of: {}",
+            directory.display(),
+            libraries
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Returns the entry point configured with `default-entry` in the manifest at `manifest_path`.
+fn manifest_default_entry(manifest_path: &Path) -> Result<Option<String>, anyhow::Error> {
+    let manifest = mun_project::Manifest::from_file(manifest_path)?;
+    Ok(manifest.default_entry().map(ToOwned::to_owned))
+}
+
+/// Invokes the entry point selected by the command line arguments in `matches`, shared by `start`
+/// and `run`. Without an explicit entry point, `default_entry` is invoked, or `main` if there is
+/// none. Returns the value returned by the first invocation, if it was read.
+fn invoke_entry_point(
+    runtime: &Rc<RefCell<Runtime>>,
+    matches: &ArgMatches,
+    default_entry: Option<&str>,
+) -> Result<(ExitStatus, Option<ReturnValue>), anyhow::Error> {
+    let options = InvokeOptions {
+        flush: matches.is_present("flush"),
+        output: matches.value_of("output").unwrap_or("text").parse()?,
+        backtrace: matches.is_present("backtrace"),
+        ignore_return: matches.is_present("ignore-return"),
+        exit_code: matches.is_present("exit-code"),
+        colors: display_color(matches).should_enable(),
+        out_file: matches.value_of_os("out-file").map(Path::new),
+    };
+    if let Some(pattern) = matches.value_of("entry-match") {
+        return invoke_matching(runtime, matches, pattern, options).map(|status| (status, None));
+    }
+
+    let entries: Vec<&str> = matches
+        .values_of("entry")
+        .map_or_else(Vec::new, Iterator::collect);
+    if entries.len() > 1 {
+        if let Some(arg) = [
+            "args",
+            "args-file",
+            "ENTRY_ARGS",
+            "dry-run",
+            "exit-code",
+            "control-socket",
+        ]
+        .iter()
+        .find(|arg| matches.is_present(arg))
+        {
+            return Err(anyhow!(
+                "'{}' cannot be used with more than one --entry",
+                arg
+            ));
+        }
+        let names: Vec<String> = entries.into_iter().map(ToOwned::to_owned).collect();
+        let status = invoke_in_order(&names, true, options.colors, |name| {
+            invoke_entry(runtime, name, &[], options).map(|(status, _)| status)
+        });
+        return Ok((status, None));
+    }
+
+    let entry_point = match matches.value_of("entry-index") {
+        Some(index) => entry_point_by_index(&runtime.borrow(), matches, index)?,
+        None => match matches.value_of("entry") {
+            Some(entry) => entry.to_owned(),
+            None if matches.is_present("interactive") && atty::is(atty::Stream::Stdin) => {
+                select_entry_point(&runtime.borrow(), matches)?
+            }
+            None => default_entry.unwrap_or("main").to_owned(),
+        },
+    };
+    let entry_point = entry_point.as_str();
+
+    let file_args = matches
+        .value_of_os("args-file")
+        .map(|path| entry_args::read_args_file(Path::new(path)))
+        .transpose()?;
+    let args = match &file_args {
+        Some(file_args) => file_args.iter().map(String::as_str).collect(),
+        None => matches
+            .values_of_os("args")
+            .or_else(|| matches.values_of_os("ENTRY_ARGS"))
+            .map_or_else(|| Ok(Vec::new()), entry_args::utf8_args)?,
+    };
+    if matches.is_present("dry-run") {
+        return check_entry_args(&runtime.borrow(), entry_point, &args, options.colors)
+            .map(|status| (status, None));
+    }
+    let args = parse_entry_args(&runtime.borrow(), entry_point, &args)?;
+
+    let result = invoke_entry(runtime, entry_point, &args, options)?;
+
+    if let Some(address) = matches.value_of("control-socket") {
+        let on_error = matches
+            .value_of("on-invoke-error")
+            .unwrap_or("skip")
+            .parse()?;
+        control::serve(address, on_error, runtime, || {
+            invoke_entry(runtime, entry_point, &args, options).map(|_| ())
+        })?;
+    }
+
+    Ok(result)
+}
+
+/// Runs `start` on a new thread, with a stack of `stack_size` bytes if specified, and waits for it
+/// to finish. The runtime is created on that thread, so it never crosses threads.
+///
+/// If a `timeout` is specified and the session has not finished in time, an error is reported
+/// without waiting any longer. The thread cannot be interrupted, so it keeps running until the
+/// process exits.
+fn start_on_thread(
+    stack_size: Option<usize>,
+    timeout: Option<Duration>,
+    matches: ArgMatches<'static>,
+    context: Context,
+) -> Result<Outcome, anyhow::Error> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let builder = std::thread::Builder::new().name("mun-start".to_owned());
+    let builder = match stack_size {
+        Some(stack_size) => builder.stack_size(stack_size),
+        None => builder,
+    };
+    builder
+        .spawn(move || {
+            // The receiver is gone if the timeout expired, in which case the result is irrelevant
+            let _ = sender.send(start(&matches, &context));
+        })
+        .map_err(|e| anyhow!("could not spawn a thread to run the entry point: {}", e))?;
 
-    let options = compiler_options(matches)?;
+    let panicked = || anyhow!("the thread running the entry point panicked");
+    match timeout {
+        Some(timeout) => match receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => {
+                eprintln!(
+                    "error: the entry point did not finish within {} s",
+                    timeout.as_secs_f64()
+                );
+                Ok(ExitStatus::Error.into())
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(panicked()),
+        },
+        None => receiver.recv().map_err(|_| panicked())?,
+    }
+}
 
-    // Locate the manifest
-    let manifest_path = match matches.value_of("manifest-path") {
-        None => {
-            let current_dir =
-                std::env::current_dir().expect("could not determine currrent working directory");
-            find_manifest(&current_dir).ok_or_else(|| {
-                anyhow::anyhow!(
-                    "could not find {} in '{}' or a parent directory",
-                    MANIFEST_FILENAME,
-                    current_dir.display()
+/// Parses a positive number of seconds, e.g. `30` or `0.5`, into a duration.
+fn parse_timeout(seconds: &str) -> Result<Duration, anyhow::Error> {
+    match seconds.parse::<f64>() {
+        Ok(value) if value.is_finite() && value > 0.0 => Ok(Duration::from_secs_f64(value)),
+        _ => Err(anyhow!(
+            "invalid value '{}' for --timeout, expected a positive number of seconds",
+            seconds
+        )),
+    }
+}
+
+/// Returns an error if a source file of the package at `manifest_path` was modified after the
+/// library at `library_path` was built.
+fn ensure_library_fresh(library_path: &Path, manifest_path: &Path) -> Result<(), anyhow::Error> {
+    let modified = |path: &Path| {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| {
+                anyhow!(
+                    "could not determine modification time of '{}': {}",
+                    path.display(),
+                    e
+                )
+            })
+    };
+
+    let package = Package::from_file(manifest_path)?;
+    let source_dir = package
+        .source_directory()
+        .ok_or_else(|| anyhow!("the source directory of package {} does not exist", package))?;
+
+    let library_modified = modified(library_path)?;
+    for source_path in mun_compiler::iter_package_source_files(package.root(), &source_dir)? {
+        if modified(&source_path)? > library_modified {
+            return Err(anyhow!(
+                "library '{}' is out of date, rebuild first ('{}' was modified after it was built)",
+                library_path.display(),
+                source_path.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Verifies that `args` match the signature of the function `entry_point` and reports the result,
+/// without invoking the function.
+fn check_entry_args(
+    runtime: &Runtime,
+    entry_point: &str,
+    args: &[&str],
+    colors: bool,
+) -> Result<ExitStatus, anyhow::Error> {
+    let fn_definition = runtime
+        .get_function_definition(entry_point)
+        .ok_or_else(|| DriverError::EntryPointNotFound(entry_point.to_owned()))?;
+
+    match entry_args::parse_args(&fn_definition.prototype.signature, args) {
+        Ok(_) => {
+            println!(
+                "arguments match the signature of '{}': {}",
+                entry_point, fn_definition.prototype.signature
+            );
+            Ok(ExitStatus::Success)
+        }
+        Err(e) => {
+            eprintln!(
+                "{}",
+                error_message(
+                    format!(
+                        "arguments do not match the signature of '{}' {}: {}",
+                        entry_point, fn_definition.prototype.signature, e
+                    ),
+                    colors
+                )
+            );
+            Ok(ExitStatus::Error)
+        }
+    }
+}
+
+/// Parses the command-line `args` of `entry_point` according to its signature.
+fn parse_entry_args(
+    runtime: &Runtime,
+    entry_point: &str,
+    args: &[&str],
+) -> Result<Vec<ArgValue>, anyhow::Error> {
+    let fn_definition = match runtime.get_function_definition(entry_point) {
+        Some(fn_definition) => fn_definition,
+        // Invoking the entry point reports that it does not exist
+        None if args.is_empty() => return Ok(Vec::new()),
+        None => return Err(DriverError::EntryPointNotFound(entry_point.to_owned()).into()),
+    };
+    entry_args::parse_args(&fn_definition.prototype.signature, args).map_err(|e| {
+        anyhow!(
+            "arguments do not match the signature of '{}' {}: {}",
+            entry_point,
+            fn_definition.prototype.signature,
+            e
+        )
+    })
+}
+
+/// Prints the name and address of every function exported by the libraries that were started, as
+/// resolved by the runtime's dispatch table.
+fn dump_fn_addresses(runtime: &Runtime, matches: &ArgMatches) -> Result<(), anyhow::Error> {
+    let mut names: Vec<&str> = library_functions(runtime, matches)?
+        .iter()
+        .map(|function| function.prototype.name())
+        .collect();
+    names.sort();
+    for name in names {
+        if let Some(function) = runtime.get_function_definition(name) {
+            println!("{:<32}{:p}", name, function.fn_ptr);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the functions exported by the libraries that were started, in the order in which the
+/// libraries were specified, excluding their dependencies. If no libraries were specified, the
+/// functions of all loaded libraries are returned.
+fn library_functions<'r>(
+    runtime: &'r Runtime,
+    matches: &ArgMatches,
+) -> Result<Vec<&'r mun_abi::FunctionDefinition>, anyhow::Error> {
+    if !matches.is_present("LIBRARY") {
+        return Ok(runtime
+            .assemblies()
+            .flat_map(|assembly| assembly.info().symbols.functions())
+            .collect());
+    }
+
+    let mut functions = Vec::new();
+    for library_path in matches.values_of("LIBRARY").into_iter().flatten() {
+        let library_path = std::fs::canonicalize(library_path)?;
+        if let Some(assembly) = runtime
+            .assemblies()
+            .find(|assembly| assembly.library_path() == library_path)
+        {
+            functions.extend(assembly.info().symbols.functions());
+        }
+    }
+    Ok(functions)
+}
+
+/// Invokes every exported function without arguments whose name matches `pattern` and prints
+/// its result. Unless `--fail-fast` is specified, all functions are invoked even if one fails.
+fn invoke_matching(
+    runtime: &Rc<RefCell<Runtime>>,
+    matches: &ArgMatches,
+    pattern: &str,
+    options: InvokeOptions,
+) -> Result<ExitStatus, anyhow::Error> {
+    let regex = regex::Regex::new(pattern)
+        .map_err(|e| anyhow!("invalid value '{}' for --entry-match: {}", pattern, e))?;
+    let names: Vec<String> = library_functions(&runtime.borrow(), matches)?
+        .iter()
+        .filter(|function| {
+            function.prototype.signature.arg_types().is_empty()
+                && regex.is_match(function.prototype.name())
+        })
+        .map(|function| function.prototype.name().to_owned())
+        .collect();
+    if names.is_empty() {
+        return Err(anyhow!(
+            "no exported function without arguments matches '{}'",
+            pattern
+        ));
+    }
+
+    Ok(invoke_in_order(
+        &names,
+        matches.is_present("fail-fast"),
+        options.colors,
+        |name| invoke_entry(runtime, name, &[], options).map(|(status, _)| status),
+    ))
+}
+
+/// Calls `invoke` with every name in `names`, in order, after printing the name as a label. An
+/// invocation fails if it returns an error status or an error, which is printed. With `fail_fast`
+/// the remaining names are skipped after the first failure. Returns an error status if any of the
+/// invocations failed.
+fn invoke_in_order<F>(names: &[String], fail_fast: bool, colors: bool, mut invoke: F) -> ExitStatus
+where
+    F: FnMut(&str) -> Result<ExitStatus, anyhow::Error>,
+{
+    let mut failed = 0;
+    for name in names {
+        println!("{}:", name);
+        let status = invoke(name).unwrap_or_else(|e| {
+            eprintln!("{}", error_message(format!("{}: {}", name, e), colors));
+            ExitStatus::Error
+        });
+        if status == ExitStatus::Error {
+            failed += 1;
+            if fail_fast {
+                break;
+            }
+        }
+    }
+    (failed == 0).into()
+}
+
+/// Returns the name of the `index`-th function exported by the library that was started.
+fn entry_point_by_index(
+    runtime: &Runtime,
+    matches: &ArgMatches,
+    index: &str,
+) -> Result<String, anyhow::Error> {
+    let index: usize = index
+        .parse()
+        .map_err(|_| anyhow!("'{}' is not a valid entry point index", index))?;
+
+    let functions = library_functions(runtime, matches)?;
+    functions
+        .get(index)
+        .map(|function| function.prototype.name().to_owned())
+        .ok_or_else(|| {
+            anyhow!(
+                "entry point index {} is out of range, the library exports {} function(s)",
+                index,
+                functions.len()
+            )
+        })
+}
+
+/// Options that control how an entry point is invoked by `start`.
+#[derive(Copy, Clone, Debug)]
+struct InvokeOptions<'a> {
+    /// Whether stdout is flushed after the entry point returns
+    flush: bool,
+    /// The format in which the return value is printed
+    output: OutputFormat,
+    /// Whether the full backtrace of a panic is printed
+    backtrace: bool,
+    /// Whether the return value is discarded instead of printed, regardless of its type
+    ignore_return: bool,
+    /// Whether the integer return value is used as exit code instead of printed
+    exit_code: bool,
+    /// Whether error messages are colored with ANSI escape codes
+    colors: bool,
+    /// The file that the return value is written to instead of stdout
+    out_file: Option<&'a Path>,
+}
+
+/// Formats an error message of `start`, with a bold red `error` prefix if `colors` is enabled.
+fn error_message(message: String, colors: bool) -> String {
+    if colors {
+        format!("{}: {}", Red.bold().paint("error"), message)
+    } else {
+        format!("error: {}", message)
+    }
+}
+
+/// Lists the exported functions without arguments and prompts the user to choose one by number.
+/// If there is at most one such function, it is selected without prompting.
+fn select_entry_point(runtime: &Runtime, matches: &ArgMatches) -> Result<String, anyhow::Error> {
+    let candidates: Vec<&mun_abi::FunctionDefinition> = library_functions(runtime, matches)?
+        .into_iter()
+        .filter(|function| function.prototype.signature.arg_types().is_empty())
+        .collect();
+    match candidates.as_slice() {
+        [] => return Ok("main".to_owned()),
+        [function] => return Ok(function.prototype.name().to_owned()),
+        _ => {}
+    }
+
+    for (index, function) in candidates.iter().enumerate() {
+        println!("[{}] {}", index, function.prototype);
+    }
+    let stdin = std::io::stdin();
+    loop {
+        print!("Select an entry point [0-{}]: ", candidates.len() - 1);
+        std::io::stdout().flush()?;
+
+        let mut line = String::new();
+        if stdin.read_line(&mut line)? == 0 {
+            return Err(anyhow!("no entry point was selected"));
+        }
+        match line.trim().parse::<usize>() {
+            Ok(index) if index < candidates.len() => {
+                return Ok(candidates[index].prototype.name().to_owned())
+            }
+            _ => eprintln!("'{}' is not a valid choice", line.trim()),
+        }
+    }
+}
+
+/// Invokes the function `entry_point` with `args` and prints its return value. If the runtime's
+/// garbage collection is deterministic, garbage is collected afterwards.
+///
+/// An invocation that is aborted because it would exceed the memory limit is reported as an
+/// error. A panic during the invocation is reported as an error with the panic message; the
+/// backtrace is only printed if requested in `options`.
+fn invoke_entry(
+    runtime: &Rc<RefCell<Runtime>>,
+    entry_point: &str,
+    args: &[ArgValue],
+    options: InvokeOptions,
+) -> Result<(ExitStatus, Option<ReturnValue>), anyhow::Error> {
+    let result = catch_panic(|| invoke_and_print(runtime, entry_point, args, options))
+        .unwrap_or_else(|panic| {
+            eprintln!(
+                "{}",
+                error_message(
+                    format!("entry point '{}' panicked: {}", entry_point, panic.message),
+                    options.colors
                 )
-            })?
+            );
+            if options.backtrace {
+                match panic.backtrace {
+                    Some(mut backtrace) => {
+                        backtrace.resolve();
+                        eprintln!("stack backtrace:\n{:?}", backtrace);
+                    }
+                    None => eprintln!("no backtrace was captured"),
+                }
+            }
+            Ok((ExitStatus::Error, None))
+        });
+    // Exceeding the memory limit fails the entry point like a panic does
+    let result = match result {
+        Err(e) => match e.downcast::<DriverError>() {
+            Ok(error @ DriverError::MemoryLimitExceeded { .. }) => {
+                eprintln!("{}", error_message(error.to_string(), options.colors));
+                Ok((ExitStatus::Error, None))
+            }
+            Ok(error) => Err(error.into()),
+            Err(e) => Err(e),
+        },
+        result => result,
+    };
+    if options.flush {
+        std::io::stdout().flush()?;
+    }
+
+    let borrowed = runtime.borrow();
+    if borrowed.is_gc_deterministic() {
+        borrowed.gc_collect();
+    }
+    result
+}
+
+thread_local! {
+    /// Whether a panic on this thread is caught by `catch_panic`
+    static CATCHING_PANIC: Cell<bool> = Cell::new(false);
+    /// The backtrace of the last panic caught by `catch_panic` on this thread
+    static PANIC_BACKTRACE: RefCell<Option<backtrace::Backtrace>> = RefCell::new(None);
+}
+
+/// Installs the panic hook of the process, once. A panic that is caught by `catch_panic` is not
+/// printed, its backtrace is only captured. Any other panic is printed by the previous hook.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if CATCHING_PANIC.with(Cell::get) {
+                // Symbols are only resolved if the backtrace is printed
+                let backtrace = backtrace::Backtrace::new_unresolved();
+                PANIC_BACKTRACE.with(|cell| *cell.borrow_mut() = Some(backtrace));
+            } else {
+                default_hook(info);
+            }
+        }));
+    });
+}
+
+/// A panic caught by `catch_panic`.
+struct CaughtPanic {
+    /// The panic message
+    message: String,
+    /// The backtrace of the panic, if it was captured by the hook of `install_panic_hook`
+    backtrace: Option<backtrace::Backtrace>,
+}
+
+/// Calls `f`, catching a panic and returning its message and backtrace instead.
+fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, CaughtPanic> {
+    let was_catching = CATCHING_PANIC.with(|catching| catching.replace(true));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+    CATCHING_PANIC.with(|catching| catching.set(was_catching));
+
+    result.map_err(|payload| CaughtPanic {
+        message: payload
+            .downcast_ref::<&str>()
+            .map(|message| (*message).to_owned())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_owned()),
+        backtrace: PANIC_BACKTRACE.with(|cell| cell.borrow_mut().take()),
+    })
+}
+
+/// Invokes the function `entry_point` with `args` and prints its return value in the requested
+/// output format, unless the return value is ignored. Returns the return value, if it was read.
+fn invoke_and_print(
+    runtime: &Rc<RefCell<Runtime>>,
+    entry_point: &str,
+    args: &[ArgValue],
+    options: InvokeOptions,
+) -> Result<(ExitStatus, Option<ReturnValue>), anyhow::Error> {
+    let borrowed = runtime.borrow();
+    let fn_definition = borrowed
+        .get_function_definition(entry_point)
+        .ok_or_else(|| DriverError::EntryPointNotFound(entry_point.to_owned()))?;
+
+    if !args.is_empty() {
+        let return_type = fn_definition.prototype.signature.return_type();
+        let value = return_value::invoke_with_args(runtime, entry_point, return_type, args)?;
+        let status = match &value {
+            Some(value) if options.exit_code => exit_code(entry_point, value)?,
+            Some(value) if !options.ignore_return => {
+                output_value(value, options)?;
+                ExitStatus::Success
+            }
+            _ => ExitStatus::Success,
+        };
+        return Ok((status, value));
+    }
+
+    if let Some(ret_type) = fn_definition.prototype.signature.return_type() {
+        if options.ignore_return {
+            return_value::invoke_discarding(runtime, entry_point, ret_type)?;
+            return Ok((ExitStatus::Success, None));
+        }
+
+        let value = ReturnValue::invoke(runtime, entry_point, ret_type)?;
+        let status = if options.exit_code {
+            exit_code(entry_point, &value)?
+        } else {
+            output_value(&value, options)?;
+            ExitStatus::Success
+        };
+        Ok((status, Some(value)))
+    } else {
+        #[allow(clippy::unit_arg)]
+        invoke_fn!(runtime, entry_point)
+            .map(|_: ()| (ExitStatus::Success, None))
+            .map_err(|e| DriverError::invocation(entry_point, e).into())
+    }
+}
+
+/// Renders `value` in the requested output format and writes it to the output file, overwriting
+/// it, or prints it if no output file is specified.
+fn output_value(value: &ReturnValue, options: InvokeOptions) -> Result<(), anyhow::Error> {
+    let rendered = value.render(options.output)?;
+    match options.out_file {
+        Some(path) => std::fs::write(path, format!("{}\n", rendered)).map_err(|e| {
+            anyhow!(
+                "could not write the return value to '{}': {}",
+                path.display(),
+                e
+            )
+        }),
+        None => {
+            println!("{}", rendered);
+            Ok(())
         }
-        Some(path) => std::fs::canonicalize(Path::new(path))
-            .map_err(|_| anyhow::anyhow!("'{}' does not refer to a valid manifest path", path))?,
+    }
+}
+
+/// Converts the integer `value` returned by `entry_point` into an exit code, truncating it to 32
+/// bits.
+fn exit_code(entry_point: &str, value: &ReturnValue) -> Result<ExitStatus, anyhow::Error> {
+    match value {
+        ReturnValue::Fundamental(value) => value.to_integer(),
+        ReturnValue::Struct { .. } => None,
+    }
+    .map(|code| ExitStatus::Code(code as i32))
+    .ok_or_else(|| {
+        anyhow!(
+            "cannot use the return value of '{}' as exit code, it is not an integer",
+            entry_point
+        )
+    })
+}
+
+/// Parses an optimization level, defaulting to level 2 if none is specified.
+fn parse_opt_level(level: Option<&str>) -> Result<mun_compiler::OptimizationLevel, anyhow::Error> {
+    Ok(match level {
+        Some("0") => mun_compiler::OptimizationLevel::None,
+        Some("1") => mun_compiler::OptimizationLevel::Less,
+        None | Some("2") => mun_compiler::OptimizationLevel::Default,
+        Some("3") => mun_compiler::OptimizationLevel::Aggressive,
+        _ => return Err(anyhow!("Only optimization levels 0-3 are supported")),
+    })
+}
+
+/// Resolves whether terminal output is colored from the `--color` argument and the environment.
+fn display_color(matches: &ArgMatches) -> DisplayColor {
+    resolve_display_color(
+        matches.value_of("color"),
+        env::var("MUN_TERMINAL_COLOR").ok().as_deref(),
+        env::var_os("NO_COLOR").map_or(false, |value| !value.is_empty()),
+    )
+}
+
+/// Resolves whether terminal output is colored. In order of precedence: `--color enable`, a set
+/// `NO_COLOR` environment variable, any other value of `--color`, the `MUN_TERMINAL_COLOR`
+/// environment variable, and finally auto-detection.
+fn resolve_display_color(
+    flag: Option<&str>,
+    mun_terminal_color: Option<&str>,
+    no_color: bool,
+) -> DisplayColor {
+    if flag == Some("enable") {
+        return DisplayColor::Enable;
+    }
+    if no_color {
+        return DisplayColor::Disable;
+    }
+    match flag.or(mun_terminal_color) {
+        Some("disable") => DisplayColor::Disable,
+        Some("enable") => DisplayColor::Enable,
+        _ => DisplayColor::Auto,
+    }
+}
+
+fn compiler_options(matches: &ArgMatches) -> Result<mun_compiler::Config, anyhow::Error> {
+    let optimization_lvl = if matches.is_present("release") {
+        mun_compiler::OptimizationLevel::Aggressive
+    } else {
+        parse_opt_level(matches.value_of("opt-level"))?
+    };
+    let display_color = display_color(matches);
+
+    let mut config = Config {
+        target: matches
+            .value_of("target")
+            .map_or_else(Target::host_target, Target::search)?,
+        optimization_lvl,
+        out_dir: matches.value_of("out-dir").map(PathBuf::from),
+        display_color,
+        data_layout: matches.value_of("data-layout").map(ToOwned::to_owned),
+        cpu: matches.value_of("cpu").map(|cpu| match cpu {
+            "native" => mun_compiler::host_cpu_name(),
+            cpu => cpu.to_owned(),
+        }),
+        target_features: matches.value_of("target-features").map(ToOwned::to_owned),
+        package_version: matches
+            .value_of("package-version")
+            .map(|version| {
+                mun_project::Version::parse(version).map_err(|e| {
+                    anyhow!(
+                        "invalid value '{}' for --package-version, expected a semantic version: {}",
+                        version,
+                        e
+                    )
+                })
+            })
+            .transpose()?,
+        print_commands: matches.is_present("print-commands"),
+        default_visibility: match matches.value_of("default-visibility") {
+            Some("hidden") => SymbolVisibility::Hidden,
+            _ => SymbolVisibility::Public,
+        },
+        overflow_behavior: match matches.value_of("overflow") {
+            Some("panic") => OverflowBehavior::Panic,
+            Some("saturate") => OverflowBehavior::Saturate,
+            _ => OverflowBehavior::Wrap,
+        },
+        instrument_calls: matches.is_present("instrument-calls"),
+        emit: match matches.value_of("emit") {
+            Some("llvm-ir") => Emit::LlvmIr,
+            Some("assembly") => Emit::Assembly,
+            _ => Emit::Munlib,
+        },
+        post_compile: None,
+        link_command_hook: None,
+        deny_warnings: matches.is_present("deny-warnings"),
+        stop_after: match matches.value_of("stop-after") {
+            Some("parse") => Stage::Parse,
+            Some("typecheck") => Stage::Typecheck,
+            _ => Stage::Codegen,
+        },
+        out_name: matches
+            .value_of("out-name")
+            .map(|name| {
+                if name.is_empty() || name.contains(|c: char| c == '/' || c == '\\') {
+                    Err(anyhow!(
+                        "invalid value '{}' for --out-name, expected a file name without directories",
+                        name
+                    ))
+                } else {
+                    Ok(name.to_owned())
+                }
+            })
+            .transpose()?,
+        input_encoding: match matches.value_of("input-encoding") {
+            Some("latin-1") => InputEncoding::Latin1,
+            _ => InputEncoding::Utf8,
+        },
+    };
+
+    if let Some(flags) = matches.values_of("COMPILER_ARGS") {
+        config.apply_flags(&flags.collect::<Vec<_>>())?;
+    }
+    if config.print_commands {
+        config.link_command_hook = Some(LinkCommandHook::new(|command| {
+            println!("{}", mun_compiler::format_command(command))
+        }));
+    }
+    Ok(config)
+}
+
+/// Applies the defaults from the `[build]` section of a manifest to the options that were not
+/// specified on the command line. In order of precedence: command-line flags (including raw `-C`
+/// compiler flags), the manifest, the built-in defaults.
+fn apply_build_defaults(
+    config: &mut Config,
+    matches: &ArgMatches,
+    defaults: &mun_project::BuildDefaults,
+) -> Result<(), anyhow::Error> {
+    let invalid_default = |key: &str, value: &dyn std::fmt::Display, flag: &str, e| {
+        anyhow!(
+            "invalid value '{}' for `{}` in the [build] section of {}: {}\n\
+             note: command-line flags take precedence over the manifest, pass `{}` to override it",
+            value,
+            key,
+            MANIFEST_FILENAME,
+            e,
+            flag
+        )
     };
 
-    log::info!("located build manifest at: {}", manifest_path.display());
+    if let Some(level) = defaults.opt_level {
+        let overridden = matches.is_present("opt-level")
+            || matches.is_present("release")
+            || has_compiler_flag(matches, "opt-level");
+        if !overridden {
+            config.optimization_lvl = parse_opt_level(Some(&level.to_string()))
+                .map_err(|e| invalid_default("opt-level", &level, "--opt-level", e))?;
+        }
+    }
 
-    if matches.is_present("watch") {
-        mun_compiler_daemon::compile_and_watch_manifest(&manifest_path, options)
-    } else {
-        mun_compiler::compile_manifest(&manifest_path, options)
+    if let Some(target) = &defaults.target {
+        if !matches.is_present("target") && !has_compiler_flag(matches, "target") {
+            config.target = Target::search(target)
+                .map_err(|e| invalid_default("target", target, "--target", anyhow!("{}", e)))?;
+        }
     }
-    .map(Into::into)
+    Ok(())
 }
 
-/// Starts the runtime with the specified library and invokes function `entry`.
-fn start(matches: &ArgMatches) -> Result<ExitStatus, anyhow::Error> {
-    let runtime = runtime(matches)?;
+/// Renders the resolved configuration for building the package at `manifest_path` as `key=value`
+/// lines, in a fixed order.
+fn render_config(config: &Config, manifest_path: &Path) -> String {
+    let opt_level = match config.optimization_lvl {
+        mun_compiler::OptimizationLevel::None => 0,
+        mun_compiler::OptimizationLevel::Less => 1,
+        mun_compiler::OptimizationLevel::Default => 2,
+        mun_compiler::OptimizationLevel::Aggressive => 3,
+    };
+    let out_dir = config.out_dir.clone().unwrap_or_else(|| {
+        manifest_path
+            .parent()
+            .expect("manifest path must have a parent directory")
+            .join("target")
+    });
+    let color = match config.display_color {
+        DisplayColor::Disable => "disable",
+        DisplayColor::Auto => "auto",
+        DisplayColor::Enable => "enable",
+    };
+    format!(
+        "target={}\nopt-level={}\nout-dir={}\ncolor={}\nstop-after={}\nmanifest-path={}\n",
+        config.target.llvm_target,
+        opt_level,
+        out_dir.display(),
+        color,
+        config.stop_after.name(),
+        manifest_path.display()
+    )
+}
 
-    let borrowed = runtime.borrow();
-    let entry_point = matches.value_of("entry").unwrap_or("main");
-    let fn_definition = borrowed
-        .get_function_definition(entry_point)
-        .ok_or_else(|| {
-            std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!("Failed to obtain entry point '{}'", entry_point),
+/// Returns true if a raw compiler flag `-C key=value` with the specified key was passed.
+fn has_compiler_flag(matches: &ArgMatches, key: &str) -> bool {
+    matches
+        .values_of("COMPILER_ARGS")
+        .into_iter()
+        .flatten()
+        .any(|flag| {
+            let option = flag.trim_start_matches("-C");
+            option == key || option.starts_with(&format!("{}=", key))
+        })
+}
+
+/// Constructs the configuration of the build watcher from the command line arguments.
+fn watch_config(matches: &ArgMatches) -> Result<mun_compiler_daemon::WatchConfig, anyhow::Error> {
+    let mut watch_config = mun_compiler_daemon::WatchConfig::default();
+    if let Some(delay) = matches.value_of("delay") {
+        let delay: u64 = delay.parse()?;
+        watch_config.delay = Duration::from_millis(delay);
+    }
+    if let Some(count) = matches.value_of("debounce-count") {
+        watch_config.debounce_count = count.parse().map_err(|_| {
+            anyhow!(
+                "invalid value '{}' for --debounce-count, expected a positive integer",
+                count
             )
         })?;
+    }
+    watch_config.full_rebuild = matches.is_present("watch-full");
+    watch_config.print_timings = matches.is_present("timings");
+    // File system events refer to canonical paths
+    watch_config.extra_paths = matches
+        .values_of("watch-paths")
+        .map_or_else(Vec::new, Iterator::collect)
+        .into_iter()
+        .map(|path| {
+            std::fs::canonicalize(path).map_err(|e| anyhow!("could not watch '{}': {}", path, e))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(watch_config)
+}
 
-    if let Some(ret_type) = fn_definition.prototype.signature.return_type() {
-        let type_guid = &ret_type.guid;
-        if *type_guid == bool::type_guid() {
-            let result: bool = invoke_fn!(runtime, entry_point).map_err(|e| anyhow!("{}", e))?;
+/// Returns the level and message with which a reload event of the runtime is logged: reloads at
+/// info level, failed reloads as warnings and all other events at debug level.
+fn reload_log_message(event: &ReloadEvent) -> (log::Level, String) {
+    let level = match event {
+        ReloadEvent::Reloaded { .. } => log::Level::Info,
+        ReloadEvent::ReloadFailed { .. } => log::Level::Warn,
+        ReloadEvent::FileChanged { .. } | ReloadEvent::Ignored { .. } => log::Level::Debug,
+    };
+    (level, event.to_string())
+}
 
-            println!("{}", result)
-        } else if *type_guid == f64::type_guid() {
-            let result: f64 = invoke_fn!(runtime, entry_point).map_err(|e| anyhow!("{}", e))?;
+/// Resolves how long the runtime delays filesystem events before reloading. In order of
+/// precedence: `--delay`, the `MUN_RELOAD_DELAY` environment variable, and the `reload-delay` of
+/// the manifest. Returns `None` if none is set, in which case the runtime's default is used.
+fn resolve_reload_delay(
+    flag: Option<&str>,
+    mun_reload_delay: Option<&str>,
+    manifest: Option<u64>,
+) -> Result<Option<Duration>, anyhow::Error> {
+    let parse = |value: &str, source: &str| match value.trim().parse::<u64>() {
+        Ok(delay) if delay > 0 => Ok(delay),
+        _ => Err(anyhow!(
+            "invalid value '{}' for {}, expected a positive number of milliseconds",
+            value,
+            source
+        )),
+    };
+    let delay = match (flag, mun_reload_delay) {
+        (Some(flag), _) => Some(parse(flag, "--delay")?),
+        (None, Some(env)) => Some(parse(env, "MUN_RELOAD_DELAY")?),
+        (None, None) => manifest,
+    };
+    Ok(delay.map(Duration::from_millis))
+}
 
-            println!("{}", result)
-        } else if *type_guid == i64::type_guid() {
-            let result: i64 = invoke_fn!(runtime, entry_point).map_err(|e| anyhow!("{}", e))?;
+/// Validates the `--seed` of the runtime. The runtime does not expose a random number generator
+/// that could be seeded, so a valid seed is rejected too instead of being ignored.
+fn seed_runtime(seed: &str) -> Result<(), anyhow::Error> {
+    let seed: u64 = seed.parse().map_err(|_| {
+        anyhow!(
+            "invalid value '{}' for --seed, expected an unsigned 64-bit integer",
+            seed
+        )
+    })?;
+    Err(anyhow!(
+        "cannot seed the runtime with {}: the runtime does not provide a random number generator\n\
+         note: without one, runs of the same library with the same arguments are already reproducible",
+        seed
+    ))
+}
 
-            println!("{}", result)
-        } else {
-            return Err(anyhow!(
-                "Only native Mun return types are supported for entry points. Found: {}",
-                ret_type.name()
-            ));
-        };
-        Ok(ExitStatus::Success)
-    } else {
-        #[allow(clippy::unit_arg)]
-        invoke_fn!(runtime, entry_point)
-            .map(|_: ()| ExitStatus::Success)
-            .map_err(|e| anyhow!("{}", e))
+fn runtime(
+    library_paths: &[&Path],
+    matches: &ArgMatches,
+    reload_summary: Option<&reload_summary::ReloadSummary>,
+) -> Result<Rc<RefCell<Runtime>>, anyhow::Error> {
+    if let Some(path) = library_paths
+        .iter()
+        .find(|path| path.extension().map_or(false, |ext| ext == WASM_EXTENSION))
+    {
+        return Err(anyhow!(
+            "'{}' is a WebAssembly module, which cannot be loaded by the Mun runtime\n\
+             help: build the package without --target, so it is compiled for this machine",
+            path.display()
+        ));
     }
-}
+    if let Some(seed) = matches.value_of("seed") {
+        seed_runtime(seed)?;
+    }
+    let (library_path, additional_library_paths) = library_paths
+        .split_first()
+        .expect("at least one library is required");
+    let builder = additional_library_paths
+        .iter()
+        .fold(RuntimeBuilder::new(*library_path), |builder, path| {
+            builder.add_library(*path)
+        });
 
-fn compiler_options(matches: &ArgMatches) -> Result<mun_compiler::Config, anyhow::Error> {
-    let optimization_lvl = match matches.value_of("opt-level") {
-        Some("0") => mun_compiler::OptimizationLevel::None,
-        Some("1") => mun_compiler::OptimizationLevel::Less,
-        None | Some("2") => mun_compiler::OptimizationLevel::Default,
-        Some("3") => mun_compiler::OptimizationLevel::Aggressive,
-        _ => return Err(anyhow!("Only optimization levels 0-3 are supported")),
+    let manifest_delay = match library_path
+        .canonicalize()
+        .ok()
+        .and_then(|path| find_manifest(path.parent()?, MANIFEST_FILENAME))
+    {
+        Some(manifest_path) => mun_project::Manifest::from_file(&manifest_path)?.reload_delay(),
+        None => None,
+    };
+    let builder = if let Some(delay) = resolve_reload_delay(
+        matches.value_of("delay"),
+        env::var("MUN_RELOAD_DELAY").ok().as_deref(),
+        manifest_delay,
+    )? {
+        builder.set_delay(delay)
+    } else {
+        builder
     };
 
-    let display_color = matches
-        .value_of("color")
-        .map(ToOwned::to_owned)
-        .or_else(|| env::var("MUN_TERMINAL_COLOR").ok())
-        .map(|value| match value.as_str() {
-            "disable" => DisplayColor::Disable,
-            "enable" => DisplayColor::Enable,
-            _ => DisplayColor::Auto,
-        })
-        .unwrap_or(DisplayColor::Auto);
+    let builder = if let Some(limit) = matches.value_of("mem-limit") {
+        builder.set_memory_limit(parse_size(limit)?)
+    } else {
+        builder
+    };
 
-    Ok(Config {
-        target: matches
-            .value_of("target")
-            .map_or_else(Target::host_target, Target::search)?,
-        optimization_lvl,
-        out_dir: None,
-        display_color,
-    })
-}
+    let builder = if let Some(size) = matches.value_of("heap-size") {
+        builder.set_heap_size(parse_size(size)?)
+    } else {
+        builder
+    };
 
-fn runtime(matches: &ArgMatches) -> Result<Rc<RefCell<Runtime>>, anyhow::Error> {
-    let builder = RuntimeBuilder::new(
-        matches.value_of("LIBRARY").unwrap(), // Safe because its a required arg
-    );
+    let mut reload_observers: Vec<Box<dyn FnMut(&ReloadEvent)>> = vec![Box::new(|event| {
+        let (level, message) = reload_log_message(event);
+        log::log!(level, "{}", message);
+    })];
+    if let Some(path) = matches.value_of("trace-reloads") {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| anyhow!("could not open reload trace file '{}': {}", path, e))?;
+        reload_observers.push(Box::new(move |event| {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            if let Err(e) = writeln!(
+                file,
+                "[{}.{:03}] {}",
+                timestamp.as_secs(),
+                timestamp.subsec_millis(),
+                event
+            ) {
+                log::warn!("could not write to reload trace file: {}", e);
+            }
+        }));
+    }
+    if let Some(reload_summary) = reload_summary {
+        reload_observers.push(Box::new(reload_summary.observer()));
+    }
+    let builder = builder.set_reload_observer(move |event| {
+        for observer in reload_observers.iter_mut() {
+            observer(event);
+        }
+    });
 
-    let builder = if let Some(delay) = matches.value_of("delay") {
-        let delay: u64 = delay.parse()?;
-        builder.set_delay(Duration::from_millis(delay))
+    let builder = if let Some(retries) = matches.value_of("watch-retries") {
+        builder.set_watch_retries(retries.parse().map_err(|_| {
+            anyhow!(
+                "invalid value '{}' for --watch-retries, expected a non-negative integer",
+                retries
+            )
+        })?)
     } else {
         builder
     };
 
-    builder.spawn()
+    builder
+        .set_watch(!matches.is_present("no-watch"))
+        .set_deterministic_gc(matches.is_present("gc-deterministic"))
+        .set_gc_strategy(gc_strategy(matches)?)
+        .set_profiling(matches.is_present("profile"))
+        .spawn()
+}
+
+/// Returns the garbage collection strategy selected with `--gc`, or the default strategy.
+fn gc_strategy(matches: &ArgMatches) -> Result<GcStrategy, anyhow::Error> {
+    matches
+        .value_of("gc")
+        .map_or_else(|| Ok(GcStrategy::default()), str::parse)
+}
+
+/// Parses a human-friendly size (e.g. `512kb`, `64mb`, `1gb`, or a plain number of bytes) into a
+/// number of bytes.
+fn parse_size(size: &str) -> Result<usize, anyhow::Error> {
+    let size = size.trim().to_lowercase();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let multiplier = match unit.trim() {
+        "" | "b" => 1,
+        "k" | "kb" => 1024,
+        "m" | "mb" => 1024 * 1024,
+        "g" | "gb" => 1024 * 1024 * 1024,
+        _ => {
+            return Err(anyhow!(
+                "invalid size unit '{}', expected b, kb, mb or gb",
+                unit
+            ))
+        }
+    };
+    number
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+        .ok_or_else(|| anyhow!("invalid size '{}'", size))
 }
 
 /// This function is invoked when the executable is invoked with the `language-server` argument. A
@@ -257,30 +4263,900 @@ fn runtime(matches: &ArgMatches) -> Result<Rc<RefCell<Runtime>>, anyhow::Error>
 ///
 /// The `bool` return type for this function indicates whether the process should exit with a
 /// success or failure error code.
-fn language_server(_matches: &ArgMatches) -> Result<ExitStatus, anyhow::Error> {
-    mun_language_server::run_server().map_err(|e| anyhow::anyhow!("{}", e))?;
+fn language_server(matches: &ArgMatches, context: &Context) -> Result<ExitStatus, anyhow::Error> {
+    language_server_with(matches, context, mun_language_server::run_server)
+}
+
+/// Starts a language server like [`language_server`], by passing its configuration to
+/// `run_server`.
+fn language_server_with<F>(
+    matches: &ArgMatches,
+    context: &Context,
+    run_server: F,
+) -> Result<ExitStatus, anyhow::Error>
+where
+    F: FnOnce(mun_language_server::Config) -> mun_language_server::Result<()>,
+{
+    if let Some(manifest_path) = matches.value_of("check") {
+        let manifest_path =
+            locate_manifest(&context.working_dir, Some(manifest_path), MANIFEST_FILENAME)?;
+        let summary =
+            mun_language_server::check(&manifest_path).map_err(|e| anyhow::anyhow!("{}", e))?;
+        println!("{} diagnostic(s)", summary.diagnostics);
+        return Ok(if summary.has_errors {
+            ExitStatus::Error
+        } else {
+            ExitStatus::Success
+        });
+    }
+
+    let manifest_paths = matches
+        .values_of("manifest-path")
+        .map_or_else(Vec::new, Iterator::collect)
+        .into_iter()
+        .map(|path| locate_manifest(&context.working_dir, Some(path), MANIFEST_FILENAME))
+        .collect::<Result<Vec<_>, _>>()?;
+    let config = mun_language_server::Config {
+        performance_trace: matches.value_of("trace-performance").map(PathBuf::from),
+        manifest_paths,
+    };
+    run_server(config).map_err(|e| anyhow::anyhow!("{}", e))?;
     Ok(ExitStatus::Success)
 }
 
 #[cfg(test)]
 mod test {
-    use crate::find_manifest;
+    use crate::{
+        apply_build_defaults, backend_unavailable_message, bench_entry_point, build_cli,
+        catch_panic, compile_manifest_timed, compiler_options, error_message,
+        find_library_in_directory, find_manifest, gc_strategy, install_panic_hook,
+        invoke_and_print, invoke_in_order, language_server_with, locate_manifest, log_level,
+        parse_size, print_artifacts, print_syntax_trees, reload_log_message, render_build_summary,
+        render_config, render_targets, resolve_display_color, resolve_reload_delay, return_value,
+        run_with_args, ArgValue, Context, DriverError, ExitStatus, InvokeOptions, OutputFormat,
+        ReturnValue,
+    };
+    use clap::{App, Arg, Shell};
+    use log::LevelFilter;
+    use mun_compiler::{DisplayColor, OptimizationLevel};
     use mun_project::MANIFEST_FILENAME;
+    use mun_runtime::{GcStrategy, ReloadEvent};
+    use std::path::Path;
+    use std::time::Duration;
     use tempdir::TempDir;
 
+    #[test]
+    fn test_bench_entry_point() {
+        let out_dir = TempDir::new("test_bench_entry_point").unwrap();
+        let config = mun_compiler::Config {
+            out_dir: Some(out_dir.path().to_path_buf()),
+            ..mun_compiler::Config::default()
+        };
+        let mut source =
+            &b"pub fn fib(n: i64) -> i64 { if n <= 1 { n } else { fib(n - 1) + fib(n - 2) } }"[..];
+        assert!(crate::multi_file::compile_reader(&mut source, config).unwrap());
+
+        let runtime = mun_runtime::RuntimeBuilder::new(out_dir.path().join("stdin.munlib"))
+            .set_watch(false)
+            .spawn()
+            .unwrap();
+        let stats = bench_entry_point(&runtime, "fib", &["10"], 5).unwrap();
+        assert_eq!(stats.iterations, 5);
+        let text = stats.to_text();
+        for label in &["iterations", "min", "median", "mean", "max"] {
+            assert!(text.contains(label), "{}", text);
+        }
+
+        assert!(bench_entry_point(&runtime, "fib", &["ten"], 5).is_err());
+    }
+
+    #[test]
+    fn test_runtime_without_watch() {
+        let dir = TempDir::new("test_runtime_without_watch").unwrap();
+        std::fs::write(
+            dir.path().join(MANIFEST_FILENAME),
+            "[package]\nname=\"test\"\nversion=\"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        let manifest_path = dir.path().join(MANIFEST_FILENAME);
+        let build = |value: i64| {
+            std::fs::write(
+                dir.path().join("src/main.mun"),
+                format!("pub fn main() -> i64 {{ {} }}", value),
+            )
+            .unwrap();
+            assert!(mun_compiler::compile_manifest(
+                &manifest_path,
+                mun_compiler::Config::default()
+            )
+            .unwrap());
+        };
+        build(5);
+
+        let library_path = dir.path().join("target/main.munlib");
+        let start = |args: &[&str]| {
+            let matches = build_cli().get_matches_from(
+                ["mun", "start", library_path.to_str().unwrap()]
+                    .iter()
+                    .chain(args.iter()),
+            );
+            crate::runtime(
+                &[&library_path],
+                matches.subcommand_matches("start").unwrap(),
+                None,
+            )
+            .unwrap()
+        };
+        let watched = start(&[]);
+        let unwatched = start(&["--no-watch"]);
+
+        // Once the watched runtime has reloaded the modified library, the other one could have too
+        build(6);
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while !watched.borrow_mut().update() {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "the library was not reloaded"
+            );
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let result: i64 = mun_runtime::invoke_fn!(watched, "main").unwrap();
+        assert_eq!(result, 6);
+
+        assert!(!unwatched.borrow_mut().update());
+        let result: i64 = mun_runtime::invoke_fn!(unwatched, "main").unwrap();
+        assert_eq!(result, 5);
+    }
+
+    #[test]
+    fn test_catch_panic() {
+        install_panic_hook();
+
+        assert_eq!(catch_panic(|| 42).ok(), Some(42));
+
+        let panic = catch_panic(|| panic!("entry point failed")).err().unwrap();
+        assert_eq!(panic.message, "entry point failed");
+        assert!(panic.backtrace.is_some());
+
+        // The backtrace of a panic is not left behind for the next one
+        let panic = catch_panic(|| std::panic::resume_unwind(Box::new("resumed")))
+            .err()
+            .unwrap();
+        assert_eq!(panic.message, "resumed");
+        assert!(panic.backtrace.is_none());
+    }
+
+    #[test]
+    fn test_locate_manifest() {
+        let dir = TempDir::new("test_locate_manifest").unwrap();
+        let package_dir = dir.path().join("package");
+        std::fs::create_dir_all(package_dir.join("src")).unwrap();
+        std::fs::write(package_dir.join(MANIFEST_FILENAME), "").unwrap();
+        let manifest_path = package_dir.join(MANIFEST_FILENAME).canonicalize().unwrap();
+
+        // Without a manifest path, the search starts in the working directory
+        assert_eq!(
+            locate_manifest(&package_dir.join("src"), None, MANIFEST_FILENAME).unwrap(),
+            manifest_path
+        );
+        // A relative manifest path is resolved against the working directory
+        assert_eq!(
+            locate_manifest(dir.path(), Some("package"), MANIFEST_FILENAME).unwrap(),
+            manifest_path
+        );
+        let error = locate_manifest(dir.path(), None, MANIFEST_FILENAME).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<DriverError>(),
+            Some(&DriverError::ManifestNotFound {
+                manifest_name: MANIFEST_FILENAME.to_owned(),
+                directory: dir.path().to_path_buf(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_driver_errors() {
+        let dir = TempDir::new("test_driver_errors").unwrap();
+        let missing_manifest = dir.path().join("missing").join(MANIFEST_FILENAME);
+        let error =
+            locate_manifest(dir.path(), missing_manifest.to_str(), MANIFEST_FILENAME).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<DriverError>(),
+            Some(&DriverError::InvalidManifestPath(missing_manifest.clone()))
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "'{}' does not refer to a valid manifest path",
+                missing_manifest.display()
+            )
+        );
+
+        let config = mun_compiler::Config {
+            out_dir: Some(dir.path().to_path_buf()),
+            ..mun_compiler::Config::default()
+        };
+        let mut source =
+            &b"pub fn size() -> isize { 1 }\npub fn double(n: i64) -> i64 { n * 2 }"[..];
+        assert!(crate::multi_file::compile_reader(&mut source, config).unwrap());
+        let runtime = mun_runtime::RuntimeBuilder::new(dir.path().join("stdin.munlib"))
+            .set_watch(false)
+            .spawn()
+            .unwrap();
+
+        let options = InvokeOptions {
+            flush: false,
+            output: OutputFormat::Text,
+            backtrace: false,
+            ignore_return: false,
+            exit_code: false,
+            colors: false,
+            out_file: None,
+        };
+        let error = invoke_and_print(&runtime, "missing", &[], options).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<DriverError>(),
+            Some(&DriverError::EntryPointNotFound("missing".to_owned()))
+        );
+        assert_eq!(error.to_string(), "Failed to obtain entry point 'missing'");
+
+        let borrowed = runtime.borrow();
+        let return_type = |name| {
+            borrowed
+                .get_function_definition(name)
+                .and_then(|function| function.prototype.signature.return_type())
+                .unwrap()
+        };
+        let error = ReturnValue::invoke(&runtime, "size", return_type("size")).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<DriverError>(),
+            Some(&DriverError::UnsupportedReturnType {
+                type_name: "core::isize".to_owned(),
+                with_args: false,
+            })
+        );
+
+        let error = return_value::invoke_with_args(
+            &runtime,
+            "double",
+            Some(return_type("double")),
+            &[ArgValue::Bool(true)],
+        )
+        .unwrap_err();
+        match error.downcast_ref::<DriverError>() {
+            Some(DriverError::Invocation { entry_point, .. }) => assert_eq!(entry_point, "double"),
+            other => panic!("expected an invocation error, found {:?}", other),
+        }
+
+        // The report names the function that failed and the reason on separate lines
+        let report = invoke_and_print(&runtime, "double", &[], options)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            report,
+            "failed to invoke 'double'\n  caused by: Invalid number of arguments. Expected: 1. Found: 0."
+        );
+    }
+
+    #[test]
+    fn test_bash_completions() {
+        let mut script = Vec::new();
+        build_cli().gen_completions_to("mun", Shell::Bash, &mut script);
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("build"));
+        assert!(script.contains("start"));
+    }
+
+    #[test]
+    fn test_backend_unavailable_message() {
+        let error = anyhow::Error::new(mun_compiler::BackendUnavailable {
+            triple: "riscv64-unknown-elf".to_owned(),
+            reason: "unknown target triple: No available targets".to_owned(),
+        })
+        .context("could not build package");
+        let message = backend_unavailable_message(&error, DisplayColor::Disable).unwrap();
+        assert!(message.starts_with(
+            "error: cannot generate code for target riscv64-unknown-elf, the code generation backend is unavailable\nnote: unknown target triple: No available targets\nhelp: "
+        ));
+        let colored = backend_unavailable_message(&error, DisplayColor::Enable).unwrap();
+        assert!(colored.starts_with("\u{1b}["), "{}", colored);
+
+        // Other errors are passed on
+        let error = anyhow::anyhow!("could not read manifest file");
+        assert_eq!(
+            backend_unavailable_message(&error, DisplayColor::Disable),
+            None
+        );
+    }
+
+    #[test]
+    fn test_invoke_in_order() {
+        let names = vec!["setup".to_owned(), "run".to_owned()];
+        let mut invoked = Vec::new();
+        let status = invoke_in_order(&names, true, false, |name| {
+            invoked.push(name.to_owned());
+            Ok(ExitStatus::Success)
+        });
+        assert_eq!(status, ExitStatus::Success);
+        assert_eq!(invoked, names);
+
+        // A failure stops the sequence
+        let mut invoked = Vec::new();
+        let status = invoke_in_order(&names, true, false, |name| {
+            invoked.push(name.to_owned());
+            Err(anyhow::anyhow!("Failed to obtain entry point '{}'", name))
+        });
+        assert_eq!(status, ExitStatus::Error);
+        assert_eq!(invoked, vec!["setup".to_owned()]);
+
+        // ... unless failures do not stop it
+        let mut invoked = Vec::new();
+        let status = invoke_in_order(&names, false, false, |name| {
+            invoked.push(name.to_owned());
+            Ok(ExitStatus::Error)
+        });
+        assert_eq!(status, ExitStatus::Error);
+        assert_eq!(invoked, names);
+    }
+
+    #[test]
+    fn test_resolve_reload_delay() {
+        let ms = |ms| Some(Duration::from_millis(ms));
+        assert_eq!(resolve_reload_delay(None, None, None).unwrap(), None);
+        assert_eq!(resolve_reload_delay(None, None, Some(30)).unwrap(), ms(30));
+        assert_eq!(
+            resolve_reload_delay(None, Some("20"), Some(30)).unwrap(),
+            ms(20)
+        );
+        assert_eq!(
+            resolve_reload_delay(Some("10"), Some("20"), Some(30)).unwrap(),
+            ms(10)
+        );
+
+        let err = resolve_reload_delay(None, Some("0"), None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value '0' for MUN_RELOAD_DELAY, expected a positive number of milliseconds"
+        );
+        let err = resolve_reload_delay(Some("soon"), None, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value 'soon' for --delay, expected a positive number of milliseconds"
+        );
+    }
+
+    #[test]
+    fn test_resolve_display_color() {
+        assert_eq!(resolve_display_color(None, None, false), DisplayColor::Auto);
+
+        // The flag takes precedence over MUN_TERMINAL_COLOR
+        assert_eq!(
+            resolve_display_color(None, Some("enable"), false),
+            DisplayColor::Enable
+        );
+        assert_eq!(
+            resolve_display_color(Some("disable"), Some("enable"), false),
+            DisplayColor::Disable
+        );
+        assert_eq!(
+            resolve_display_color(Some("auto"), Some("disable"), false),
+            DisplayColor::Auto
+        );
+
+        // NO_COLOR takes precedence over everything but an explicit `--color enable`
+        assert_eq!(
+            resolve_display_color(None, None, true),
+            DisplayColor::Disable
+        );
+        assert_eq!(
+            resolve_display_color(None, Some("enable"), true),
+            DisplayColor::Disable
+        );
+        assert_eq!(
+            resolve_display_color(Some("auto"), None, true),
+            DisplayColor::Disable
+        );
+        assert_eq!(
+            resolve_display_color(Some("enable"), None, true),
+            DisplayColor::Enable
+        );
+    }
+
+    #[test]
+    fn test_log_level() {
+        assert_eq!(log_level(0, false), LevelFilter::Warn);
+        assert_eq!(log_level(1, false), LevelFilter::Info);
+        assert_eq!(log_level(2, false), LevelFilter::Debug);
+        assert_eq!(log_level(3, false), LevelFilter::Trace);
+        assert_eq!(log_level(5, false), LevelFilter::Trace);
+        assert_eq!(log_level(0, true), LevelFilter::Error);
+    }
+
+    #[test]
+    fn test_reload_log_message() {
+        let path = std::path::Path::new("main.munlib");
+        assert_eq!(
+            reload_log_message(&ReloadEvent::Reloaded { path }),
+            (
+                log::Level::Info,
+                "reloaded assembly: main.munlib".to_owned()
+            )
+        );
+        assert_eq!(
+            reload_log_message(&ReloadEvent::ReloadFailed {
+                path,
+                error: &anyhow::anyhow!("invalid assembly"),
+            }),
+            (
+                log::Level::Warn,
+                "failed to reload assembly 'main.munlib': invalid assembly".to_owned()
+            )
+        );
+        let (level, message) = reload_log_message(&ReloadEvent::FileChanged {
+            kind: "write",
+            path,
+        });
+        assert_eq!(level, log::Level::Debug);
+        assert!(message.contains("main.munlib"));
+    }
+
+    #[test]
+    fn test_verbosity_flags() {
+        let dir = TempDir::new("test_verbosity_flags").unwrap();
+        for (index, flags) in [&["-vvv"][..], &["--quiet"], &["-v", "-v"]]
+            .iter()
+            .enumerate()
+        {
+            let path = dir.path().join(format!("package{}", index));
+            let mut args: Vec<std::ffi::OsString> = vec!["mun".into()];
+            args.extend(flags.iter().map(Into::into));
+            args.push("new".into());
+            args.push(path.clone().into());
+            assert_eq!(run_with_args(args).unwrap(), ExitStatus::Success);
+            assert!(path.join(MANIFEST_FILENAME).is_file());
+        }
+
+        let args: Vec<std::ffi::OsString> =
+            vec!["mun".into(), "-v".into(), "-q".into(), "ast".into()];
+        assert_eq!(run_with_args(args).unwrap(), ExitStatus::Error);
+    }
+
+    #[test]
+    fn test_find_library_in_directory() {
+        let dir = TempDir::new("test_find_library_in_directory").unwrap();
+        let error = find_library_in_directory(dir.path()).unwrap_err();
+        assert!(error.to_string().contains("no .munlib library found"));
+
+        std::fs::write(dir.path().join("main.munlib"), "").unwrap();
+        std::fs::write(dir.path().join("main.ll"), "").unwrap();
+        std::fs::create_dir(dir.path().join("deps.munlib")).unwrap();
+        assert_eq!(
+            find_library_in_directory(dir.path()).unwrap(),
+            dir.path().join("main.munlib")
+        );
+
+        std::fs::write(dir.path().join("physics.munlib"), "").unwrap();
+        let error = find_library_in_directory(dir.path())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("multiple libraries"), "{}", error);
+        assert!(error.contains("main.munlib"), "{}", error);
+        assert!(error.contains("physics.munlib"), "{}", error);
+    }
+
     #[test]
     fn test_find_manifest() {
         let dir = TempDir::new("test_find_manifest").unwrap();
         let path = dir.path();
         let manifest_path = path.join(MANIFEST_FILENAME);
 
-        assert_eq!(find_manifest(path), None);
+        assert_eq!(find_manifest(path, MANIFEST_FILENAME), None);
 
         std::fs::write(&manifest_path, "").unwrap();
-        assert_eq!(find_manifest(path).as_ref(), Some(&manifest_path));
+        assert_eq!(
+            find_manifest(path, MANIFEST_FILENAME).as_ref(),
+            Some(&manifest_path)
+        );
 
         let subdir_path = path.join("some/random/subdir");
         std::fs::create_dir_all(&subdir_path).unwrap();
-        assert_eq!(find_manifest(&subdir_path).as_ref(), Some(&manifest_path));
+        assert_eq!(
+            find_manifest(&subdir_path, MANIFEST_FILENAME).as_ref(),
+            Some(&manifest_path)
+        );
+
+        assert_eq!(find_manifest(&subdir_path, "mun.ci.toml"), None);
+        let ci_manifest_path = path.join("mun.ci.toml");
+        std::fs::write(&ci_manifest_path, "").unwrap();
+        assert_eq!(
+            find_manifest(&subdir_path, "mun.ci.toml").as_ref(),
+            Some(&ci_manifest_path)
+        );
+    }
+
+    #[test]
+    fn test_new_package_has_manifest() {
+        let dir = TempDir::new("test_new_package_has_manifest").unwrap();
+        let path = dir.path().join("my.game");
+
+        let args = vec![
+            "mun".into(),
+            "new".into(),
+            path.clone().into_os_string(),
+            "--name".into(),
+            "my_game".into(),
+        ];
+        assert_eq!(run_with_args(args).unwrap(), ExitStatus::Success);
+
+        let manifest_path = find_manifest(&path.join("src"), MANIFEST_FILENAME).unwrap();
+        assert_eq!(manifest_path, path.join(MANIFEST_FILENAME));
+        let package = mun_project::Package::from_file(manifest_path).unwrap();
+        assert_eq!(package.name(), "my_game");
+    }
+
+    #[test]
+    fn test_render_build_summary() {
+        let units = vec![
+            ("/broken/mun.toml".to_owned(), false),
+            ("test 0.1.0".to_owned(), true),
+        ];
+        assert_eq!(
+            render_build_summary(&units),
+            "\nsummary:\n    /broken/mun.toml ... FAILED\n    test 0.1.0 ... ok\n"
+        );
+    }
+
+    #[test]
+    fn test_render_targets() {
+        let triples: Vec<String> = mun_target::spec::get_targets().collect();
+        let host = mun_target::host_triple();
+        let text = render_targets(&triples, Some(host));
+        assert!(text.lines().any(|line| line == format!("* {}", host)));
+        assert_eq!(text.lines().filter(|line| line.starts_with('*')).count(), 1);
+        assert_eq!(text.lines().count(), triples.len());
+    }
+
+    #[test]
+    fn test_apply_build_defaults() {
+        let app = App::new("mun")
+            .arg(
+                Arg::with_name("opt-level")
+                    .long("opt-level")
+                    .takes_value(true),
+            )
+            .arg(Arg::with_name("release").long("release"))
+            .arg(Arg::with_name("target").long("target").takes_value(true))
+            .arg(
+                Arg::with_name("COMPILER_ARGS")
+                    .multiple(true)
+                    .last(true)
+                    .allow_hyphen_values(true),
+            );
+        let defaults = mun_project::BuildDefaults {
+            opt_level: Some(0),
+            target: None,
+        };
+        let options = |args: &[&str], defaults: &mun_project::BuildDefaults| {
+            let matches = app.clone().get_matches_from(args);
+            let mut config = compiler_options(&matches).unwrap();
+            apply_build_defaults(&mut config, &matches, defaults).map(|_| config)
+        };
+
+        // Only the manifest
+        let config = options(&["mun"], &defaults).unwrap();
+        assert_eq!(config.optimization_lvl, OptimizationLevel::None);
+
+        // Only flags
+        let config = options(
+            &["mun", "--opt-level", "1"],
+            &mun_project::BuildDefaults::default(),
+        )
+        .unwrap();
+        assert_eq!(config.optimization_lvl, OptimizationLevel::Less);
+
+        // Flags override the manifest
+        let config = options(&["mun", "--opt-level", "1"], &defaults).unwrap();
+        assert_eq!(config.optimization_lvl, OptimizationLevel::Less);
+        let config = options(&["mun", "--release"], &defaults).unwrap();
+        assert_eq!(config.optimization_lvl, OptimizationLevel::Aggressive);
+        let config = options(&["mun", "--", "-C", "opt-level=3"], &defaults).unwrap();
+        assert_eq!(config.optimization_lvl, OptimizationLevel::Aggressive);
+
+        // Invalid values in the manifest are reported, unless they are overridden
+        let defaults = mun_project::BuildDefaults {
+            opt_level: Some(4),
+            target: Some("no-such-target".to_owned()),
+        };
+        let error = options(&["mun", "--opt-level", "2"], &defaults)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("invalid value 'no-such-target' for `target`"));
+        assert!(error.contains("pass `--target` to override it"));
+        let error = options(&["mun", "--target", "x86_64-unknown-linux-gnu"], &defaults)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("invalid value '4' for `opt-level`"));
+    }
+
+    #[test]
+    fn test_compile_manifest_timed() {
+        let dir = TempDir::new("test_compile_manifest_timed").unwrap();
+        std::fs::write(
+            dir.path().join(MANIFEST_FILENAME),
+            "[package]\nname=\"test\"\nversion=\"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/main.mun"),
+            "pub fn main() -> i32 { 1 }",
+        )
+        .unwrap();
+        let manifest_path = dir.path().join(MANIFEST_FILENAME);
+
+        let mut out = Vec::new();
+        assert!(
+            compile_manifest_timed(
+                &manifest_path,
+                mun_compiler::Config::default(),
+                false,
+                &mut out
+            )
+            .unwrap()
+            .0
+        );
+        assert!(out.is_empty());
+
+        assert!(
+            compile_manifest_timed(
+                &manifest_path,
+                mun_compiler::Config::default(),
+                true,
+                &mut out
+            )
+            .unwrap()
+            .0
+        );
+        let summary = String::from_utf8(out).unwrap();
+        for phase in &[
+            "load sources",
+            "analysis",
+            "codegen",
+            "write artifacts",
+            "total",
+        ] {
+            assert!(summary.contains(phase), "{}", summary);
+        }
+    }
+
+    #[test]
+    fn test_print_syntax_trees() {
+        let dir = TempDir::new("test_print_syntax_trees").unwrap();
+        let manifest_path = dir.path().join(MANIFEST_FILENAME);
+        std::fs::write(
+            &manifest_path,
+            "[package]\nname=\"test\"\nversion=\"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/main.mun"),
+            "pub fn answer() -> i32 { 42 }",
+        )
+        .unwrap();
+
+        let mut out = Vec::new();
+        assert!(print_syntax_trees(&manifest_path, &mut out).unwrap());
+        let tree = String::from_utf8(out).unwrap();
+        assert!(tree.starts_with("// main.mun"), "{}", tree);
+        assert!(tree.contains("FUNCTION_DEF"), "{}", tree);
+        assert!(tree.contains("\"answer\""), "{}", tree);
+
+        std::fs::write(dir.path().join("src/main.mun"), "pub fn answer( {").unwrap();
+        let mut out = Vec::new();
+        assert!(!print_syntax_trees(&manifest_path, &mut out).unwrap());
+        assert!(String::from_utf8(out).unwrap().contains("error"));
+    }
+
+    #[test]
+    fn test_print_artifacts() {
+        let dir = TempDir::new("test_print_artifacts").unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join(MANIFEST_FILENAME),
+            "[package]\nname=\"test\"\nversion=\"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("src/main.mun"),
+            "pub fn main() -> i32 { 5 }",
+        )
+        .unwrap();
+
+        let manifest_path = dir.path().join(MANIFEST_FILENAME);
+        let args: Vec<&std::ffi::OsStr> = vec![
+            "mun".as_ref(),
+            "build".as_ref(),
+            "--manifest-path".as_ref(),
+            manifest_path.as_os_str(),
+            "--emit=llvm-ir".as_ref(),
+            "--emit-build-record".as_ref(),
+            "--print-artifacts".as_ref(),
+        ];
+        let outcome = run_with_args(args).unwrap();
+        assert_eq!(outcome, ExitStatus::Success);
+
+        let mut out = Vec::new();
+        print_artifacts(&outcome, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let paths: Vec<&Path> = out.lines().map(Path::new).collect();
+        assert_eq!(paths.len(), 3, "{}", out);
+        for path in paths.iter() {
+            assert!(path.is_absolute(), "{}", path.display());
+            assert!(path.is_file(), "{}", path.display());
+        }
+        assert!(paths[0].ends_with("target/main.munlib"));
+        assert!(paths[1].ends_with("target/main.ll"));
+    }
+
+    #[test]
+    fn test_render_config() {
+        let matches = App::new("mun")
+            .arg(Arg::with_name("target").long("target").takes_value(true))
+            .arg(Arg::with_name("color").long("color").takes_value(true))
+            .get_matches_from(&["mun", "--color", "disable"]);
+        let config = compiler_options(&matches).unwrap();
+        let manifest_path = std::env::current_dir().unwrap().join(MANIFEST_FILENAME);
+
+        let rendered = render_config(&config, &manifest_path);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                format!(
+                    "target={}",
+                    mun_compiler::Target::host_target().unwrap().llvm_target
+                ),
+                "opt-level=2".to_owned(),
+                format!(
+                    "out-dir={}",
+                    manifest_path.parent().unwrap().join("target").display()
+                ),
+                "color=disable".to_owned(),
+                "stop-after=codegen".to_owned(),
+                format!("manifest-path={}", manifest_path.display()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compiler_options_cpu() {
+        let app = App::new("mun")
+            .arg(Arg::with_name("cpu").long("cpu").takes_value(true))
+            .arg(
+                Arg::with_name("target-features")
+                    .long("target-features")
+                    .takes_value(true),
+            );
+
+        let matches = app.clone().get_matches_from(&["mun"]);
+        let config = compiler_options(&matches).unwrap();
+        assert_eq!(config.cpu, None);
+        assert_eq!(config.target_features, None);
+
+        let matches = app.clone().get_matches_from(&[
+            "mun",
+            "--cpu",
+            "skylake",
+            "--target-features",
+            "+avx2,-sse4.1",
+        ]);
+        let config = compiler_options(&matches).unwrap();
+        assert_eq!(config.cpu, Some("skylake".to_owned()));
+        assert_eq!(config.target_features, Some("+avx2,-sse4.1".to_owned()));
+
+        let matches = app.get_matches_from(&["mun", "--cpu", "native"]);
+        let config = compiler_options(&matches).unwrap();
+        assert_eq!(config.cpu, Some(mun_compiler::host_cpu_name()));
+        assert_ne!(config.cpu, Some("native".to_owned()));
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("100").unwrap(), 100);
+        assert_eq!(parse_size("512kb").unwrap(), 512 * 1024);
+        assert_eq!(parse_size("64MB").unwrap(), 64 * 1024 * 1024);
+        assert_eq!(parse_size("1g").unwrap(), 1024 * 1024 * 1024);
+        assert!(parse_size("mb").is_err());
+        assert!(parse_size("12parsecs").is_err());
+    }
+
+    #[test]
+    fn test_gc_strategy() {
+        let strategy = |args: &[&str]| {
+            let matches = build_cli().get_matches_from(args);
+            gc_strategy(matches.subcommand_matches("start").unwrap())
+        };
+        assert_eq!(
+            strategy(&["mun", "start", "main.munlib"]).unwrap(),
+            GcStrategy::MarkSweep
+        );
+        assert_eq!(
+            strategy(&["mun", "start", "main.munlib", "--gc", "mark-sweep"]).unwrap(),
+            GcStrategy::MarkSweep
+        );
+        assert_eq!(
+            strategy(&["mun", "start", "main.munlib", "--gc=none"]).unwrap(),
+            GcStrategy::None
+        );
+        assert!(build_cli()
+            .get_matches_from_safe(&["mun", "start", "main.munlib", "--gc", "copying"])
+            .is_err());
+
+        let error = "copying".parse::<GcStrategy>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the garbage collection strategy 'copying' is not supported, expected one of: mark-sweep, none"
+        );
+    }
+
+    #[test]
+    fn test_language_server_manifest_paths() {
+        let first = TempDir::new("test_language_server_first").unwrap();
+        let second = TempDir::new("test_language_server_second").unwrap();
+        for dir in [&first, &second].iter() {
+            std::fs::write(
+                dir.path().join(MANIFEST_FILENAME),
+                "[package]\nname=\"test\"\nversion=\"0.1.0\"\n",
+            )
+            .unwrap();
+        }
+
+        let manifest_paths = |args: &[&std::ffi::OsStr]| {
+            let matches = build_cli().get_matches_from(args);
+            let mut manifest_paths = None;
+            language_server_with(
+                matches.subcommand_matches("language-server").unwrap(),
+                &Context::from_env().unwrap(),
+                |config| {
+                    manifest_paths = Some(config.manifest_paths);
+                    Ok(())
+                },
+            )
+            .unwrap();
+            manifest_paths.unwrap()
+        };
+
+        assert!(manifest_paths(&["mun".as_ref(), "language-server".as_ref()]).is_empty());
+
+        // Both a manifest file and a directory containing one are accepted
+        let first_manifest = first.path().join(MANIFEST_FILENAME);
+        assert_eq!(
+            manifest_paths(&[
+                "mun".as_ref(),
+                "language-server".as_ref(),
+                "--manifest-path".as_ref(),
+                first_manifest.as_os_str(),
+                "--manifest-path".as_ref(),
+                second.path().as_os_str(),
+            ]),
+            vec![
+                std::fs::canonicalize(&first_manifest).unwrap(),
+                std::fs::canonicalize(second.path().join(MANIFEST_FILENAME)).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error_message() {
+        assert_eq!(
+            error_message("entry point 'main' panicked".to_owned(), false),
+            "error: entry point 'main' panicked"
+        );
+        assert_eq!(
+            error_message("entry point 'main' panicked".to_owned(), true),
+            "\u{1b}[1;31merror\u{1b}[0m: entry point 'main' panicked"
+        );
     }
 }
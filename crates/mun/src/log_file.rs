@@ -0,0 +1,73 @@
+//! A logger that writes every record to a file in addition to stderr.
+
+use anyhow::anyhow;
+use log::{Log, Metadata, Record};
+use pretty_env_logger::env_logger;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Forwards records to the logger that prints to stderr and appends them, without colors, to a
+/// file. Records are filtered by the filters of the stderr logger.
+pub struct TeeLogger {
+    stderr: env_logger::Logger,
+    file: Mutex<BufWriter<File>>,
+}
+
+impl TeeLogger {
+    /// Constructs a logger that appends to the file at `path`, creating it if it does not exist.
+    pub fn new(stderr: env_logger::Logger, path: &Path) -> Result<Self, anyhow::Error> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| anyhow!("could not open log file '{}': {}", path.display(), e))?;
+        Ok(TeeLogger {
+            stderr,
+            file: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    /// Returns the most verbose level that is logged.
+    pub fn max_level(&self) -> log::LevelFilter {
+        self.stderr.filter()
+    }
+}
+
+impl Log for TeeLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.stderr.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.stderr.matches(record) {
+            return;
+        }
+        self.stderr.log(record);
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        if let Ok(mut file) = self.file.lock() {
+            // A logger has no way to report errors, so a failed write is dropped
+            let _ = writeln!(
+                file,
+                "[{}.{:03}] {:<5} {}: {}",
+                timestamp.as_secs(),
+                timestamp.subsec_millis(),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {
+        self.stderr.flush();
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+    }
+}
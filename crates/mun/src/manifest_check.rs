@@ -0,0 +1,193 @@
+//! Strict validation of a manifest, as performed by `mun build --strict-manifest`. Unlike parsing
+//! the manifest, which ignores keys it does not know and only checks the types of the values, this
+//! reports every problem with the key that causes it.
+
+use crate::scaffold::validate_package_name;
+use anyhow::anyhow;
+use std::fmt;
+use std::path::Path;
+use toml::Value;
+
+/// The keys that are allowed in each section of a manifest, by section.
+const KNOWN_KEYS: &[(&str, &[&str])] = &[
+    (
+        "package",
+        &[
+            "name",
+            "version",
+            "authors",
+            "license",
+            "default-entry",
+            "reload-delay",
+        ],
+    ),
+    ("build", &["opt-level", "target"]),
+];
+
+/// A problem with a key of a manifest.
+#[derive(Debug, PartialEq)]
+pub struct ManifestProblem {
+    /// The dotted path of the key, e.g. `package.name`, or empty if the manifest cannot be parsed
+    pub key: String,
+    pub message: String,
+}
+
+impl fmt::Display for ManifestProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.key.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "`{}`: {}", self.key, self.message)
+        }
+    }
+}
+
+/// Validates the manifest at `manifest_path`, printing every problem that was found. Returns `false`
+/// if there are any problems.
+pub fn check_manifest_file(manifest_path: &Path) -> Result<bool, anyhow::Error> {
+    let contents = std::fs::read_to_string(manifest_path)
+        .map_err(|e| anyhow!("could not read manifest file: {}", e))?;
+    let problems = check_manifest(&contents);
+    for problem in problems.iter() {
+        eprintln!("error: {}: {}", manifest_path.display(), problem);
+    }
+    Ok(problems.is_empty())
+}
+
+/// Validates the contents of a manifest, returning all problems that were found.
+pub fn check_manifest(contents: &str) -> Vec<ManifestProblem> {
+    let mut problems = Vec::new();
+    let mut problem = |key: &str, message: String| {
+        problems.push(ManifestProblem {
+            key: key.to_owned(),
+            message,
+        })
+    };
+
+    let root = match contents.parse::<Value>() {
+        Ok(Value::Table(root)) => root,
+        Ok(_) => unreachable!("a TOML document is always a table"),
+        Err(e) => {
+            problem("", format!("not valid TOML: {}", e));
+            return problems;
+        }
+    };
+
+    for (section, value) in root.iter() {
+        match section.as_str() {
+            "dependencies" => match value.as_table() {
+                Some(dependencies) => {
+                    for (name, dependency) in dependencies {
+                        let key = format!("dependencies.{}", name);
+                        match dependency.get("path") {
+                            Some(Value::String(_)) => {}
+                            Some(_) => problem(&key, "`path` must be a string".to_owned()),
+                            None => problem(&key, "missing required key `path`".to_owned()),
+                        }
+                    }
+                }
+                None => problem(section, "expected a table".to_owned()),
+            },
+            _ => match KNOWN_KEYS.iter().find(|(known, _)| known == section) {
+                Some((_, keys)) => match value.as_table() {
+                    Some(table) => {
+                        for key in table.keys().filter(|key| !keys.contains(&key.as_str())) {
+                            problem(
+                                &format!("{}.{}", section, key),
+                                format!("unknown key, expected one of {}", keys.join(", ")),
+                            );
+                        }
+                    }
+                    None => problem(section, "expected a table".to_owned()),
+                },
+                None => problem(section, "unknown section".to_owned()),
+            },
+        }
+    }
+
+    let package = root.get("package").and_then(Value::as_table);
+    if package.is_none() {
+        problem("package", "missing required section".to_owned());
+    }
+    let field = |name: &str| package.and_then(|package| package.get(name));
+
+    match field("name") {
+        Some(Value::String(name)) => {
+            if let Err(e) = validate_package_name(name) {
+                problem("package.name", e.to_string());
+            }
+        }
+        Some(_) => problem("package.name", "expected a string".to_owned()),
+        None if package.is_some() => {
+            problem("package.name", "missing required key".to_owned());
+        }
+        None => {}
+    }
+
+    match field("version") {
+        Some(Value::String(version)) => {
+            if let Err(e) = mun_project::Version::parse(version) {
+                problem(
+                    "package.version",
+                    format!("invalid version '{}': {}", version, e),
+                );
+            }
+        }
+        Some(_) => problem("package.version", "expected a string".to_owned()),
+        None if package.is_some() => {
+            problem("package.version", "missing required key".to_owned());
+        }
+        None => {}
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_manifest, ManifestProblem};
+
+    fn keys(problems: Vec<ManifestProblem>) -> Vec<String> {
+        problems.into_iter().map(|problem| problem.key).collect()
+    }
+
+    #[test]
+    fn test_check_manifest() {
+        assert!(check_manifest(
+            r#"
+[package]
+name = "game"
+version = "0.1.0"
+authors = ["Mun Team"]
+
+[dependencies]
+utils = { path = "../utils" }
+
+[build]
+opt-level = 2
+"#
+        )
+        .is_empty());
+
+        assert_eq!(
+            keys(check_manifest("[package]\nversion = \"0.1.0\"\n")),
+            vec!["package.name"]
+        );
+        assert_eq!(
+            keys(check_manifest(
+                "[package]\nname = \"my game\"\nversion = \"1\"\nauthor = \"me\"\n\n[dependecies]\n"
+            )),
+            vec![
+                "dependecies",
+                "package.author",
+                "package.name",
+                "package.version"
+            ]
+        );
+        assert_eq!(
+            keys(check_manifest("[build]\nopt-level = 2\n")),
+            vec!["package"]
+        );
+        assert_eq!(keys(check_manifest("[package")), vec![""]);
+    }
+}
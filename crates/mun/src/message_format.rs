@@ -0,0 +1,155 @@
+//! Compiling a package for `mun build`, with its diagnostics written in the format that is
+//! selected with `--message-format`.
+
+use crate::diagnostics;
+use anyhow::anyhow;
+use clap::ArgMatches;
+use mun_compiler::{Config, Driver, Snippet};
+use mun_project::Package;
+use std::path::Path;
+
+/// Compiles the package at `manifest_path` with `config`, writing its diagnostics in the format
+/// selected in `matches`, or keeps compiling it on every change with `--watch`. Returns whether the
+/// package compiled, together with the driver that compiled it, which is not available in watch
+/// mode.
+pub fn compile(
+    matches: &ArgMatches,
+    manifest_path: &Path,
+    config: Config,
+    working_dir: &Path,
+) -> Result<(bool, Option<Driver>), anyhow::Error> {
+    let message_format = matches.value_of("message-format").unwrap_or("human");
+    if matches.is_present("timings") && message_format != "human" {
+        return Err(anyhow!(
+            "--timings is not supported with --message-format={}",
+            message_format
+        ));
+    }
+    if matches.is_present("watch") {
+        if message_format != "human" {
+            return Err(anyhow!(
+                "--message-format={} is not supported in watch mode",
+                message_format
+            ));
+        }
+        mun_compiler_daemon::compile_and_watch_manifest(
+            manifest_path,
+            config,
+            crate::watch_config(matches)?,
+        )
+        .map(|success| (success, None))
+    } else if message_format == "json" {
+        compile_manifest_json(manifest_path, config)
+            .map(|(success, driver)| (success, Some(driver)))
+    } else if message_format == "sarif" {
+        compile_manifest_sarif(manifest_path, config)
+            .map(|(success, driver)| (success, Some(driver)))
+    } else if message_format == "github" {
+        compile_manifest_github(manifest_path, config, working_dir)
+            .map(|(success, driver)| (success, Some(driver)))
+    } else if matches.is_present("group-by-file") {
+        compile_manifest_with(manifest_path, config, |_package, snippets, colors| {
+            diagnostics::emit_grouped_by_file(&mut std::io::stderr(), snippets, colors)
+        })
+        .map(|(success, driver)| (success, Some(driver)))
+    } else {
+        crate::compile_manifest_timed(
+            manifest_path,
+            config,
+            matches.is_present("timings"),
+            &mut std::io::stderr(),
+        )
+        .map(|(success, driver)| (success, Some(driver)))
+    }
+}
+
+/// Compiles the package at the specified manifest path, writing every diagnostic to stdout as a
+/// JSON object on a single line. Returns `false` if errors were emitted, together with the driver
+/// that compiled the package.
+fn compile_manifest_json(
+    manifest_path: &Path,
+    config: Config,
+) -> Result<(bool, Driver), anyhow::Error> {
+    compile_manifest_with(manifest_path, config, |package, snippets, _colors| {
+        let source_dir = package
+            .source_directory()
+            .unwrap_or_else(|| package.root().join("src"));
+        diagnostics::write_json(
+            &mut std::io::stdout(),
+            &diagnostics::from_snippets(snippets),
+            &source_dir,
+        )
+    })
+}
+
+/// Compiles the package at the specified manifest path, writing all diagnostics to stdout as a
+/// SARIF log instead of in a human-readable format. Returns `false` if errors were emitted,
+/// together with the driver that compiled the package.
+fn compile_manifest_sarif(
+    manifest_path: &Path,
+    config: Config,
+) -> Result<(bool, Driver), anyhow::Error> {
+    compile_manifest_with(manifest_path, config, |package, snippets, _colors| {
+        let source_dir = package
+            .source_directory()
+            .unwrap_or_else(|| package.root().join("src"));
+        diagnostics::write_sarif(
+            &mut std::io::stdout(),
+            &diagnostics::from_snippets(snippets),
+            &source_dir,
+        )
+    })
+}
+
+/// Compiles the package at the specified manifest path, writing all diagnostics to stdout as GitHub
+/// Actions workflow commands. Returns `false` if errors were emitted, together with the driver that
+/// compiled the package.
+fn compile_manifest_github(
+    manifest_path: &Path,
+    config: Config,
+    working_dir: &Path,
+) -> Result<(bool, Driver), anyhow::Error> {
+    compile_manifest_with(manifest_path, config, |package, snippets, _colors| {
+        let source_dir = package
+            .source_directory()
+            .unwrap_or_else(|| package.root().join("src"));
+        diagnostics::write_github(
+            &mut std::io::stdout(),
+            &diagnostics::from_snippets(snippets),
+            &source_dir,
+            working_dir,
+        )
+    })
+}
+
+/// Compiles the package at the specified manifest path, passing all diagnostics to `emit` instead
+/// of writing them to stderr as they are. Returns `false` if errors were emitted, together with the
+/// driver that compiled the package.
+fn compile_manifest_with<F>(
+    manifest_path: &Path,
+    config: Config,
+    emit: F,
+) -> Result<(bool, Driver), anyhow::Error>
+where
+    F: FnOnce(&Package, &[Snippet], bool) -> Result<(), anyhow::Error>,
+{
+    let colors = config.display_color.should_enable();
+    let deny_warnings = config.deny_warnings;
+    let (package, mut driver) = Driver::with_package_path(manifest_path, config)?;
+
+    let snippets = driver.diagnostics();
+    emit(&package, &snippets, colors)?;
+
+    if diagnostics::from_snippets(&snippets)
+        .iter()
+        .any(|diagnostic| {
+            diagnostic.is_error()
+                || (deny_warnings && diagnostic.severity == diagnostics::Severity::Warning)
+        })
+    {
+        return Ok((false, driver));
+    }
+
+    driver.write_all_assemblies()?;
+    Ok((true, driver))
+}
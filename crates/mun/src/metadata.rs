@@ -0,0 +1,49 @@
+//! Machine-readable metadata about a package, as printed by `mun metadata`.
+
+use mun_project::Package;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// The version of the metadata schema. This is incremented whenever a field is removed or its
+/// meaning changes; new fields may be added without bumping the version.
+pub const METADATA_VERSION: u32 = 1;
+
+/// The metadata of a package, serialized as:
+///
+/// ```json
+/// {
+///   "metadata_version": 1,
+///   "name": "hello_world",
+///   "version": "0.1.0",
+///   "authors": ["Jane Doe <jane@example.com>"],
+///   "manifest_path": "/path/to/hello_world/mun.toml",
+///   "root": "/path/to/hello_world",
+///   "source_directory": "/path/to/hello_world/src"
+/// }
+/// ```
+///
+/// `source_directory` is `null` if the package has no `src` directory.
+#[derive(Serialize)]
+pub struct Metadata {
+    pub metadata_version: u32,
+    pub name: String,
+    pub version: String,
+    pub authors: Vec<String>,
+    pub manifest_path: PathBuf,
+    pub root: PathBuf,
+    pub source_directory: Option<PathBuf>,
+}
+
+impl From<&Package> for Metadata {
+    fn from(package: &Package) -> Self {
+        Metadata {
+            metadata_version: METADATA_VERSION,
+            name: package.name().to_owned(),
+            version: package.version().to_string(),
+            authors: package.manifest().metadata().authors.clone(),
+            manifest_path: package.manifest_path().to_path_buf(),
+            root: package.root().to_path_buf(),
+            source_directory: package.source_directory(),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Compilation of several explicitly listed source files, or of source code read from a stream,
+//! into a single Mun library, without the need for a manifest.
+
+use anyhow::anyhow;
+use mun_compiler::{Config, Driver, PathOrInline, RelativePathBuf, Snippet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// The name of the module that is compiled from source code read from a stream.
+const STREAM_MODULE: &str = "stdin.mun";
+
+/// The location of an input file within the combined source.
+struct InputFile {
+    path: PathBuf,
+    /// The 1-based line of the combined source at which the file starts
+    first_line: usize,
+}
+
+/// Compiles all `inputs` as a single module into the library at `output_path`. Diagnostics refer
+/// to the original input files. Returns `false` if errors were emitted.
+pub fn compile_files(
+    inputs: &[PathBuf],
+    output_path: &Path,
+    mut config: Config,
+) -> Result<bool, anyhow::Error> {
+    let mut contents = String::new();
+    let mut files = Vec::with_capacity(inputs.len());
+    for path in inputs {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("could not read contents of '{}': {}", path.display(), e))?;
+        files.push(InputFile {
+            path: path.clone(),
+            first_line: contents.lines().count() + 1,
+        });
+        contents.push_str(&text);
+        if !text.ends_with('\n') {
+            contents.push('\n');
+        }
+    }
+
+    let file_stem = output_path
+        .file_stem()
+        .ok_or_else(|| anyhow!("'{}' is not a valid output path", output_path.display()))?;
+    let out_dir = output_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+    std::fs::create_dir_all(&out_dir)?;
+    config.out_dir = Some(out_dir);
+
+    let colors = config.display_color.should_enable();
+    let rel_path = RelativePathBuf::from(format!("{}.mun", file_stem.to_string_lossy()));
+    let (mut driver, file_id) =
+        Driver::with_file(config, PathOrInline::Inline { rel_path, contents })?;
+
+    let mut diagnostics = driver.diagnostics();
+    for snippet in diagnostics.iter_mut() {
+        map_to_input_files(snippet, &files);
+    }
+    mun_compiler::diagnostics::emit_diagnostics(&mut std::io::stderr(), &diagnostics, colors)?;
+    if diagnostics.iter().any(|snippet| {
+        snippet.title.as_ref().map_or(false, |title| {
+            matches!(title.annotation_type, mun_compiler::AnnotationType::Error)
+        })
+    }) {
+        return Ok(false);
+    }
+
+    driver.write_assembly(file_id, true)?;
+    let assembly_path = driver.assembly_output_path(file_id);
+    if assembly_path != output_path {
+        std::fs::rename(&assembly_path, output_path)?;
+    }
+    Ok(true)
+}
+
+/// Reads all source code from `reader` and compiles it as a single module into `stdin.munlib` in
+/// the configured output directory, or the current directory. Returns `false` if errors were emitted.
+pub fn compile_reader(reader: &mut dyn Read, config: Config) -> Result<bool, anyhow::Error> {
+    let mut contents = String::new();
+    reader
+        .read_to_string(&mut contents)
+        .map_err(|e| anyhow!("could not read source code: {}", e))?;
+    if contents.trim().is_empty() {
+        return Err(anyhow!("no source code was provided"));
+    }
+
+    let (mut driver, file_id) = Driver::with_file(
+        config,
+        PathOrInline::Inline {
+            rel_path: RelativePathBuf::from(STREAM_MODULE),
+            contents,
+        },
+    )?;
+    if driver.emit_diagnostics(&mut std::io::stderr())? {
+        return Ok(false);
+    }
+
+    driver.write_assembly(file_id, true)?;
+    Ok(true)
+}
+
+/// Rewrites the origin and line numbers of a snippet, that refers to the combined source, to refer
+/// to the input file that contains it instead.
+fn map_to_input_files(snippet: &mut Snippet, files: &[InputFile]) {
+    for slice in snippet.slices.iter_mut() {
+        if let Some(file) = files
+            .iter()
+            .rev()
+            .find(|file| file.first_line <= slice.line_start)
+        {
+            slice.origin = Some(file.path.display().to_string());
+            slice.line_start = slice.line_start + 1 - file.first_line;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::compile_reader;
+    use mun_compiler::Config;
+
+    #[test]
+    fn test_compile_reader() {
+        let out_dir = tempdir::TempDir::new("test_compile_reader").unwrap();
+        let config = || Config {
+            out_dir: Some(out_dir.path().to_path_buf()),
+            ..Config::default()
+        };
+
+        let mut source = &b"pub fn main() -> i32 { 5 }"[..];
+        assert!(compile_reader(&mut source, config()).unwrap());
+        assert!(out_dir.path().join("stdin.munlib").is_file());
+
+        let mut source = &b"pub fn main() -> i32 { true }"[..];
+        assert!(!compile_reader(&mut source, config()).unwrap());
+
+        let mut source = &b" \n"[..];
+        assert_eq!(
+            compile_reader(&mut source, config())
+                .unwrap_err()
+                .to_string(),
+            "no source code was provided"
+        );
+    }
+}
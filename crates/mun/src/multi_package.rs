@@ -0,0 +1,130 @@
+//! Building of all packages in a directory tree.
+
+use anyhow::anyhow;
+use mun_compiler::{is_source_file, Config};
+use mun_project::{Package, Workspace, MANIFEST_FILENAME, WORKSPACE_FILENAME};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Describes where the artifacts of each package are stored in a multi-package build.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Layout {
+    /// Every package is stored in its own `<out>/<package-name>` directory
+    PerPackage,
+    /// All packages share the same output directory
+    Flat,
+}
+
+/// Recursively finds all manifests in `dir`, skipping output and hidden directories.
+pub fn find_manifests(dir: &Path) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let mut manifests = Vec::new();
+    let manifest_path = dir.join(MANIFEST_FILENAME);
+    if manifest_path.is_file() {
+        manifests.push(manifest_path);
+    }
+
+    let mut entries = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for path in entries {
+        let is_skipped = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(true, |name| name == "target" || name.starts_with('.'));
+        if path.is_dir() && !is_skipped {
+            manifests.extend(find_manifests(&path)?);
+        }
+    }
+    Ok(manifests)
+}
+
+/// Builds all packages in `dir` and its subdirectories, or only the members of the workspace if
+/// `dir` contains a workspace file. Artifacts are stored in `out_dir` according to the specified
+/// `layout`. Returns `false` if one of the packages failed to compile.
+pub fn build_all(
+    dir: &Path,
+    out_dir: &Path,
+    config: Config,
+    layout: Layout,
+) -> Result<bool, anyhow::Error> {
+    let workspace_path = dir.join(WORKSPACE_FILENAME);
+    let manifests = if workspace_path.is_file() {
+        Workspace::from_file(&workspace_path)?
+            .members()
+            .iter()
+            .map(|member| dir.join(member).join(MANIFEST_FILENAME))
+            .collect()
+    } else {
+        find_manifests(dir)?
+    };
+    if manifests.is_empty() {
+        return Err(anyhow!(
+            "could not find any {} in '{}'",
+            MANIFEST_FILENAME,
+            dir.display()
+        ));
+    }
+
+    let packages = manifests
+        .iter()
+        .map(Package::from_file)
+        .collect::<Result<Vec<_>, _>>()?;
+    if layout == Layout::Flat {
+        ensure_no_artifact_collisions(&packages)?;
+    }
+
+    let mut success = true;
+    for package in packages.iter() {
+        let package_out_dir = match layout {
+            Layout::PerPackage => out_dir.join(package.name()),
+            Layout::Flat => out_dir.to_path_buf(),
+        };
+        log::info!("building {} into {}", package, package_out_dir.display());
+        let config = Config {
+            out_dir: Some(package_out_dir),
+            ..config.clone()
+        };
+        success &= mun_compiler::compile_manifest(package.manifest_path(), config)?;
+    }
+    Ok(success)
+}
+
+/// Returns an error if two packages would write an artifact to the same path in a flat layout.
+fn ensure_no_artifact_collisions(packages: &[Package]) -> Result<(), anyhow::Error> {
+    let mut artifacts: HashMap<PathBuf, &Package> = HashMap::new();
+    for package in packages {
+        let source_dir = match package.source_directory() {
+            Some(source_dir) => source_dir,
+            None => continue,
+        };
+        for source_path in source_files(&source_dir)? {
+            let artifact = source_path
+                .strip_prefix(&source_dir)?
+                .with_extension(crate::LIBRARY_EXTENSION);
+            if let Some(other) = artifacts.insert(artifact.clone(), package) {
+                return Err(anyhow!(
+                    "packages '{}' and '{}' both produce '{}', use a per-package layout instead",
+                    other,
+                    package,
+                    artifact.display()
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Recursively collects all source files in `dir`.
+fn source_files(dir: &Path) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(source_files(&path)?);
+        } else if is_source_file(&path) {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
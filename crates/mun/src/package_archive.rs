@@ -0,0 +1,87 @@
+//! Bundles the libraries of a package and their metadata into a single archive for distribution,
+//! as written by `mun package`.
+
+use crate::abi::{LibraryMetadata, METADATA_EXTENSION};
+use anyhow::anyhow;
+use mun_project::Package;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+/// The extension of a package archive, which is a zip archive.
+pub const ARCHIVE_EXTENSION: &str = "munpkg";
+
+/// The name of the file in the archive that describes its contents.
+pub const CONTENTS_FILENAME: &str = "munpkg.json";
+
+/// The description of the contents of a package archive.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contents {
+    pub name: String,
+    pub version: String,
+    /// The version of the ABI that the libraries were compiled against
+    pub abi_version: u32,
+    pub libraries: Vec<LibraryEntry>,
+}
+
+/// A library in a package archive.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LibraryEntry {
+    /// The path of the library in the archive
+    pub path: String,
+    /// The path of the metadata of the library in the archive, as written by
+    /// `build --emit-metadata`
+    pub metadata: String,
+}
+
+/// Writes an archive of `package` to `output_path`, with the specified libraries, their metadata
+/// and a description of the contents. Every library is specified by its path in the archive,
+/// which uses forward slashes, and its path on disk.
+pub fn write_archive(
+    package: &Package,
+    libraries: &[(String, PathBuf)],
+    output_path: &Path,
+) -> Result<Contents, anyhow::Error> {
+    // Collect the metadata first, so no archive is written if a library is missing
+    let mut entries = Vec::new();
+    for (archive_path, library_path) in libraries {
+        if !library_path.is_file() {
+            return Err(anyhow!(
+                "the library '{}' does not exist\nhelp: build the package first",
+                library_path.display()
+            ));
+        }
+        let metadata = LibraryMetadata::from_library(library_path)?;
+        let metadata_path = Path::new(archive_path).with_extension(METADATA_EXTENSION);
+        let entry = LibraryEntry {
+            path: archive_path.clone(),
+            metadata: metadata_path.to_string_lossy().into_owned(),
+        };
+        entries.push((entry, library_path, metadata));
+    }
+
+    let file = File::create(output_path)
+        .map_err(|e| anyhow!("could not create '{}': {}", output_path.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = FileOptions::default();
+    for (entry, library_path, metadata) in entries.iter() {
+        zip.start_file(entry.path.as_str(), options)?;
+        zip.write_all(&std::fs::read(library_path)?)?;
+        zip.start_file(entry.metadata.as_str(), options)?;
+        zip.write_all((serde_json::to_string_pretty(metadata)? + "\n").as_bytes())?;
+    }
+
+    let contents = Contents {
+        name: package.name().to_owned(),
+        version: package.version().to_string(),
+        abi_version: mun_abi::ABI_VERSION,
+        libraries: entries.into_iter().map(|(entry, _, _)| entry).collect(),
+    };
+    zip.start_file(CONTENTS_FILENAME, options)?;
+    zip.write_all((serde_json::to_string_pretty(&contents)? + "\n").as_bytes())?;
+    zip.finish()?;
+    Ok(contents)
+}
@@ -0,0 +1,177 @@
+//! The steps of `mun build` that run after a package compiled successfully, like writing reports
+//! next to its libraries, and the collection of the artifacts of the build.
+
+use crate::{abi, build_record, control, dep_info, size_report};
+use anyhow::anyhow;
+use clap::ArgMatches;
+use mun_compiler::{Config, Driver, Stage};
+use mun_project::Package;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Runs the steps requested in `matches` after the package at `manifest_path` was compiled with
+/// `config` into `out_dir` by `driver`, which is not available in watch mode. Returns whether the
+/// build succeeded, together with its artifacts.
+pub fn run(
+    matches: &ArgMatches,
+    manifest_path: &Path,
+    out_dir: &Path,
+    config: &Config,
+    driver: Option<Driver>,
+) -> Result<(bool, Vec<PathBuf>), anyhow::Error> {
+    if let Some(address) = matches.value_of("notify") {
+        control::notify(address)?;
+    }
+    if matches.is_present("open") {
+        open_directory(out_dir);
+    }
+    if let (true, Some(driver)) = (matches.is_present("fail-on-empty"), &driver) {
+        if is_build_empty(driver) {
+            eprintln!(
+                "error: the build of {} produced no artifacts that export functions",
+                manifest_path.display()
+            );
+            return Ok((false, Vec::new()));
+        }
+    }
+
+    // Files written next to the assemblies, which are part of the artifacts of the build
+    let mut sidecars = Vec::new();
+    match (matches.value_of("emit"), &driver) {
+        (Some("size-report"), Some(driver)) => {
+            let report_path = size_report::write_size_report(
+                driver,
+                out_dir,
+                matches.value_of("size-report-format").unwrap_or("text"),
+            )?;
+            print_status(
+                matches,
+                &format!("Wrote size report to {}", report_path.display()),
+            );
+            sidecars.push(report_path);
+        }
+        (Some("dep-info"), Some(driver)) => {
+            let package = Package::from_file(manifest_path)?;
+            let dep_info_path = dep_info::write_dep_info(&package, driver, out_dir)?;
+            print_status(
+                matches,
+                &format!("Wrote dependency file to {}", dep_info_path.display()),
+            );
+            sidecars.push(dep_info_path);
+        }
+        _ => {}
+    }
+    if matches.is_present("emit-metadata") {
+        if config.target.options.is_like_wasm {
+            return Err(anyhow!(
+                "--emit-metadata is not supported for WebAssembly targets"
+            ));
+        }
+        if let (Stage::Codegen, Some(driver)) = (config.stop_after, &driver) {
+            for library_path in driver.assembly_output_paths() {
+                sidecars.push(abi::LibraryMetadata::write_for_library(&library_path)?);
+            }
+        }
+    }
+    // The changelog is printed first, as the previous record may be overwritten by this build
+    if let Some(path) = matches.value_of("abi-changelog") {
+        build_record::print_abi_changelog(Path::new(path), out_dir)?;
+    }
+    if matches.is_present("emit-build-record") {
+        let variables: Vec<&str> = matches
+            .values_of("capture-env")
+            .map_or_else(Vec::new, Iterator::collect);
+        let record_path = out_dir.join(build_record::FILENAME);
+        build_record::BuildRecord::new(manifest_path, config)?
+            .with_environment(&variables)
+            .with_abi(abi::AbiSnapshot::from_directory(out_dir)?)
+            .write_to_file(&record_path)?;
+        sidecars.push(record_path);
+    }
+    if let Some(path) = matches.value_of("verify-build-record") {
+        if !build_record::verify(manifest_path, config, Path::new(path))? {
+            return Ok((false, Vec::new()));
+        }
+    }
+
+    let artifacts = match driver {
+        Some(driver) if config.stop_after == Stage::Codegen => artifacts(&driver, config, sidecars),
+        _ => Vec::new(),
+    };
+    Ok((true, artifacts))
+}
+
+/// Returns the artifacts of a build by `driver`: the assemblies it wrote, the files emitted next
+/// to them as requested by `config`, and the `sidecars` written by the build steps.
+fn artifacts(driver: &Driver, config: &Config, sidecars: Vec<PathBuf>) -> Vec<PathBuf> {
+    let assemblies: Vec<PathBuf> = driver
+        .assembly_output_paths()
+        .into_iter()
+        .filter(|path| path.is_file())
+        .collect();
+    let emitted = config.emit.extension().map_or_else(Vec::new, |extension| {
+        assemblies
+            .iter()
+            .map(|path| path.with_extension(extension))
+            .filter(|path| path.is_file())
+            .collect()
+    });
+    assemblies
+        .into_iter()
+        .chain(emitted)
+        .chain(sidecars)
+        .collect()
+}
+
+/// Returns whether the build of `driver` produced no artifacts, or only artifacts that export no
+/// functions (e.g. because all code is commented out).
+fn is_build_empty(driver: &Driver) -> bool {
+    let has_artifacts = driver
+        .assembly_output_paths()
+        .iter()
+        .any(|path| path.is_file());
+    let exports_functions = driver
+        .function_sizes()
+        .iter()
+        .any(|(_, functions)| !functions.is_empty());
+    !has_artifacts || !exports_functions
+}
+
+/// Opens the specified directory in the system's file manager. In headless environments, like CI,
+/// the path of the directory is printed instead.
+fn open_directory(dir: &Path) {
+    let is_headless = env::var_os("CI").is_some()
+        || (cfg!(all(unix, not(target_os = "macos")))
+            && env::var_os("DISPLAY").is_none()
+            && env::var_os("WAYLAND_DISPLAY").is_none());
+    if is_headless {
+        println!("Build artifacts are located in: {}", dir.display());
+        return;
+    }
+
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    if let Err(e) = std::process::Command::new(program).arg(dir).spawn() {
+        log::warn!("could not run '{}': {}", program, e);
+        println!("Build artifacts are located in: {}", dir.display());
+    }
+}
+
+/// Prints a status message of `build`, unless `--quiet` is specified. Diagnostics and errors are not
+/// status messages, they are always printed. With `--print-artifacts` status messages are printed
+/// to stderr, so stdout only lists the artifacts.
+fn print_status(matches: &ArgMatches, message: &str) {
+    if matches.is_present("quiet") {
+        return;
+    }
+    if matches.is_present("print-artifacts") {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
@@ -0,0 +1,91 @@
+//! Counting the reloads of a `mun start` session, to summarize them when the session ends.
+
+use crate::startup_profile::millis;
+use mun_runtime::ReloadEvent;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// The number of successful and failed reloads.
+#[derive(Default, Debug, PartialEq)]
+struct ReloadCounts {
+    reloads: usize,
+    failures: usize,
+}
+
+/// Accumulates the reload events of a session, from the moment it was created.
+pub struct ReloadSummary {
+    start: Instant,
+    counts: Rc<RefCell<ReloadCounts>>,
+}
+
+impl ReloadSummary {
+    /// Starts a new session.
+    pub fn new() -> Self {
+        ReloadSummary {
+            start: Instant::now(),
+            counts: Rc::new(RefCell::new(ReloadCounts::default())),
+        }
+    }
+
+    /// Returns a reload observer for the runtime that counts the reloads of this session.
+    pub fn observer(&self) -> impl FnMut(&ReloadEvent) + 'static {
+        let counts = self.counts.clone();
+        move |event| match event {
+            ReloadEvent::Reloaded { .. } => counts.borrow_mut().reloads += 1,
+            ReloadEvent::ReloadFailed { .. } => counts.borrow_mut().failures += 1,
+            ReloadEvent::FileChanged { .. } | ReloadEvent::Ignored { .. } => (),
+        }
+    }
+
+    /// Renders the number of reloads and the duration of the session so far.
+    pub fn to_text(&self) -> String {
+        render(&self.counts.borrow(), self.start.elapsed())
+    }
+}
+
+fn render(counts: &ReloadCounts, duration: Duration) -> String {
+    format!(
+        "reload summary: {} reload(s), {} failed, session lasted {:.3} s",
+        counts.reloads,
+        counts.failures,
+        millis(duration) / 1000.0
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, ReloadCounts, ReloadSummary};
+    use mun_runtime::ReloadEvent;
+    use std::path::Path;
+    use std::time::Duration;
+
+    #[test]
+    fn test_reload_summary() {
+        let summary = ReloadSummary::new();
+        let mut observer = summary.observer();
+        let path = Path::new("main.munlib");
+        observer(&ReloadEvent::FileChanged {
+            kind: "write",
+            path,
+        });
+        observer(&ReloadEvent::Reloaded { path });
+        observer(&ReloadEvent::Reloaded { path });
+        observer(&ReloadEvent::ReloadFailed {
+            path,
+            error: &anyhow::anyhow!("invalid assembly"),
+        });
+        assert_eq!(
+            *summary.counts.borrow(),
+            ReloadCounts {
+                reloads: 2,
+                failures: 1
+            }
+        );
+
+        assert_eq!(
+            render(&summary.counts.borrow(), Duration::from_millis(12_500)),
+            "reload summary: 2 reload(s), 1 failed, session lasted 12.500 s"
+        );
+    }
+}
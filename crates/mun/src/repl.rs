@@ -0,0 +1,231 @@
+//! An interactive session that evaluates Mun expressions, as used by `mun repl`.
+//!
+//! Every input is compiled into a single module together with the definitions and `let`
+//! statements of earlier inputs, so these remain available for the rest of the session. An
+//! expression is wrapped in a function whose return type is inferred from the expression, which is
+//! then invoked and its value read like the return value of an entry point.
+
+use crate::return_value::ReturnValue;
+use crate::DriverError;
+use mun_compiler::{Config, Driver, FileId, PathOrInline, RelativePathBuf};
+use mun_runtime::{invoke_fn, Runtime, RuntimeBuilder};
+use std::cell::RefCell;
+use std::io::Write;
+use std::rc::Rc;
+
+/// The name of the function that is used to infer the type of an expression.
+const PROBE_FN: &str = "__repl_probe";
+
+/// The name of the function that evaluates an expression.
+const EVAL_FN: &str = "__repl_eval";
+
+/// The result of evaluating a single input.
+#[derive(Debug, PartialEq)]
+pub enum Evaluation {
+    /// A definition or `let` statement was added to the session
+    Added,
+    /// An expression was evaluated to a value
+    Value(ReturnValue),
+    /// An expression without a value was evaluated
+    Nothing,
+    /// The input contains errors, which were emitted; the session is unchanged
+    Rejected,
+}
+
+/// A session that keeps the definitions and `let` statements of all inputs that were accepted.
+pub struct Repl {
+    driver: Driver,
+    file_id: FileId,
+    runtime: Option<Rc<RefCell<Runtime>>>,
+    definitions: Vec<String>,
+    statements: Vec<String>,
+    // Removed when the session ends
+    _out_dir: tempdir::TempDir,
+}
+
+impl Repl {
+    /// Constructs an empty session that compiles with the specified configuration, into a
+    /// temporary directory.
+    pub fn new(config: Config) -> Result<Self, anyhow::Error> {
+        let out_dir = tempdir::TempDir::new("mun_repl")?;
+        let config = Config {
+            out_dir: Some(out_dir.path().to_path_buf()),
+            ..config
+        };
+        let (driver, file_id) = Driver::with_file(
+            config,
+            PathOrInline::Inline {
+                rel_path: RelativePathBuf::from("repl.mun"),
+                contents: String::new(),
+            },
+        )?;
+        Ok(Repl {
+            driver,
+            file_id,
+            runtime: None,
+            definitions: Vec::new(),
+            statements: Vec::new(),
+            _out_dir: out_dir,
+        })
+    }
+
+    /// Evaluates a single line of input: a function or struct definition, a `let` statement, or an
+    /// expression. Diagnostics are written to `diagnostics`.
+    pub fn eval(
+        &mut self,
+        input: &str,
+        diagnostics: &mut dyn Write,
+    ) -> Result<Evaluation, anyhow::Error> {
+        let input = input.trim();
+        if is_definition(input) {
+            let mut definitions = self.definitions.clone();
+            definitions.push(input.to_owned());
+            if !self.check(&source(&definitions, &[], ""), diagnostics)? {
+                return Ok(Evaluation::Rejected);
+            }
+            self.definitions = definitions;
+            return Ok(Evaluation::Added);
+        }
+
+        if input.starts_with("let ") {
+            let mut statements = self.statements.clone();
+            statements.push(terminated(input));
+            let probe = format!("fn {}() {{\n{}}}\n", PROBE_FN, statements.join("\n"));
+            if !self.check(&source(&self.definitions, &[], &probe), diagnostics)? {
+                return Ok(Evaluation::Rejected);
+            }
+            self.statements = statements;
+            return Ok(Evaluation::Added);
+        }
+
+        // An expression followed by a semicolon is evaluated for its side effects only
+        let expr = input.trim_end_matches(';');
+        let return_type = if input.ends_with(';') {
+            None
+        } else {
+            let probe = format!("fn {}() {{\nlet __repl_value = {};\n}}\n", PROBE_FN, expr);
+            if !self.check(
+                &source(&self.definitions, &self.statements, &probe),
+                diagnostics,
+            )? {
+                return Ok(Evaluation::Rejected);
+            }
+            self.driver
+                .type_of_last_binding(self.file_id, PROBE_FN)
+                .filter(|ty| ty != "nothing" && ty != "never")
+        };
+
+        let eval = match &return_type {
+            Some(ty) => format!("pub fn {}() -> {} {{\n{}\n}}\n", EVAL_FN, ty, expr),
+            None => format!("pub fn {}() {{\n{};\n}}\n", EVAL_FN, expr),
+        };
+        if !self.check(
+            &source(&self.definitions, &self.statements, &eval),
+            diagnostics,
+        )? {
+            return Ok(Evaluation::Rejected);
+        }
+        let runtime = self.load()?;
+
+        if return_type.is_none() {
+            #[allow(clippy::unit_arg)]
+            let result = invoke_fn!(runtime, EVAL_FN).map(|_: ()| Evaluation::Nothing);
+            return result.map_err(|e| DriverError::invocation(EVAL_FN, e).into());
+        }
+        let borrowed = runtime.borrow();
+        let return_type = borrowed
+            .get_function_definition(EVAL_FN)
+            .and_then(|definition| definition.prototype.signature.return_type())
+            .ok_or_else(|| DriverError::EntryPointNotFound(EVAL_FN.to_owned()))?;
+        ReturnValue::invoke(&runtime, EVAL_FN, return_type).map(Evaluation::Value)
+    }
+
+    /// Replaces the module with `source` and emits its diagnostics. Returns `false` if there are
+    /// errors.
+    fn check(&mut self, source: &str, diagnostics: &mut dyn Write) -> Result<bool, anyhow::Error> {
+        self.driver.set_file_text(self.file_id, source);
+        Ok(!self.driver.emit_diagnostics(diagnostics)?)
+    }
+
+    /// Writes the assembly of the current module and loads it into the runtime, reusing the
+    /// runtime of earlier evaluations if the assembly can be reloaded into it.
+    fn load(&mut self) -> Result<Rc<RefCell<Runtime>>, anyhow::Error> {
+        self.driver.write_assembly(self.file_id, true)?;
+        if let Some(runtime) = &self.runtime {
+            if runtime.borrow_mut().reload().is_ok() {
+                return Ok(runtime.clone());
+            }
+        }
+
+        let runtime = RuntimeBuilder::new(self.driver.assembly_output_path(self.file_id))
+            .set_watch(false)
+            .spawn()?;
+        self.runtime = Some(runtime.clone());
+        Ok(runtime)
+    }
+}
+
+/// Returns true if `input` defines a function or a struct.
+fn is_definition(input: &str) -> bool {
+    let input = input.trim_start_matches("pub ").trim_start();
+    input.starts_with("fn ") || input.starts_with("struct ") || input.starts_with("struct(")
+}
+
+/// Appends a semicolon to `statement` if it does not end with one.
+fn terminated(statement: &str) -> String {
+    if statement.ends_with(';') {
+        statement.to_owned()
+    } else {
+        format!("{};", statement)
+    }
+}
+
+/// Combines the `definitions` of the session with `function`, into whose body the `statements`
+/// of the session are inserted.
+fn source(definitions: &[String], statements: &[String], function: &str) -> String {
+    let mut source = definitions.join("\n");
+    source.push('\n');
+    if let Some(body) = function.find("{\n") {
+        source.push_str(&function[..body + 2]);
+        for statement in statements {
+            source.push_str(statement);
+            source.push('\n');
+        }
+        source.push_str(&function[body + 2..]);
+    }
+    source
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Evaluation, Repl};
+    use crate::{ArgValue, ReturnValue};
+    use mun_compiler::Config;
+
+    #[test]
+    fn test_eval() {
+        let mut repl = Repl::new(Config::default()).unwrap();
+        let mut diagnostics = Vec::new();
+        let mut eval = |input: &str| repl.eval(input, &mut diagnostics).unwrap();
+        let value = |value| Evaluation::Value(ReturnValue::Fundamental(value));
+
+        assert_eq!(eval("1 + 2"), value(ArgValue::I32(3)));
+        assert_eq!(
+            eval("fn double(x: i64) -> i64 { x * 2 }"),
+            Evaluation::Added
+        );
+        assert_eq!(eval("double(21)"), value(ArgValue::I64(42)));
+        assert_eq!(eval("let a = 2.5;"), Evaluation::Added);
+        assert_eq!(eval("a * 2.0"), value(ArgValue::F64(5.0)));
+        assert_eq!(eval("double(2) > 3"), value(ArgValue::Bool(true)));
+        assert_eq!(eval("double(2);"), Evaluation::Nothing);
+
+        // Errors are reported without ending the session
+        assert_eq!(eval("missing(1)"), Evaluation::Rejected);
+        assert_eq!(eval("fn double() {}"), Evaluation::Rejected);
+        assert_eq!(eval("double(a)"), Evaluation::Rejected);
+        assert_eq!(eval("double(1) + 1"), value(ArgValue::I64(3)));
+        drop(eval);
+        assert!(!diagnostics.is_empty());
+    }
+}
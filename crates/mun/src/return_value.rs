@@ -0,0 +1,464 @@
+//! Reading the value returned by an entry point and rendering it as text, a table or JSON.
+
+use crate::entry_args::ArgValue;
+use crate::DriverError;
+use anyhow::anyhow;
+use mun_runtime::{invoke_fn, InvokeError, Runtime, StructRef};
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::fmt;
+use std::rc::Rc;
+use std::str::FromStr;
+
+/// Every type of value that an entry point can return to be printed by `start`, together with a
+/// description of how it is formatted as text. `read_fundamental!` must handle every fundamental
+/// type in this list.
+pub const SUPPORTED_TYPES: &[(&str, &str)] = &[
+    ("core::bool", "`true` or `false`"),
+    ("core::i8", "signed decimal integer"),
+    ("core::i16", "signed decimal integer"),
+    ("core::i32", "signed decimal integer"),
+    ("core::i64", "signed decimal integer"),
+    ("core::i128", "signed decimal integer"),
+    ("core::u8", "unsigned decimal integer"),
+    ("core::u16", "unsigned decimal integer"),
+    ("core::u32", "unsigned decimal integer"),
+    ("core::u64", "unsigned decimal integer"),
+    ("core::u128", "unsigned decimal integer"),
+    ("core::f32", "shortest decimal that round-trips, e.g. `1.5`"),
+    ("core::f64", "shortest decimal that round-trips, e.g. `1.5`"),
+    (
+        "struct",
+        "`Name { field: value, .. }` with fields of supported types; see --output",
+    ),
+];
+
+/// Reads a value of the fundamental Mun type named `$type_name` by evaluating `$read`, which must
+/// be generic over its return type. Evaluates to `None` if the type is not a fundamental type.
+macro_rules! read_fundamental {
+    ($type_name:expr, $read:expr) => {
+        match $type_name {
+            "core::bool" => Some($read.map(ArgValue::Bool)),
+            "core::i8" => Some($read.map(ArgValue::I8)),
+            "core::i16" => Some($read.map(ArgValue::I16)),
+            "core::i32" => Some($read.map(ArgValue::I32)),
+            "core::i64" => Some($read.map(ArgValue::I64)),
+            "core::i128" => Some($read.map(ArgValue::I128)),
+            "core::u8" => Some($read.map(ArgValue::U8)),
+            "core::u16" => Some($read.map(ArgValue::U16)),
+            "core::u32" => Some($read.map(ArgValue::U32)),
+            "core::u64" => Some($read.map(ArgValue::U64)),
+            "core::u128" => Some($read.map(ArgValue::U128)),
+            "core::f32" => Some($read.map(ArgValue::F32)),
+            "core::f64" => Some($read.map(ArgValue::F64)),
+            _ => None,
+        }
+    };
+}
+
+/// Returns a function that converts an error of the runtime while invoking `entry_point` into a
+/// [`DriverError::Invocation`].
+fn invocation_error<E: InvokeError>(entry_point: &str) -> impl Fn(E) -> anyhow::Error + '_ {
+    move |e| DriverError::invocation(entry_point, e).into()
+}
+
+/// Invokes the function `entry_point`, which returns a value of type `return_type`, and discards
+/// its return value. This also supports return types that cannot be printed.
+pub fn invoke_discarding(
+    runtime: &Rc<RefCell<Runtime>>,
+    entry_point: &str,
+    return_type: &mun_abi::TypeInfo,
+) -> Result<(), anyhow::Error> {
+    macro_rules! invoke {
+        ($ty:ty) => {
+            invoke_fn!(runtime, entry_point)
+                .map(|_: $ty| ())
+                .map_err(invocation_error(entry_point))
+        };
+    }
+
+    match return_type.name() {
+        _ if return_type.group.is_struct() => invoke!(StructRef),
+        "core::isize" => invoke!(isize),
+        "core::usize" => invoke!(usize),
+        type_name => read_fundamental!(
+            type_name,
+            invoke_fn!(runtime, entry_point).map_err(invocation_error(entry_point))
+        )
+        .map(|result| result.map(|_| ()))
+        .unwrap_or_else(|| {
+            Err(DriverError::UnsupportedReturnType {
+                type_name: type_name.to_owned(),
+                with_args: false,
+            }
+            .into())
+        }),
+    }
+}
+
+/// The maximum number of arguments that can be passed to an entry point.
+pub const MAX_ARGS: usize = 3;
+
+/// Invokes the function `entry_point`, which returns a value of type `return_type` (if any), with
+/// the specified `args` and reads its return value. Up to [`MAX_ARGS`] arguments of type `bool`,
+/// `i64` and `f64` are supported, and the return type must be a fundamental type or a struct.
+pub fn invoke_with_args(
+    runtime: &Rc<RefCell<Runtime>>,
+    entry_point: &str,
+    return_type: Option<&mun_abi::TypeInfo>,
+    args: &[ArgValue],
+) -> Result<Option<ReturnValue>, anyhow::Error> {
+    macro_rules! invoke {
+        ($($arg:expr),*) => {
+            match return_type {
+                None => invoke_fn!(runtime, entry_point $(, $arg)*)
+                    .map(|_: ()| None)
+                    .map_err(invocation_error(entry_point)),
+                Some(return_type) if return_type.group.is_struct() => {
+                    let result: StructRef = invoke_fn!(runtime, entry_point $(, $arg)*)
+                        .map_err(invocation_error(entry_point))?;
+                    ReturnValue::from_struct(&result, &runtime.borrow()).map(Some)
+                }
+                Some(return_type) => read_fundamental!(
+                    return_type.name(),
+                    invoke_fn!(runtime, entry_point $(, $arg)*).map_err(invocation_error(entry_point))
+                )
+                .ok_or_else(|| DriverError::UnsupportedReturnType {
+                    type_name: return_type.name().to_owned(),
+                    with_args: true,
+                })?
+                .map(|value| Some(ReturnValue::Fundamental(value))),
+            }
+        };
+    }
+
+    // Calls `$body` with `$value` bound to the Rust value of the argument
+    macro_rules! with_arg {
+        ($arg:expr, $value:ident => $body:expr) => {
+            match $arg {
+                ArgValue::Bool($value) => $body,
+                ArgValue::I64($value) => $body,
+                ArgValue::F64($value) => $body,
+                other => Err(anyhow!(
+                    "passing arguments of type `{}` to an entry point is not supported yet, only `core::bool`, `core::i64` and `core::f64` are",
+                    other.type_name()
+                )),
+            }
+        };
+    }
+
+    match *args {
+        [] => invoke!(),
+        [a] => with_arg!(a, a => invoke!(a)),
+        [a, b] => with_arg!(a, a => with_arg!(b, b => invoke!(a, b))),
+        [a, b, c] => with_arg!(a, a => with_arg!(b, b => with_arg!(c, c => invoke!(a, b, c)))),
+        _ => Err(anyhow!(
+            "at most {} arguments can be passed to an entry point",
+            MAX_ARGS
+        )),
+    }
+}
+
+/// The format in which the value returned by an entry point is printed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// A single line, e.g. `Stats { count: 3, mean: 1.5 }`
+    Text,
+    /// An aligned two-column table with a row per (nested) field
+    Table,
+    /// A pretty-printed JSON value
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(anyhow!("unknown output format '{}'", s)),
+        }
+    }
+}
+
+/// A value returned by an entry point.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReturnValue {
+    Fundamental(ArgValue),
+    Struct {
+        name: String,
+        fields: Vec<(String, ReturnValue)>,
+    },
+}
+
+impl ReturnValue {
+    /// Invokes the function `entry_point`, which returns a value of type `return_type`, and reads
+    /// its return value.
+    pub fn invoke(
+        runtime: &Rc<RefCell<Runtime>>,
+        entry_point: &str,
+        return_type: &mun_abi::TypeInfo,
+    ) -> Result<ReturnValue, anyhow::Error> {
+        if return_type.group.is_struct() {
+            let result: StructRef =
+                invoke_fn!(runtime, entry_point).map_err(invocation_error(entry_point))?;
+            return Self::from_struct(&result, &runtime.borrow());
+        }
+
+        read_fundamental!(
+            return_type.name(),
+            invoke_fn!(runtime, entry_point).map_err(invocation_error(entry_point))
+        )
+        .ok_or_else(|| DriverError::UnsupportedReturnType {
+            type_name: return_type.name().to_owned(),
+            with_args: false,
+        })?
+        .map(ReturnValue::Fundamental)
+    }
+
+    /// Reflectively reads all fields of the struct referenced by `struct_ref`.
+    pub fn from_struct(
+        struct_ref: &StructRef,
+        runtime: &Runtime,
+    ) -> Result<ReturnValue, anyhow::Error> {
+        let type_info = StructRef::type_info(struct_ref, runtime);
+        // Safe because `StructRef`s always refer to a struct
+        let struct_info = type_info.as_struct().unwrap();
+
+        let fields = struct_info
+            .field_names()
+            .zip(struct_info.field_types().iter())
+            .map(|(field_name, field_type)| {
+                let value = if field_type.group.is_struct() {
+                    let field: StructRef = struct_ref.get(field_name).map_err(|e| anyhow!(e))?;
+                    Self::from_struct(&field, runtime)?
+                } else {
+                    read_fundamental!(
+                        field_type.name(),
+                        struct_ref.get(field_name).map_err(|e| anyhow!(e))
+                    )
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "cannot read field `{}::{}` of type `{}`",
+                            type_info.name(),
+                            field_name,
+                            field_type.name()
+                        )
+                    })?
+                    .map(ReturnValue::Fundamental)?
+                };
+                Ok((field_name.to_owned(), value))
+            })
+            .collect::<Result<_, anyhow::Error>>()?;
+
+        Ok(ReturnValue::Struct {
+            name: type_info.name().to_owned(),
+            fields,
+        })
+    }
+
+    /// Renders the value in the specified `format`.
+    pub fn render(&self, format: OutputFormat) -> Result<String, anyhow::Error> {
+        Ok(match format {
+            OutputFormat::Text => self.to_string(),
+            OutputFormat::Table => self.to_table(),
+            OutputFormat::Json => serde_json::to_string_pretty(&self.to_json())?,
+        })
+    }
+
+    /// Renders the value as a two-column table of field names and values. Fields of nested
+    /// structs are flattened into dotted names, e.g. `position.x`.
+    fn to_table(&self) -> String {
+        let mut rows = Vec::new();
+        self.collect_rows(None, &mut rows);
+
+        let width = rows
+            .iter()
+            .map(|(name, _)| name.len())
+            .chain(std::iter::once("field".len()))
+            .max()
+            .unwrap();
+
+        let mut table = format!("{:<width$}  value\n", "field", width = width);
+        table.push_str(&format!("{:-<width$}  -----\n", "", width = width));
+        for (name, value) in rows {
+            table.push_str(&format!("{:<width$}  {}\n", name, value, width = width));
+        }
+        table.truncate(table.trim_end().len());
+        table
+    }
+
+    /// Appends a row for every fundamental value in this value to `rows`.
+    fn collect_rows(&self, path: Option<&str>, rows: &mut Vec<(String, String)>) {
+        match self {
+            ReturnValue::Fundamental(value) => {
+                rows.push((path.unwrap_or("").to_owned(), value.to_string()))
+            }
+            ReturnValue::Struct { fields, .. } => {
+                for (name, value) in fields {
+                    let path = match path {
+                        Some(path) => format!("{}.{}", path, name),
+                        None => name.clone(),
+                    };
+                    value.collect_rows(Some(&path), rows);
+                }
+            }
+        }
+    }
+
+    /// Converts the value into JSON. Structs become objects; non-finite floats become `null`.
+    fn to_json(&self) -> serde_json::Value {
+        use serde_json::Value;
+
+        match self {
+            ReturnValue::Fundamental(value) => match *value {
+                ArgValue::Bool(value) => Value::from(value),
+                ArgValue::I8(value) => Value::from(value),
+                ArgValue::I16(value) => Value::from(value),
+                ArgValue::I32(value) => Value::from(value),
+                ArgValue::I64(value) => Value::from(value),
+                // JSON numbers beyond 64 bits lose precision in most parsers
+                ArgValue::I128(value) => i64::try_from(value)
+                    .map(Value::from)
+                    .unwrap_or_else(|_| Value::from(value.to_string())),
+                ArgValue::U8(value) => Value::from(value),
+                ArgValue::U16(value) => Value::from(value),
+                ArgValue::U32(value) => Value::from(value),
+                ArgValue::U64(value) => Value::from(value),
+                ArgValue::U128(value) => u64::try_from(value)
+                    .map(Value::from)
+                    .unwrap_or_else(|_| Value::from(value.to_string())),
+                ArgValue::F32(value) => Value::from(value),
+                ArgValue::F64(value) => Value::from(value),
+            },
+            ReturnValue::Struct { fields, .. } => Value::Object(
+                fields
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.to_json()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ReturnValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReturnValue::Fundamental(value) => write!(f, "{}", value),
+            ReturnValue::Struct { name, fields } => {
+                write!(f, "{} {{", name)?;
+                for (idx, (field_name, value)) in fields.iter().enumerate() {
+                    let separator = if idx == 0 { " " } else { ", " };
+                    write!(f, "{}{}: {}", separator, field_name, value)?;
+                }
+                if fields.is_empty() {
+                    write!(f, "}}")
+                } else {
+                    write!(f, " }}")
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArgValue, OutputFormat, ReturnValue, SUPPORTED_TYPES};
+
+    fn stats() -> ReturnValue {
+        ReturnValue::Struct {
+            name: "Stats".to_owned(),
+            fields: vec![
+                (
+                    "count".to_owned(),
+                    ReturnValue::Fundamental(ArgValue::U32(3)),
+                ),
+                (
+                    "range".to_owned(),
+                    ReturnValue::Struct {
+                        name: "Range".to_owned(),
+                        fields: vec![
+                            (
+                                "min".to_owned(),
+                                ReturnValue::Fundamental(ArgValue::F64(0.5)),
+                            ),
+                            (
+                                "max".to_owned(),
+                                ReturnValue::Fundamental(ArgValue::F64(2.0)),
+                            ),
+                        ],
+                    },
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            stats().render(OutputFormat::Text).unwrap(),
+            "Stats { count: 3, range: Range { min: 0.5, max: 2 } }"
+        );
+        assert_eq!(
+            stats().render(OutputFormat::Table).unwrap(),
+            "field      value\n\
+             ---------  -----\n\
+             count      3\n\
+             range.min  0.5\n\
+             range.max  2"
+        );
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&stats().render(OutputFormat::Json).unwrap())
+                .unwrap(),
+            serde_json::json!({ "count": 3, "range": { "min": 0.5, "max": 2.0 } })
+        );
+    }
+
+    #[test]
+    fn test_supported_types() {
+        for (type_name, _) in SUPPORTED_TYPES.iter().filter(|(name, _)| *name != "struct") {
+            let value: Option<Result<ArgValue, ()>> = read_fundamental!(*type_name, Err(()));
+            assert!(value.is_some(), "`{}` is not handled", type_name);
+        }
+    }
+
+    #[test]
+    fn test_render_integers() {
+        let render = |value| {
+            ReturnValue::Fundamental(value)
+                .render(OutputFormat::Text)
+                .unwrap()
+        };
+        assert_eq!(render(ArgValue::I8(-128)), "-128");
+        assert_eq!(render(ArgValue::I16(-300)), "-300");
+        assert_eq!(render(ArgValue::I32(-70000)), "-70000");
+        assert_eq!(
+            render(ArgValue::I64(i64::min_value())),
+            "-9223372036854775808"
+        );
+        assert_eq!(
+            render(ArgValue::I128(i128::min_value())),
+            "-170141183460469231731687303715884105728"
+        );
+        assert_eq!(render(ArgValue::U8(255)), "255");
+        assert_eq!(render(ArgValue::U16(65535)), "65535");
+        assert_eq!(render(ArgValue::U32(u32::max_value())), "4294967295");
+        assert_eq!(
+            render(ArgValue::U64(u64::max_value())),
+            "18446744073709551615"
+        );
+        assert_eq!(
+            render(ArgValue::U128(u128::max_value())),
+            "340282366920938463463374607431768211455"
+        );
+
+        assert_eq!(
+            ReturnValue::Fundamental(ArgValue::U128(u128::max_value()))
+                .render(OutputFormat::Json)
+                .unwrap(),
+            "\"340282366920938463463374607431768211455\""
+        );
+    }
+}
@@ -0,0 +1,403 @@
+//! Scaffolding of new packages and workspaces.
+
+use anyhow::anyhow;
+use mun_project::{
+    Manifest, ManifestMetadata, Version, Workspace, MANIFEST_FILENAME, WORKSPACE_FILENAME,
+};
+use mun_syntax::SourceFile;
+use std::path::{Path, PathBuf};
+
+/// The contents of the source file of a new binary package.
+const MAIN_SOURCE: &str = "pub fn main() {\n}\n";
+
+/// The contents of the source file of a new library package.
+const LIB_SOURCE: &str =
+    "/// Returns the sum of `a` and `b`.\npub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n";
+
+/// The kind of package that is created, which determines its source file.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PackageKind {
+    /// A package with a `main` entry point that can be started, in `src/main.mun`
+    Bin,
+    /// A package that only exports functions for other code to call, in `src/lib.mun`
+    Lib,
+}
+
+impl PackageKind {
+    /// Returns the path of the source file of the package, relative to its root.
+    pub fn source_path(self) -> &'static str {
+        match self {
+            PackageKind::Bin => "src/main.mun",
+            PackageKind::Lib => "src/lib.mun",
+        }
+    }
+
+    /// Returns the contents of the source file of a new package.
+    fn source(self) -> &'static str {
+        match self {
+            PackageKind::Bin => MAIN_SOURCE,
+            PackageKind::Lib => LIB_SOURCE,
+        }
+    }
+}
+
+/// The version of a new package.
+pub const INITIAL_VERSION: &str = "0.1.0";
+
+/// Returns an error if `name` cannot be used as the name of a new package.
+pub fn validate_package_name(name: &str) -> Result<(), anyhow::Error> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(anyhow!(
+            "invalid package name '{}', only letters, digits, '_' and '-' are allowed",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Returns an error if `license` is not a plausible SPDX license expression, e.g. `MIT` or
+/// `MIT OR Apache-2.0`.
+pub fn validate_license(license: &str) -> Result<(), anyhow::Error> {
+    if license.trim().is_empty()
+        || !license
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || " -.+()".contains(c))
+    {
+        return Err(anyhow!(
+            "invalid license '{}', expected an SPDX license expression such as 'MIT'",
+            license
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the author configured in git as `name <email>`, or no authors if git is not
+/// configured.
+pub fn default_authors() -> Vec<String> {
+    let git_config = |key: &str| {
+        std::process::Command::new("git")
+            .args(&["config", "--get", key])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|value| value.trim().to_owned())
+            .filter(|value| !value.is_empty())
+    };
+
+    match (git_config("user.name"), git_config("user.email")) {
+        (Some(name), Some(email)) => vec![format!("{} <{}>", name, email)],
+        (Some(name), None) => vec![name],
+        _ => vec![],
+    }
+}
+
+/// Reads the template at `path`, whose contents are used for the source file of a new package
+/// instead of the built-in contents. Returns an error if the template contains syntax errors.
+pub fn read_template(path: &Path) -> Result<String, anyhow::Error> {
+    let template = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("could not read template '{}': {}", path.display(), e))?;
+    if let Some(error) = SourceFile::parse(&template).errors().first() {
+        let offset = error.location().offset().to_usize();
+        return Err(anyhow!(
+            "template '{}' is not valid Mun: {} at line {}",
+            path.display(),
+            error,
+            template[..offset].matches('\n').count() + 1
+        ));
+    }
+    Ok(template)
+}
+
+/// Returns whether `path` is an existing directory that contains any files or directories.
+pub fn is_populated(path: &Path) -> Result<bool, anyhow::Error> {
+    if !path.is_dir() {
+        return Ok(false);
+    }
+    Ok(std::fs::read_dir(path)?.next().is_some())
+}
+
+/// Creates a package with the specified `manifest` in the directory `path`, with a single source
+/// file for the specified kind of package. The source file contains the `template` if specified, or
+/// the built-in contents for the kind of package otherwise.
+pub fn create_package(
+    path: &Path,
+    manifest: &Manifest,
+    kind: PackageKind,
+    template: Option<&str>,
+) -> Result<(), anyhow::Error> {
+    let manifest_path = path.join(MANIFEST_FILENAME);
+    if manifest_path.exists() {
+        return Err(anyhow!("'{}' already exists", manifest_path.display()));
+    }
+
+    std::fs::create_dir_all(path)?;
+    write_package_files(
+        path,
+        manifest,
+        kind,
+        template.unwrap_or_else(|| kind.source()),
+    )
+}
+
+/// Turns the existing directory `path` into a package with the specified `manifest`. A source file
+/// that contains an empty `main` function is only written if there is no `src/main.mun` yet.
+pub fn init_package(path: &Path, manifest: &Manifest) -> Result<(), anyhow::Error> {
+    if !path.is_dir() {
+        return Err(anyhow!(
+            "'{}' is not a directory, use `mun new` to create a package in a new directory",
+            path.display()
+        ));
+    }
+
+    let manifest_path = path.join(MANIFEST_FILENAME);
+    if manifest_path.exists() {
+        return Err(anyhow!(
+            "'{}' already exists, the directory already contains a package",
+            manifest_path.display()
+        ));
+    }
+
+    write_package_files(path, manifest, PackageKind::Bin, PackageKind::Bin.source())
+}
+
+/// Writes the manifest and, if it does not exist yet, the source file of the specified kind of
+/// package with the contents `source` in the existing directory `path`.
+fn write_package_files(
+    path: &Path,
+    manifest: &Manifest,
+    kind: PackageKind,
+    source: &str,
+) -> Result<(), anyhow::Error> {
+    let source_path = path.join(kind.source_path());
+    if !source_path.exists() {
+        std::fs::create_dir_all(path.join("src"))?;
+        std::fs::write(&source_path, source)?;
+    }
+    std::fs::write(path.join(MANIFEST_FILENAME), manifest.to_toml_string()?)?;
+    Ok(())
+}
+
+/// Creates a workspace in the directory `path` with a package for each of the specified
+/// `members`. All members are validated before any file is created.
+pub fn create_workspace(path: &Path, members: &[&str]) -> Result<(), anyhow::Error> {
+    let workspace_path = path.join(WORKSPACE_FILENAME);
+    if workspace_path.exists() {
+        return Err(anyhow!("'{}' already exists", workspace_path.display()));
+    }
+
+    let manifests = members
+        .iter()
+        .map(|member| {
+            validate_package_name(member)?;
+            if path.join(member).join(MANIFEST_FILENAME).exists() {
+                return Err(anyhow!("member '{}' already contains a package", member));
+            }
+            Manifest::new(
+                member,
+                Version::parse(INITIAL_VERSION).unwrap(),
+                ManifestMetadata::default(),
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let workspace = Workspace::new(members.iter().map(|member| (*member).to_owned()).collect());
+    std::fs::create_dir_all(path)?;
+    std::fs::write(&workspace_path, workspace.to_toml_string()?)?;
+    for (member, manifest) in members.iter().zip(manifests.iter()) {
+        create_package(&path.join(member), manifest, PackageKind::Bin, None)?;
+    }
+    Ok(())
+}
+
+/// Adds the package in the directory `path` to the members of the workspace in the closest parent
+/// directory, if any. Returns the path of the workspace file if the package was added, or `None`
+/// if there is no workspace or the package already is a member.
+pub fn register_in_workspace(path: &Path) -> Result<Option<PathBuf>, anyhow::Error> {
+    let path = path.canonicalize()?;
+    let workspace_path = match path
+        .parent()
+        .and_then(|parent| crate::find_manifest(parent, WORKSPACE_FILENAME))
+    {
+        Some(workspace_path) => workspace_path,
+        None => return Ok(None),
+    };
+
+    // Members are written with forward slashes, regardless of the platform
+    let member = path
+        .strip_prefix(workspace_path.parent().unwrap())?
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let mut workspace = Workspace::from_file(&workspace_path)?;
+    if !workspace.add_member(member) {
+        return Ok(None);
+    }
+    std::fs::write(&workspace_path, workspace.to_toml_string()?)?;
+    Ok(Some(workspace_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        create_package, create_workspace, init_package, read_template, register_in_workspace,
+        validate_license, validate_package_name, PackageKind, INITIAL_VERSION,
+    };
+    use mun_project::{
+        Manifest, ManifestMetadata, Package, Version, Workspace, MANIFEST_FILENAME,
+        WORKSPACE_FILENAME,
+    };
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_init_package() {
+        let dir = TempDir::new("test_init_package").unwrap();
+        let manifest = Manifest::new(
+            "game",
+            Version::parse(INITIAL_VERSION).unwrap(),
+            ManifestMetadata::default(),
+        )
+        .unwrap();
+
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/main.mun"), "pub fn existing() {}").unwrap();
+        init_package(dir.path(), &manifest).unwrap();
+        assert!(dir.path().join(MANIFEST_FILENAME).is_file());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("src/main.mun")).unwrap(),
+            "pub fn existing() {}"
+        );
+
+        assert!(init_package(dir.path(), &manifest).is_err());
+        assert!(init_package(&dir.path().join("missing"), &manifest).is_err());
+    }
+
+    #[test]
+    fn test_register_in_workspace() {
+        let dir = TempDir::new("test_register_in_workspace").unwrap();
+        let manifest = Manifest::new(
+            "game",
+            Version::parse(INITIAL_VERSION).unwrap(),
+            ManifestMetadata::default(),
+        )
+        .unwrap();
+
+        // Without a workspace, nothing is registered
+        let standalone_path = dir.path().join("standalone");
+        create_package(&standalone_path, &manifest, PackageKind::Bin, None).unwrap();
+        assert_eq!(register_in_workspace(&standalone_path).unwrap(), None);
+
+        let workspace_dir = dir.path().join("workspace");
+        create_workspace(&workspace_dir, &["core"]).unwrap();
+        let package_path = workspace_dir.join("packages/game");
+        create_package(&package_path, &manifest, PackageKind::Bin, None).unwrap();
+        let workspace_path = register_in_workspace(&package_path).unwrap().unwrap();
+        assert_eq!(
+            workspace_path,
+            workspace_dir
+                .join(WORKSPACE_FILENAME)
+                .canonicalize()
+                .unwrap()
+        );
+
+        // Registering the package again does not duplicate it
+        assert_eq!(register_in_workspace(&package_path).unwrap(), None);
+        let workspace = Workspace::from_file(&workspace_path).unwrap();
+        assert_eq!(workspace.members(), &["core", "packages/game"]);
+    }
+
+    #[test]
+    fn test_create_package() {
+        let dir = TempDir::new("test_create_package").unwrap();
+        let manifest = Manifest::new(
+            "game",
+            Version::parse(INITIAL_VERSION).unwrap(),
+            ManifestMetadata::default(),
+        )
+        .unwrap();
+
+        let bin_path = dir.path().join("bin");
+        create_package(&bin_path, &manifest, PackageKind::Bin, None).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(bin_path.join("src/main.mun")).unwrap(),
+            "pub fn main() {\n}\n"
+        );
+        assert!(!bin_path.join("src/lib.mun").exists());
+
+        let lib_path = dir.path().join("lib");
+        create_package(&lib_path, &manifest, PackageKind::Lib, None).unwrap();
+        let source = std::fs::read_to_string(lib_path.join("src/lib.mun")).unwrap();
+        assert!(source.contains("pub fn add(a: i32, b: i32) -> i32"));
+        assert!(!source.contains("fn main"));
+        assert!(!lib_path.join("src/main.mun").exists());
+        assert_eq!(
+            Package::from_file(lib_path.join(MANIFEST_FILENAME))
+                .unwrap()
+                .name(),
+            "game"
+        );
+    }
+
+    #[test]
+    fn test_read_template() {
+        let dir = TempDir::new("test_read_template").unwrap();
+        let template_path = dir.path().join("template.mun");
+        std::fs::write(&template_path, "pub fn main() -> i32 {\n    0\n}\n").unwrap();
+        let template = read_template(&template_path).unwrap();
+
+        let manifest = Manifest::new(
+            "game",
+            Version::parse(INITIAL_VERSION).unwrap(),
+            ManifestMetadata::default(),
+        )
+        .unwrap();
+        let path = dir.path().join("game");
+        create_package(&path, &manifest, PackageKind::Bin, Some(&template)).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(path.join("src/main.mun")).unwrap(),
+            template
+        );
+
+        std::fs::write(&template_path, "pub fn main() {\n    let a = ;\n}\n").unwrap();
+        let error = read_template(&template_path).unwrap_err().to_string();
+        assert!(error.contains("is not valid Mun"), "{}", error);
+        assert!(error.ends_with("at line 2"), "{}", error);
+        assert!(read_template(&dir.path().join("missing.mun")).is_err());
+    }
+
+    #[test]
+    fn test_create_workspace() {
+        let dir = TempDir::new("test_create_workspace").unwrap();
+        let path = dir.path().join("workspace");
+
+        create_workspace(&path, &["core", "game"]).unwrap();
+        let workspace = Workspace::from_file(path.join(WORKSPACE_FILENAME)).unwrap();
+        assert_eq!(workspace.members(), &["core", "game"]);
+        for member in workspace.members() {
+            let package = Package::from_file(path.join(member).join("mun.toml")).unwrap();
+            assert_eq!(package.name(), member);
+            assert!(path.join(member).join("src/main.mun").is_file());
+        }
+
+        assert!(create_workspace(&path, &[]).is_err());
+        assert!(create_workspace(&dir.path().join("other"), &["../escape"]).is_err());
+        assert!(!dir.path().join("other").exists());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate_package_name("my_game-2").is_ok());
+        assert!(validate_package_name("").is_err());
+        assert!(validate_package_name("my game").is_err());
+
+        assert!(validate_license("MIT").is_ok());
+        assert!(validate_license("MIT OR Apache-2.0").is_ok());
+        assert!(validate_license(" ").is_err());
+        assert!(validate_license("MIT\"").is_err());
+    }
+}
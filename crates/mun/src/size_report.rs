@@ -0,0 +1,115 @@
+//! A report of the compiled size of every exported function of a package, to find out which
+//! functions contribute most to the size of an assembly.
+
+use mun_compiler::Driver;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// The filename of a size report in the output directory, without its extension
+pub const FILE_STEM: &str = "size-report";
+
+/// The compiled size of an exported function.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct FunctionSize {
+    /// The path of the source file that defines the function, relative to the source directory
+    pub file: String,
+    /// The name of the function
+    pub name: String,
+    /// The size of the function's machine code in bytes
+    pub size: u64,
+}
+
+/// Returns the size of every exported function of the package compiled by `driver`, sorted by size
+/// in descending order.
+pub fn function_sizes(driver: &Driver) -> Vec<FunctionSize> {
+    let mut sizes: Vec<FunctionSize> = driver
+        .function_sizes()
+        .into_iter()
+        .flat_map(|(file, functions)| {
+            functions.into_iter().map(move |(name, size)| FunctionSize {
+                file: file.as_str().to_owned(),
+                name,
+                size,
+            })
+        })
+        .collect();
+    sizes.sort_by(|a, b| {
+        b.size
+            .cmp(&a.size)
+            .then_with(|| a.file.cmp(&b.file))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    sizes
+}
+
+/// Renders the function sizes as a table with a column for the size, source file and name.
+pub fn render_text(sizes: &[FunctionSize]) -> String {
+    let file_width = sizes
+        .iter()
+        .map(|function| function.file.len())
+        .chain(std::iter::once("file".len()))
+        .max()
+        .unwrap();
+
+    let mut report = format!(
+        "{:>12}  {:<width$}  function\n",
+        "size (bytes)",
+        "file",
+        width = file_width
+    );
+    for function in sizes {
+        report.push_str(&format!(
+            "{:>12}  {:<width$}  {}\n",
+            function.size,
+            function.file,
+            function.name,
+            width = file_width
+        ));
+    }
+    report
+}
+
+/// Writes a report of the function sizes of the package compiled by `driver` in the specified
+/// `format` (`text` or `json`) to `out_dir`. Returns the path of the report.
+pub fn write_size_report(
+    driver: &Driver,
+    out_dir: &Path,
+    format: &str,
+) -> Result<PathBuf, anyhow::Error> {
+    let sizes = function_sizes(driver);
+    let (report, extension) = match format {
+        "json" => (serde_json::to_string_pretty(&sizes)? + "\n", "json"),
+        _ => (render_text(&sizes), "txt"),
+    };
+
+    let report_path = out_dir.join(FILE_STEM).with_extension(extension);
+    std::fs::write(&report_path, report)?;
+    Ok(report_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_text, FunctionSize};
+
+    #[test]
+    fn test_render_text() {
+        let sizes = vec![
+            FunctionSize {
+                file: "physics.mun".to_owned(),
+                name: "integrate".to_owned(),
+                size: 1024,
+            },
+            FunctionSize {
+                file: "main.mun".to_owned(),
+                name: "main".to_owned(),
+                size: 16,
+            },
+        ];
+        assert_eq!(
+            render_text(&sizes),
+            "size (bytes)  file         function\n\
+             \x20       1024  physics.mun  integrate\n\
+             \x20         16  main.mun     main\n"
+        );
+    }
+}
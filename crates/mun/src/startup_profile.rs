@@ -0,0 +1,139 @@
+//! Measuring the latency from starting `mun` to the first call into a library.
+
+use crate::{return_value, DriverError};
+use mun_runtime::{invoke_fn, RuntimeBuilder};
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// The duration of a single phase of starting up.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Phase {
+    pub name: &'static str,
+    #[serde(rename = "duration_ms")]
+    #[serde(serialize_with = "serialize_millis")]
+    pub duration: Duration,
+}
+
+/// The durations of all phases from the moment `mun` started to the return of the first call.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct StartupProfile {
+    pub phases: Vec<Phase>,
+}
+
+impl StartupProfile {
+    /// Returns the combined duration of all phases.
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|phase| phase.duration).sum()
+    }
+
+    /// Renders the profile as a table with a row per phase and a total.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for phase in self.phases.iter() {
+            text.push_str(&format!(
+                "{:<16}{:>10.3} ms\n",
+                phase.name,
+                millis(phase.duration)
+            ));
+        }
+        text.push_str(&format!(
+            "{:<16}{:>10.3} ms\n",
+            "total",
+            millis(self.total())
+        ));
+        text
+    }
+}
+
+/// Measures how long it takes to spawn a runtime for the library at `library_path` and to complete
+/// the first call to `entry_point`. The first phase spans from `process_start`, the moment `mun`
+/// started parsing its arguments, until profiling started.
+pub fn profile_startup(
+    process_start: Instant,
+    library_path: &Path,
+    entry_point: &str,
+) -> Result<StartupProfile, anyhow::Error> {
+    let mut phases = Vec::new();
+    let mut phase_start = process_start;
+    let mut end_phase = |name| {
+        let now = Instant::now();
+        phases.push(Phase {
+            name,
+            duration: now - phase_start,
+        });
+        phase_start = now;
+    };
+    end_phase("parse arguments");
+
+    let runtime = RuntimeBuilder::new(library_path).set_watch(false).spawn()?;
+    end_phase("spawn runtime");
+
+    {
+        let borrowed = runtime.borrow();
+        let fn_definition = borrowed
+            .get_function_definition(entry_point)
+            .ok_or_else(|| DriverError::EntryPointNotFound(entry_point.to_owned()))?;
+        match fn_definition.prototype.signature.return_type() {
+            Some(return_type) => {
+                return_value::invoke_discarding(&runtime, entry_point, return_type)?
+            }
+            None => invoke_fn!(runtime, entry_point)
+                .map(|_: ()| ())
+                .map_err(|e| DriverError::invocation(entry_point, e))?,
+        }
+    }
+    end_phase("first call");
+
+    Ok(StartupProfile { phases })
+}
+
+/// Converts a duration into fractional milliseconds.
+pub(crate) fn millis(duration: Duration) -> f64 {
+    duration.as_nanos() as f64 / 1_000_000.0
+}
+
+/// Serializes a duration as fractional milliseconds.
+pub(crate) fn serialize_millis<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(millis(*duration))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Phase, StartupProfile};
+    use std::time::Duration;
+
+    #[test]
+    fn test_render() {
+        let profile = StartupProfile {
+            phases: vec![
+                Phase {
+                    name: "spawn runtime",
+                    duration: Duration::from_micros(2500),
+                },
+                Phase {
+                    name: "first call",
+                    duration: Duration::from_micros(125),
+                },
+            ],
+        };
+        assert_eq!(
+            profile.to_text(),
+            "spawn runtime        2.500 ms\n\
+             first call           0.125 ms\n\
+             total                2.625 ms\n"
+        );
+        assert_eq!(
+            serde_json::to_value(&profile).unwrap(),
+            serde_json::json!({
+                "phases": [
+                    { "name": "spawn runtime", "duration_ms": 2.5 },
+                    { "name": "first call", "duration_ms": 0.125 },
+                ]
+            })
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Serialization of the syntax tree of a source file, for tools that are built around Mun source
+//! without linking the compiler.
+
+use mun_syntax::{SourceFile, SyntaxElement, SyntaxNode, TextRange};
+use serde::Serialize;
+
+/// A node or token of the syntax tree. Only tokens have text; only nodes have children.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Node {
+    pub kind: String,
+    pub span: Span,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Node>,
+}
+
+/// A range of byte offsets into the source text; `end` is exclusive.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// An error that occurred while parsing the source text.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Span,
+}
+
+/// The syntax tree of a source file and the errors that occurred while parsing it.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct SyntaxTree {
+    pub root: Node,
+    pub errors: Vec<ParseError>,
+}
+
+impl SyntaxTree {
+    /// Parses `text` into a syntax tree. A tree is produced even if the text contains errors.
+    pub fn parse(text: &str) -> Self {
+        let parse = SourceFile::parse(text);
+        let errors = parse
+            .errors()
+            .iter()
+            .map(|error| {
+                let location = error.location();
+                ParseError {
+                    message: error.to_string(),
+                    span: Span {
+                        start: location.offset().to_usize(),
+                        end: location.end_offset().to_usize(),
+                    },
+                }
+            })
+            .collect();
+
+        SyntaxTree {
+            root: from_node(&parse.syntax_node()),
+            errors,
+        }
+    }
+}
+
+/// Converts a node and all of its descendants.
+fn from_node(node: &SyntaxNode) -> Node {
+    Node {
+        kind: format!("{:?}", node.kind()),
+        span: from_range(node.text_range()),
+        text: None,
+        children: node
+            .children_with_tokens()
+            .map(|element| match element {
+                SyntaxElement::Node(node) => from_node(&node),
+                SyntaxElement::Token(token) => Node {
+                    kind: format!("{:?}", token.kind()),
+                    span: from_range(token.text_range()),
+                    text: Some(token.text().to_string()),
+                    children: Vec::new(),
+                },
+            })
+            .collect(),
+    }
+}
+
+fn from_range(range: TextRange) -> Span {
+    Span {
+        start: range.start().to_usize(),
+        end: range.end().to_usize(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SyntaxTree;
+
+    #[test]
+    fn test_parse() {
+        let tree = SyntaxTree::parse("fn a() {}");
+        assert!(tree.errors.is_empty());
+        assert_eq!(
+            serde_json::to_value(&tree.root).unwrap(),
+            serde_json::json!({
+                "kind": "SOURCE_FILE",
+                "span": { "start": 0, "end": 9 },
+                "children": [{
+                    "kind": "FUNCTION_DEF",
+                    "span": { "start": 0, "end": 9 },
+                    "children": [
+                        { "kind": "FN_KW", "span": { "start": 0, "end": 2 }, "text": "fn" },
+                        { "kind": "WHITESPACE", "span": { "start": 2, "end": 3 }, "text": " " },
+                        {
+                            "kind": "NAME",
+                            "span": { "start": 3, "end": 4 },
+                            "children": [
+                                { "kind": "IDENT", "span": { "start": 3, "end": 4 }, "text": "a" }
+                            ]
+                        },
+                        {
+                            "kind": "PARAM_LIST",
+                            "span": { "start": 4, "end": 6 },
+                            "children": [
+                                { "kind": "L_PAREN", "span": { "start": 4, "end": 5 }, "text": "(" },
+                                { "kind": "R_PAREN", "span": { "start": 5, "end": 6 }, "text": ")" }
+                            ]
+                        },
+                        { "kind": "WHITESPACE", "span": { "start": 6, "end": 7 }, "text": " " },
+                        {
+                            "kind": "BLOCK_EXPR",
+                            "span": { "start": 7, "end": 9 },
+                            "children": [
+                                { "kind": "L_CURLY", "span": { "start": 7, "end": 8 }, "text": "{" },
+                                { "kind": "R_CURLY", "span": { "start": 8, "end": 9 }, "text": "}" }
+                            ]
+                        }
+                    ]
+                }]
+            })
+        );
+
+        let tree = SyntaxTree::parse("fn a(");
+        assert!(!tree.errors.is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+//! Discovers and runs the test functions of a compiled package, as used by `mun test`.
+
+use crate::DriverError;
+use anyhow::anyhow;
+use mun_runtime::{invoke_fn, ReturnTypeReflection, Runtime, RuntimeBuilder};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// The prefix of the names of test functions. A test is a public function with this prefix that
+/// takes no arguments and either returns a `bool` that indicates whether it passed, or returns
+/// nothing, in which case it always passes.
+pub const TEST_PREFIX: &str = "test_";
+
+/// The results of a test run.
+#[derive(Default)]
+pub struct TestSummary {
+    pub passed: usize,
+    pub failed: Vec<String>,
+}
+
+impl TestSummary {
+    /// Returns true if none of the tests failed.
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Runs the tests in all `libraries` whose name contains `filter`, printing the result of every
+/// test and a summary.
+pub fn run_tests(
+    libraries: &[PathBuf],
+    filter: Option<&str>,
+) -> Result<TestSummary, anyhow::Error> {
+    let mut summary = TestSummary::default();
+    for library_path in libraries {
+        let runtime = RuntimeBuilder::new(library_path)
+            .set_watch(false)
+            .spawn()
+            .map_err(|e| anyhow!("could not load '{}': {}", library_path.display(), e))?;
+
+        for name in test_names(&runtime.borrow(), library_path, filter)? {
+            match run_test(&runtime, &name) {
+                Ok(true) => {
+                    println!("test {} ... ok", name);
+                    summary.passed += 1;
+                }
+                Ok(false) => {
+                    println!("test {} ... FAILED", name);
+                    summary.failed.push(name);
+                }
+                // A test that cannot be invoked fails, without stopping the other tests
+                Err(e) => {
+                    println!("test {} ... FAILED: {}", name, e);
+                    summary.failed.push(name);
+                }
+            }
+        }
+    }
+
+    if !summary.failed.is_empty() {
+        println!("\nfailures:");
+        for name in &summary.failed {
+            println!("    {}", name);
+        }
+    }
+    println!(
+        "\ntest result: {}. {} passed; {} failed",
+        if summary.is_success() { "ok" } else { "FAILED" },
+        summary.passed,
+        summary.failed.len()
+    );
+    Ok(summary)
+}
+
+/// Returns the names of the test functions exported by the library at `library_path` whose name
+/// contains `filter`.
+fn test_names(
+    runtime: &Runtime,
+    library_path: &Path,
+    filter: Option<&str>,
+) -> Result<Vec<String>, anyhow::Error> {
+    let library_path = std::fs::canonicalize(library_path)?;
+    let functions = runtime
+        .assemblies()
+        .find(|assembly| assembly.library_path() == library_path)
+        .map(|assembly| assembly.info().symbols.functions())
+        .unwrap_or_default();
+
+    Ok(functions
+        .iter()
+        .filter(|function| {
+            let name = function.prototype.name();
+            name.starts_with(TEST_PREFIX)
+                && function.prototype.signature.arg_types().is_empty()
+                && filter.map_or(true, |filter| name.contains(filter))
+        })
+        .map(|function| function.prototype.name().to_owned())
+        .collect())
+}
+
+/// Runs the test function `name`, returning whether it passed. Returns an error if the function
+/// could not be invoked.
+fn run_test(runtime: &Rc<RefCell<Runtime>>, name: &str) -> Result<bool, anyhow::Error> {
+    let return_type = runtime
+        .borrow()
+        .get_function_definition(name)
+        .and_then(|definition| definition.prototype.signature.return_type())
+        .map(|ty| (ty.guid, ty.name().to_owned()));
+
+    match return_type {
+        None =>
+        {
+            #[allow(clippy::unit_arg)]
+            invoke_fn!(runtime, name)
+                .map(|_: ()| true)
+                .map_err(|e| DriverError::invocation(name, e).into())
+        }
+        Some((guid, _)) if guid == bool::type_guid() => {
+            invoke_fn!(runtime, name).map_err(|e| DriverError::invocation(name, e).into())
+        }
+        Some((_, type_name)) => Err(anyhow!(
+            "test '{}' must return `bool` or nothing, but returns `{}`",
+            name,
+            type_name
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_tests;
+    use crate::multi_file::compile_reader;
+    use mun_compiler::Config;
+
+    #[test]
+    fn test_run_tests() {
+        let out_dir = tempdir::TempDir::new("test_run_tests").unwrap();
+        let config = Config {
+            out_dir: Some(out_dir.path().to_path_buf()),
+            ..Config::default()
+        };
+        let mut source = &b"pub fn test_pass() -> bool { true }\n\
+            pub fn test_unit() {}\n\
+            pub fn test_fail() -> bool { false }\n\
+            pub fn test_wrong_type() -> i32 { 1 }\n\
+            pub fn test_with_args(a: i32) -> bool { false }\n\
+            pub fn helper() -> bool { false }"[..];
+        assert!(compile_reader(&mut source, config).unwrap());
+        let libraries = vec![out_dir.path().join("stdin.munlib")];
+
+        let mut summary = run_tests(&libraries, None).unwrap();
+        summary.failed.sort();
+        assert!(!summary.is_success());
+        assert_eq!(summary.passed, 2);
+        assert_eq!(summary.failed, vec!["test_fail", "test_wrong_type"]);
+
+        let summary = run_tests(&libraries, Some("pass")).unwrap();
+        assert!(summary.is_success());
+        assert_eq!(summary.passed, 1);
+    }
+}
@@ -0,0 +1,55 @@
+//! The versions of Mun and its backend, and the host target, as printed by `mun version`.
+
+use mun_compiler::Target;
+use serde::Serialize;
+
+/// The version details that are needed to reproduce a build.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct VersionInfo {
+    pub version: String,
+    pub llvm_version: String,
+    /// The triple of the host target, or `None` if Mun cannot compile for the host
+    pub host_target: Option<String>,
+}
+
+impl VersionInfo {
+    /// Collects the version details of this build of Mun.
+    pub fn current() -> Self {
+        VersionInfo {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            llvm_version: mun_compiler::LLVM_VERSION.to_owned(),
+            host_target: Target::host_target().ok().map(|target| target.llvm_target),
+        }
+    }
+
+    /// Renders the details that follow the `mun <version>` line printed by `--version`.
+    pub fn details_to_text(&self) -> String {
+        format!(
+            "llvm: {}\nhost: {}\n",
+            self.llvm_version,
+            self.host_target.as_deref().unwrap_or("unsupported")
+        )
+    }
+
+    /// Renders all details, with a line per detail.
+    pub fn to_text(&self) -> String {
+        format!("mun {}\n{}", self.version, self.details_to_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VersionInfo;
+
+    #[test]
+    fn test_version_info() {
+        let info = VersionInfo::current();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.llvm_version.is_empty());
+        assert!(!info.host_target.as_deref().unwrap_or_default().is_empty());
+
+        let text = info.to_text();
+        assert!(text.starts_with(&format!("mun {}\n", env!("CARGO_PKG_VERSION"))));
+        assert!(text.contains(&format!("\nhost: {}\n", info.host_target.unwrap())));
+    }
+}
@@ -0,0 +1,193 @@
+//! Watching a library for reloads without invoking any of its functions.
+
+use mun_runtime::{ReloadEvent, Runtime, RuntimeBuilder};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The interval at which the runtime is polled for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A flag that is raised to ask a long-running loop to stop at the end of its current iteration.
+#[derive(Clone, Debug, Default)]
+pub struct ShutdownFlag(Arc<AtomicBool>);
+
+impl ShutdownFlag {
+    /// Constructs a flag that is raised when the process is interrupted with Ctrl-C. Only one
+    /// handler can be installed per process; if that fails, e.g. because the compiler daemon
+    /// installed its own, the flag can only be raised by `request`.
+    pub fn on_ctrl_c() -> Self {
+        let flag = ShutdownFlag::default();
+        let handler_flag = flag.clone();
+        if let Err(e) = ctrlc::set_handler(move || handler_flag.request()) {
+            log::warn!("could not install a Ctrl-C handler: {}", e);
+        }
+        flag
+    }
+
+    /// Raises the flag.
+    pub fn request(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if the flag was raised.
+    pub fn is_requested(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Loads the library at `library_path` into a watching runtime and prints a line every time it is
+/// reloaded, followed by the exported functions that were added, removed or changed. Returns when
+/// `shutdown` is raised, after unloading the library.
+pub fn watch_library(
+    library_path: &Path,
+    delay: Option<Duration>,
+    shutdown: &ShutdownFlag,
+) -> Result<(), anyhow::Error> {
+    let builder = RuntimeBuilder::new(library_path)
+        .set_watch(true)
+        .set_reload_observer(|event| match event {
+            ReloadEvent::Reloaded { path } => {
+                println!("[{}] reloaded {}", timestamp(), path.display())
+            }
+            ReloadEvent::ReloadFailed { path, error } => println!(
+                "[{}] failed to reload {}: {}",
+                timestamp(),
+                path.display(),
+                error
+            ),
+            _ => {}
+        });
+    let builder = match delay {
+        Some(delay) => builder.set_delay(delay),
+        None => builder,
+    };
+    let runtime = builder.spawn()?;
+
+    let mut functions = exported_functions(&runtime.borrow());
+    println!(
+        "[{}] watching {} ({} exported functions)",
+        timestamp(),
+        library_path.display(),
+        functions.len()
+    );
+
+    // The handler only raises the flag, so the runtime is never borrowed outside of this loop
+    while !shutdown.is_requested() {
+        if runtime.borrow_mut().update() {
+            let new_functions = exported_functions(&runtime.borrow());
+            for change in diff_functions(&functions, &new_functions) {
+                println!("    {}", change);
+            }
+            functions = new_functions;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    println!("[{}] shutting down", timestamp());
+    drop(runtime);
+    Ok(())
+}
+
+/// Returns the prototype of every function exported by the loaded assemblies, keyed by name.
+fn exported_functions(runtime: &Runtime) -> BTreeMap<String, String> {
+    runtime
+        .assemblies()
+        .flat_map(|assembly| assembly.info().symbols.functions().iter())
+        .map(|function| {
+            (
+                function.prototype.name().to_owned(),
+                function.prototype.to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Describes the functions that were added, removed or changed between `old` and `new`.
+fn diff_functions(old: &BTreeMap<String, String>, new: &BTreeMap<String, String>) -> Vec<String> {
+    let mut changes = Vec::new();
+    for (name, prototype) in old {
+        match new.get(name) {
+            None => changes.push(format!("- {}", prototype)),
+            Some(new_prototype) if new_prototype != prototype => {
+                changes.push(format!("~ {} -> {}", prototype, new_prototype))
+            }
+            _ => {}
+        }
+    }
+    for (name, prototype) in new {
+        if !old.contains_key(name) {
+            changes.push(format!("+ {}", prototype));
+        }
+    }
+    changes
+}
+
+/// Returns the current time as seconds since the Unix epoch, with millisecond precision.
+fn timestamp() -> String {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format!("{}.{:03}", timestamp.as_secs(), timestamp.subsec_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_functions, watch_library, ShutdownFlag};
+    use crate::multi_file::compile_reader;
+    use mun_compiler::Config;
+    use std::collections::BTreeMap;
+
+    fn functions(prototypes: &[(&str, &str)]) -> BTreeMap<String, String> {
+        prototypes
+            .iter()
+            .map(|(name, prototype)| ((*name).to_owned(), (*prototype).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_functions() {
+        let old = functions(&[
+            ("main", "fn main()"),
+            ("tick", "fn tick():core::f32"),
+            ("old", "fn old()"),
+        ]);
+        let new = functions(&[
+            ("main", "fn main()"),
+            ("tick", "fn tick():core::f64"),
+            ("new", "fn new(core::i32)"),
+        ]);
+        assert_eq!(
+            diff_functions(&old, &new),
+            vec![
+                "- fn old()",
+                "~ fn tick():core::f32 -> fn tick():core::f64",
+                "+ fn new(core::i32)"
+            ]
+        );
+        assert!(diff_functions(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let flag = ShutdownFlag::default();
+        let other = flag.clone();
+        assert!(!flag.is_requested());
+        other.request();
+        assert!(flag.is_requested());
+
+        let out_dir = tempdir::TempDir::new("test_shutdown").unwrap();
+        let config = Config {
+            out_dir: Some(out_dir.path().to_path_buf()),
+            ..Config::default()
+        };
+        let mut source = &b"pub fn main() -> i32 { 5 }"[..];
+        assert!(compile_reader(&mut source, config).unwrap());
+
+        // A raised flag stops the loop before it polls for changes
+        let library_path = out_dir.path().join("stdin.munlib");
+        watch_library(&library_path, None, &flag).unwrap();
+    }
+}
@@ -1,12 +1,25 @@
-use mun::run_with_args;
+use mun::{run_with_args, run_with_context, Context};
 use mun_runtime::{invoke_fn, RuntimeBuilder};
 use std::ffi::OsString;
+use std::path::PathBuf;
 use tempdir::TempDir;
 
 const TEST_VAL: i32 = 567;
 
 /// Creates a simple test project in a temporary directory and returns the directory.
 fn create_project() -> tempdir::TempDir {
+    create_project_with_source(&format!(
+        r#"
+pub fn main() -> i32 {{
+    {}
+}}"#,
+        TEST_VAL
+    ))
+}
+
+/// Creates a test project with the specified `main.mun` contents in a temporary directory and
+/// returns the directory.
+fn create_project_with_source(source: &str) -> tempdir::TempDir {
     let project_dir = TempDir::new("mun_project_example").unwrap();
     let project_path = project_dir.path();
 
@@ -23,17 +36,7 @@ version="0.1.0"
 
     std::fs::create_dir_all(project_path.join("src")).unwrap();
 
-    std::fs::write(
-        project_path.join("src/main.mun"),
-        format!(
-            r#"
-pub fn main() -> i32 {{
-    {}
-}}"#,
-            TEST_VAL
-        ),
-    )
-    .unwrap();
+    std::fs::write(project_path.join("src/main.mun"), source).unwrap();
 
     project_dir
 }
@@ -61,3 +64,2276 @@ fn build_and_run() {
     let result: i32 = invoke_fn!(runtime, "main").unwrap();
     assert_eq!(result, TEST_VAL);
 }
+
+/// Builds the project in the specified directory and returns the path of the resulting library.
+fn build_project(project: &TempDir) -> PathBuf {
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+
+    let library_path = project.path().join("target/main.munlib");
+    assert!(library_path.is_file());
+    library_path
+}
+
+#[test]
+fn start_without_watch() {
+    let project = create_project_with_source(
+        r#"
+pub fn main() -> i64 {
+    5
+}"#,
+    );
+    let library_path = build_project(&project);
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        library_path.into(),
+        "--no-watch".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+}
+
+#[test]
+fn start_multiple_entries() {
+    let project = create_project_with_source(
+        r#"
+pub fn setup() -> i64 {
+    1
+}
+
+pub fn main() -> i64 {
+    5
+}"#,
+    );
+    let library_path = build_project(&project);
+    let start = |entries: &[&str]| {
+        let mut args: Vec<OsString> = vec![
+            "mun".into(),
+            "start".into(),
+            library_path.clone().into(),
+            "--no-watch".into(),
+        ];
+        for entry in entries {
+            args.push("--entry".into());
+            args.push(entry.into());
+        }
+        run_with_args(args).unwrap()
+    };
+
+    assert_eq!(start(&["setup", "main"]), mun::ExitStatus::Success);
+    assert_eq!(start(&["missing", "main"]), mun::ExitStatus::Error);
+    assert_eq!(start(&["setup", "missing"]), mun::ExitStatus::Error);
+}
+
+#[test]
+fn start_seed() {
+    let project = create_project();
+    let library_path = build_project(&project);
+    let start = |seed: &str| {
+        run_with_args(vec![
+            OsString::from("mun"),
+            "start".into(),
+            library_path.clone().into(),
+            "--no-watch".into(),
+            "--seed".into(),
+            seed.into(),
+        ])
+    };
+
+    // The runtime cannot be seeded, which is reported instead of ignoring the seed
+    let error = start("42").unwrap_err().to_string();
+    assert!(
+        error.contains("the runtime does not provide a random number generator"),
+        "{}",
+        error
+    );
+    let error = start("forty-two").unwrap_err().to_string();
+    assert!(
+        error.starts_with("invalid value 'forty-two' for --seed"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn start_dump_layouts() {
+    let project = create_project_with_source(
+        r#"
+pub struct(value) Vec2 {
+    x: f32,
+    y: f32,
+}
+
+pub fn main() -> Vec2 {
+    Vec2 { x: 1.0, y: 2.0 }
+}"#,
+    );
+    let library_path = build_project(&project);
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        library_path.into(),
+        "--dump-layouts=json".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+}
+
+#[test]
+fn build_deny_warnings() {
+    // The camel case name of `addOne` results in a warning
+    let project = create_project_with_source(
+        r#"
+pub fn main() -> i32 {
+    addOne(1)
+}
+
+fn addOne(a: i32) -> i32 {
+    a + 1
+}"#,
+    );
+    let build = |flags: &[&str]| {
+        let mut args: Vec<OsString> = vec![
+            "mun".into(),
+            "build".into(),
+            "--manifest-path".into(),
+            project.path().join("mun.toml").into(),
+        ];
+        args.extend(flags.iter().map(OsString::from));
+        run_with_args(args).unwrap()
+    };
+
+    assert_eq!(build(&[]), mun::ExitStatus::Success);
+    assert_eq!(build(&["--allow-warnings"]), mun::ExitStatus::Success);
+    assert_eq!(build(&["--deny-warnings"]), mun::ExitStatus::Error);
+    assert_eq!(
+        build(&["--deny-warnings", "--message-format", "json"]),
+        mun::ExitStatus::Error
+    );
+    assert_eq!(
+        build(&["--deny-warnings", "--allow-warnings"]),
+        mun::ExitStatus::Success
+    );
+}
+
+#[test]
+fn doc_json() {
+    let project = create_project_with_source(
+        r#"
+/// Returns the answer to everything.
+pub fn main() -> i32 {
+    42
+}
+
+fn undocumented() {}"#,
+    );
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "doc".into(),
+        "--format".into(),
+        "json".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+
+    let doc: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(project.path().join("target/doc/doc.json")).unwrap(),
+    )
+    .unwrap();
+    let functions = &doc["modules"][0]["functions"];
+    assert_eq!(functions[0]["name"], "main");
+    assert_eq!(functions[0]["signature"], "fn main() -> i32");
+    assert_eq!(functions[0]["doc"], "Returns the answer to everything.");
+    assert_eq!(functions[1]["name"], "undocumented");
+    assert!(functions[1]["doc"].is_null());
+}
+
+#[test]
+fn fmt_check() {
+    let project = create_project_with_source("pub fn main()->i32{\n// The answer\n42\n}");
+    let source_path = project.path().join("src/main.mun");
+    let fmt = |flags: &[&str]| {
+        let mut args: Vec<OsString> = vec!["mun".into(), "fmt".into()];
+        args.extend(flags.iter().map(OsString::from));
+        run_with_args(args).unwrap()
+    };
+    let manifest_path = project.path().join("mun.toml");
+    let manifest_path = manifest_path.to_str().unwrap();
+
+    // Checking does not modify the file
+    assert_eq!(
+        fmt(&["--check", "--manifest-path", manifest_path]),
+        mun::ExitStatus::Error
+    );
+    assert_eq!(
+        std::fs::read_to_string(&source_path).unwrap(),
+        "pub fn main()->i32{\n// The answer\n42\n}"
+    );
+
+    assert_eq!(
+        fmt(&["--manifest-path", manifest_path]),
+        mun::ExitStatus::Success
+    );
+    assert_eq!(
+        std::fs::read_to_string(&source_path).unwrap(),
+        "pub fn main() -> i32 {\n    // The answer\n    42\n}\n"
+    );
+    assert_eq!(
+        fmt(&["--check", "--manifest-path", manifest_path]),
+        mun::ExitStatus::Success
+    );
+
+    // Explicit files are formatted instead of the package
+    let other_path = project.path().join("other.mun");
+    std::fs::write(&other_path, "fn other( ) { }").unwrap();
+    let other_path = other_path.to_str().unwrap();
+    assert_eq!(fmt(&["--check", other_path]), mun::ExitStatus::Error);
+    assert_eq!(fmt(&[other_path]), mun::ExitStatus::Success);
+    assert_eq!(fmt(&["--check", other_path]), mun::ExitStatus::Success);
+    assert_eq!(
+        std::fs::read_to_string(other_path).unwrap(),
+        "fn other() {}\n"
+    );
+}
+
+#[test]
+fn fmt_check_munignore() {
+    let project = create_project_with_source("pub fn main() -> i32 {\n    42\n}\n");
+    std::fs::create_dir_all(project.path().join("src/generated")).unwrap();
+    std::fs::write(
+        project.path().join("src/generated/bindings.mun"),
+        "pub fn bindings()->i32{1}",
+    )
+    .unwrap();
+    let manifest_path = project.path().join("mun.toml");
+    let fmt_check = || {
+        run_with_args(vec![
+            OsString::from("mun"),
+            "fmt".into(),
+            "--check".into(),
+            "--manifest-path".into(),
+            manifest_path.clone().into(),
+        ])
+        .unwrap()
+    };
+
+    // The unformatted file is processed until it is ignored
+    assert_eq!(fmt_check(), mun::ExitStatus::Error);
+    std::fs::write(project.path().join(".munignore"), "src/generated/\n").unwrap();
+    assert_eq!(fmt_check(), mun::ExitStatus::Success);
+
+    // Files that are not ignored are still checked
+    std::fs::write(
+        project.path().join("src/main.mun"),
+        "pub fn main()->i32{42}",
+    )
+    .unwrap();
+    assert_eq!(fmt_check(), mun::ExitStatus::Error);
+}
+
+#[test]
+fn build_multiple_files() {
+    let dir = TempDir::new("mun_multiple_files").unwrap();
+    let a_path = dir.path().join("a.mun");
+    let b_path = dir.path().join("b.mun");
+    std::fs::write(&a_path, "fn value() -> i64 { 5 }").unwrap();
+    std::fs::write(&b_path, "pub fn main() -> i64 { value() }").unwrap();
+
+    let library_path = dir.path().join("out/combined.munlib");
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        a_path.into(),
+        b_path.into(),
+        "-o".into(),
+        library_path.clone().into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    assert!(library_path.is_file());
+}
+
+#[test]
+fn metadata() {
+    let project = create_project();
+    let manifest_path = project.path().join("mun.toml");
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "metadata".into(),
+        "--manifest-path".into(),
+        manifest_path.clone().into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+
+    std::fs::write(&manifest_path, "[package]\nname=").unwrap();
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "metadata".into(),
+        "--manifest-path".into(),
+        manifest_path.into(),
+    ];
+    assert!(run_with_args(args).is_err());
+}
+
+#[test]
+fn fix_missing_paren() {
+    let project = create_project_with_source("pub fn main() -> i64 { (5 }");
+    let source_path = project.path().join("src/main.mun");
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "fix".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--allow-dirty".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    assert_eq!(
+        std::fs::read_to_string(&source_path).unwrap(),
+        "pub fn main() -> i64 { (5) }"
+    );
+
+    build_project(&project);
+}
+
+#[test]
+fn start_require_fresh() {
+    let project = create_project_with_source("pub fn main() -> i64 { 5 }");
+    let library_path = build_project(&project);
+
+    let start_args = || -> Vec<OsString> {
+        vec![
+            "mun".into(),
+            "start".into(),
+            library_path.clone().into(),
+            "--no-watch".into(),
+            "--require-fresh".into(),
+            "--manifest-path".into(),
+            project.path().join("mun.toml").into(),
+        ]
+    };
+    assert_eq!(
+        run_with_args(start_args()).unwrap(),
+        mun::ExitStatus::Success
+    );
+
+    // Make sure the modification time of the source is observably later than that of the library
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    std::fs::write(
+        project.path().join("src/main.mun"),
+        "pub fn main() -> i64 { 6 }",
+    )
+    .unwrap();
+    assert!(run_with_args(start_args()).is_err());
+}
+
+#[test]
+fn bug_report() {
+    let project = create_project_with_source("pub fn main() -> i64 { foo }");
+    let output_path = project.path().join("report.zip");
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "bug-report".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "-o".into(),
+        output_path.clone().into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    assert!(output_path.is_file());
+}
+
+#[test]
+fn test_subcommand() {
+    let project = create_project_with_source(
+        r#"
+pub fn test_pass() -> bool {
+    true
+}
+
+pub fn test_fail() -> bool {
+    false
+}
+
+pub fn test_wrong_type() -> i32 {
+    0
+}"#,
+    );
+
+    let test_args = |filter: Option<&str>| -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec![
+            "mun".into(),
+            "test".into(),
+            "--manifest-path".into(),
+            project.path().join("mun.toml").into(),
+        ];
+        if let Some(filter) = filter {
+            args.push("--filter".into());
+            args.push(filter.into());
+        }
+        args
+    };
+    assert_eq!(
+        run_with_args(test_args(None)).unwrap(),
+        mun::ExitStatus::Error
+    );
+    assert_eq!(
+        run_with_args(test_args(Some("pass"))).unwrap(),
+        mun::ExitStatus::Success
+    );
+    // A test that cannot be invoked fails instead of aborting the run
+    assert_eq!(
+        run_with_args(test_args(Some("wrong"))).unwrap(),
+        mun::ExitStatus::Error
+    );
+}
+
+#[test]
+fn start_entry_match() {
+    let project = create_project_with_source(
+        r#"
+pub fn bench_a() -> i64 { 1 }
+pub fn bench_b() -> bool { true }
+pub fn other() -> i64 { 3 }"#,
+    );
+    let library_path = build_project(&project);
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        library_path.into(),
+        "--no-watch".into(),
+        "--entry-match".into(),
+        "^bench_".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+}
+
+#[test]
+fn start_output_table() {
+    let project = create_project_with_source(
+        r#"
+struct Stats { count: i64, mean: f64 }
+
+pub fn stats() -> Stats { Stats { count: 3, mean: 1.5 } }"#,
+    );
+    let library_path = build_project(&project);
+
+    for format in &["text", "table", "json"] {
+        let args: Vec<OsString> = vec![
+            "mun".into(),
+            "start".into(),
+            library_path.clone().into(),
+            "--no-watch".into(),
+            "--entry".into(),
+            "stats".into(),
+            "--output".into(),
+            (*format).into(),
+        ];
+        assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    }
+}
+
+#[test]
+fn build_size_report() {
+    let project = create_project_with_source(
+        r#"
+pub fn fibonacci(n: i64) -> i64 {
+    if n <= 1 { n } else { fibonacci(n - 1) + fibonacci(n - 2) }
+}
+pub fn main() -> i64 { fibonacci(5) }"#,
+    );
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--emit=size-report".into(),
+        "--size-report-format".into(),
+        "json".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+
+    let report = std::fs::read_to_string(project.path().join("target/size-report.json")).unwrap();
+    let report: Vec<serde_json::Value> = serde_json::from_str(&report).unwrap();
+    let names: Vec<&str> = report
+        .iter()
+        .map(|function| function["name"].as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"fibonacci"));
+    assert!(names.contains(&"main"));
+}
+
+#[test]
+fn build_raw_compiler_flags() {
+    let project = create_project();
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--".into(),
+        "-C".into(),
+        "opt-level=0".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--".into(),
+        "-C".into(),
+        "no-such-option".into(),
+    ];
+    assert!(run_with_args(args).is_err());
+}
+
+#[test]
+fn build_multiple_manifests() {
+    let valid = create_project();
+    let invalid = create_project_with_source("pub fn main() -> i32 { true }");
+    let build = |manifest_paths: &[PathBuf]| {
+        let mut args: Vec<OsString> = vec!["mun".into(), "build".into()];
+        for manifest_path in manifest_paths {
+            args.push("--manifest-path".into());
+            args.push(manifest_path.into());
+        }
+        run_with_args(args).unwrap()
+    };
+
+    assert_eq!(
+        build(&[valid.path().join("mun.toml"), valid.path().into()]),
+        mun::ExitStatus::Success
+    );
+    assert!(valid.path().join("target/main.munlib").is_file());
+
+    // The valid package is built even though the other one fails
+    std::fs::remove_file(valid.path().join("target/main.munlib")).unwrap();
+    assert_eq!(
+        build(&[
+            invalid.path().join("mun.toml"),
+            valid.path().join("mun.toml")
+        ]),
+        mun::ExitStatus::Error
+    );
+    assert!(valid.path().join("target/main.munlib").is_file());
+
+    // A glob pattern
+    let dir = TempDir::new("build_multiple_manifests").unwrap();
+    for name in &["a", "b"] {
+        let package_dir = dir.path().join(name);
+        std::fs::create_dir_all(package_dir.join("src")).unwrap();
+        std::fs::copy(valid.path().join("mun.toml"), package_dir.join("mun.toml")).unwrap();
+        std::fs::copy(
+            valid.path().join("src/main.mun"),
+            package_dir.join("src/main.mun"),
+        )
+        .unwrap();
+    }
+    assert_eq!(
+        build(&[dir.path().join("*").join("mun.toml")]),
+        mun::ExitStatus::Success
+    );
+    assert!(dir.path().join("a/target/main.munlib").is_file());
+    assert!(dir.path().join("b/target/main.munlib").is_file());
+}
+
+#[test]
+fn build_input_encoding() {
+    let project = create_project();
+    let main_path = project.path().join("src/main.mun");
+    let source = std::fs::read(&main_path).unwrap();
+    let build = |encoding: Option<&str>| {
+        let mut args: Vec<OsString> = vec![
+            "mun".into(),
+            "build".into(),
+            "--manifest-path".into(),
+            project.path().join("mun.toml").into(),
+        ];
+        if let Some(encoding) = encoding {
+            args.push("--input-encoding".into());
+            args.push(encoding.into());
+        }
+        run_with_args(args)
+    };
+
+    // A leading byte order mark is ignored
+    std::fs::write(&main_path, [&b"\xef\xbb\xbf"[..], &source].concat()).unwrap();
+    assert_eq!(build(None).unwrap(), mun::ExitStatus::Success);
+
+    // A Latin-1 `é` in a comment is only valid in the specified encoding
+    std::fs::write(&main_path, [&b"// caf\xe9\n"[..], &source].concat()).unwrap();
+    let error = build(None).unwrap_err().to_string();
+    assert!(error.contains("main.mun"), "{}", error);
+    assert!(error.contains("is not valid UTF-8"), "{}", error);
+    assert_eq!(build(Some("latin-1")).unwrap(), mun::ExitStatus::Success);
+}
+
+#[test]
+fn build_keep_going() {
+    let broken = create_project();
+    std::fs::write(
+        broken.path().join("mun.toml"),
+        "[package]\nname=\"broken\"\n",
+    )
+    .unwrap();
+    let valid = create_project();
+    let build = |keep_going: bool| {
+        let mut args: Vec<OsString> = vec!["mun".into(), "build".into()];
+        for project in &[&broken, &valid] {
+            args.push("--manifest-path".into());
+            args.push(project.path().join("mun.toml").into());
+        }
+        if keep_going {
+            args.push("--keep-going".into());
+        }
+        run_with_args(args)
+    };
+
+    // Without the flag, the manifest that cannot be loaded stops the build
+    assert!(build(false).is_err());
+    assert!(!valid.path().join("target/main.munlib").exists());
+
+    assert_eq!(build(true).unwrap(), mun::ExitStatus::Error);
+    assert!(valid.path().join("target/main.munlib").is_file());
+}
+
+#[test]
+fn build_strict_manifest() {
+    let project = create_project();
+    let build = || {
+        let args: Vec<OsString> = vec![
+            "mun".into(),
+            "build".into(),
+            "--manifest-path".into(),
+            project.path().join("mun.toml").into(),
+            "--strict-manifest".into(),
+        ];
+        run_with_args(args).unwrap()
+    };
+    assert_eq!(build(), mun::ExitStatus::Success);
+
+    std::fs::write(
+        project.path().join("mun.toml"),
+        "[package]\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    assert_eq!(build(), mun::ExitStatus::Error);
+}
+
+#[test]
+fn build_print_ast() {
+    let project = create_project();
+    let build = |flags: &[&str]| {
+        let mut args: Vec<OsString> = vec![
+            "mun".into(),
+            "build".into(),
+            "--manifest-path".into(),
+            project.path().join("mun.toml").into(),
+            "--print-ast".into(),
+        ];
+        args.extend(flags.iter().map(OsString::from));
+        run_with_args(args)
+    };
+
+    // Only parsing does not write any artifacts
+    assert_eq!(
+        build(&["--stop-after", "parse"]).unwrap(),
+        mun::ExitStatus::Success
+    );
+    assert!(!project.path().join("target/main.munlib").exists());
+
+    assert_eq!(build(&[]).unwrap(), mun::ExitStatus::Success);
+    assert!(project.path().join("target/main.munlib").is_file());
+}
+
+#[test]
+fn build_stop_after() {
+    let project = create_project_with_source("pub fn main() -> i32 { false }");
+    let build = |stage: &str| {
+        run_with_args(vec![
+            OsString::from("mun"),
+            "build".into(),
+            "--manifest-path".into(),
+            project.path().join("mun.toml").into(),
+            "--stop-after".into(),
+            stage.into(),
+        ])
+    };
+
+    // The file parses, but does not typecheck
+    assert_eq!(build("parse").unwrap(), mun::ExitStatus::Success);
+    assert!(!project.path().join("target/main.munlib").exists());
+    assert_eq!(build("typecheck").unwrap(), mun::ExitStatus::Error);
+    assert_eq!(build("codegen").unwrap(), mun::ExitStatus::Error);
+    assert_eq!(build("link").unwrap(), mun::ExitStatus::Error);
+}
+
+#[test]
+fn start_profile() {
+    let project = create_project_with_source(
+        r#"
+pub fn helper(n: i32) -> i32 { n + 1 }
+pub fn main() -> i32 {
+    let mut n = 0;
+    while n < 10 {
+        n = helper(n);
+    }
+    n
+}
+"#,
+    );
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--instrument-calls".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    let library_path = project.path().join("target/main.munlib");
+    let profile_path = project.path().join("profile.json");
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        library_path.into(),
+        "--entry".into(),
+        "main".into(),
+        "--profile".into(),
+        profile_path.clone().into(),
+        "--no-watch".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&profile_path).unwrap()).unwrap();
+    let calls = |name: &str| {
+        report["functions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|function| function["name"] == name)
+            .map(|function| function["calls"].clone())
+    };
+    assert_eq!(calls("helper"), Some(10.into()));
+    assert_eq!(calls("main"), Some(1.into()));
+}
+
+#[test]
+fn start_mem_limit() {
+    let project = create_project_with_source(
+        r#"
+pub struct Foo { a: i64, b: i64 }
+pub fn runaway() {
+    loop {
+        let foo = Foo { a: 1, b: 2 };
+    }
+}
+pub fn main() -> Foo {
+    Foo { a: 1, b: 2 }
+}
+"#,
+    );
+    let library_path = build_project(&project);
+    let start = |entry: &str, limit: &str| {
+        let args: Vec<OsString> = vec![
+            "mun".into(),
+            "start".into(),
+            library_path.clone().into(),
+            "--entry".into(),
+            entry.into(),
+            "--mem-limit".into(),
+            limit.into(),
+            "--no-watch".into(),
+        ];
+        run_with_args(args).unwrap()
+    };
+
+    assert_eq!(start("main", "16b"), mun::ExitStatus::Success);
+    assert_eq!(start("main", "8b"), mun::ExitStatus::Error);
+    // The allocation that exceeds the limit aborts the entry point
+    assert_eq!(start("runaway", "1kb"), mun::ExitStatus::Error);
+}
+
+#[test]
+fn package_archive() {
+    let project = create_project();
+    let archive_path = project.path().join("test.munpkg");
+    let package = |flags: &[&str]| {
+        let mut args: Vec<OsString> = vec![
+            "mun".into(),
+            "package".into(),
+            "--manifest-path".into(),
+            project.path().join("mun.toml").into(),
+            "--out".into(),
+            archive_path.clone().into(),
+        ];
+        args.extend(flags.iter().map(OsString::from));
+        run_with_args(args)
+    };
+
+    // Nothing has been built into the directory yet
+    let prebuilt_dir = project.path().join("prebuilt");
+    assert!(package(&["--prebuilt", prebuilt_dir.to_str().unwrap()]).is_err());
+
+    assert_eq!(package(&[]).unwrap(), mun::ExitStatus::Success);
+    assert!(project.path().join("target/main.munlib").is_file());
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&archive_path).unwrap()).unwrap();
+    assert!(archive.by_name("main.munlib").unwrap().size() > 0);
+    let read = |archive: &mut zip::ZipArchive<std::fs::File>, name: &str| {
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name(name).unwrap(), &mut contents).unwrap();
+        serde_json::from_str::<serde_json::Value>(&contents).unwrap()
+    };
+    let metadata = read(&mut archive, "main.metadata.json");
+    assert!(metadata["functions"]["main"].is_object(), "{}", metadata);
+    let contents = read(&mut archive, "munpkg.json");
+    assert_eq!(contents["name"], "test");
+    assert_eq!(contents["version"], "0.1.0");
+    assert_eq!(contents["abi_version"], mun_abi::ABI_VERSION);
+    assert_eq!(
+        contents["libraries"],
+        serde_json::json!([{ "path": "main.munlib", "metadata": "main.metadata.json" }])
+    );
+}
+
+#[test]
+fn build_out_name() {
+    let project = create_project();
+    let build = |out_name: &str| {
+        run_with_args(vec![
+            OsString::from("mun"),
+            "build".into(),
+            "--manifest-path".into(),
+            project.path().join("mun.toml").into(),
+            "--out-name".into(),
+            out_name.into(),
+        ])
+    };
+
+    assert_eq!(build("custom").unwrap(), mun::ExitStatus::Success);
+    assert!(project.path().join("target/custom.munlib").is_file());
+    assert!(!project.path().join("target/main.munlib").exists());
+
+    assert!(build("../custom").is_err());
+}
+
+#[test]
+fn build_with_context() {
+    let project = create_project();
+    let build = |working_dir: PathBuf, args: &[&str]| {
+        let args = ["mun", "build"].iter().chain(args.iter());
+        run_with_context(args.copied(), &Context { working_dir })
+    };
+    let library_path = project
+        .path()
+        .canonicalize()
+        .unwrap()
+        .join("target/main.munlib");
+
+    // The manifest is located from the injected directory instead of the current directory
+    match build(project.path().join("src"), &[]).unwrap() {
+        mun::Outcome::Build { status, artifacts } => {
+            assert_eq!(status, mun::ExitStatus::Success);
+            assert_eq!(artifacts.first(), Some(&library_path));
+        }
+        outcome => panic!("unexpected outcome: {:?}", outcome),
+    }
+
+    // Relative paths are resolved against the injected directory
+    let project_name = project.path().file_name().unwrap().to_str().unwrap();
+    let working_dir = project.path().parent().unwrap().to_path_buf();
+    assert_eq!(
+        build(
+            working_dir,
+            &[
+                "--manifest-path",
+                project_name,
+                "--out-dir",
+                &format!("{}/out", project_name)
+            ]
+        )
+        .unwrap(),
+        mun::ExitStatus::Success
+    );
+    assert!(project.path().join("out/main.munlib").is_file());
+}
+
+#[test]
+fn subcommands_with_context() {
+    let project = create_project();
+    let run = |working_dir: PathBuf, args: &[&str]| {
+        let args = ["mun"].iter().chain(args.iter());
+        run_with_context(args.copied(), &Context { working_dir })
+            .unwrap()
+            .exit_status()
+    };
+
+    // The manifest is located from the injected directory instead of the current directory
+    let src_dir = project.path().join("src");
+    assert_eq!(run(src_dir.clone(), &["check"]), mun::ExitStatus::Success);
+    assert_eq!(run(src_dir.clone(), &["run"]), mun::ExitStatus::Success);
+    assert_eq!(
+        run(src_dir, &["start", "--no-watch"]),
+        mun::ExitStatus::Success
+    );
+
+    // A relative manifest path is resolved against the injected directory
+    let project_name = project.path().file_name().unwrap().to_str().unwrap();
+    let working_dir = project.path().parent().unwrap().to_path_buf();
+    assert_eq!(
+        run(working_dir, &["check", "--manifest-path", project_name]),
+        mun::ExitStatus::Success
+    );
+}
+
+#[test]
+fn language_server_check() {
+    let check = |project: &tempdir::TempDir| {
+        run_with_args(vec![
+            OsString::from("mun"),
+            "language-server".into(),
+            "--check".into(),
+            project.path().join("mun.toml").into(),
+        ])
+        .unwrap()
+    };
+
+    let project = create_project_with_source("pub fn main() -> i32 { false }");
+    assert_eq!(check(&project), mun::ExitStatus::Error);
+    let project = create_project();
+    assert_eq!(check(&project), mun::ExitStatus::Success);
+}
+
+#[test]
+fn build_emit_metadata() {
+    let project = create_project_with_source(
+        "pub struct Vec2 { x: f32, y: f32 }\npub fn length(v: Vec2) -> f32 { v.x }",
+    );
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--emit-metadata".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+
+    let metadata: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(project.path().join("target/main.metadata.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(metadata["abi_version"], mun_abi::ABI_VERSION);
+    assert_eq!(
+        metadata["functions"]["length"],
+        serde_json::json!({ "arg_types": ["Vec2"], "return_type": "core::f32" })
+    );
+    assert_eq!(
+        metadata["structs"]["Vec2"]["fields"],
+        serde_json::json!([["x", "core::f32"], ["y", "core::f32"]])
+    );
+}
+
+#[test]
+fn build_wasm_target() {
+    let project = create_project();
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--target".into(),
+        "wasm32-unknown-unknown".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    let wasm_path = project.path().join("target/main.wasm");
+    assert!(wasm_path.is_file());
+    assert!(!project.path().join("target/main.munlib").exists());
+
+    // WebAssembly modules cannot be run
+    let error = run_with_args(vec![
+        OsString::from("mun"),
+        "start".into(),
+        wasm_path.into(),
+        "--no-watch".into(),
+    ])
+    .unwrap_err();
+    assert!(error.to_string().contains("WebAssembly"), "{}", error);
+    let error = run_with_args(vec![
+        OsString::from("mun"),
+        "run".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--target".into(),
+        "wasm32-unknown-unknown".into(),
+    ])
+    .unwrap_err();
+    assert!(error.to_string().contains("WebAssembly"), "{}", error);
+}
+
+#[test]
+fn explain() {
+    let explain = |code: &str| run_with_args(vec!["mun", "--explain", code]);
+    assert_eq!(explain("E002").unwrap(), mun::ExitStatus::Success);
+    assert_eq!(explain("w001").unwrap(), mun::ExitStatus::Success);
+    assert!(explain("E999").is_err());
+}
+
+#[test]
+fn version() {
+    let args: Vec<OsString> = vec!["mun".into(), "version".into()];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    let args: Vec<OsString> = vec!["mun".into(), "version".into(), "--json".into()];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    let args: Vec<OsString> = vec!["mun".into(), "--version".into(), "--verbose".into()];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+}
+
+#[test]
+fn new_package() {
+    let dir = TempDir::new("new_package").unwrap();
+    let package_path = dir.path().join("mygame");
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "new".into(),
+        package_path.clone().into(),
+        "--author".into(),
+        "Jane".into(),
+        "--version".into(),
+        "0.2.0".into(),
+        "--license".into(),
+        "MIT".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+
+    let package = mun_project::Package::from_file(package_path.join("mun.toml")).unwrap();
+    assert_eq!(package.name(), "mygame");
+    assert_eq!(package.version().to_string(), "0.2.0");
+    assert_eq!(package.manifest().metadata().authors, vec!["Jane"]);
+    assert_eq!(
+        package.manifest().metadata().license,
+        Some("MIT".to_owned())
+    );
+
+    let invalid_path = dir.path().join("invalid");
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "new".into(),
+        invalid_path.clone().into(),
+        "--version".into(),
+        "two".into(),
+    ];
+    assert!(run_with_args(args).is_err());
+    assert!(!invalid_path.exists());
+}
+
+#[test]
+fn new_in_workspace() {
+    let dir = TempDir::new("new_in_workspace").unwrap();
+    let new_package = |path: std::path::PathBuf| {
+        let args: Vec<OsString> = vec!["mun".into(), "new".into(), path.into()];
+        assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    };
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "new-workspace".into(),
+        dir.path().join("workspace").into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    let workspace_path = dir.path().join("workspace/mun-workspace.toml");
+    let workspace = std::fs::read_to_string(&workspace_path).unwrap();
+
+    // A package outside of the workspace leaves it untouched
+    new_package(dir.path().join("standalone"));
+    assert_eq!(std::fs::read_to_string(&workspace_path).unwrap(), workspace);
+
+    new_package(dir.path().join("workspace/packages/game"));
+    let workspace = mun_project::Workspace::from_file(&workspace_path).unwrap();
+    assert_eq!(workspace.members(), &["packages/game"]);
+}
+
+#[test]
+fn new_package_kinds() {
+    let dir = TempDir::new("new_package_kinds").unwrap();
+    let new_package = |path: &std::path::Path, flags: &[&str]| {
+        let mut args: Vec<OsString> = vec!["mun".into(), "new".into(), path.into()];
+        args.extend(flags.iter().map(OsString::from));
+        run_with_args(args)
+    };
+
+    let default_path = dir.path().join("default");
+    assert_eq!(
+        new_package(&default_path, &[]).unwrap(),
+        mun::ExitStatus::Success
+    );
+    assert_eq!(
+        std::fs::read_to_string(default_path.join("src/main.mun")).unwrap(),
+        "pub fn main() {\n}\n"
+    );
+
+    let bin_path = dir.path().join("bin");
+    assert_eq!(
+        new_package(&bin_path, &["--bin"]).unwrap(),
+        mun::ExitStatus::Success
+    );
+    assert!(bin_path.join("src/main.mun").is_file());
+    assert!(!bin_path.join("src/lib.mun").exists());
+
+    let lib_path = dir.path().join("lib");
+    assert_eq!(
+        new_package(&lib_path, &["--lib"]).unwrap(),
+        mun::ExitStatus::Success
+    );
+    assert!(!lib_path.join("src/main.mun").exists());
+    let source = std::fs::read_to_string(lib_path.join("src/lib.mun")).unwrap();
+    assert!(source.contains("pub fn add(a: i32, b: i32) -> i32"));
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        lib_path.join("mun.toml").into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    assert!(lib_path.join("target/lib.munlib").is_file());
+
+    let both_path = dir.path().join("both");
+    assert!(new_package(&both_path, &["--bin", "--lib"]).is_err());
+    assert!(!both_path.exists());
+}
+
+#[test]
+fn new_package_template() {
+    let dir = TempDir::new("new_package_template").unwrap();
+    let template_path = dir.path().join("template.mun");
+    let new_package = |path: &std::path::Path| {
+        run_with_args(vec![
+            OsString::from("mun"),
+            "new".into(),
+            path.into(),
+            "--template".into(),
+            template_path.clone().into(),
+        ])
+    };
+
+    let template = "pub fn main() -> i32 {\n    42\n}\n";
+    std::fs::write(&template_path, template).unwrap();
+    let path = dir.path().join("templated");
+    assert_eq!(new_package(&path).unwrap(), mun::ExitStatus::Success);
+    assert_eq!(
+        std::fs::read_to_string(path.join("src/main.mun")).unwrap(),
+        template
+    );
+
+    // An invalid template is rejected before anything is created
+    std::fs::write(&template_path, "pub fn main( {\n").unwrap();
+    let invalid_path = dir.path().join("invalid");
+    let error = new_package(&invalid_path).unwrap_err().to_string();
+    assert!(error.contains("is not valid Mun"), "{}", error);
+    assert!(!invalid_path.exists());
+}
+
+#[test]
+fn new_package_in_existing_directory() {
+    let dir = TempDir::new("new_package_in_existing_directory").unwrap();
+    let new_package = |path: &std::path::Path, force: bool| {
+        let mut args: Vec<OsString> = vec!["mun".into(), "new".into(), path.into()];
+        if force {
+            args.push("--force".into());
+        }
+        run_with_args(args).unwrap()
+    };
+
+    let empty_path = dir.path().join("empty");
+    std::fs::create_dir(&empty_path).unwrap();
+    assert_eq!(new_package(&empty_path, false), mun::ExitStatus::Success);
+    assert!(empty_path.join("mun.toml").is_file());
+
+    let populated_path = dir.path().join("populated");
+    std::fs::create_dir_all(populated_path.join("src")).unwrap();
+    std::fs::write(populated_path.join("src/main.mun"), "pub fn existing() {}").unwrap();
+    assert_eq!(new_package(&populated_path, false), mun::ExitStatus::Error);
+    assert!(!populated_path.join("mun.toml").exists());
+
+    assert_eq!(new_package(&populated_path, true), mun::ExitStatus::Success);
+    assert!(populated_path.join("mun.toml").is_file());
+    assert_eq!(
+        std::fs::read_to_string(populated_path.join("src/main.mun")).unwrap(),
+        "pub fn existing() {}"
+    );
+}
+
+#[test]
+fn init_package() {
+    let dir = TempDir::new("init_package").unwrap();
+    let package_path = dir.path().join("mygame");
+    std::fs::create_dir(&package_path).unwrap();
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "init".into(),
+        package_path.clone().into(),
+        "--author".into(),
+        "Jane".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+
+    let package = mun_project::Package::from_file(package_path.join("mun.toml")).unwrap();
+    assert_eq!(package.name(), "mygame");
+    assert_eq!(package.manifest().metadata().authors, vec!["Jane"]);
+    assert!(package_path.join("src/main.mun").is_file());
+
+    let args: Vec<OsString> = vec!["mun".into(), "init".into(), package_path.into()];
+    let err = run_with_args(args).unwrap_err();
+    assert!(err.to_string().contains("already contains a package"));
+}
+
+#[test]
+fn build_fail_on_empty() {
+    let project = create_project_with_source("// pub fn main() -> i32 { 0 }");
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--fail-on-empty".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Error);
+
+    let project = create_project();
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--fail-on-empty".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+}
+
+#[test]
+fn start_ignore_return() {
+    let project = create_project_with_source(
+        r#"
+struct Counter { value: i64 }
+
+pub fn main() -> Counter { Counter { value: 1 } }"#,
+    );
+    let library_path = build_project(&project);
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        library_path.into(),
+        "--no-watch".into(),
+        "--ignore-return".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+}
+
+#[test]
+fn start_heap_size() {
+    let project = create_project();
+    let library_path = build_project(&project);
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        library_path.clone().into(),
+        "--no-watch".into(),
+        "--heap-size".into(),
+        "32mb".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        library_path.into(),
+        "--no-watch".into(),
+        "--heap-size".into(),
+        "lots".into(),
+    ];
+    assert!(run_with_args(args).is_err());
+}
+
+#[test]
+fn start_timeout() {
+    let project = create_project_with_source("pub fn main() -> i32 { loop {} }");
+    let library_path = build_project(&project);
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        library_path.clone().into(),
+        "--no-watch".into(),
+        "--timeout".into(),
+        "0.5".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Error);
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        library_path.into(),
+        "--no-watch".into(),
+        "--timeout".into(),
+        "0".into(),
+    ];
+    assert!(run_with_args(args).is_err());
+}
+
+#[test]
+fn run_watch() {
+    let project = create_project();
+    let library_path = project.path().join("target/main.munlib");
+    let source_path = project.path().join("src/main.mun");
+    let modifier = std::thread::spawn(move || {
+        // Change the source after the first run, which directly follows the first build
+        while !library_path.is_file() {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        std::fs::write(source_path, "pub fn main() -> i32 { 568 }").unwrap();
+    });
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "run".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--watch".into(),
+        "--max-runs".into(),
+        "2".into(),
+    ];
+    let outcome = run_with_args(args).unwrap();
+    modifier.join().unwrap();
+    assert_eq!(
+        outcome,
+        mun::Outcome::Start {
+            status: mun::ExitStatus::Success,
+            return_value: Some(mun::ReturnValue::Fundamental(mun::ArgValue::I32(568))),
+        }
+    );
+}
+
+#[test]
+fn start_from_manifest() {
+    let project = create_project();
+    let start_args = |library: Option<PathBuf>| {
+        let mut args: Vec<OsString> = vec!["mun".into(), "start".into()];
+        args.extend(library.map(Into::into));
+        args.extend(vec![
+            "--manifest-path".into(),
+            project.path().join("mun.toml").into(),
+            "--no-watch".into(),
+        ]);
+        args
+    };
+    let expected = mun::Outcome::Start {
+        status: mun::ExitStatus::Success,
+        return_value: Some(mun::ReturnValue::Fundamental(mun::ArgValue::I32(TEST_VAL))),
+    };
+
+    // The library has to be built first
+    let error = run_with_args(start_args(None)).unwrap_err().to_string();
+    assert!(error.contains("has not been built yet"), "{}", error);
+
+    // The library derived from the manifest
+    let library_path = build_project(&project);
+    assert_eq!(run_with_args(start_args(None)).unwrap(), expected);
+
+    // An explicit library
+    assert_eq!(
+        run_with_args(start_args(Some(library_path))).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn build_and_start_outcome() {
+    let project = create_project();
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+    ];
+    let library_path = match run_with_args(args).unwrap() {
+        mun::Outcome::Build {
+            status: mun::ExitStatus::Success,
+            artifacts,
+        } => {
+            assert_eq!(artifacts.len(), 1);
+            artifacts[0].clone()
+        }
+        outcome => panic!("unexpected outcome: {:?}", outcome),
+    };
+    assert_eq!(
+        library_path.canonicalize().unwrap(),
+        project
+            .path()
+            .join("target/main.munlib")
+            .canonicalize()
+            .unwrap()
+    );
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        library_path.into(),
+        "--no-watch".into(),
+    ];
+    assert_eq!(
+        run_with_args(args).unwrap(),
+        mun::Outcome::Start {
+            status: mun::ExitStatus::Success,
+            return_value: Some(mun::ReturnValue::Fundamental(mun::ArgValue::I32(567))),
+        }
+    );
+
+    let args: Vec<OsString> = vec!["mun".into(), "ast".into(), "--no-such-flag".into()];
+    assert_eq!(
+        run_with_args(args).unwrap(),
+        mun::Outcome::Other(mun::ExitStatus::Error)
+    );
+}
+
+#[test]
+fn start_out_file() {
+    let project = create_project();
+    let library_path = build_project(&project);
+    let out_path = project.path().join("result.json");
+    std::fs::write(&out_path, "stale contents that are overwritten").unwrap();
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        library_path.into(),
+        "--no-watch".into(),
+        "--output".into(),
+        "json".into(),
+        "--out-file".into(),
+        out_path.clone().into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    assert_eq!(std::fs::read_to_string(&out_path).unwrap(), "567\n");
+}
+
+#[test]
+fn start_reload_summary() {
+    let project = create_project();
+    let library_path = build_project(&project);
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        library_path.into(),
+        "--no-watch".into(),
+        "--reload-summary".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+}
+
+#[test]
+fn profile_startup() {
+    let project = create_project();
+    let library_path = build_project(&project);
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "profile-startup".into(),
+        library_path.into(),
+        "--json".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+}
+
+#[test]
+fn start_with_args() {
+    let project = create_project_with_source(
+        "pub fn add(a: i64, b: i64) -> i64 { a + b }\npub fn scale(x: f64, enabled: bool) -> f64 { if enabled { x * 2.0 } else { x } }",
+    );
+    let library_path = build_project(&project);
+
+    let start_args = |entry: &str, args: &[&str]| -> Vec<OsString> {
+        let mut start_args: Vec<OsString> = vec![
+            "mun".into(),
+            "start".into(),
+            library_path.clone().into(),
+            "--no-watch".into(),
+            "--entry".into(),
+            entry.into(),
+            "--".into(),
+        ];
+        start_args.extend(args.iter().map(OsString::from));
+        start_args
+    };
+
+    assert_eq!(
+        run_with_args(start_args("add", &["3", "4"])).unwrap(),
+        mun::ExitStatus::Success
+    );
+    assert_eq!(
+        run_with_args(start_args("scale", &["-1.5", "true"])).unwrap(),
+        mun::ExitStatus::Success
+    );
+
+    // Argument count mismatch
+    assert!(run_with_args(start_args("add", &["3"])).is_err());
+    assert!(run_with_args(start_args("add", &[])).is_err());
+
+    // Type parse failure
+    assert!(run_with_args(start_args("add", &["3", "four"])).is_err());
+
+    // Everything after `--` is an argument of the entry point, even if it looks like a flag
+    assert_eq!(
+        run_with_args(start_args("add", &["-3", "-4"])).unwrap(),
+        mun::ExitStatus::Success
+    );
+    let error = run_with_args(start_args("add", &["--help", "--no-watch"])).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("cannot convert '--help' to `core::i64`"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn start_struct_with_args() {
+    let project = create_project_with_source(
+        r#"
+struct Point { x: i64, y: i64 }
+struct Line { from: Point, to: Point }
+pub fn point(x: i64, y: i64) -> Point { Point { x: x, y: y } }
+pub fn line(length: i64) -> Line { Line { from: point(0, 0), to: point(length, 0) } }"#,
+    );
+    let library_path = build_project(&project);
+    let start = |entry: &str, args: &[&str]| {
+        let mut start_args: Vec<OsString> = vec![
+            "mun".into(),
+            "start".into(),
+            library_path.clone().into(),
+            "--no-watch".into(),
+            "--entry".into(),
+            entry.into(),
+            "--".into(),
+        ];
+        start_args.extend(args.iter().map(OsString::from));
+        match run_with_args(start_args).unwrap() {
+            mun::Outcome::Start {
+                status: mun::ExitStatus::Success,
+                return_value: Some(return_value),
+            } => return_value.to_string(),
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        }
+    };
+
+    assert_eq!(start("point", &["1", "2"]), "Point { x: 1, y: 2 }");
+    assert_eq!(
+        start("line", &["3"]),
+        "Line { from: Point { x: 0, y: 0 }, to: Point { x: 3, y: 0 } }"
+    );
+}
+
+#[test]
+fn build_out_dir() {
+    let project = create_project();
+    let out_dir = TempDir::new("mun_out_dir").unwrap();
+    let out_path = out_dir.path().join("target/mun");
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--out-dir".into(),
+        out_path.clone().into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+
+    assert!(out_path.join("main.munlib").is_file());
+    assert!(!project.path().join("target/main.munlib").exists());
+}
+
+#[test]
+fn start_integer_return_types() {
+    let widths = [
+        "i8", "i16", "i32", "i64", "i128", "u8", "u16", "u32", "u64", "u128",
+    ];
+    let source: String = widths
+        .iter()
+        .map(|width| {
+            let value = if width.starts_with('i') { "-5" } else { "5" };
+            format!("pub fn get_{}() -> {} {{ {} }}\n", width, width, value)
+        })
+        .collect();
+    let project = create_project_with_source(&source);
+    let library_path = build_project(&project);
+
+    for width in widths.iter() {
+        let args: Vec<OsString> = vec![
+            "mun".into(),
+            "start".into(),
+            library_path.clone().into(),
+            "--no-watch".into(),
+            "--entry".into(),
+            format!("get_{}", width).into(),
+        ];
+        assert_eq!(
+            run_with_args(args).unwrap(),
+            mun::ExitStatus::Success,
+            "returning `{}`",
+            width
+        );
+    }
+}
+
+#[test]
+fn run_package() {
+    let project = create_project_with_source(
+        r#"
+pub fn main() -> i64 { 5 }
+pub fn add(a: i64, b: i64) -> i64 { a + b }"#,
+    );
+    let manifest_path: OsString = project.path().join("mun.toml").into();
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "run".into(),
+        "--manifest-path".into(),
+        manifest_path.clone(),
+        "--opt-level".into(),
+        "0".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    assert!(project.path().join("target/main.munlib").is_file());
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "run".into(),
+        "--manifest-path".into(),
+        manifest_path,
+        "--entry".into(),
+        "add".into(),
+        "--".into(),
+        "3".into(),
+        "4".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+}
+
+#[test]
+fn start_library_directory() {
+    let project = create_project();
+    let library_path = build_project(&project);
+    let target_dir = library_path.parent().unwrap().to_path_buf();
+    let start = || {
+        let args: Vec<OsString> = vec!["mun".into(), "start".into(), target_dir.clone().into()];
+        run_with_args(args)
+    };
+
+    assert_eq!(start().unwrap(), mun::ExitStatus::Success);
+
+    std::fs::copy(&library_path, target_dir.join("copy.munlib")).unwrap();
+    assert!(start().is_err());
+}
+
+#[test]
+fn start_default_entry() {
+    let project = create_project_with_source(
+        r#"
+pub fn main() -> i64 { 1 }
+pub fn game_main() -> i64 { 2 }
+pub fn other() -> i64 { 3 }"#,
+    );
+    let manifest_path = project.path().join("mun.toml");
+    let invoke = |subcommand: &str, flags: &[&str]| {
+        let mut args: Vec<OsString> = vec![
+            "mun".into(),
+            subcommand.into(),
+            "--manifest-path".into(),
+            manifest_path.clone().into(),
+        ];
+        args.extend(flags.iter().map(OsString::from));
+        run_with_args(args).unwrap()
+    };
+    let returned = |value| mun::Outcome::Start {
+        status: mun::ExitStatus::Success,
+        return_value: Some(mun::ReturnValue::Fundamental(mun::ArgValue::I64(value))),
+    };
+
+    // Without a default entry in the manifest, `main` is invoked
+    assert_eq!(invoke("run", &[]), returned(1));
+
+    let manifest = std::fs::read_to_string(&manifest_path).unwrap();
+    std::fs::write(
+        &manifest_path,
+        manifest.replace("[package]", "[package]\ndefault-entry = \"game_main\""),
+    )
+    .unwrap();
+
+    // The default entry of the manifest, unless an entry point is passed
+    assert_eq!(invoke("run", &[]), returned(2));
+    assert_eq!(invoke("run", &["--entry", "other"]), returned(3));
+    assert_eq!(invoke("start", &["--no-watch"]), returned(2));
+    assert_eq!(
+        invoke("start", &["--no-watch", "--entry", "other"]),
+        returned(3)
+    );
+
+    // The manifest is found next to the target directory of an explicit library
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        project.path().join("target/main.munlib").into(),
+        "--no-watch".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), returned(2));
+}
+
+#[test]
+fn start_args_file() {
+    let project = create_project_with_source(
+        "pub fn scale(enabled: bool, value: f64) -> f64 { if enabled { value * 2.0 } else { value } }",
+    );
+    let library_path = build_project(&project);
+    let args_path = project.path().join("args.json");
+    let start = || {
+        let args: Vec<OsString> = vec![
+            "mun".into(),
+            "start".into(),
+            library_path.clone().into(),
+            "--no-watch".into(),
+            "--entry".into(),
+            "scale".into(),
+            "--args-file".into(),
+            args_path.clone().into(),
+        ];
+        run_with_args(args)
+    };
+
+    std::fs::write(&args_path, "[true, 1.5]").unwrap();
+    assert_eq!(
+        start().unwrap(),
+        mun::Outcome::Start {
+            status: mun::ExitStatus::Success,
+            return_value: Some(mun::ReturnValue::Fundamental(mun::ArgValue::F64(3.0))),
+        }
+    );
+
+    std::fs::write(&args_path, "[true]").unwrap();
+    let error = start().unwrap_err().to_string();
+    assert!(
+        error.contains("expected 2 argument(s) but 1 were provided"),
+        "{}",
+        error
+    );
+
+    std::fs::write(&args_path, "[true, 1.5").unwrap();
+    let error = start().unwrap_err().to_string();
+    assert!(error.contains("invalid arguments file"), "{}", error);
+}
+
+#[test]
+fn run_compile_error() {
+    let project = create_project_with_source("pub fn main() -> i64 { true }");
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "run".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Error);
+}
+
+#[test]
+fn build_dep_info() {
+    let project = create_project();
+    std::fs::write(
+        project.path().join("src/physics.mun"),
+        "pub fn gravity() -> f64 { 9.81 }",
+    )
+    .unwrap();
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--emit=dep-info".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+
+    let dep_info = std::fs::read_to_string(project.path().join("target/test.d")).unwrap();
+    let rules: Vec<&str> = dep_info.lines().collect();
+    assert_eq!(rules.len(), 2, "{}", dep_info);
+    for (rule, name) in rules.iter().zip(&["main", "physics"]) {
+        let (target, sources) = rule.split_at(rule.find(": ").unwrap());
+        assert!(target.ends_with(&format!("{}.munlib", name)), "{}", rule);
+        assert!(sources.contains(&format!("{}.mun", name)), "{}", rule);
+        assert!(sources.contains("mun.toml"), "{}", rule);
+    }
+}
+
+#[test]
+fn build_quiet() {
+    let project = create_project();
+    let build = || {
+        let args: Vec<OsString> = vec![
+            "mun".into(),
+            "build".into(),
+            "--manifest-path".into(),
+            project.path().join("mun.toml").into(),
+            "--quiet".into(),
+            "--emit=dep-info".into(),
+        ];
+        run_with_args(args).unwrap()
+    };
+
+    assert_eq!(build(), mun::ExitStatus::Success);
+    assert!(project.path().join("target/test.d").is_file());
+
+    // The exit status still reflects errors
+    std::fs::write(
+        project.path().join("src/main.mun"),
+        "pub fn main() -> i32 { missing }",
+    )
+    .unwrap();
+    assert_eq!(build(), mun::ExitStatus::Error);
+}
+
+#[test]
+fn build_emit() {
+    for (emit, extension) in &[
+        ("munlib", None),
+        ("llvm-ir", Some("ll")),
+        ("assembly", Some("s")),
+    ] {
+        let project = create_project();
+
+        let args: Vec<OsString> = vec![
+            "mun".into(),
+            "build".into(),
+            "--manifest-path".into(),
+            project.path().join("mun.toml").into(),
+            format!("--emit={}", emit).into(),
+        ];
+        assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+
+        let target_dir = project.path().join("target");
+        assert!(target_dir.join("main.munlib").is_file());
+        for other in &["ll", "s"] {
+            assert_eq!(
+                target_dir.join("main").with_extension(other).is_file(),
+                *extension == Some(*other),
+                "--emit={} and .{}",
+                emit,
+                other
+            );
+        }
+    }
+}
+
+#[test]
+fn build_invalid_utf8() {
+    let project = create_project();
+    std::fs::write(
+        project.path().join("src/main.mun"),
+        b"pub fn main() -> i32 { \xc0 }",
+    )
+    .unwrap();
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+    ];
+    let error = run_with_args(args).unwrap_err().to_string();
+    assert!(error.contains("main.mun"), "{}", error);
+    assert!(error.contains("byte offset 23"), "{}", error);
+}
+
+#[test]
+fn start_exit_code() {
+    let project = create_project_with_source(
+        r#"
+pub fn main() -> i64 { 2 }
+pub fn truncated() -> i64 { 4294967298 }
+pub fn flag() -> bool { true }"#,
+    );
+    let library_path = build_project(&project);
+
+    let start_args = |entry: &str| -> Vec<OsString> {
+        vec![
+            "mun".into(),
+            "start".into(),
+            library_path.clone().into(),
+            "--no-watch".into(),
+            "--entry".into(),
+            entry.into(),
+            "--exit-code".into(),
+        ]
+    };
+
+    assert_eq!(
+        run_with_args(start_args("main")).unwrap(),
+        mun::ExitStatus::Code(2)
+    );
+    assert_eq!(
+        run_with_args(start_args("truncated")).unwrap(),
+        mun::ExitStatus::Code(2)
+    );
+    assert!(run_with_args(start_args("flag")).is_err());
+
+    // Without the flag the value is printed
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        library_path.clone().into(),
+        "--no-watch".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+}
+
+#[test]
+fn start_list_supported_types() {
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        "--list-supported-types".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+}
+
+#[test]
+fn build_message_format_json() {
+    let project = create_project_with_source("pub fn main() -> i32 { true }");
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--message-format=json".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Error);
+    assert!(!project.path().join("target/main.munlib").exists());
+}
+
+#[test]
+fn check_package() {
+    let project = create_project();
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "check".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    assert!(!project.path().join("target/main.munlib").exists());
+
+    let project = create_project_with_source("pub fn main() -> i32 { true }");
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "check".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--color=disable".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Error);
+    assert!(!project.path().join("target/main.munlib").exists());
+}
+
+#[test]
+fn build_path_dependencies() {
+    let dir = TempDir::new("mun_path_dependencies").unwrap();
+    let write_package = |name: &str, dependency: &str| {
+        let package_dir = dir.path().join(name);
+        std::fs::create_dir_all(package_dir.join("src")).unwrap();
+        std::fs::write(
+            package_dir.join("mun.toml"),
+            format!(
+                "[package]\nname=\"{}\"\nversion=\"0.1.0\"\n\n[dependencies]\n{} = {{ path = \"../{}\" }}\n",
+                name, dependency, dependency
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            package_dir.join("src/main.mun"),
+            "pub fn main() -> i32 { 1 }",
+        )
+        .unwrap();
+    };
+    write_package("game", "utils");
+    std::fs::create_dir_all(dir.path().join("utils/src")).unwrap();
+    std::fs::write(
+        dir.path().join("utils/mun.toml"),
+        "[package]\nname=\"utils\"\nversion=\"0.1.0\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("utils/src/main.mun"),
+        "pub fn one() -> i32 { 1 }",
+    )
+    .unwrap();
+
+    let args = |name: &str| -> Vec<OsString> {
+        vec![
+            "mun".into(),
+            "build".into(),
+            "--manifest-path".into(),
+            dir.path().join(name).join("mun.toml").into(),
+        ]
+    };
+    assert_eq!(
+        run_with_args(args("game")).unwrap(),
+        mun::ExitStatus::Success
+    );
+    assert!(dir.path().join("utils/target/main.munlib").is_file());
+    assert!(dir.path().join("game/target/main.munlib").is_file());
+
+    write_package("utils", "game");
+    let error = run_with_args(args("game")).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "cyclic dependency: game -> utils -> game"
+    );
+}
+
+#[test]
+fn clean_package() {
+    let project = create_project();
+    let library_path = build_project(&project);
+
+    let args = |extra: &[&str]| -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec![
+            "mun".into(),
+            "clean".into(),
+            "--manifest-path".into(),
+            project.path().join("mun.toml").into(),
+        ];
+        args.extend(extra.iter().map(OsString::from));
+        args
+    };
+    assert_eq!(
+        run_with_args(args(&["--dry-run"])).unwrap(),
+        mun::ExitStatus::Success
+    );
+    assert!(library_path.is_file());
+
+    assert_eq!(
+        run_with_args(args(&["--yes"])).unwrap(),
+        mun::ExitStatus::Success
+    );
+    assert!(!library_path.exists());
+    assert!(project.path().join("src/main.mun").is_file());
+    assert!(project.path().join("mun.toml").is_file());
+}
+
+#[test]
+fn build_abi_changelog() {
+    let project = create_project();
+    let args = |extra: &[&str]| -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec![
+            "mun".into(),
+            "build".into(),
+            "--manifest-path".into(),
+            project.path().join("mun.toml").into(),
+        ];
+        args.extend(extra.iter().map(OsString::from));
+        args
+    };
+    assert_eq!(
+        run_with_args(args(&["--emit-build-record"])).unwrap(),
+        mun::ExitStatus::Success
+    );
+
+    let record_path = project.path().join("previous-record.json");
+    std::fs::rename(
+        project.path().join("target/build-record.json"),
+        &record_path,
+    )
+    .unwrap();
+    std::fs::write(
+        project.path().join("src/main.mun"),
+        "pub fn main() -> i64 { 1 }",
+    )
+    .unwrap();
+    assert_eq!(
+        run_with_args(args(&["--abi-changelog", record_path.to_str().unwrap()])).unwrap(),
+        mun::ExitStatus::Success
+    );
+
+    // Records without an ABI cannot be compared against
+    let mut record: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&record_path).unwrap()).unwrap();
+    record.as_object_mut().unwrap().remove("abi");
+    std::fs::write(&record_path, record.to_string()).unwrap();
+    assert!(run_with_args(args(&["--abi-changelog", record_path.to_str().unwrap()])).is_err());
+}
+
+#[test]
+fn start_multiple_libraries() {
+    let game = create_project_with_source(
+        "extern fn helper() -> i32;\n\npub fn main() -> i32 { helper() + 1 }",
+    );
+    let utils = create_project_with_source("pub fn helper() -> i32 { 41 }");
+    let game_library = build_project(&game);
+    let utils_library = build_project(&utils);
+
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        game_library.clone().into(),
+        utils_library.clone().into(),
+        "--entry".into(),
+        "main".into(),
+        "--exit-code".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Code(42));
+
+    // A library that is specified twice is only loaded once
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        game_library.into(),
+        utils_library.clone().into(),
+        utils_library.clone().into(),
+        "--entry".into(),
+        "main".into(),
+        "--exit-code".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Code(42));
+
+    // Both libraries export `helper`
+    let other = create_project_with_source("pub fn helper() -> i32 { 1 }");
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        utils_library.into(),
+        build_project(&other).into(),
+        "--entry".into(),
+        "helper".into(),
+    ];
+    let error = run_with_args(args).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("function 'helper' is exported by both"));
+}
+
+#[test]
+fn build_print_search_paths() {
+    let project = create_project();
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--print=search-paths".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    assert!(!project.path().join("target").exists());
+}
+
+#[test]
+fn build_release() {
+    let build = |flags: &[&str]| {
+        let project = create_project();
+        let mut args: Vec<OsString> = vec![
+            "mun".into(),
+            "build".into(),
+            "--manifest-path".into(),
+            project.path().join("mun.toml").into(),
+            "--emit-build-record".into(),
+        ];
+        args.extend(flags.iter().map(OsString::from));
+        let status = run_with_args(args).unwrap().exit_status();
+        let opt_level = std::fs::read_to_string(project.path().join("target/build-record.json"))
+            .ok()
+            .map(|record| {
+                let record: serde_json::Value = serde_json::from_str(&record).unwrap();
+                record["opt_level"].as_str().unwrap().to_owned()
+            });
+        (status, opt_level)
+    };
+
+    assert_eq!(
+        build(&["--release"]),
+        (mun::ExitStatus::Success, Some("Aggressive".to_owned()))
+    );
+    assert_eq!(
+        build(&[]),
+        (mun::ExitStatus::Success, Some("Default".to_owned()))
+    );
+    assert_eq!(
+        build(&["--release", "--opt-level", "1"]),
+        (mun::ExitStatus::Error, None)
+    );
+}
+
+#[test]
+fn start_dump_fn_addresses() {
+    let project = create_project();
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "start".into(),
+        build_project(&project).into(),
+        "--no-watch".into(),
+        "--dump-fn-addresses".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+}
+
+#[test]
+fn start_watch_retries() {
+    let project = create_project();
+    let library_path = build_project(&project);
+    let args = |retries: &str| -> Vec<OsString> {
+        vec![
+            "mun".into(),
+            "start".into(),
+            library_path.clone().into(),
+            "--watch-retries".into(),
+            retries.into(),
+        ]
+    };
+    assert_eq!(run_with_args(args("0")).unwrap(), mun::ExitStatus::Success);
+    assert!(run_with_args(args("many")).is_err());
+}
+
+#[test]
+fn build_bench_compile() {
+    let project = create_project();
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "build".into(),
+        "--manifest-path".into(),
+        project.path().join("mun.toml").into(),
+        "--bench-compile=1".into(),
+        "--bench-format".into(),
+        "json".into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+    assert!(!project.path().join("target/main.munlib").exists());
+}
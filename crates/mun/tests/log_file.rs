@@ -0,0 +1,38 @@
+//! The logger can only be initialized once per process, so logging to a file is tested in a
+//! separate test binary.
+
+use mun::run_with_args;
+use std::ffi::OsString;
+use tempdir::TempDir;
+
+#[test]
+fn build_log_file() {
+    let project_dir = TempDir::new("mun_log_file").unwrap();
+    std::fs::write(
+        project_dir.path().join("mun.toml"),
+        "[package]\nname=\"test\"\nversion=\"0.1.0\"\n",
+    )
+    .unwrap();
+    std::fs::create_dir_all(project_dir.path().join("src")).unwrap();
+    std::fs::write(
+        project_dir.path().join("src/main.mun"),
+        "pub fn main() -> i32 { 5 }",
+    )
+    .unwrap();
+
+    let log_path = project_dir.path().join("mun.log");
+    let args: Vec<OsString> = vec![
+        "mun".into(),
+        "--log-file".into(),
+        log_path.clone().into(),
+        "build".into(),
+        "-vvv".into(),
+        "--manifest-path".into(),
+        project_dir.path().join("mun.toml").into(),
+    ];
+    assert_eq!(run_with_args(args).unwrap(), mun::ExitStatus::Success);
+
+    let log = std::fs::read_to_string(&log_path).unwrap();
+    assert!(log.contains("located build manifest at"), "{}", log);
+    assert!(log.contains("TRACE mun: starting build"), "{}", log);
+}
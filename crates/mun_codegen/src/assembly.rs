@@ -1,4 +1,5 @@
-use crate::{IrDatabase, ModuleBuilder};
+use crate::{IrDatabase, Module, ModuleBuilder};
+use std::collections::HashSet;
 use std::path::Path;
 use std::sync::Arc;
 use tempfile::NamedTempFile;
@@ -6,6 +7,9 @@ use tempfile::NamedTempFile;
 #[derive(Debug)]
 pub struct Assembly {
     file: NamedTempFile,
+    link_command: Vec<String>,
+    function_sizes: Vec<(String, u64)>,
+    module: Arc<Module>,
 }
 
 impl PartialEq for Assembly {
@@ -19,11 +23,39 @@ impl Eq for Assembly {}
 impl Assembly {
     pub const EXTENSION: &'static str = "munlib";
 
+    /// The extension of assemblies compiled for WebAssembly targets
+    pub const WASM_EXTENSION: &'static str = "wasm";
+
+    /// Returns the extension of the assemblies that are compiled for `target`.
+    pub fn extension(target: &mun_target::spec::Target) -> &'static str {
+        if target.options.is_like_wasm {
+            Self::WASM_EXTENSION
+        } else {
+            Self::EXTENSION
+        }
+    }
+
     /// Returns the current location of the assembly
     pub fn path(&self) -> &Path {
         self.file.path()
     }
 
+    /// Returns the command line of the linker invocation that produced the assembly
+    pub fn link_command(&self) -> &[String] {
+        &self.link_command
+    }
+
+    /// Returns the name and compiled size in bytes of every exported function, sorted by size in
+    /// descending order
+    pub fn function_sizes(&self) -> &[(String, u64)] {
+        &self.function_sizes
+    }
+
+    /// Returns the optimized module from which the assembly was generated
+    pub fn module(&self) -> &Module {
+        &self.module
+    }
+
     /// Copies the assembly to the specified location
     pub fn copy_to<P: AsRef<Path>>(&self, destination: P) -> Result<(), std::io::Error> {
         std::fs::copy(self.path(), destination).map(|_| ())
@@ -38,9 +70,41 @@ pub fn assembly_query(db: &impl IrDatabase, file_id: hir::FileId) -> Arc<Assembl
     let obj_file = module_builder
         .build()
         .expect("unable to create object file");
-    obj_file
+
+    let exported_functions: HashSet<String> = db
+        .file_ir(file_id)
+        .api
+        .iter()
+        .map(|function| function.name(db).to_string())
+        .collect();
+    let mut function_sizes: Vec<(String, u64)> = obj_file
+        .symbol_sizes()
+        .iter()
+        .filter_map(|(symbol, size)| {
+            let name = if exported_functions.contains(symbol) {
+                symbol.as_str()
+            } else if symbol.starts_with('_') && exported_functions.contains(&symbol[1..]) {
+                // Symbols are prefixed with an underscore on some platforms
+                &symbol[1..]
+            } else {
+                return None;
+            };
+            Some((name.to_owned(), *size))
+        })
+        .collect();
+    function_sizes.sort_by(|(a_name, a_size), (b_name, b_size)| {
+        b_size.cmp(a_size).then_with(|| a_name.cmp(b_name))
+    });
+
+    let module = obj_file.module().clone();
+    let link_command = obj_file
         .into_shared_object(file.path())
         .expect("could not link object file");
 
-    Arc::new(Assembly { file })
+    Arc::new(Assembly {
+        file,
+        link_command,
+        function_sizes,
+        module,
+    })
 }
@@ -37,15 +37,63 @@ enum CodeGenerationError {
     CodeGenerationError(String),
 }
 
+/// The LLVM backend Mun is built with cannot generate machine code for a target, e.g. because the
+/// backend of its architecture was not enabled when LLVM was built.
+#[derive(Debug, Error)]
+#[error("{reason}")]
+pub struct BackendUnavailable {
+    /// The LLVM triple of the target
+    pub triple: String,
+    /// Why LLVM cannot generate code for the target
+    pub reason: String,
+}
+
 impl From<LinkerError> for CodeGenerationError {
     fn from(e: LinkerError) -> Self {
         CodeGenerationError::LinkerError(e)
     }
 }
 
+/// Returns the name of the CPU of the host machine, as detected by LLVM.
+pub fn host_cpu_name() -> String {
+    TargetMachine::get_host_cpu_name().to_string()
+}
+
+/// Verifies that the LLVM backend Mun is built with is able to generate machine code for the
+/// specified `target`. Fails with [`BackendUnavailable`] otherwise.
+pub fn check_target(target: &spec::Target) -> Result<(), anyhow::Error> {
+    initialize_targets();
+
+    let unavailable = |reason: String| BackendUnavailable {
+        triple: target.llvm_target.clone(),
+        reason,
+    };
+    let llvm_target = Target::from_triple(&target.llvm_target)
+        .map_err(|e| unavailable(format!("unknown target triple: {}", e)))?;
+    llvm_target
+        .create_target_machine(
+            &target.llvm_target,
+            &target.options.cpu,
+            &target.options.features,
+            OptimizationLevel::Default,
+            RelocMode::PIC,
+            CodeModel::Default,
+        )
+        .ok_or_else(|| unavailable("error creating target machine".to_owned()))?;
+    Ok(())
+}
+
+/// Initializes the LLVM backends of all supported targets.
+fn initialize_targets() {
+    Target::initialize_x86(&InitializationConfig::default());
+    Target::initialize_webassembly(&InitializationConfig::default());
+}
+
 pub struct ObjectFile {
     target: spec::Target,
     obj_file: NamedTempFile,
+    symbol_sizes: Vec<(String, u64)>,
+    module: Arc<inkwell::module::Module>,
 }
 
 impl ObjectFile {
@@ -65,23 +113,49 @@ impl ObjectFile {
             .write(obj.as_slice())
             .map_err(CodeGenerationError::CouldNotCreateObjectFile)?;
 
+        let symbol_sizes = obj
+            .create_object_file()
+            .map(|obj| {
+                obj.get_symbols()
+                    .filter_map(|symbol| {
+                        let name = symbol.get_name()?.to_str().ok()?;
+                        Some((name.to_owned(), symbol.get_size()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
         Ok(Self {
             target: target.clone(),
             obj_file,
+            symbol_sizes,
+            module,
         })
     }
 
-    /// Links the object file into a shared object.
-    pub fn into_shared_object(self, output_path: &Path) -> Result<(), anyhow::Error> {
+    /// Returns the name and size in bytes of every named symbol in the object file
+    pub fn symbol_sizes(&self) -> &[(String, u64)] {
+        &self.symbol_sizes
+    }
+
+    /// Returns the module from which the object file was generated
+    pub fn module(&self) -> &Arc<inkwell::module::Module> {
+        &self.module
+    }
+
+    /// Links the object file into a shared object. Returns the command line of the equivalent
+    /// linker invocation.
+    pub fn into_shared_object(self, output_path: &Path) -> Result<Vec<String>, anyhow::Error> {
         // Construct a linker for the target
         let mut linker = linker::create_with_target(&self.target);
         linker.add_object(self.obj_file.path())?;
 
         // Link the object
         linker.build_shared_object(&output_path)?;
+        let command = linker.command();
         linker.finalize()?;
 
-        Ok(())
+        Ok(command)
     }
 }
 
@@ -105,13 +179,13 @@ impl<'a, D: IrDatabase> ModuleBuilder<'a, D> {
                 .create_module(db.file_relative_path(file_id).as_str()),
         );
 
-        // Initialize the x86 target
-        Target::initialize_x86(&InitializationConfig::default());
+        initialize_targets();
 
         // Retrieve the LLVM target using the specified target.
         let llvm_target = Target::from_triple(&target.llvm_target)
             .map_err(|e| CodeGenerationError::UnknownTargetTriple(e.to_string()))?;
         assembly_module.set_target(&llvm_target);
+        assembly_module.set_data_layout(&db.target_data().get_data_layout());
 
         // Construct target machine for machine code generation
         let target_machine = llvm_target
@@ -136,6 +210,34 @@ impl<'a, D: IrDatabase> ModuleBuilder<'a, D> {
 
     /// Constructs an object file.
     pub fn build(self) -> Result<ObjectFile, anyhow::Error> {
+        self.finish_module()?;
+
+        ObjectFile::new(
+            &self.db.target(),
+            &self.target_machine,
+            self.assembly_module,
+        )
+    }
+
+    /// Constructs the optimized module and renders it as textual LLVM IR.
+    pub fn build_llvm_ir(self) -> Result<String, anyhow::Error> {
+        self.finish_module()?;
+        Ok(self.assembly_module.print_to_string().to_string())
+    }
+
+    /// Constructs the optimized module and renders it as assembly for the target.
+    pub fn build_assembly(self) -> Result<String, anyhow::Error> {
+        self.finish_module()?;
+        let assembly = self
+            .target_machine
+            .write_to_memory_buffer(&self.assembly_module, FileType::Assembly)
+            .map_err(|e| CodeGenerationError::CodeGenerationError(e.to_string()))?;
+        Ok(String::from_utf8_lossy(assembly.as_slice()).into_owned())
+    }
+
+    /// Links the IR of the file into the assembly module, generates the reflection information and
+    /// optimizes the result.
+    fn finish_module(&self) -> Result<(), anyhow::Error> {
         let group_ir = self.db.group_ir(self.file_id);
         let file = self.db.file_ir(self.file_id);
 
@@ -177,11 +279,7 @@ impl<'a, D: IrDatabase> ModuleBuilder<'a, D> {
         // Debug print the IR
         //println!("{}", assembly_module.print_to_string().to_string());
 
-        ObjectFile::new(
-            &self.db.target(),
-            &self.target_machine,
-            self.assembly_module,
-        )
+        Ok(())
     }
 }
 
@@ -31,6 +31,7 @@ pub fn create_with_target(target: &spec::Target) -> Box<dyn Linker> {
         LinkerFlavor::Ld => Box::new(LdLinker::new(target)),
         LinkerFlavor::Ld64 => Box::new(Ld64Linker::new(target)),
         LinkerFlavor::Msvc => Box::new(MsvcLinker::new(target)),
+        LinkerFlavor::WasmLd => Box::new(WasmLdLinker::new(target)),
     }
 }
 
@@ -38,6 +39,10 @@ pub trait Linker {
     fn add_object(&mut self, path: &Path) -> Result<(), LinkerError>;
     fn build_shared_object(&mut self, path: &Path) -> Result<(), LinkerError>;
     fn finalize(&mut self) -> Result<(), LinkerError>;
+
+    /// Returns the command line of the standalone LLD invocation that is equivalent to the
+    /// internal invocation performed by `finalize`.
+    fn command(&self) -> Vec<String>;
 }
 
 struct LdLinker {
@@ -82,6 +87,12 @@ impl Linker for LdLinker {
             .ok()
             .map_err(LinkerError::LinkError)
     }
+
+    fn command(&self) -> Vec<String> {
+        std::iter::once("ld.lld".to_owned())
+            .chain(self.args.iter().cloned())
+            .collect()
+    }
 }
 
 struct Ld64Linker {
@@ -138,6 +149,12 @@ impl Linker for Ld64Linker {
             .ok()
             .map_err(LinkerError::LinkError)
     }
+
+    fn command(&self) -> Vec<String> {
+        std::iter::once("ld64.lld".to_owned())
+            .chain(self.args.iter().cloned())
+            .collect()
+    }
 }
 
 struct MsvcLinker {
@@ -188,4 +205,69 @@ impl Linker for MsvcLinker {
             .ok()
             .map_err(LinkerError::LinkError)
     }
+
+    fn command(&self) -> Vec<String> {
+        std::iter::once("lld-link".to_owned())
+            .chain(self.args.iter().cloned())
+            .collect()
+    }
+}
+
+struct WasmLdLinker {
+    args: Vec<String>,
+}
+
+impl WasmLdLinker {
+    fn new(_target: &spec::Target) -> Self {
+        WasmLdLinker {
+            args: Vec::default(),
+        }
+    }
+}
+
+impl Linker for WasmLdLinker {
+    fn add_object(&mut self, path: &Path) -> Result<(), LinkerError> {
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| LinkerError::PathError(path.to_owned()))?
+            .to_owned();
+        self.args.push(path_str);
+        Ok(())
+    }
+
+    fn build_shared_object(&mut self, path: &Path) -> Result<(), LinkerError> {
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| LinkerError::PathError(path.to_owned()))?;
+
+        // A WebAssembly module has no entry point; all public functions and the functions that
+        // describe the assembly are exported, functions of the runtime are imported
+        self.args.push("--no-entry".to_owned());
+        self.args.push("--export-dynamic".to_owned());
+        self.args.push("--allow-undefined".to_owned());
+        self.args
+            .push(format!("--export={}", abi::GET_INFO_FN_NAME));
+        self.args
+            .push(format!("--export={}", abi::GET_VERSION_FN_NAME));
+        self.args
+            .push(format!("--export={}", abi::SET_ALLOCATOR_HANDLE_FN_NAME));
+
+        // Specify output path
+        self.args.push("-o".to_owned());
+        self.args.push(path_str.to_owned());
+
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<(), LinkerError> {
+        mun_lld::link(mun_lld::LldFlavor::Wasm, &self.args)
+            .ok()
+            .map_err(LinkerError::LinkError)
+    }
+
+    fn command(&self) -> Vec<String> {
+        std::iter::once("wasm-ld".to_owned())
+            .chain(self.args.iter().cloned())
+            .collect()
+    }
 }
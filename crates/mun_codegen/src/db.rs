@@ -4,7 +4,7 @@ use crate::{
     assembly::Assembly,
     ir::{file::FileIR, file_group::FileGroupIR},
     type_info::TypeInfo,
-    CodeGenParams, Context,
+    CodeGenParams, Context, OverflowBehavior, SymbolVisibility,
 };
 use by_address::ByAddress;
 use inkwell::{
@@ -31,6 +31,19 @@ pub trait IrDatabase: hir::HirDatabase {
     #[salsa::input]
     fn optimization_lvl(&self) -> OptimizationLevel;
 
+    /// Gets the visibility of the symbols of functions that are not marked `pub`.
+    #[salsa::input]
+    fn default_visibility(&self) -> SymbolVisibility;
+
+    /// Gets what happens when integer arithmetic overflows.
+    #[salsa::input]
+    fn overflow_behavior(&self) -> OverflowBehavior;
+
+    /// Gets whether calls between Mun functions are reported to the runtime, so they can be
+    /// profiled.
+    #[salsa::input]
+    fn instrument_calls(&self) -> bool;
+
     /// Returns the target machine's data layout for code generation.
     #[salsa::invoke(crate::code_gen::target_data_query)]
     fn target_data(&self) -> Arc<TargetData>;
@@ -21,4 +21,10 @@ pub trait Intrinsic: Sync {
 intrinsics! {
     /// Allocates memory for the specified `type` in the allocator referred to by `alloc_handle`.
     pub fn new(type: *const TypeInfo, alloc_handle: *mut ffi::c_void) -> *const *mut ffi::c_void;
+
+    /// Reports that the function with the null-terminated `name` is about to be called.
+    pub fn enter_call(name: *const u8) -> ();
+
+    /// Reports that the function with the null-terminated `name` returned.
+    pub fn exit_call(name: *const u8) -> ();
 }
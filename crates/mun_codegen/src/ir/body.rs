@@ -1,7 +1,7 @@
 use crate::intrinsics;
 use crate::{
     ir::{dispatch_table::DispatchTable, try_convert_any_to_basic, type_table::TypeTable},
-    CodeGenParams, IrDatabase,
+    CodeGenParams, IrDatabase, Module, OverflowBehavior,
 };
 use hir::{
     ArenaId, ArithOp, BinaryOp, Body, CmpOp, Expr, ExprId, HirDisplay, InferenceResult, Literal,
@@ -45,6 +45,7 @@ pub(crate) struct BodyIrGenerator<'a, 'b, D: IrDatabase> {
     pat_to_local: HashMap<PatId, inkwell::values::PointerValue>,
     pat_to_name: HashMap<PatId, String>,
     function_map: &'a HashMap<hir::Function, FunctionValue>,
+    module: &'b Module,
     dispatch_table: &'b DispatchTable,
     type_table: &'b TypeTable,
     active_loop: Option<LoopInfo>,
@@ -58,6 +59,7 @@ impl<'a, 'b, D: IrDatabase> BodyIrGenerator<'a, 'b, D> {
         db: &'a D,
         function: (hir::Function, FunctionValue),
         function_map: &'a HashMap<hir::Function, FunctionValue>,
+        module: &'b Module,
         dispatch_table: &'b DispatchTable,
         type_table: &'b TypeTable,
         params: CodeGenParams,
@@ -85,6 +87,7 @@ impl<'a, 'b, D: IrDatabase> BodyIrGenerator<'a, 'b, D> {
             pat_to_local: HashMap::default(),
             pat_to_name: HashMap::default(),
             function_map,
+            module,
             dispatch_table,
             type_table,
             active_loop: None,
@@ -957,6 +960,31 @@ impl<'a, 'b, D: IrDatabase> BodyIrGenerator<'a, 'b, D> {
         rhs: IntValue,
         op: ArithOp,
         signedness: hir::Signedness,
+    ) -> IntValue {
+        match (op, self.db.overflow_behavior()) {
+            (ArithOp::Add, OverflowBehavior::Saturate)
+            | (ArithOp::Subtract, OverflowBehavior::Saturate) => {
+                self.gen_saturating_int_op(lhs, rhs, op, signedness)
+            }
+            (ArithOp::Add, OverflowBehavior::Panic)
+            | (ArithOp::Subtract, OverflowBehavior::Panic)
+            | (ArithOp::Multiply, OverflowBehavior::Panic) => {
+                self.gen_checked_int_op(lhs, rhs, op, signedness)
+            }
+            (ArithOp::Multiply, OverflowBehavior::Saturate) => {
+                self.gen_saturating_int_mul(lhs, rhs, signedness)
+            }
+            _ => self.gen_wrapping_arith_bin_op_int(lhs, rhs, op, signedness),
+        }
+    }
+
+    /// Generates IR for an integer binary operation that wraps around on overflow.
+    fn gen_wrapping_arith_bin_op_int(
+        &mut self,
+        lhs: IntValue,
+        rhs: IntValue,
+        op: ArithOp,
+        signedness: hir::Signedness,
     ) -> IntValue {
         match op {
             ArithOp::Add => self.builder.build_int_add(lhs, rhs, "add"),
@@ -981,6 +1009,163 @@ impl<'a, 'b, D: IrDatabase> BodyIrGenerator<'a, 'b, D> {
         }
     }
 
+    /// Returns the LLVM intrinsic with the specified name that operates on two integers of the
+    /// type of `value`, declaring it in the module if required.
+    fn int_intrinsic(
+        &self,
+        name: &str,
+        value: IntValue,
+        signedness: hir::Signedness,
+        with_overflow: bool,
+    ) -> FunctionValue {
+        let int_type = value.get_type();
+        let name = format!(
+            "llvm.{}{}.i{}",
+            if signedness.is_signed() { 's' } else { 'u' },
+            name,
+            int_type.get_bit_width()
+        );
+        self.module.get_function(&name).unwrap_or_else(|| {
+            let fn_type = if with_overflow {
+                let context = self.db.context();
+                context
+                    .struct_type(&[int_type.into(), context.bool_type().into()], false)
+                    .fn_type(&[int_type.into(), int_type.into()], false)
+            } else {
+                int_type.fn_type(&[int_type.into(), int_type.into()], false)
+            };
+            self.module.add_function(&name, fn_type, None)
+        })
+    }
+
+    /// Generates IR for an operation on two integers that returns the result and whether it
+    /// overflowed, using an `llvm.*.with.overflow` intrinsic.
+    fn gen_int_op_with_overflow(
+        &mut self,
+        lhs: IntValue,
+        rhs: IntValue,
+        op: ArithOp,
+        signedness: hir::Signedness,
+    ) -> (IntValue, IntValue) {
+        let name = match op {
+            ArithOp::Add => "add.with.overflow",
+            ArithOp::Subtract => "sub.with.overflow",
+            ArithOp::Multiply => "mul.with.overflow",
+            _ => unreachable!("operator {:?} cannot overflow", op),
+        };
+        let intrinsic = self.int_intrinsic(name, lhs, signedness, true);
+        let result = self
+            .builder
+            .build_call(intrinsic, &[lhs.into(), rhs.into()], name)
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_struct_value();
+        let value = self
+            .builder
+            .build_extract_value(result, 0, "result")
+            .unwrap()
+            .into_int_value();
+        let overflow = self
+            .builder
+            .build_extract_value(result, 1, "overflow")
+            .unwrap()
+            .into_int_value();
+        (value, overflow)
+    }
+
+    /// Generates IR for an integer addition or subtraction that is clamped to the bounds of the
+    /// type on overflow.
+    fn gen_saturating_int_op(
+        &mut self,
+        lhs: IntValue,
+        rhs: IntValue,
+        op: ArithOp,
+        signedness: hir::Signedness,
+    ) -> IntValue {
+        let name = match op {
+            ArithOp::Add => "add.sat",
+            ArithOp::Subtract => "sub.sat",
+            _ => unreachable!("operator {:?} has no saturating intrinsic", op),
+        };
+        let intrinsic = self.int_intrinsic(name, lhs, signedness, false);
+        self.builder
+            .build_call(intrinsic, &[lhs.into(), rhs.into()], name)
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_int_value()
+    }
+
+    /// Generates IR for an integer multiplication that is clamped to the bounds of the type on
+    /// overflow. LLVM has no saturating multiplication intrinsic for integers, so the bound is
+    /// selected based on the sign of the operands.
+    fn gen_saturating_int_mul(
+        &mut self,
+        lhs: IntValue,
+        rhs: IntValue,
+        signedness: hir::Signedness,
+    ) -> IntValue {
+        let (value, overflow) =
+            self.gen_int_op_with_overflow(lhs, rhs, ArithOp::Multiply, signedness);
+
+        let int_type = lhs.get_type();
+        let all_ones = int_type.const_all_ones();
+        let bound = match signedness {
+            hir::Signedness::Unsigned => all_ones,
+            hir::Signedness::Signed => {
+                let max = self.builder.build_right_shift(
+                    all_ones,
+                    int_type.const_int(1, false),
+                    false,
+                    "max",
+                );
+                let min = self.builder.build_not(max, "min");
+                let sign = self.builder.build_xor(lhs, rhs, "sign");
+                let is_negative = self.builder.build_int_compare(
+                    IntPredicate::SLT,
+                    sign,
+                    int_type.const_zero(),
+                    "is_negative",
+                );
+                self.builder
+                    .build_select(is_negative, min, max, "bound")
+                    .into_int_value()
+            }
+        };
+        self.builder
+            .build_select(overflow, bound, value, "mul")
+            .into_int_value()
+    }
+
+    /// Generates IR for an integer operation that aborts the program on overflow.
+    fn gen_checked_int_op(
+        &mut self,
+        lhs: IntValue,
+        rhs: IntValue,
+        op: ArithOp,
+        signedness: hir::Signedness,
+    ) -> IntValue {
+        let (value, overflow) = self.gen_int_op_with_overflow(lhs, rhs, op, signedness);
+
+        let context = self.db.context();
+        let overflow_block = context.append_basic_block(&self.fn_value, "overflow");
+        let continue_block = context.append_basic_block(&self.fn_value, "no_overflow");
+        self.builder
+            .build_conditional_branch(overflow, &overflow_block, &continue_block);
+
+        self.builder.position_at_end(&overflow_block);
+        let trap = self.module.get_function("llvm.trap").unwrap_or_else(|| {
+            self.module
+                .add_function("llvm.trap", context.void_type().fn_type(&[], false), None)
+        });
+        self.builder.build_call(trap, &[], "trap");
+        self.builder.build_unreachable();
+
+        self.builder.position_at_end(&continue_block);
+        value
+    }
+
     fn gen_arith_bin_op_float(
         &mut self,
         lhs: FloatValue,
@@ -1052,8 +1237,35 @@ impl<'a, 'b, D: IrDatabase> BodyIrGenerator<'a, 'b, D> {
                 &self.builder,
                 function,
             );
-            self.builder
-                .build_call(ptr_value, &args, &function.name(self.db).to_string())
+            if !self.db.instrument_calls() {
+                return self.builder.build_call(
+                    ptr_value,
+                    &args,
+                    &function.name(self.db).to_string(),
+                );
+            }
+
+            // Report the call to the runtime, so it can be profiled
+            let name = function.name(self.db).to_string();
+            let name_ptr: BasicValueEnum = self
+                .builder
+                .build_global_string_ptr(&name, "fn_name")
+                .as_pointer_value()
+                .into();
+            let enter_fn_ptr = self.dispatch_table.gen_intrinsic_lookup(
+                self.external_globals.dispatch_table,
+                &self.builder,
+                &intrinsics::enter_call,
+            );
+            self.builder.build_call(enter_fn_ptr, &[name_ptr], "");
+            let call = self.builder.build_call(ptr_value, &args, &name);
+            let exit_fn_ptr = self.dispatch_table.gen_intrinsic_lookup(
+                self.external_globals.dispatch_table,
+                &self.builder,
+                &intrinsics::exit_call,
+            );
+            self.builder.build_call(exit_fn_ptr, &[name_ptr], "");
+            call
         } else {
             let llvm_function = self.function_map.get(&function).unwrap_or_else(|| {
                 panic!(
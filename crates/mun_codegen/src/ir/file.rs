@@ -1,9 +1,9 @@
 use super::body::ExternalGlobals;
 use crate::ir::{function, type_table::TypeTable};
 use crate::value::Global;
-use crate::{CodeGenParams, IrDatabase};
+use crate::{CodeGenParams, IrDatabase, SymbolVisibility};
 use hir::{FileId, ModuleDef};
-use inkwell::module::Module;
+use inkwell::{module::Module, GlobalVisibility};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 
@@ -41,6 +41,13 @@ pub(crate) fn ir_query(db: &impl IrDatabase, file_id: FileId) -> Arc<FileIR> {
                         make_marshallable: false,
                     },
                 );
+                // Functions marked `pub` always keep the default visibility
+                if db.default_visibility() == SymbolVisibility::Hidden
+                    && f.data(db).visibility().is_private()
+                {
+                    fun.as_global_value()
+                        .set_visibility(GlobalVisibility::Hidden);
+                }
                 functions.insert(*f, fun);
 
                 let fn_sig = f.ty(db).callable_sig(db).unwrap();
@@ -88,6 +95,7 @@ pub(crate) fn ir_query(db: &impl IrDatabase, file_id: FileId) -> Arc<FileIR> {
             db,
             (*hir_function, *llvm_function),
             &functions,
+            &llvm_module,
             &group_ir.dispatch_table,
             &group_ir.type_table,
             external_globals.clone(),
@@ -100,6 +108,7 @@ pub(crate) fn ir_query(db: &impl IrDatabase, file_id: FileId) -> Arc<FileIR> {
             db,
             (*hir_function, *llvm_function),
             &functions,
+            &llvm_module,
             &group_ir.dispatch_table,
             &group_ir.type_table,
             external_globals.clone(),
@@ -53,6 +53,7 @@ pub(crate) fn gen_body<'a, 'b, D: IrDatabase>(
     db: &'a D,
     function: (hir::Function, FunctionValue),
     llvm_functions: &'a HashMap<hir::Function, FunctionValue>,
+    module: &'b Module,
     dispatch_table: &'b DispatchTable,
     type_table: &'b TypeTable,
     external_globals: ExternalGlobals,
@@ -61,6 +62,7 @@ pub(crate) fn gen_body<'a, 'b, D: IrDatabase>(
         db,
         function,
         llvm_functions,
+        module,
         dispatch_table,
         type_table,
         CodeGenParams {
@@ -78,6 +80,7 @@ pub(crate) fn gen_wrapper_body<'a, 'b, D: IrDatabase>(
     db: &'a D,
     function: (hir::Function, FunctionValue),
     llvm_functions: &'a HashMap<hir::Function, FunctionValue>,
+    module: &'b Module,
     dispatch_table: &'b DispatchTable,
     type_table: &'b TypeTable,
     external_globals: ExternalGlobals,
@@ -86,6 +89,7 @@ pub(crate) fn gen_wrapper_body<'a, 'b, D: IrDatabase>(
         db,
         function,
         llvm_functions,
+        module,
         dispatch_table,
         type_table,
         CodeGenParams {
@@ -40,7 +40,12 @@ fn collect_expr<D: IrDatabase>(
                 // self.collect_intrinsic(module, entries, &intrinsics::drop);
                 *needs_alloc = true;
             }
-            Some(hir::CallableDef::Function(_)) => (),
+            Some(hir::CallableDef::Function(_)) => {
+                if db.instrument_calls() {
+                    collect_intrinsic(db, entries, &intrinsics::enter_call);
+                    collect_intrinsic(db, entries, &intrinsics::exit_call);
+                }
+            }
             None => panic!("expected a callable expression"),
         }
     }
@@ -16,10 +16,11 @@ pub(crate) mod intrinsics;
 pub(crate) mod type_info;
 
 pub use inkwell::{builder::Builder, context::Context, module::Module, OptimizationLevel};
+pub use mun_lld::LLVM_VERSION;
 
 pub use crate::{
     assembly::Assembly,
-    code_gen::ModuleBuilder,
+    code_gen::{check_target, host_cpu_name, BackendUnavailable, ModuleBuilder},
     db::{IrDatabase, IrDatabaseStorage},
 };
 
@@ -29,3 +30,35 @@ pub struct CodeGenParams {
     /// This allows function parameters with `struct(value)` types to be marshalled.
     make_marshallable: bool,
 }
+
+/// The visibility of the symbols of functions that are not marked `pub` in the source.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SymbolVisibility {
+    /// Symbols are visible outside of the shared object, which is the default of LLVM.
+    Public,
+    /// Symbols are hidden, reducing the surface exported by the shared object.
+    Hidden,
+}
+
+impl Default for SymbolVisibility {
+    fn default() -> Self {
+        SymbolVisibility::Public
+    }
+}
+
+/// What happens when integer addition, subtraction or multiplication overflows.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum OverflowBehavior {
+    /// The result wraps around in two's complement, which is the default.
+    Wrap,
+    /// The program is aborted.
+    Panic,
+    /// The result is clamped to the minimum or maximum value of the type.
+    Saturate,
+}
+
+impl Default for OverflowBehavior {
+    fn default() -> Self {
+        OverflowBehavior::Wrap
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{IrDatabase, OptimizationLevel};
+use crate::{IrDatabase, OptimizationLevel, OverflowBehavior, SymbolVisibility};
 use hir::{FileId, RelativePathBuf, SourceDatabase, SourceRoot, SourceRootId};
 use parking_lot::Mutex;
 use std::sync::Arc;
@@ -47,6 +47,9 @@ impl MockDatabase {
 
         db.set_source_root(source_root_id, Arc::new(source_root));
         db.set_optimization_lvl(OptimizationLevel::None);
+        db.set_default_visibility(SymbolVisibility::default());
+        db.set_overflow_behavior(OverflowBehavior::default());
+        db.set_instrument_calls(false);
 
         let context = crate::Context::create();
         db.set_context(Arc::new(context));
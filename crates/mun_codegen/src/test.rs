@@ -1,4 +1,4 @@
-use crate::{mock::MockDatabase, IrDatabase, ModuleBuilder};
+use crate::{mock::MockDatabase, IrDatabase, ModuleBuilder, OverflowBehavior, SymbolVisibility};
 use hir::{
     diagnostics::DiagnosticSink, line_index::LineIndex, HirDatabase, Module, SourceDatabase,
 };
@@ -818,6 +818,81 @@ fn incremental_compilation() {
     // TODO: Add support for multiple files in a group
 }
 
+#[test]
+fn default_visibility_hidden() {
+    let (mut db, file_id) = MockDatabase::with_single_file(
+        r#"
+        fn private_fn() -> i32 { 5 }
+
+        pub fn main() -> i32 {
+            private_fn()
+        }
+        "#,
+    );
+    db.set_target(Target::host_target().unwrap());
+
+    let ir = db
+        .file_ir(file_id)
+        .llvm_module
+        .print_to_string()
+        .to_string();
+    assert!(!ir.contains("define hidden"), "{}", ir);
+
+    db.set_default_visibility(SymbolVisibility::Hidden);
+
+    let ir = db
+        .file_ir(file_id)
+        .llvm_module
+        .print_to_string()
+        .to_string();
+    assert!(ir.contains("define hidden i32 @private_fn"), "{}", ir);
+    assert!(!ir.contains("define hidden i32 @main"), "{}", ir);
+}
+
+#[test]
+fn overflow_behavior() {
+    let (mut db, file_id) = MockDatabase::with_single_file(
+        r#"
+        pub fn add(a: i32, b: i32) -> i32 { a + b }
+        pub fn mul(a: u8, b: u8) -> u8 { a * b }
+        "#,
+    );
+    db.set_target(Target::host_target().unwrap());
+
+    let ir = |db: &MockDatabase| {
+        db.file_ir(file_id)
+            .llvm_module
+            .print_to_string()
+            .to_string()
+    };
+
+    let wrapping_ir = ir(&db);
+    assert!(wrapping_ir.contains("add i32"), "{}", wrapping_ir);
+    assert!(!wrapping_ir.contains("@llvm."), "{}", wrapping_ir);
+
+    db.set_overflow_behavior(OverflowBehavior::Saturate);
+    let saturating_ir = ir(&db);
+    assert!(
+        saturating_ir.contains("@llvm.sadd.sat.i32"),
+        "{}",
+        saturating_ir
+    );
+    assert!(
+        saturating_ir.contains("@llvm.umul.with.overflow.i8"),
+        "{}",
+        saturating_ir
+    );
+
+    db.set_overflow_behavior(OverflowBehavior::Panic);
+    let checked_ir = ir(&db);
+    assert!(
+        checked_ir.contains("@llvm.sadd.with.overflow.i32"),
+        "{}",
+        checked_ir
+    );
+    assert!(checked_ir.contains("@llvm.trap"), "{}", checked_ir);
+}
+
 #[test]
 fn nested_structs() {
     test_snapshot(
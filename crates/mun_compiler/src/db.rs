@@ -31,8 +31,21 @@ impl CompilerDatabase {
 
     /// Applies the given configuration to the database
     pub fn set_config(&mut self, config: &Config) {
-        self.set_target(config.target.clone());
+        let mut target = config.target.clone();
+        if let Some(data_layout) = &config.data_layout {
+            target.data_layout = data_layout.clone();
+        }
+        if let Some(cpu) = &config.cpu {
+            target.options.cpu = cpu.clone();
+        }
+        if let Some(target_features) = &config.target_features {
+            target.options.features = target_features.clone();
+        }
+        self.set_target(target);
         self.set_optimization_lvl(config.optimization_lvl);
+        self.set_default_visibility(config.default_visibility);
+        self.set_overflow_behavior(config.overflow_behavior);
+        self.set_instrument_calls(config.instrument_calls);
     }
 }
 
@@ -0,0 +1,223 @@
+//! The codes that identify the kinds of diagnostics emitted by the compiler, together with an
+//! extended description of every kind, as printed by `mun --explain <code>`.
+
+pub(crate) const SYNTAX_ERROR: &str = "E001";
+pub(crate) const UNRESOLVED_VALUE: &str = "E002";
+pub(crate) const UNRESOLVED_TYPE: &str = "E003";
+pub(crate) const EXPECTED_FUNCTION: &str = "E004";
+pub(crate) const MISMATCHED_TYPE: &str = "E005";
+pub(crate) const DUPLICATE_DEFINITION: &str = "E006";
+pub(crate) const POSSIBLY_UNINITIALIZED_VARIABLE: &str = "E007";
+pub(crate) const ACCESS_UNKNOWN_FIELD: &str = "E008";
+pub(crate) const NON_SNAKE_CASE_FUNCTION: &str = "W001";
+
+/// A kind of diagnostic, identified by its code.
+#[derive(Debug, PartialEq)]
+pub struct DiagnosticCode {
+    /// The code, e.g. `E001`
+    pub code: &'static str,
+    /// A one-line summary of the diagnostic
+    pub summary: &'static str,
+    /// What causes the diagnostic and how to resolve it, including an example
+    pub explanation: &'static str,
+}
+
+impl DiagnosticCode {
+    /// Renders the code, its summary and the explanation as text.
+    pub fn to_text(&self) -> String {
+        format!("{}: {}\n\n{}", self.code, self.summary, self.explanation)
+    }
+}
+
+/// All diagnostic codes, ordered by code.
+pub const DIAGNOSTIC_CODES: &[DiagnosticCode] = &[
+    DiagnosticCode {
+        code: SYNTAX_ERROR,
+        summary: "syntax error",
+        explanation: "\
+The source code does not follow the grammar of the language, e.g. because of a missing
+delimiter:
+
+    fn main( {
+    }
+
+Compilation continues after a syntax error, so later errors may be caused by it. Fix the
+first syntax error in a file before looking at the others. `mun fix` inserts missing closing
+delimiters where it can:
+
+    fn main() {
+    }
+",
+    },
+    DiagnosticCode {
+        code: UNRESOLVED_VALUE,
+        summary: "cannot find value in this scope",
+        explanation: "\
+A name is used that does not refer to a variable, parameter or function that is visible at
+that point:
+
+    fn main() {
+        let b = a;
+    }
+
+Check the spelling of the name, or declare it before it is used:
+
+    fn main() {
+        let a = 1;
+        let b = a;
+    }
+",
+    },
+    DiagnosticCode {
+        code: UNRESOLVED_TYPE,
+        summary: "cannot find type in this scope",
+        explanation: "\
+A type is used that is not a built-in type or a struct defined in the module:
+
+    fn main() {
+        let a = Foo { x: 1.0 };
+    }
+
+Check the spelling of the type, or define the struct:
+
+    struct Foo { x: f64 }
+
+    fn main() {
+        let a = Foo { x: 1.0 };
+    }
+",
+    },
+    DiagnosticCode {
+        code: EXPECTED_FUNCTION,
+        summary: "expected function type",
+        explanation: "\
+A value that is not a function is called:
+
+    fn main() {
+        let a = 5;
+        a();
+    }
+
+Only functions can be called. Make sure the name refers to a function and is not shadowed by
+a variable with the same name.
+",
+    },
+    DiagnosticCode {
+        code: MISMATCHED_TYPE,
+        summary: "mismatched type",
+        explanation: "\
+An expression has a different type than the type that is expected at its location, e.g. the
+declared type of a variable or the return type of a function:
+
+    fn main() {
+        let a: f64 = false;
+    }
+
+Values are never converted implicitly. Change the expression or the expected type so that
+they agree:
+
+    fn main() {
+        let a: f64 = 0.0;
+    }
+",
+    },
+    DiagnosticCode {
+        code: DUPLICATE_DEFINITION,
+        summary: "the name is defined multiple times",
+        explanation: "\
+Two items of a module have the same name in the same namespace. Functions share the value
+namespace, structs share the type namespace, and unit and tuple structs are also values:
+
+    fn foo() {}
+    fn foo() {}
+
+Rename or remove one of the definitions.
+",
+    },
+    DiagnosticCode {
+        code: POSSIBLY_UNINITIALIZED_VARIABLE,
+        summary: "use of possibly-uninitialized variable",
+        explanation: "\
+A variable is read while it may not have been assigned a value on every path that leads to
+the read:
+
+    fn main() {
+        let a;
+        if 5 > 6 {
+            a = 5
+        }
+        let b = a;
+    }
+
+Assign a value to the variable on every path, e.g. by adding an `else` branch or by
+initializing it when it is declared:
+
+    fn main() {
+        let a = 0;
+        if 5 > 6 {
+            a = 5
+        }
+        let b = a;
+    }
+",
+    },
+    DiagnosticCode {
+        code: ACCESS_UNKNOWN_FIELD,
+        summary: "no field on type",
+        explanation: "\
+A field is accessed that the struct does not have:
+
+    struct Foo { i: bool }
+
+    fn main() {
+        let a = Foo { i: false };
+        let b = a.t;
+    }
+
+Check the spelling of the field against the definition of the struct.
+",
+    },
+    DiagnosticCode {
+        code: NON_SNAKE_CASE_FUNCTION,
+        summary: "function should have a snake case name",
+        explanation: "\
+The name of a function mixes uppercase and lowercase letters:
+
+    fn fooBar() {}
+
+By convention, functions are named in snake case. Rename the function and its calls:
+
+    fn foo_bar() {}
+",
+    },
+];
+
+/// Returns the diagnostic with the specified code, ignoring case.
+pub fn find(code: &str) -> Option<&'static DiagnosticCode> {
+    DIAGNOSTIC_CODES
+        .iter()
+        .find(|diagnostic| diagnostic.code.eq_ignore_ascii_case(code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find, DIAGNOSTIC_CODES};
+
+    #[test]
+    fn test_find() {
+        let diagnostic = find("E002").unwrap();
+        assert_eq!(diagnostic.summary, "cannot find value in this scope");
+        assert!(diagnostic
+            .to_text()
+            .starts_with("E002: cannot find value in this scope\n\n"));
+        assert_eq!(find("w001").map(|d| d.code), Some("W001"));
+        assert_eq!(find("E999"), None);
+    }
+
+    #[test]
+    fn test_codes_are_unique_and_ordered() {
+        for pair in DIAGNOSTIC_CODES.windows(2) {
+            assert!(pair[0].code < pair[1].code, "{}", pair[1].code);
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use mun_hir::diagnostics::DiagnosticSink;
 use mun_hir::{FileId, HirDatabase, Module};
+use mun_syntax::ast::{self, ModuleItemOwner, NameOwner};
 
 use std::cell::RefCell;
 
-use crate::diagnostics_snippets;
+use crate::{diagnostics_snippets, Stage};
 use annotate_snippets::{
     display_list::DisplayList, formatter::DisplayListFormatter, snippet::Snippet,
 };
@@ -22,8 +23,9 @@ pub fn emit_diagnostics<'a>(
     Ok(())
 }
 
-/// Constructs diagnostic messages for the given file.
-pub fn diagnostics(db: &impl HirDatabase, file_id: FileId) -> Vec<Snippet> {
+/// Constructs diagnostic messages for the given file. If `stop_after` is [`Stage::Parse`], only
+/// syntax errors are reported.
+pub fn diagnostics(db: &impl HirDatabase, file_id: FileId, stop_after: Stage) -> Vec<Snippet> {
     let parse = db.parse(file_id);
 
     let mut result = Vec::new();
@@ -47,6 +49,9 @@ pub fn diagnostics(db: &impl HirDatabase, file_id: FileId) -> Vec<Snippet> {
             &line_index,
         )
     }));
+    if stop_after == Stage::Parse {
+        return result;
+    }
 
     let result = RefCell::new(result);
     let mut sink = DiagnosticSink::new(|d| {
@@ -150,7 +155,26 @@ pub fn diagnostics(db: &impl HirDatabase, file_id: FileId) -> Vec<Snippet> {
 
     drop(sink);
 
-    result.into_inner()
+    let mut result = result.into_inner();
+    result.extend(
+        parse
+            .tree()
+            .items()
+            .filter_map(|item| match item.kind() {
+                ast::ModuleItemKind::FunctionDef(function) => function.name(),
+                _ => None,
+            })
+            .filter(|name| diagnostics_snippets::is_camel_case(name.text()))
+            .map(|name| {
+                diagnostics_snippets::non_snake_case_function_warning(
+                    &name,
+                    &relative_file_path,
+                    &source_code,
+                    &line_index,
+                )
+            }),
+    );
+    result
 }
 
 #[cfg(test)]
@@ -234,4 +258,13 @@ mod tests {
             "\n\nstruct Foo {\ni: bool\n}\n\nfn main() {\nlet a = Foo { i: false };\nlet b = a.t;\n}"
         ));
     }
+
+    #[test]
+    fn test_non_snake_case_function_warning() {
+        let output = compilation_errors("fn fooBar() {}\n\nfn foo_bar() {}\n\nfn BAZ() {}");
+        assert!(output.contains("warning[W001]: function `fooBar` should have a snake case name"));
+        assert!(output.contains("help: convert the identifier to snake case: `foo_bar`"));
+        assert!(!output.contains("`foo_bar` should"));
+        assert!(!output.contains("`BAZ`"));
+    }
 }
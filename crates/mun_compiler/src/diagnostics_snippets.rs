@@ -9,6 +9,7 @@ use std::sync::Arc;
 use mun_hir::line_index::LineIndex;
 
 use crate::annotate::{AnnotationBuilder, SliceBuilder, SnippetBuilder};
+use crate::diagnostic_codes;
 
 use annotate_snippets::snippet::{AnnotationType, Snippet};
 
@@ -46,6 +47,7 @@ pub(crate) fn syntax_error(
     let mut snippet = SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic_codes::SYNTAX_ERROR)
                 .label("syntax error")
                 .build(),
         )
@@ -113,6 +115,7 @@ pub(crate) fn unresolved_value_error(
     SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic_codes::UNRESOLVED_VALUE)
                 .label(&format!(
                     "cannot find value `{}` in this scope",
                     unresolved_value
@@ -150,6 +153,7 @@ pub(crate) fn unresolved_type_error(
     SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic_codes::UNRESOLVED_TYPE)
                 .label(&format!(
                     "cannot find type `{}` in this scope",
                     unresolved_type
@@ -180,6 +184,7 @@ pub(crate) fn expected_function_error(
     SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic_codes::EXPECTED_FUNCTION)
                 .label(&diagnostic.message())
                 .build(),
         )
@@ -210,6 +215,7 @@ pub(crate) fn mismatched_type_error(
     SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic_codes::MISMATCHED_TYPE)
                 .label(&diagnostic.message())
                 .build(),
         )
@@ -253,6 +259,7 @@ pub(crate) fn duplicate_definition_error(
     SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic_codes::DUPLICATE_DEFINITION)
                 .label(&diagnostic.message())
                 .build(),
         )
@@ -300,6 +307,7 @@ pub(crate) fn possibly_uninitialized_variable_error(
     SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic_codes::POSSIBLY_UNINITIALIZED_VARIABLE)
                 .label(&format!("{}: `{}`", diagnostic.message(), variable_name))
                 .build(),
         )
@@ -331,6 +339,7 @@ pub(crate) fn access_unknown_field_error(
     SnippetBuilder::new()
         .title(
             AnnotationBuilder::new(AnnotationType::Error)
+                .id(diagnostic_codes::ACCESS_UNKNOWN_FIELD)
                 .label(&format!(
                     "no field `{}` on type `{}`",
                     diagnostic.name,
@@ -351,10 +360,78 @@ pub(crate) fn access_unknown_field_error(
         .build()
 }
 
+pub(crate) fn non_snake_case_function_warning(
+    name: &ast::Name,
+    relative_file_path: &str,
+    source_code: &str,
+    line_index: &Arc<LineIndex>,
+) -> Snippet {
+    let name_text = name.text().to_string();
+
+    SnippetBuilder::new()
+        .title(
+            AnnotationBuilder::new(AnnotationType::Warning)
+                .id(diagnostic_codes::NON_SNAKE_CASE_FUNCTION)
+                .label(&format!(
+                    "function `{}` should have a snake case name",
+                    name_text
+                ))
+                .build(),
+        )
+        .slice(
+            SliceBuilder::new(true)
+                .origin(relative_file_path)
+                .source_annotation(
+                    text_range_to_tuple(name.syntax().text_range()),
+                    &format!(
+                        "help: convert the identifier to snake case: `{}`",
+                        to_snake_case(&name_text)
+                    ),
+                    AnnotationType::Warning,
+                )
+                .build(&source_code, &line_index),
+        )
+        .build()
+}
+
+/// Returns true if `name` mixes uppercase and lowercase letters, e.g. `fooBar` or `FooBar`.
+/// Names that are entirely uppercase are not considered camel case.
+pub(crate) fn is_camel_case(name: &str) -> bool {
+    name.chars().any(char::is_uppercase) && name.chars().any(char::is_lowercase)
+}
+
+/// Converts a camel case identifier to snake case, e.g. `FooBar` to `foo_bar`.
+fn to_snake_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len() + 4);
+    let mut previous_is_lower = false;
+    for c in name.chars() {
+        if c.is_uppercase() {
+            if previous_is_lower {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+            previous_is_lower = false;
+        } else {
+            result.push(c);
+            previous_is_lower = c.is_lowercase() || c.is_ascii_digit();
+        }
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_to_snake_case() {
+        assert!(is_camel_case("fooBar"));
+        assert!(!is_camel_case("foo_bar2"));
+        assert!(!is_camel_case("BAZ"));
+        assert_eq!(to_snake_case("FooBar"), "foo_bar");
+        assert_eq!(to_snake_case("fooBar2Baz"), "foo_bar2_baz");
+    }
+
     #[test]
     fn test_text_range_to_tuple() {
         let text_range = TextRange::from_to(3.into(), 5.into());
@@ -5,17 +5,22 @@ use crate::{
     compute_source_relative_path,
     db::CompilerDatabase,
     diagnostics::{diagnostics, emit_diagnostics},
-    ensure_package_output_dir, is_source_file, PathOrInline, RelativePath,
+    ensure_package_output_dir,
+    fix::{fixes, Fix},
+    is_source_file, iter_package_source_files, read_source_file, PathOrInline, RelativePath,
+};
+use mun_codegen::{Assembly, IrDatabase, ModuleBuilder};
+use mun_hir::{
+    Expr, FileId, HirDatabase, HirDisplay, Module, ModuleDef, RelativePathBuf, SourceDatabase,
+    SourceRoot, SourceRootId, Statement,
 };
-use mun_codegen::{Assembly, IrDatabase};
-use mun_hir::{FileId, RelativePathBuf, SourceDatabase, SourceRoot, SourceRootId};
 
 use std::{path::PathBuf, sync::Arc};
 
 mod config;
 mod display_color;
 
-pub use self::config::Config;
+pub use self::config::{Config, Emit, InputEncoding, LinkCommandHook, PostCompileHook, Stage};
 pub use self::display_color::DisplayColor;
 
 use annotate_snippets::snippet::{AnnotationType, Snippet};
@@ -27,6 +32,22 @@ use walkdir::WalkDir;
 
 pub const WORKSPACE: SourceRootId = SourceRootId(0);
 
+/// Formats a command line so that it can be pasted into a shell; arguments that contain
+/// whitespace or quotes are quoted.
+pub fn format_command(command: &[String]) -> String {
+    command
+        .iter()
+        .map(|arg| {
+            if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || c == '"') {
+                format!("\"{}\"", arg.replace('"', "\\\""))
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 #[derive(Debug)]
 pub struct Driver {
     db: CompilerDatabase,
@@ -40,6 +61,12 @@ pub struct Driver {
     file_id_to_temp_assembly_path: HashMap<FileId, PathBuf>,
 
     display_color: DisplayColor,
+    emit: Emit,
+    post_compile: Option<PostCompileHook>,
+    link_command_hook: Option<LinkCommandHook>,
+    deny_warnings: bool,
+    stop_after: Stage,
+    out_name: Option<String>,
 }
 
 impl Driver {
@@ -54,6 +81,12 @@ impl Driver {
             next_file_id: 0,
             file_id_to_temp_assembly_path: Default::default(),
             display_color: config.display_color,
+            emit: config.emit,
+            post_compile: config.post_compile,
+            link_command_hook: config.link_command_hook,
+            deny_warnings: config.deny_warnings,
+            stop_after: config.stop_after,
+            out_name: config.out_name,
         })
     }
 
@@ -65,6 +98,7 @@ impl Driver {
         let out_dir = config.out_dir.clone().unwrap_or_else(|| {
             std::env::current_dir().expect("could not determine current working directory")
         });
+        let input_encoding = config.input_encoding;
 
         let mut driver = Driver::with_config(config, out_dir)?;
 
@@ -79,7 +113,7 @@ impl Driver {
                 })?;
                 (
                     RelativePathBuf::from_path(filename).unwrap(),
-                    std::fs::read_to_string(p)?,
+                    read_source_file(&p, input_encoding)?,
                 )
             }
             PathOrInline::Inline { rel_path, contents } => (rel_path, contents),
@@ -106,12 +140,18 @@ impl Driver {
     ) -> Result<(Package, Driver), anyhow::Error> {
         // Load the manifest file as a package
         let package = Package::from_file(package_path)?;
+        let package = match &config.package_version {
+            Some(version) => package.with_version(version.clone()),
+            None => package,
+        };
+        log::info!("compiling {}", package);
 
         // Determine output directory
         let output_dir = ensure_package_output_dir(&package, &config)
             .map_err(|e| anyhow::anyhow!("could not create package output directory: {}", e))?;
 
         // Construct the driver
+        let input_encoding = config.input_encoding;
         let mut driver = Driver::with_config(config, output_dir)?;
 
         // Iterate over all files in the source directory of the package and store their information in
@@ -120,17 +160,11 @@ impl Driver {
             .source_directory()
             .ok_or_else(|| anyhow::anyhow!("the source directory does not exist"))?;
 
-        for source_file_path in iter_source_files(&source_directory) {
+        for source_file_path in iter_package_source_files(package.root(), &source_directory)? {
             let relative_path = compute_source_relative_path(&source_directory, &source_file_path)?;
 
             // Load the contents of the file
-            let file_contents = std::fs::read_to_string(&source_file_path).map_err(|e| {
-                anyhow::anyhow!(
-                    "could not read contents of '{}': {}",
-                    source_file_path.display(),
-                    e
-                )
-            })?;
+            let file_contents = read_source_file(&source_file_path, input_encoding)?;
 
             let file_id = driver.alloc_file_id(&relative_path)?;
             driver
@@ -198,13 +232,56 @@ impl Driver {
         self.db
             .source_root(WORKSPACE)
             .files()
-            .map(|f| diagnostics(&self.db, f))
+            .map(|f| diagnostics(&self.db, f, self.stop_after))
             .flatten()
             .collect()
     }
 
+    /// Returns the inferred type of the value that is bound by the last `let` statement in the body
+    /// of the function `function_name` in the specified file, as it is written in source, e.g.
+    /// `i32`, or `nothing` if it has no value. Returns `None` if there is no such function or its
+    /// body does not end with a `let` statement with an initializer.
+    pub fn type_of_last_binding(&self, file_id: FileId, function_name: &str) -> Option<String> {
+        let function = Module::from(file_id)
+            .declarations(&self.db)
+            .into_iter()
+            .find_map(|def| match def {
+                ModuleDef::Function(function)
+                    if function.name(&self.db).to_string() == function_name =>
+                {
+                    Some(function)
+                }
+                _ => None,
+            })?;
+
+        let body = function.body(&self.db);
+        let initializer = match &body[body.body_expr()] {
+            Expr::Block { statements, .. } => match statements.last()? {
+                Statement::Let { initializer, .. } => (*initializer)?,
+                Statement::Expr(_) => return None,
+            },
+            _ => return None,
+        };
+        Some(
+            function.infer(&self.db)[initializer]
+                .display(&self.db)
+                .to_string(),
+        )
+    }
+
+    /// Returns all machine-applicable fixes for the project, grouped by the relative path of the
+    /// file they apply to. Files without fixes are omitted.
+    pub fn fixes(&self) -> Vec<(RelativePathBuf, Vec<Fix>)> {
+        self.db
+            .source_root(WORKSPACE)
+            .files()
+            .map(|f| (self.db.file_relative_path(f), fixes(&self.db, f)))
+            .filter(|(_, fixes)| !fixes.is_empty())
+            .collect()
+    }
+
     /// Emits all diagnostic messages currently in the database; returns true if errors were
-    /// emitted.
+    /// emitted, or warnings if warnings are denied.
     pub fn emit_diagnostics(&self, writer: &mut dyn std::io::Write) -> Result<bool, anyhow::Error> {
         let diagnostics = self.diagnostics();
 
@@ -212,11 +289,13 @@ impl Driver {
         emit_diagnostics(writer, &diagnostics, self.display_color.should_enable())?;
 
         // Determine if one of the snippets is actually an error
+        let deny_warnings = self.deny_warnings;
         Ok(diagnostics.iter().any(|d| {
             d.title
                 .as_ref()
                 .map(|a| match a.annotation_type {
                     AnnotationType::Error => true,
+                    AnnotationType::Warning => deny_warnings,
                     _ => false,
                 })
                 .unwrap_or(false)
@@ -227,20 +306,90 @@ impl Driver {
 impl Driver {
     /// Get the path where the driver will write the assembly for the specified file.
     pub fn assembly_output_path(&self, file_id: FileId) -> PathBuf {
-        self.db
-            .file_relative_path(file_id)
-            .with_extension(Assembly::EXTENSION)
-            .to_path(&self.out_dir)
+        let relative_path = self.db.file_relative_path(file_id);
+        let extension = Assembly::extension(&self.db.target());
+        match &self.out_name {
+            Some(out_name)
+                if relative_path.as_str() == "main.mun"
+                    || self.source_root.files().count() == 1 =>
+            {
+                relative_path.with_file_name(format!("{}.{}", out_name, extension))
+            }
+            _ => relative_path.with_extension(extension),
+        }
+        .to_path(&self.out_dir)
+    }
+
+    /// Get the paths where the driver writes the assemblies of all files.
+    pub fn assembly_output_paths(&self) -> Vec<PathBuf> {
+        self.source_root
+            .files()
+            .map(|file_id| self.assembly_output_path(file_id))
+            .collect()
+    }
+
+    /// Returns the relative path of every source file together with the path where the driver
+    /// writes the assembly that is compiled from it.
+    pub fn source_assembly_paths(&self) -> Vec<(RelativePathBuf, PathBuf)> {
+        self.source_root
+            .files()
+            .map(|file_id| {
+                (
+                    self.db.file_relative_path(file_id),
+                    self.assembly_output_path(file_id),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the relative path of every source file together with the name and compiled size in
+    /// bytes of each of its exported functions, sorted by size in descending order.
+    pub fn function_sizes(&self) -> Vec<(RelativePathBuf, Vec<(String, u64)>)> {
+        self.source_root
+            .files()
+            .map(|file_id| {
+                (
+                    self.db.file_relative_path(file_id),
+                    self.db.assembly(file_id).function_sizes().to_vec(),
+                )
+            })
+            .collect()
+    }
+
+    /// Generates the assemblies of all files without writing them. Writing the assemblies
+    /// afterwards only copies the generated files. Does nothing if the configuration stops before
+    /// code generation.
+    pub fn generate_assemblies(&self) {
+        if self.stop_after < Stage::Codegen {
+            return;
+        }
+        for file_id in self.source_root.files() {
+            self.db.assembly(file_id);
+        }
     }
 
     /// Writes all assemblies
     pub fn write_all_assemblies(&mut self) -> Result<(), anyhow::Error> {
+        self.write_changed_assemblies().map(|_| ())
+    }
+
+    /// Writes the assemblies of all files that changed since their assemblies were last written.
+    /// Returns the relative paths of the files whose assemblies were written, which is none if the
+    /// configuration stops before code generation.
+    pub fn write_changed_assemblies(&mut self) -> Result<Vec<RelativePathBuf>, anyhow::Error> {
+        if self.stop_after < Stage::Codegen {
+            return Ok(Vec::new());
+        }
+
         // Create a copy of all current files
         let files = self.source_root.files().collect::<Vec<_>>();
+        let mut written = Vec::new();
         for file_id in files {
-            self.write_assembly(file_id, false)?;
+            if self.write_assembly(file_id, false)? {
+                written.push(self.db.file_relative_path(file_id));
+            }
         }
-        Ok(())
+        Ok(written)
     }
 
     /// Generates an assembly for the given file and stores it in the output location. If `force` is
@@ -267,8 +416,30 @@ impl Driver {
             return Ok(false);
         }
 
+        if let Some(post_compile) = &self.post_compile {
+            post_compile.call(assembly.module()).map_err(|e| {
+                anyhow::anyhow!(
+                    "post-compile hook rejected '{}': {}",
+                    self.db.file_relative_path(file_id),
+                    e
+                )
+            })?;
+        }
+
+        if let Some(link_command_hook) = &self.link_command_hook {
+            link_command_hook.call(assembly.link_command());
+        }
+
         // It did change or we are forced, so write it to disk
         assembly.copy_to(&assembly_path)?;
+        if let Some(extension) = self.emit.extension() {
+            let builder = ModuleBuilder::new(&self.db, file_id)?;
+            let contents = match self.emit {
+                Emit::LlvmIr => builder.build_llvm_ir()?,
+                _ => builder.build_assembly()?,
+            };
+            std::fs::write(assembly_path.with_extension(extension), contents)?;
+        }
 
         // Store the information so we maybe don't have to write it next time
         self.file_id_to_temp_assembly_path
@@ -1,7 +1,9 @@
 use crate::DisplayColor;
-pub use mun_codegen::OptimizationLevel;
+pub use mun_codegen::{Module, OptimizationLevel, OverflowBehavior, SymbolVisibility};
 use mun_target::spec::Target;
+use std::fmt;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 /// Describes all the permanent settings that are used during compilations.
 #[derive(Debug, Clone)]
@@ -18,6 +20,251 @@ pub struct Config {
 
     /// Whether or not to use colors in terminal output
     pub display_color: DisplayColor,
+
+    /// An optional [data layout](http://llvm.org/docs/LangRef.html#data-layout) that overrides the
+    /// default data layout of the target.
+    pub data_layout: Option<String>,
+
+    /// An optional CPU that overrides the default CPU of the target, which is usually `generic`.
+    pub cpu: Option<String>,
+
+    /// Optional LLVM target features, e.g. `+avx2,-sse4.1`, that override the default features of
+    /// the target.
+    pub target_features: Option<String>,
+
+    /// An optional version that overrides the version specified in the manifest of the package.
+    pub package_version: Option<mun_project::Version>,
+
+    /// Whether the linker command of every assembly that is written should be printed. The
+    /// compiler itself does not print anything; frontends print the commands that are passed to
+    /// `link_command_hook`.
+    pub print_commands: bool,
+
+    /// The visibility of the symbols of functions that are not marked `pub` in the source.
+    pub default_visibility: SymbolVisibility,
+
+    /// What happens when integer addition, subtraction or multiplication overflows.
+    pub overflow_behavior: OverflowBehavior,
+
+    /// Whether calls between Mun functions are reported to the runtime, so a profiling runtime
+    /// counts them too.
+    pub instrument_calls: bool,
+
+    /// Additional output to write next to every assembly.
+    pub emit: Emit,
+
+    /// An optional hook that inspects the module of every file before its assembly is written.
+    pub post_compile: Option<PostCompileHook>,
+
+    /// An optional hook that receives the linker command of every assembly that is written.
+    pub link_command_hook: Option<LinkCommandHook>,
+
+    /// Whether warnings fail the build, just like errors.
+    pub deny_warnings: bool,
+
+    /// The last stage of the compilation that is run; the stages after it are skipped.
+    pub stop_after: Stage,
+
+    /// An optional file stem that overrides the name of the assembly compiled from the main file of
+    /// the package: `main.mun`, or the only source file. The extension is kept.
+    pub out_name: Option<String>,
+
+    /// The encoding in which source files are read.
+    pub input_encoding: InputEncoding,
+}
+
+/// A callback that receives the optimized module of a file, including its reflection information,
+/// before the assembly of the file is written. Returning an error prevents the assembly from being
+/// written, which allows embedders to enforce their own rules, e.g. forbidding certain exports.
+#[derive(Clone)]
+pub struct PostCompileHook(Arc<dyn Fn(&Module) -> Result<(), anyhow::Error> + Send + Sync>);
+
+impl PostCompileHook {
+    /// Constructs a hook from a callback.
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(&Module) -> Result<(), anyhow::Error> + Send + Sync + 'static,
+    {
+        PostCompileHook(Arc::new(f))
+    }
+
+    /// Invokes the hook with the specified module.
+    pub fn call(&self, module: &Module) -> Result<(), anyhow::Error> {
+        (self.0)(module)
+    }
+}
+
+impl fmt::Debug for PostCompileHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("PostCompileHook(..)")
+    }
+}
+
+/// A callback that receives the command line of the linker invocation that produced an assembly,
+/// whenever the assembly is written.
+#[derive(Clone)]
+pub struct LinkCommandHook(Arc<dyn Fn(&[String]) + Send + Sync>);
+
+impl LinkCommandHook {
+    /// Constructs a hook from a callback.
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(&[String]) + Send + Sync + 'static,
+    {
+        LinkCommandHook(Arc::new(f))
+    }
+
+    /// Invokes the hook with the specified command line.
+    pub fn call(&self, command: &[String]) {
+        (self.0)(command)
+    }
+}
+
+impl fmt::Debug for LinkCommandHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("LinkCommandHook(..)")
+    }
+}
+
+/// The kind of output that is written for every source file, in addition to its assembly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Emit {
+    /// Only the assembly (`.munlib`)
+    Munlib,
+    /// The optimized LLVM IR of the assembly (`.ll`)
+    LlvmIr,
+    /// The assembly code for the target (`.s`)
+    Assembly,
+}
+
+impl Emit {
+    /// Returns the extension of the additional output file, or `None` if nothing is written in
+    /// addition to the assembly.
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            Emit::Munlib => None,
+            Emit::LlvmIr => Some("ll"),
+            Emit::Assembly => Some("s"),
+        }
+    }
+}
+
+/// A stage of the compilation. Stages are ordered by the order in which they run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    /// Parsing the source files; only syntax errors are reported
+    Parse,
+    /// Name resolution and type inference; all diagnostics are reported but no code is generated
+    Typecheck,
+    /// Generating and writing the assemblies, i.e. a full build
+    Codegen,
+}
+
+impl Stage {
+    /// Returns the name of the stage as it is specified on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Parse => "parse",
+            Stage::Typecheck => "typecheck",
+            Stage::Codegen => "codegen",
+        }
+    }
+}
+
+/// The character encoding of source files. Files are transcoded to UTF-8 before they are parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputEncoding {
+    /// UTF-8; a leading byte order mark is ignored
+    Utf8,
+    /// ISO-8859-1, in which every byte is the code point of a character
+    Latin1,
+}
+
+impl InputEncoding {
+    /// Returns the name of the encoding as it is specified on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            InputEncoding::Utf8 => "utf-8",
+            InputEncoding::Latin1 => "latin-1",
+        }
+    }
+}
+
+impl Default for InputEncoding {
+    fn default() -> Self {
+        InputEncoding::Utf8
+    }
+}
+
+impl Config {
+    /// Applies raw compiler flags of the form `-C key=value` (or `-Ckey=value`) on top of this
+    /// configuration. This provides access to compiler options that are not (yet) exposed
+    /// otherwise. Supported keys are `opt-level`, `target`, `data-layout` and
+    /// `print-commands`.
+    pub fn apply_flags<S: AsRef<str>>(&mut self, flags: &[S]) -> Result<(), anyhow::Error> {
+        let mut flags = flags.iter().map(AsRef::as_ref);
+        while let Some(flag) = flags.next() {
+            let option = if flag == "-C" {
+                flags
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("compiler flag '-C' requires a value"))?
+            } else if flag.starts_with("-C") {
+                &flag[2..]
+            } else {
+                return Err(anyhow::anyhow!(
+                    "unknown compiler flag '{}', expected '-C key=value'",
+                    flag
+                ));
+            };
+            self.apply_option(option)?;
+        }
+        Ok(())
+    }
+
+    /// Applies a single `key=value` compiler option.
+    fn apply_option(&mut self, option: &str) -> Result<(), anyhow::Error> {
+        let mut parts = option.splitn(2, '=');
+        let key = parts.next().unwrap_or_default();
+        let value = parts.next();
+        let invalid_value = || {
+            anyhow::anyhow!(
+                "invalid value '{}' for compiler option '{}'",
+                value.unwrap_or_default(),
+                key
+            )
+        };
+
+        match (key, value) {
+            ("opt-level", Some(value)) => {
+                self.optimization_lvl = match value {
+                    "0" => OptimizationLevel::None,
+                    "1" => OptimizationLevel::Less,
+                    "2" => OptimizationLevel::Default,
+                    "3" => OptimizationLevel::Aggressive,
+                    _ => return Err(invalid_value()),
+                }
+            }
+            ("target", Some(value)) => {
+                self.target = Target::search(value).map_err(|e| {
+                    anyhow::anyhow!(
+                        "invalid value '{}' for compiler option 'target': {}",
+                        value,
+                        e
+                    )
+                })?
+            }
+            ("data-layout", Some(value)) => self.data_layout = Some(value.to_owned()),
+            ("print-commands", None) => self.print_commands = true,
+            ("opt-level", None) | ("target", None) | ("data-layout", None) => {
+                return Err(anyhow::anyhow!(
+                    "compiler option '{}' requires a value",
+                    key
+                ))
+            }
+            _ => return Err(anyhow::anyhow!("unknown compiler option '{}'", key)),
+        }
+        Ok(())
+    }
 }
 
 impl Default for Config {
@@ -30,6 +277,49 @@ impl Default for Config {
             optimization_lvl: OptimizationLevel::Default,
             out_dir: None,
             display_color: DisplayColor::Auto,
+            data_layout: None,
+            cpu: None,
+            target_features: None,
+            package_version: None,
+            print_commands: false,
+            default_visibility: SymbolVisibility::default(),
+            overflow_behavior: OverflowBehavior::default(),
+            instrument_calls: false,
+            emit: Emit::Munlib,
+            post_compile: None,
+            link_command_hook: None,
+            deny_warnings: false,
+            stop_after: Stage::Codegen,
+            out_name: None,
+            input_encoding: InputEncoding::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, OptimizationLevel};
+
+    #[test]
+    fn test_apply_flags() {
+        let mut config = Config::default();
+        config
+            .apply_flags(&[
+                "-C",
+                "opt-level=0",
+                "-Cprint-commands",
+                "-C",
+                "data-layout=e",
+            ])
+            .unwrap();
+        assert_eq!(config.optimization_lvl, OptimizationLevel::None);
+        assert!(config.print_commands);
+        assert_eq!(config.data_layout, Some("e".to_owned()));
+
+        assert!(config.apply_flags(&["-C", "opt-level=4"]).is_err());
+        assert!(config.apply_flags(&["-C", "opt-level"]).is_err());
+        assert!(config.apply_flags(&["-C", "unknown=1"]).is_err());
+        assert!(config.apply_flags(&["-C"]).is_err());
+        assert!(config.apply_flags(&["--opt-level=1"]).is_err());
+    }
+}
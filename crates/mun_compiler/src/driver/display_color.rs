@@ -2,7 +2,7 @@ use std::env;
 #[cfg(target_os = "windows")]
 use std::process::Command;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DisplayColor {
     Disable,
     Auto,
@@ -0,0 +1,107 @@
+//! Machine-applicable fixes for diagnostics, as used by `mun fix`.
+
+use mun_hir::{FileId, HirDatabase};
+use mun_syntax::{SyntaxKind, TextRange, TextUnit};
+
+/// A suggested edit that fixes a diagnostic by replacing the text in `range` with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    /// A short description of the edit
+    pub message: String,
+
+    /// The range of the source text that is replaced
+    pub range: TextRange,
+
+    /// The text that replaces the range
+    pub replacement: String,
+}
+
+/// Closing tokens that can be inserted verbatim when the parser expected them but they were
+/// missing. Separators like `,` are omitted because their absence is usually the symptom of a
+/// different error.
+const INSERTABLE_TOKENS: &[char] = &[';', ':', ')', ']', '}'];
+
+/// Returns all machine-applicable fixes for the diagnostics of the given file.
+pub fn fixes(db: &impl HirDatabase, file_id: FileId) -> Vec<Fix> {
+    let parse = db.parse(file_id);
+    parse
+        .errors()
+        .iter()
+        .filter_map(|err| {
+            let message = err.to_string();
+            INSERTABLE_TOKENS
+                .iter()
+                .find(|token| {
+                    SyntaxKind::from_char(**token)
+                        .map_or(false, |kind| message == format!("expected {:?}", kind))
+                })
+                .map(|token| Fix {
+                    message: format!("insert missing `{}`", token),
+                    range: TextRange::offset_len(err.location().offset(), TextUnit::from(0)),
+                    replacement: token.to_string(),
+                })
+        })
+        .collect()
+}
+
+/// Applies `fixes` to `text`, returning the resulting text and the number of fixes that were
+/// applied. A fix that overlaps with, or inserts at the same position as, an earlier fix is
+/// skipped; running the fixes again after recompiling picks it up if it still applies.
+pub fn apply_fixes(text: &str, fixes: &[Fix]) -> (String, usize) {
+    let mut sorted: Vec<&Fix> = fixes.iter().collect();
+    sorted.sort_by_key(|fix| (fix.range.start(), fix.range.end()));
+
+    let mut result = String::with_capacity(text.len());
+    let mut last_end: Option<usize> = None;
+    let mut applied = 0;
+    for fix in sorted {
+        let start = fix.range.start().to_usize();
+        let end = fix.range.end().to_usize();
+        let overlaps = match last_end {
+            Some(last_end) => start < last_end || (start == last_end && start == end),
+            None => false,
+        };
+        if overlaps || end > text.len() {
+            continue;
+        }
+
+        result.push_str(&text[last_end.unwrap_or(0)..start]);
+        result.push_str(&fix.replacement);
+        last_end = Some(end);
+        applied += 1;
+    }
+    result.push_str(&text[last_end.unwrap_or(0)..]);
+
+    (result, applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_fixes, Fix};
+    use mun_syntax::{TextRange, TextUnit};
+
+    fn insert(offset: u32, replacement: &str) -> Fix {
+        Fix {
+            message: String::new(),
+            range: TextRange::offset_len(TextUnit::from(offset), TextUnit::from(0)),
+            replacement: replacement.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_apply_fixes() {
+        let text = "fn main(a: i32 { (5 }";
+        let fixes = vec![insert(19, ")"), insert(14, ")")];
+        assert_eq!(
+            apply_fixes(text, &fixes),
+            ("fn main(a: i32) { (5) }".to_owned(), 2)
+        );
+
+        // Only one insertion at the same position is applied
+        let fixes = vec![insert(14, ")"), insert(14, ";")];
+        assert_eq!(
+            apply_fixes(text, &fixes),
+            ("fn main(a: i32) { (5 }".to_owned(), 1)
+        );
+    }
+}
@@ -1,25 +1,38 @@
 #![allow(clippy::enum_variant_names)] // This is a HACK because we use salsa
 mod annotate;
 mod db;
+pub mod diagnostic_codes;
 ///! This library contains the code required to go from source code to binaries.
 pub mod diagnostics;
 mod diagnostics_snippets;
 mod driver;
+mod fix;
+mod source_ignore;
+mod timings;
 
 pub use mun_hir::{FileId, RelativePath, RelativePathBuf};
 pub use mun_target::spec::Target;
 use std::path::{Path, PathBuf};
 
 pub use crate::driver::DisplayColor;
-pub use crate::driver::{Config, Driver};
+pub use crate::driver::{
+    format_command, iter_source_files, Config, Driver, Emit, InputEncoding, LinkCommandHook,
+    PostCompileHook, Stage,
+};
+pub use crate::fix::{apply_fixes, Fix};
+pub use crate::source_ignore::{iter_package_source_files, SourceIgnore, IGNORE_FILENAME};
+pub use crate::timings::BuildTimings;
 pub use annotate::{AnnotationBuilder, SliceBuilder, SnippetBuilder};
-pub use mun_codegen::OptimizationLevel;
+pub use mun_codegen::{
+    check_target, host_cpu_name, BackendUnavailable, Module, OptimizationLevel, OverflowBehavior,
+    SymbolVisibility, LLVM_VERSION,
+};
 
 pub use crate::db::CompilerDatabase;
-pub use annotate_snippets::snippet::AnnotationType;
+pub use annotate_snippets::snippet::{AnnotationType, Snippet};
 use mun_project::Package;
 use std::ffi::OsStr;
-use std::io::stderr;
+use std::io::{stderr, Write};
 
 #[derive(Debug, Clone)]
 pub enum PathOrInline {
@@ -93,6 +106,68 @@ pub fn compile_manifest(manifest_path: &Path, config: Config) -> Result<bool, an
     Ok(true)
 }
 
+/// Compiles the package at the specified manifest path like [`compile_manifest`] and returns how
+/// long loading the sources, analyzing them, generating code and writing the assemblies took,
+/// together with the driver that compiled the package.
+pub fn compile_manifest_with_timings(
+    manifest_path: &Path,
+    config: Config,
+) -> Result<(bool, Driver, BuildTimings), anyhow::Error> {
+    let mut timings = BuildTimings::default();
+    let (_package, mut driver) = timings.measure("load sources", || {
+        Driver::with_package_path(manifest_path, config)
+    })?;
+    let success = build_with_timings(&mut driver, &mut stderr(), &mut timings)?.is_some();
+    Ok((success, driver, timings))
+}
+
+/// Emits the diagnostics of the `driver` to `diagnostics` and, if there are no errors, generates
+/// and writes the assemblies that changed, recording how long every phase took in `timings`.
+/// Returns the relative paths of the files whose assemblies were written, or `None` if errors were
+/// emitted.
+pub fn build_with_timings(
+    driver: &mut Driver,
+    diagnostics: &mut dyn Write,
+    timings: &mut BuildTimings,
+) -> Result<Option<Vec<RelativePathBuf>>, anyhow::Error> {
+    if timings.measure("analysis", || driver.emit_diagnostics(diagnostics))? {
+        return Ok(None);
+    }
+    timings.measure("codegen", || driver.generate_assemblies());
+    timings
+        .measure("write artifacts", || driver.write_changed_assemblies())
+        .map(Some)
+}
+
+/// Compiles all path dependencies of the package at the specified manifest path, every dependency
+/// before the packages that depend on it. If an output directory is configured, the artifacts of a
+/// dependency are written to `deps/<name>` inside it. Returns `false` as soon as a dependency fails
+/// to compile.
+pub fn compile_dependencies(manifest_path: &Path, config: &Config) -> Result<bool, anyhow::Error> {
+    let package = Package::from_file(manifest_path)?;
+    for dependency in package.resolve_dependencies()? {
+        let config = Config {
+            out_dir: config
+                .out_dir
+                .as_ref()
+                .map(|out_dir| out_dir.join("deps").join(dependency.name())),
+            package_version: None,
+            ..config.clone()
+        };
+        if !compile_manifest(dependency.manifest_path(), config)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Analyzes the package at the specified manifest path and emits its diagnostics without
+/// generating code or writing any artifacts. Returns `false` if errors were emitted.
+pub fn check_manifest(manifest_path: &Path, config: Config) -> Result<bool, anyhow::Error> {
+    let (_package, driver) = Driver::with_package_path(manifest_path, config)?;
+    Ok(!driver.emit_diagnostics(&mut stderr())?)
+}
+
 /// Determines the relative path of a file to the source directory.
 pub fn compute_source_relative_path(
     source_dir: &Path,
@@ -114,10 +189,42 @@ pub fn compute_source_relative_path(
     })
 }
 
+/// The byte order mark with which some editors start UTF-8 files.
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// Reads the contents of the source file at `path` in the specified encoding, and transcodes them
+/// to UTF-8. A leading UTF-8 byte order mark is removed. If the file is not valid UTF-8, the error
+/// reports the byte offset of the first invalid sequence.
+pub fn read_source_file(path: &Path, encoding: InputEncoding) -> Result<String, anyhow::Error> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| anyhow::anyhow!("could not read contents of '{}': {}", path.display(), e))?;
+    match encoding {
+        InputEncoding::Utf8 => {
+            let offset = if bytes.starts_with(UTF8_BOM) {
+                UTF8_BOM.len()
+            } else {
+                0
+            };
+            String::from_utf8(bytes[offset..].to_vec()).map_err(|e| {
+                anyhow::anyhow!(
+                    "'{}' is not valid UTF-8: invalid byte sequence at byte offset {}",
+                    path.display(),
+                    offset + e.utf8_error().valid_up_to()
+                )
+            })
+        }
+        InputEncoding::Latin1 => Ok(bytes.into_iter().map(char::from).collect()),
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::{compute_source_relative_path, is_source_file, RelativePath};
+    use crate::{
+        compute_source_relative_path, is_source_file, read_source_file, Config, Driver,
+        InputEncoding, PathOrInline, PostCompileHook, RelativePath, RelativePathBuf, Stage,
+    };
     use std::path::Path;
+    use tempfile::NamedTempFile;
 
     #[test]
     fn test_is_source_file() {
@@ -140,4 +247,205 @@ mod test {
             RelativePath::new("foo/bar/main.mun")
         );
     }
+
+    #[test]
+    fn test_write_changed_assemblies() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("mun.toml"),
+            "[package]\nname=\"test\"\nversion=\"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("src/main.mun"),
+            "pub fn main() -> i32 { 1 }",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("src/other.mun"),
+            "pub fn other() -> i32 { 2 }",
+        )
+        .unwrap();
+
+        let (_package, mut driver) =
+            Driver::with_package_path(dir.path().join("mun.toml"), Config::default()).unwrap();
+        let mut written = driver.write_changed_assemblies().unwrap();
+        written.sort();
+        assert_eq!(
+            written,
+            vec![
+                RelativePath::new("main.mun"),
+                RelativePath::new("other.mun")
+            ]
+        );
+        assert!(driver.write_changed_assemblies().unwrap().is_empty());
+
+        driver.update_file("other.mun", "pub fn other() -> i32 { 3 }".to_owned());
+        assert_eq!(
+            driver.write_changed_assemblies().unwrap(),
+            vec![RelativePath::new("other.mun")]
+        );
+    }
+
+    #[test]
+    fn test_stop_after() {
+        let dir = tempfile::tempdir().unwrap();
+        let driver = |stop_after, contents: &str| {
+            let config = Config {
+                out_dir: Some(dir.path().to_path_buf()),
+                stop_after,
+                ..Config::default()
+            };
+            Driver::with_file(
+                config,
+                PathOrInline::Inline {
+                    rel_path: RelativePathBuf::from("main.mun"),
+                    contents: contents.to_owned(),
+                },
+            )
+            .unwrap()
+        };
+
+        // Type errors are only reported from the typecheck stage on
+        let type_error = "pub fn main() -> i32 { false }";
+        let (driver_parse, _) = driver(Stage::Parse, type_error);
+        assert!(driver_parse.diagnostics().is_empty());
+        let (driver_typecheck, _) = driver(Stage::Typecheck, type_error);
+        assert_eq!(driver_typecheck.diagnostics().len(), 1);
+        let (driver_parse, _) = driver(Stage::Parse, "pub fn main( {");
+        assert!(!driver_parse.diagnostics().is_empty());
+
+        // Assemblies are only written by the codegen stage
+        let (mut driver_typecheck, file_id) = driver(Stage::Typecheck, "pub fn main() {}");
+        driver_typecheck.write_all_assemblies().unwrap();
+        assert!(!driver_typecheck.assembly_output_path(file_id).exists());
+        let (mut driver_codegen, file_id) = driver(Stage::Codegen, "pub fn main() {}");
+        driver_codegen.write_all_assemblies().unwrap();
+        assert!(driver_codegen.assembly_output_path(file_id).is_file());
+    }
+
+    #[test]
+    fn test_wasm_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            target: crate::Target::search("wasm32-unknown-unknown").unwrap(),
+            out_dir: Some(dir.path().to_path_buf()),
+            ..Config::default()
+        };
+        let (mut driver, file_id) = Driver::with_file(
+            config,
+            PathOrInline::Inline {
+                rel_path: RelativePathBuf::from("main.mun"),
+                contents: "pub fn main() -> i32 { 5 }".to_owned(),
+            },
+        )
+        .unwrap();
+
+        let assembly_path = driver.assembly_output_path(file_id);
+        assert_eq!(assembly_path, dir.path().join("main.wasm"));
+        driver.write_all_assemblies().unwrap();
+
+        // Every WebAssembly module starts with the magic number `\0asm`
+        let contents = std::fs::read(&assembly_path).unwrap();
+        assert_eq!(&contents[..4], b"\0asm");
+    }
+
+    #[test]
+    fn test_post_compile_hook() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            out_dir: Some(dir.path().to_path_buf()),
+            post_compile: Some(PostCompileHook::new(|module| {
+                match module.get_function("forbidden") {
+                    Some(_) => Err(anyhow::anyhow!("`forbidden` may not be exported")),
+                    None => Ok(()),
+                }
+            })),
+            ..Config::default()
+        };
+        let (mut driver, file_id) = Driver::with_file(
+            config,
+            PathOrInline::Inline {
+                rel_path: RelativePathBuf::from("main.mun"),
+                contents: "pub fn allowed() -> i32 { 1 }".to_owned(),
+            },
+        )
+        .unwrap();
+        assert!(driver.write_assembly(file_id, false).unwrap());
+        assert!(driver.assembly_output_path(file_id).is_file());
+
+        std::fs::remove_file(driver.assembly_output_path(file_id)).unwrap();
+        driver.set_file_text(file_id, "pub fn forbidden() -> i32 { 1 }");
+        let error = driver.write_assembly(file_id, false).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "post-compile hook rejected 'main.mun': `forbidden` may not be exported"
+        );
+        assert!(!driver.assembly_output_path(file_id).is_file());
+    }
+
+    #[test]
+    fn test_type_of_last_binding() {
+        let (driver, file_id) = Driver::with_file(
+            Config::default(),
+            PathOrInline::Inline {
+                rel_path: RelativePathBuf::from("main.mun"),
+                contents: "struct Foo { a: f32 }\n\
+                    fn int() { let a = 1 + 2; }\n\
+                    fn float() { let a: i32 = 1; let b = 2.0; }\n\
+                    fn foo() { let a = Foo { a: 1.0 }; }\n\
+                    fn unit() { let a = int(); }\n\
+                    fn no_let() { int(); }"
+                    .to_owned(),
+            },
+        )
+        .unwrap();
+        let type_of = |name| driver.type_of_last_binding(file_id, name);
+        assert_eq!(type_of("int").as_deref(), Some("i32"));
+        assert_eq!(type_of("float").as_deref(), Some("f64"));
+        assert_eq!(type_of("foo").as_deref(), Some("Foo"));
+        assert_eq!(type_of("unit").as_deref(), Some("nothing"));
+        assert_eq!(type_of("no_let"), None);
+        assert_eq!(type_of("missing"), None);
+    }
+
+    #[test]
+    fn test_read_source_file() {
+        let file = NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "fn main() {}").unwrap();
+        assert_eq!(
+            read_source_file(file.path(), InputEncoding::Utf8).unwrap(),
+            "fn main() {}"
+        );
+
+        // The byte order mark is not part of the source
+        std::fs::write(file.path(), b"\xef\xbb\xbffn main() {}").unwrap();
+        assert_eq!(
+            read_source_file(file.path(), InputEncoding::Utf8).unwrap(),
+            "fn main() {}"
+        );
+
+        std::fs::write(file.path(), b"fn m\xc3\xa4in() {\xff}").unwrap();
+        let error = read_source_file(file.path(), InputEncoding::Utf8)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.ends_with("is not valid UTF-8: invalid byte sequence at byte offset 12"),
+            "{}",
+            error
+        );
+        assert!(
+            error.contains(&file.path().display().to_string()),
+            "{}",
+            error
+        );
+
+        // A comment with a Latin-1 `ä`
+        std::fs::write(file.path(), b"// m\xe4in\nfn main() {}").unwrap();
+        assert_eq!(
+            read_source_file(file.path(), InputEncoding::Latin1).unwrap(),
+            "// m\u{e4}in\nfn main() {}"
+        );
+    }
 }
@@ -0,0 +1,104 @@
+//! Excluding files from the sources of a package with a `.munignore` file in the root of the
+//! package. The file contains glob patterns in the format of a `.gitignore` file, e.g.
+//! `src/generated/` or `/src/vendor/**/*.mun`.
+
+use crate::driver::iter_source_files;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::{Path, PathBuf};
+
+/// The name of the file in the root of a package that lists the paths to exclude from its sources.
+pub const IGNORE_FILENAME: &str = ".munignore";
+
+/// The patterns of the `.munignore` file of a package.
+#[derive(Debug, Clone)]
+pub struct SourceIgnore {
+    root: PathBuf,
+    patterns: Gitignore,
+}
+
+impl SourceIgnore {
+    /// Loads the `.munignore` file in `package_root`. Without such a file, no paths are ignored.
+    pub fn from_package_root(package_root: &Path) -> Result<Self, anyhow::Error> {
+        let ignore_path = package_root.join(IGNORE_FILENAME);
+        let patterns = if ignore_path.is_file() {
+            let mut builder = GitignoreBuilder::new(package_root);
+            if let Some(e) = builder.add(&ignore_path) {
+                return Err(anyhow::anyhow!(
+                    "could not read '{}': {}",
+                    ignore_path.display(),
+                    e
+                ));
+            }
+            builder.build().map_err(|e| {
+                anyhow::anyhow!("invalid pattern in '{}': {}", ignore_path.display(), e)
+            })?
+        } else {
+            Gitignore::empty()
+        };
+        Ok(Self {
+            root: package_root.to_path_buf(),
+            patterns,
+        })
+    }
+
+    /// Returns true if the file at `path`, or one of its parent directories, matches a pattern.
+    /// Files outside of the package are never ignored.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        match path.strip_prefix(&self.root) {
+            Ok(relative_path) => self
+                .patterns
+                .matched_path_or_any_parents(relative_path, false)
+                .is_ignore(),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Returns the source files in `source_dir` of the package in `package_root`, except those that
+/// are ignored by the `.munignore` file of the package.
+pub fn iter_package_source_files(
+    package_root: &Path,
+    source_dir: &Path,
+) -> Result<impl Iterator<Item = PathBuf>, anyhow::Error> {
+    let ignore = SourceIgnore::from_package_root(package_root)?;
+    Ok(iter_source_files(source_dir).filter(move |path| !ignore.is_ignored(path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{iter_package_source_files, SourceIgnore, IGNORE_FILENAME};
+
+    #[test]
+    fn test_iter_package_source_files() {
+        let root = tempfile::tempdir().unwrap();
+        let source_dir = root.path().join("src");
+        std::fs::create_dir_all(source_dir.join("generated")).unwrap();
+        std::fs::write(source_dir.join("main.mun"), "").unwrap();
+        std::fs::write(source_dir.join("vendored.mun"), "").unwrap();
+        std::fs::write(source_dir.join("generated/bindings.mun"), "").unwrap();
+
+        let sources = |root| {
+            let mut sources: Vec<_> = iter_package_source_files(root, &source_dir)
+                .unwrap()
+                .map(|path| path.strip_prefix(&source_dir).unwrap().to_path_buf())
+                .collect();
+            sources.sort();
+            sources
+        };
+        assert_eq!(sources(root.path()).len(), 3);
+
+        std::fs::write(
+            root.path().join(IGNORE_FILENAME),
+            "# Not maintained here\ngenerated/\n/src/vendored.mun\n",
+        )
+        .unwrap();
+        assert_eq!(
+            sources(root.path()),
+            vec![std::path::PathBuf::from("main.mun")]
+        );
+
+        let ignore = SourceIgnore::from_package_root(root.path()).unwrap();
+        assert!(ignore.is_ignored(&source_dir.join("generated/nested/more.mun")));
+        assert!(!ignore.is_ignored(std::path::Path::new("/elsewhere/generated/main.mun")));
+    }
+}
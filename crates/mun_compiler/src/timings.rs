@@ -0,0 +1,72 @@
+//! Measuring how long the phases of a build take.
+
+use std::time::{Duration, Instant};
+
+/// The durations of the phases of a build, in the order in which they ran.
+#[derive(Debug, Default)]
+pub struct BuildTimings {
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl BuildTimings {
+    /// Runs `f` and records how long it took as the phase `name`.
+    pub fn measure<T>(&mut self, name: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.phases.push((name, start.elapsed()));
+        result
+    }
+
+    /// Returns the name and duration of every phase.
+    pub fn phases(&self) -> &[(&'static str, Duration)] {
+        &self.phases
+    }
+
+    /// Returns the combined duration of all phases.
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|(_, duration)| *duration).sum()
+    }
+
+    /// Renders the timings as a table with a row per phase and a total.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (name, duration) in self
+            .phases
+            .iter()
+            .chain(std::iter::once(&("total", self.total())))
+        {
+            text.push_str(&format!(
+                "{:<16}{:>10.3} ms\n",
+                name,
+                duration.as_nanos() as f64 / 1_000_000.0
+            ));
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BuildTimings;
+    use std::time::Duration;
+
+    #[test]
+    fn test_render() {
+        let timings = BuildTimings {
+            phases: vec![
+                ("analysis", Duration::from_micros(2500)),
+                ("codegen", Duration::from_micros(125)),
+            ],
+        };
+        assert_eq!(
+            timings.to_text(),
+            "analysis             2.500 ms\n\
+             codegen              0.125 ms\n\
+             total                2.625 ms\n"
+        );
+
+        let mut timings = BuildTimings::default();
+        assert_eq!(timings.measure("load sources", || 5), 5);
+        assert_eq!(timings.phases()[0].0, "load sources");
+    }
+}
@@ -1,20 +1,86 @@
 use std::sync::mpsc::channel;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use mun_compiler::{compute_source_relative_path, is_source_file, Config, Driver};
-use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use mun_compiler::{
+    build_with_timings, compute_source_relative_path, is_source_file, read_source_file,
+    BuildTimings, Config, Driver, InputEncoding, SourceIgnore,
+};
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
 
-use std::io::stderr;
-use std::path::Path;
+use std::io::{stderr, stdout, Write};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// Describes how file system events are batched before triggering a recompilation.
+#[derive(Debug, Clone)]
+pub struct WatchConfig {
+    /// The maximum time to wait after the first event of a batch before recompiling.
+    pub delay: Duration,
+
+    /// The number of events after which a batch is recompiled without waiting for `delay` to
+    /// elapse.
+    pub debounce_count: NonZeroUsize,
+
+    /// Whether all modules are recompiled from scratch after every batch, instead of only the
+    /// modules whose sources changed.
+    pub full_rebuild: bool,
+
+    /// The number of successful builds after which watching stops, or `None` to watch until
+    /// interrupted.
+    pub max_builds: Option<NonZeroUsize>,
+
+    /// Whether to print how long the phases of every build took.
+    pub print_timings: bool,
+
+    /// Additional files and directories that are watched, e.g. directories with generated source
+    /// files. A change to any file in these paths reloads the package and recompiles it.
+    pub extra_paths: Vec<PathBuf>,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        WatchConfig {
+            delay: Duration::from_millis(10),
+            // This unwrap is safe because 1 is not zero.
+            debounce_count: NonZeroUsize::new(1).unwrap(),
+            full_rebuild: false,
+            max_builds: None,
+            print_timings: false,
+            extra_paths: Vec::new(),
+        }
+    }
+}
+
 /// Compiles and watches the package at the specified path. Recompiles changes that occur.
 pub fn compile_and_watch_manifest(
     manifest_path: &Path,
     config: Config,
+    watch_config: WatchConfig,
 ) -> Result<bool, anyhow::Error> {
+    compile_and_watch_manifest_with(manifest_path, config, watch_config, |_| Ok(()))
+}
+
+/// Compiles and watches the package at the specified path. Recompiles changes that occur and
+/// calls `on_compiled` every time the assemblies were written without errors.
+///
+/// Every source file is a separate module, so only the assemblies of the modules that changed are
+/// recompiled and written, unless `watch_config.full_rebuild` is set. Watching stops on Ctrl+C or
+/// once `watch_config.max_builds` builds have succeeded.
+pub fn compile_and_watch_manifest_with<F>(
+    manifest_path: &Path,
+    config: Config,
+    watch_config: WatchConfig,
+    mut on_compiled: F,
+) -> Result<bool, anyhow::Error>
+where
+    F: FnMut(&Driver) -> Result<(), anyhow::Error>,
+{
     // Create the compiler driver
-    let (package, mut driver) = Driver::with_package_path(manifest_path, config)?;
+    let (package, mut driver) = Driver::with_package_path(manifest_path, config.clone())?;
+
+    // The status line is updated in place if the terminal supports it
+    let interactive = config.display_color.should_enable() && atty::is(atty::Stream::Stdout);
 
     // Start watching the source directory
     let (watcher_tx, watcher_rx) = channel();
@@ -24,10 +90,25 @@ pub fn compile_and_watch_manifest(
         .expect("missing source directory");
     watcher.watch(&source_directory, RecursiveMode::Recursive)?;
     println!("Watching: {}", source_directory.display());
+    let ignore = SourceIgnore::from_package_root(package.root())?;
+    for path in watch_config.extra_paths.iter() {
+        watcher.watch(path, RecursiveMode::Recursive)?;
+        println!("Watching: {}", path.display());
+    }
 
     // Emit all current errors, and write the assemblies if no errors occured
-    if !driver.emit_diagnostics(&mut stderr())? {
-        driver.write_all_assemblies()?
+    let mut builds = 0;
+    let reached_max_builds = |builds: usize| {
+        watch_config
+            .max_builds
+            .map_or(false, |max| builds >= max.get())
+    };
+    if build(&mut driver, watch_config.print_timings, interactive)? {
+        on_compiled(&driver)?;
+        builds += 1;
+        if reached_max_builds(builds) {
+            return Ok(true);
+        }
     }
 
     // Insert Ctrl+C handler so we can gracefully quit
@@ -38,61 +119,290 @@ pub fn compile_and_watch_manifest(
     })
     .expect("error setting ctrl-c handler");
 
-    // Start watching filesystem events.
+    // Start watching filesystem events. Events are accumulated until either enough events have
+    // been received or the delay has elapsed, after which the batch is compiled at once.
+    let mut pending_events = Vec::new();
+    let mut batch_start: Option<Instant> = None;
     while !should_quit.load(std::sync::atomic::Ordering::SeqCst) {
         if let Ok(event) = watcher_rx.recv_timeout(Duration::from_millis(1)) {
-            use notify::DebouncedEvent::*;
-            match event {
-                Write(ref path) if is_source_file(path) => {
-                    let relative_path = compute_source_relative_path(&source_directory, path)?;
-                    let file_contents = std::fs::read_to_string(path)?;
-                    log::info!("Modifying {}", relative_path.display());
-                    driver.update_file(relative_path, file_contents);
-                    if !driver.emit_diagnostics(&mut stderr())? {
-                        driver.write_all_assemblies()?;
-                    }
-                }
-                Create(ref path) if is_source_file(path) => {
-                    let relative_path = compute_source_relative_path(&source_directory, path)?;
-                    let file_contents = std::fs::read_to_string(path)?;
-                    log::info!("Creating {}", relative_path.display());
-                    driver.add_file(relative_path, file_contents);
-                    if !driver.emit_diagnostics(&mut stderr())? {
-                        driver.write_all_assemblies()?;
-                    }
-                }
-                Remove(ref path) if is_source_file(path) => {
-                    // Simply remove the source file from the source root
-                    let relative_path = compute_source_relative_path(&source_directory, path)?;
-                    log::info!("Removing {}", relative_path.display());
-                    let assembly_path = driver.assembly_output_path(driver.get_file_id_for_path(&relative_path).expect("cannot remove a file that was not part of the compilation in the first place"));
-                    if assembly_path.is_file() {
-                        std::fs::remove_file(assembly_path)?;
-                    }
-                    driver.remove_file(relative_path);
-                    driver.emit_diagnostics(&mut stderr())?;
+            if is_relevant_event(&event, &ignore)
+                || is_extra_path_event(&event, &source_directory, &watch_config.extra_paths)
+            {
+                pending_events.push(event);
+                batch_start.get_or_insert_with(Instant::now);
+            }
+        }
+
+        let batch_complete = pending_events.len() >= watch_config.debounce_count.get()
+            || batch_start.map_or(false, |start| start.elapsed() >= watch_config.delay);
+        if batch_complete && !pending_events.is_empty() {
+            log::info!("Processing {} file system event(s)", pending_events.len());
+            let mut extra_path_changed = false;
+            for event in pending_events.drain(..) {
+                if is_extra_path_event(&event, &source_directory, &watch_config.extra_paths) {
+                    extra_path_changed = true;
+                } else {
+                    apply_event(&mut driver, &source_directory, config.input_encoding, event)?;
                 }
-                Rename(ref from, ref to) => {
-                    // Renaming is done by changing the relative path of the original source file but
-                    // not modifying any text. This ensures that most of the cache for the renamed file
-                    // stays alive. This is effectively a rename of the file_id in the database.
-                    let from_relative_path = compute_source_relative_path(&source_directory, from)?;
-                    let to_relative_path = compute_source_relative_path(&source_directory, to)?;
-
-                    log::info!(
-                        "Renaming {} to {}",
-                        from_relative_path.display(),
-                        to_relative_path.display(),
-                    );
-                    driver.rename(from_relative_path, to_relative_path);
-                    if !driver.emit_diagnostics(&mut stderr())? {
-                        driver.write_all_assemblies()?;
-                    }
+            }
+            batch_start = None;
+
+            // A new driver has an empty cache, so all modules are compiled and written again
+            if watch_config.full_rebuild || extra_path_changed {
+                driver = Driver::with_package_path(manifest_path, config.clone())?.1;
+            }
+
+            if build(&mut driver, watch_config.print_timings, interactive)? {
+                on_compiled(&driver)?;
+                builds += 1;
+                if reached_max_builds(builds) {
+                    break;
                 }
-                _ => {}
             }
         }
     }
 
     Ok(true)
 }
+
+/// Emits the diagnostics of the driver and, if there are no errors, writes the assemblies that
+/// changed. The progress of the build is printed as a status line, which is updated in place if
+/// `interactive` is set. If `print_timings` is set, how long every phase took is printed
+/// afterwards. Returns `false` if errors were emitted.
+fn build(
+    driver: &mut Driver,
+    print_timings: bool,
+    interactive: bool,
+) -> Result<bool, anyhow::Error> {
+    let start = Instant::now();
+    print_status(BuildStatus::Building, interactive)?;
+
+    // Diagnostics are buffered so the status line can be cleared before they are written
+    let mut timings = BuildTimings::default();
+    let mut diagnostics = Vec::new();
+    let written = build_with_timings(driver, &mut diagnostics, &mut timings)?;
+    if !diagnostics.is_empty() {
+        if interactive {
+            print!("{}", CLEAR_LINE);
+            stdout().flush()?;
+        }
+        stderr().write_all(&diagnostics)?;
+    }
+    let written = match written {
+        Some(written) => written,
+        None => {
+            print_status(BuildStatus::Failed(start.elapsed()), interactive)?;
+            return Ok(false);
+        }
+    };
+
+    for relative_path in written {
+        log::info!("Rebuilt {}", relative_path.display());
+    }
+    print_status(BuildStatus::Built(start.elapsed()), interactive)?;
+    if print_timings {
+        eprint!("{}", timings.to_text());
+    }
+    Ok(true)
+}
+
+/// The ANSI escape sequence that moves the cursor to the start of the line and clears it.
+const CLEAR_LINE: &str = "\r\x1b[2K";
+
+/// The progress of a build in watch mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BuildStatus {
+    /// The build is in flight
+    Building,
+    /// The build succeeded after the specified duration
+    Built(Duration),
+    /// The build emitted errors after the specified duration
+    Failed(Duration),
+}
+
+/// Formats the status line of a build. If `interactive` is set, the line replaces the previous
+/// status line and a build in flight is not terminated by a newline, so it can be replaced by
+/// the outcome of the build. Otherwise every status is printed on a line of its own.
+fn format_status(status: BuildStatus, interactive: bool) -> String {
+    let message = match status {
+        BuildStatus::Building => "Building...".to_owned(),
+        BuildStatus::Built(duration) => format!("Built in {:.1}s", duration.as_secs_f64()),
+        BuildStatus::Failed(duration) => format!("Failed after {:.1}s", duration.as_secs_f64()),
+    };
+    match (interactive, status) {
+        (true, BuildStatus::Building) => format!("{}{}", CLEAR_LINE, message),
+        (true, _) => format!("{}{}\n", CLEAR_LINE, message),
+        (false, _) => format!("{}\n", message),
+    }
+}
+
+/// Prints the status line of a build to stdout.
+fn print_status(status: BuildStatus, interactive: bool) -> Result<(), anyhow::Error> {
+    let mut stdout = stdout();
+    write!(stdout, "{}", format_status(status, interactive))?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Returns true if the event affects the source files of the package, which excludes the files that
+/// are ignored.
+fn is_relevant_event(event: &DebouncedEvent, ignore: &SourceIgnore) -> bool {
+    use notify::DebouncedEvent::*;
+    match event {
+        Write(path) | Create(path) | Remove(path) => {
+            is_source_file(path) && !ignore.is_ignored(path)
+        }
+        Rename(_, _) => true,
+        _ => false,
+    }
+}
+
+/// Returns true if the event affects a file in one of the `extra_paths` that is not a source file
+/// of the package.
+fn is_extra_path_event(
+    event: &DebouncedEvent,
+    source_directory: &Path,
+    extra_paths: &[PathBuf],
+) -> bool {
+    use notify::DebouncedEvent::*;
+    match event {
+        Write(path) | Create(path) | Remove(path) | Rename(_, path) => {
+            !path.starts_with(source_directory)
+                && extra_paths.iter().any(|extra| path.starts_with(extra))
+        }
+        _ => false,
+    }
+}
+
+/// Updates the files of the driver according to the file system event.
+fn apply_event(
+    driver: &mut Driver,
+    source_directory: &Path,
+    input_encoding: InputEncoding,
+    event: DebouncedEvent,
+) -> Result<(), anyhow::Error> {
+    use notify::DebouncedEvent::*;
+    match event {
+        Write(ref path) => {
+            let relative_path = compute_source_relative_path(source_directory, path)?;
+            let file_contents = read_source_file(path, input_encoding)?;
+            log::info!("Modifying {}", relative_path.display());
+            driver.update_file(relative_path, file_contents);
+        }
+        Create(ref path) => {
+            let relative_path = compute_source_relative_path(source_directory, path)?;
+            let file_contents = read_source_file(path, input_encoding)?;
+            log::info!("Creating {}", relative_path.display());
+            driver.add_file(relative_path, file_contents);
+        }
+        Remove(ref path) => {
+            // Simply remove the source file from the source root
+            let relative_path = compute_source_relative_path(source_directory, path)?;
+            log::info!("Removing {}", relative_path.display());
+            let assembly_path =
+                driver.assembly_output_path(driver.get_file_id_for_path(&relative_path).expect(
+                    "cannot remove a file that was not part of the compilation in the first place",
+                ));
+            if assembly_path.is_file() {
+                std::fs::remove_file(assembly_path)?;
+            }
+            driver.remove_file(relative_path);
+        }
+        Rename(ref from, ref to) => {
+            // Renaming is done by changing the relative path of the original source file but
+            // not modifying any text. This ensures that most of the cache for the renamed file
+            // stays alive. This is effectively a rename of the file_id in the database.
+            let from_relative_path = compute_source_relative_path(source_directory, from)?;
+            let to_relative_path = compute_source_relative_path(source_directory, to)?;
+
+            log::info!(
+                "Renaming {} to {}",
+                from_relative_path.display(),
+                to_relative_path.display(),
+            );
+            driver.rename(from_relative_path, to_relative_path);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compile_and_watch_manifest_with, format_status, BuildStatus, WatchConfig};
+    use mun_compiler::{Config, DisplayColor};
+    use std::num::NonZeroUsize;
+    use std::time::Duration;
+
+    #[test]
+    fn test_extra_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::create_dir_all(dir.join("generated")).unwrap();
+        std::fs::write(
+            dir.join("mun.toml"),
+            "[package]\nname=\"test\"\nversion=\"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("src/main.mun"), "pub fn main() -> i32 { 1 }").unwrap();
+
+        // Change a file outside of the source directory after the first build
+        let library_path = dir.join("target/main.munlib");
+        let generated_path = dir.join("generated/main.mun");
+        let modifier = std::thread::spawn(move || {
+            while !library_path.is_file() {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            std::thread::sleep(Duration::from_millis(500));
+            std::fs::write(generated_path, "pub fn generated() {}").unwrap();
+        });
+
+        let config = Config {
+            out_dir: Some(dir.join("target")),
+            display_color: DisplayColor::Disable,
+            ..Config::default()
+        };
+        let watch_config = WatchConfig {
+            max_builds: NonZeroUsize::new(2),
+            extra_paths: vec![dir.join("generated")],
+            ..WatchConfig::default()
+        };
+        let mut builds = 0;
+        compile_and_watch_manifest_with(&dir.join("mun.toml"), config, watch_config, |_| {
+            builds += 1;
+            Ok(())
+        })
+        .unwrap();
+        modifier.join().unwrap();
+        assert_eq!(builds, 2);
+    }
+
+    #[test]
+    fn test_format_status() {
+        let duration = Duration::from_millis(1234);
+
+        assert_eq!(
+            format_status(BuildStatus::Building, true),
+            "\r\x1b[2KBuilding..."
+        );
+        assert_eq!(
+            format_status(BuildStatus::Built(duration), true),
+            "\r\x1b[2KBuilt in 1.2s\n"
+        );
+        assert_eq!(
+            format_status(BuildStatus::Failed(duration), true),
+            "\r\x1b[2KFailed after 1.2s\n"
+        );
+
+        assert_eq!(format_status(BuildStatus::Building, false), "Building...\n");
+        assert_eq!(
+            format_status(BuildStatus::Built(duration), false),
+            "Built in 1.2s\n"
+        );
+        assert_eq!(
+            format_status(BuildStatus::Failed(duration), false),
+            "Failed after 1.2s\n"
+        );
+    }
+}
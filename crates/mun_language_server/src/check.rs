@@ -0,0 +1,28 @@
+use crate::Result;
+use mun_compiler::{Config, DisplayColor, Driver};
+use std::path::Path;
+
+/// The outcome of analyzing a project once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckSummary {
+    /// The number of diagnostics of all files of the project, including warnings
+    pub diagnostics: usize,
+    /// Whether any of the diagnostics is an error
+    pub has_errors: bool,
+}
+
+/// Loads the project of the manifest at `manifest_path` and analyzes it once, without a client.
+/// This verifies that the server is able to load and analyze a project, e.g. as a smoke test of an
+/// editor integration.
+pub fn check(manifest_path: &Path) -> Result<CheckSummary> {
+    log::info!("loading project {}", manifest_path.display());
+    let config = Config {
+        display_color: DisplayColor::Disable,
+        ..Config::default()
+    };
+    let (_package, driver) = Driver::with_package_path(manifest_path, config)?;
+    Ok(CheckSummary {
+        diagnostics: driver.diagnostics().len(),
+        has_errors: driver.emit_diagnostics(&mut std::io::sink())?,
+    })
+}
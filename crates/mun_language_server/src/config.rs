@@ -0,0 +1,14 @@
+use std::path::PathBuf;
+
+/// The configuration of the language server.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// An optional path of a file to which a trace of the time spent handling every message is
+    /// written when the server shuts down. The trace uses the Chrome trace event format, which can
+    /// be inspected with `chrome://tracing` or similar tools.
+    pub performance_trace: Option<PathBuf>,
+
+    /// The manifest files of the projects that are analyzed from the start, before the client
+    /// provides its workspace folders. If empty, only the projects of the client are analyzed.
+    pub manifest_paths: Vec<PathBuf>,
+}
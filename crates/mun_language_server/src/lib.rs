@@ -1,7 +1,12 @@
 mod capabilities;
+mod check;
+mod config;
 mod main_loop;
+mod performance_trace;
 pub mod protocol;
 
+pub use check::{check, CheckSummary};
+pub use config::Config;
 pub use main_loop::main_loop;
 
 use serde::de::DeserializeOwned;
@@ -21,7 +26,7 @@ pub fn to_json<T: Serialize>(value: T) -> Result<serde_json::Value> {
 }
 
 /// Main entry point for the language server
-pub async fn run_server_async() -> Result<()> {
+pub async fn run_server_async(config: Config) -> Result<()> {
     log::info!("language server started");
 
     // Setup IO connections
@@ -57,12 +62,12 @@ pub async fn run_server_async() -> Result<()> {
         );
     }
 
-    main_loop(connection).await?;
+    main_loop(connection, config).await?;
 
     Ok(())
 }
 
 /// Main entry point for the language server
-pub fn run_server() -> Result<()> {
-    async_std::task::block_on(run_server_async())
+pub fn run_server(config: Config) -> Result<()> {
+    async_std::task::block_on(run_server_async(config))
 }
@@ -1,13 +1,48 @@
+use crate::performance_trace::PerformanceTrace;
 use crate::protocol::{Connection, Message};
-use crate::Result;
+use crate::{Config, Result};
 use futures::StreamExt;
+use std::time::Instant;
 
 enum Event {
     Msg(Message),
 }
 
+impl Event {
+    /// Returns a name that describes the event
+    fn name(&self) -> String {
+        match self {
+            Event::Msg(Message::Request(req)) => req.method.clone(),
+            Event::Msg(Message::Notification(not)) => not.method.clone(),
+            Event::Msg(Message::Response(_)) => "response".to_owned(),
+        }
+    }
+}
+
 /// Runs the main loop of the language server. This will receive requests and handle them.
-pub async fn main_loop(mut connection: Connection) -> Result<()> {
+pub async fn main_loop(mut connection: Connection, config: Config) -> Result<()> {
+    for manifest_path in config.manifest_paths.iter() {
+        log::info!("loading project {}", manifest_path.display());
+    }
+
+    let mut trace = config
+        .performance_trace
+        .as_ref()
+        .map(|_| PerformanceTrace::new());
+    let result = run_loop(&mut connection, trace.as_mut()).await;
+
+    if let (Some(trace), Some(path)) = (trace, &config.performance_trace) {
+        trace.write_to_file(path)?;
+    }
+    result
+}
+
+/// Receives and handles events until the server is shut down, recording the time it takes to
+/// handle every event in `trace`.
+async fn run_loop(
+    connection: &mut Connection,
+    mut trace: Option<&mut PerformanceTrace>,
+) -> Result<()> {
     loop {
         // Determine what to do next. This selects from different channels, the first message to
         // arrive is returned. If an error occurs on one of the channel the main loop is shutdown
@@ -20,7 +55,14 @@ pub async fn main_loop(mut connection: Connection) -> Result<()> {
         };
 
         // Handle the event
-        match handle_event(event, &mut connection).await? {
+        let name = event.name();
+        let start = Instant::now();
+        let state = handle_event(event, connection).await?;
+        if let Some(trace) = trace.as_mut() {
+            trace.record(name, start, start.elapsed());
+        }
+
+        match state {
             LoopState::Continue => {}
             LoopState::Shutdown => {
                 break;
@@ -0,0 +1,63 @@
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Records how long it takes to handle messages and writes them as a Chrome trace.
+pub(crate) struct PerformanceTrace {
+    start: Instant,
+    events: Vec<TraceEvent>,
+}
+
+/// A complete event in the Chrome trace event format. Timestamps are in microseconds.
+#[derive(Serialize)]
+struct TraceEvent {
+    name: String,
+    cat: &'static str,
+    ph: &'static str,
+    ts: u128,
+    dur: u128,
+    pid: u32,
+    tid: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Trace<'a> {
+    trace_events: &'a [TraceEvent],
+}
+
+impl PerformanceTrace {
+    pub fn new() -> Self {
+        PerformanceTrace {
+            start: Instant::now(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Records that handling the message `name` started at `start` and took `duration`.
+    pub fn record(&mut self, name: String, start: Instant, duration: Duration) {
+        self.events.push(TraceEvent {
+            name,
+            cat: "lsp",
+            ph: "X",
+            ts: start.duration_since(self.start).as_micros(),
+            dur: duration.as_micros(),
+            pid: 1,
+            tid: 1,
+        });
+    }
+
+    /// Writes the trace to the file at `path`.
+    pub fn write_to_file(&self, path: &Path) -> anyhow::Result<()> {
+        let trace = Trace {
+            trace_events: &self.events,
+        };
+        std::fs::write(path, serde_json::to_string(&trace)?).map_err(|e| {
+            anyhow::anyhow!(
+                "could not write performance trace to '{}': {}",
+                path.display(),
+                e
+            )
+        })
+    }
+}
@@ -0,0 +1,26 @@
+use mun_language_server::{check, CheckSummary};
+
+#[test]
+fn test_check() {
+    let project = tempfile::tempdir().unwrap();
+    std::fs::write(
+        project.path().join("mun.toml"),
+        "[package]\nname=\"test\"\nversion=\"0.1.0\"\n",
+    )
+    .unwrap();
+    std::fs::create_dir(project.path().join("src")).unwrap();
+    std::fs::write(
+        project.path().join("src/main.mun"),
+        "pub fn main() -> i32 { false }",
+    )
+    .unwrap();
+
+    assert_eq!(
+        check(&project.path().join("mun.toml")).unwrap(),
+        CheckSummary {
+            diagnostics: 1,
+            has_errors: true,
+        }
+    );
+    assert!(check(&project.path().join("missing.toml")).is_err());
+}
@@ -2,8 +2,8 @@ use async_std::future::timeout;
 use async_std::task::JoinHandle;
 use futures::{SinkExt, StreamExt};
 use lsp_types::{notification::Exit, request::Shutdown};
-use mun_language_server::main_loop;
 use mun_language_server::protocol::{Connection, Message, Notification, Request};
+use mun_language_server::{main_loop, Config};
 use serde::Serialize;
 use serde_json::Value;
 use std::time::Duration;
@@ -22,7 +22,7 @@ impl Server {
         let (connection, client) = Connection::memory();
 
         let worker = async_std::task::spawn(async move {
-            main_loop(connection).await.unwrap();
+            main_loop(connection, Config::default()).await.unwrap();
         });
 
         Self {
@@ -322,6 +322,11 @@ fn main() {
         .file("wrapper/lld-c.cpp")
         .compile("lldwrapper");
 
+    // Export the version of LLVM so it can be reported at runtime
+    let llvm_version = llvm_version(&*LLVM_CONFIG_PATH)
+        .map_or_else(|_| CRATE_VERSION.to_string(), |version| version.to_string());
+    println!("cargo:rustc-env=MUN_LLVM_VERSION={}", llvm_version);
+
     if cfg!(feature = "no-llvm-linking") {
         return;
     }
@@ -5,6 +5,9 @@ use std::{
     os::raw::{c_char, c_int},
 };
 
+/// The version of LLVM that LLD is linked against.
+pub const LLVM_VERSION: &str = env!("MUN_LLVM_VERSION");
+
 #[repr(C)]
 struct LldInvokeResult {
     success: bool,
@@ -24,6 +24,7 @@ where
     objects: RwLock<HashMap<GcPtr, Pin<Box<ObjectInfo<T>>>>>,
     observer: O,
     stats: RwLock<Stats>,
+    memory_limit: Option<usize>,
 }
 
 impl<T, O> Default for MarkSweep<T, O>
@@ -36,6 +37,7 @@ where
             objects: RwLock::new(HashMap::new()),
             observer: O::default(),
             stats: RwLock::new(Stats::default()),
+            memory_limit: None,
         }
     }
 }
@@ -51,9 +53,30 @@ where
             objects: RwLock::new(HashMap::new()),
             observer,
             stats: RwLock::new(Stats::default()),
+            memory_limit: None,
         }
     }
 
+    /// Sets the maximum number of bytes that may be allocated by the collector. The collector does
+    /// not enforce the limit itself; users should check `would_exceed_memory_limit` before
+    /// allocating.
+    pub fn with_memory_limit(mut self, memory_limit: Option<usize>) -> Self {
+        self.memory_limit = memory_limit;
+        self
+    }
+
+    /// Returns the maximum number of bytes that may be allocated by the collector, if any.
+    pub fn memory_limit(&self) -> Option<usize> {
+        self.memory_limit
+    }
+
+    /// Returns true if allocating another `size` bytes would exceed the memory limit.
+    pub fn would_exceed_memory_limit(&self, size: usize) -> bool {
+        self.memory_limit.map_or(false, |limit| {
+            self.stats.read().allocated_memory + size > limit
+        })
+    }
+
     /// Logs an allocation
     fn log_alloc(&self, handle: GcPtr, ty: T) {
         {
@@ -1,7 +1,11 @@
 mod manifest;
 mod package;
+mod workspace;
 
-pub use manifest::{Manifest, ManifestMetadata, PackageId};
+pub use manifest::{BuildDefaults, Dependency, Manifest, ManifestMetadata, PackageId};
 pub use package::Package;
+pub use semver::Version;
+pub use workspace::Workspace;
 
 pub const MANIFEST_FILENAME: &str = "mun.toml";
+pub const WORKSPACE_FILENAME: &str = "mun-workspace.toml";
@@ -1,5 +1,6 @@
+use std::collections::BTreeMap;
 use std::fmt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 mod toml;
@@ -8,13 +9,41 @@ mod toml;
 #[derive(PartialEq, Clone, Debug)]
 pub struct Manifest {
     package_id: PackageId,
+    default_entry: Option<String>,
+    reload_delay: Option<u64>,
     metadata: ManifestMetadata,
+    dependencies: BTreeMap<String, Dependency>,
+    build: BuildDefaults,
 }
 
 /// General metadata for a package.
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug, Default)]
 pub struct ManifestMetadata {
     pub authors: Vec<String>,
+    pub license: Option<String>,
+}
+
+/// Default compiler options for building a package, specified in the `build` section of its
+/// manifest. Options specified on the command line take precedence.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct BuildDefaults {
+    /// The optimization level, 0-3
+    pub opt_level: Option<u8>,
+    /// The target triple to compile for
+    pub target: Option<String>,
+}
+
+impl BuildDefaults {
+    /// Returns true if no defaults are specified.
+    pub fn is_empty(&self) -> bool {
+        self.opt_level.is_none() && self.target.is_none()
+    }
+}
+
+/// A package that another package depends on.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Dependency {
+    path: PathBuf,
 }
 
 /// Unique identifier of a package and version
@@ -25,6 +54,32 @@ pub struct PackageId {
 }
 
 impl Manifest {
+    /// Constructs a new manifest for the package `name` with the specified `version`
+    pub fn new(
+        name: &str,
+        version: semver::Version,
+        metadata: ManifestMetadata,
+    ) -> Result<Manifest, anyhow::Error> {
+        toml::TomlManifest::from_real_manifest(&Manifest {
+            package_id: PackageId {
+                name: name.to_owned(),
+                version,
+            },
+            default_entry: None,
+            reload_delay: None,
+            metadata,
+            dependencies: BTreeMap::new(),
+            build: BuildDefaults::default(),
+        })
+        .into_real_manifest()
+    }
+
+    /// Serializes the manifest into the contents of a mun.toml file
+    pub fn to_toml_string(&self) -> Result<String, anyhow::Error> {
+        ::toml::to_string(&toml::TomlManifest::from_real_manifest(self))
+            .map_err(|e| anyhow::anyhow!("could not serialize manifest: {}", e))
+    }
+
     /// Try to read a manifest from a file
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Manifest, anyhow::Error> {
         // Load the contents of the file
@@ -48,10 +103,50 @@ impl Manifest {
         &self.package_id.version()
     }
 
+    /// Returns the function that `mun start` and `mun run` invoke if no entry point is specified,
+    /// if the package configures one with `default-entry`
+    pub fn default_entry(&self) -> Option<&str> {
+        self.default_entry.as_deref()
+    }
+
+    /// Returns the delay in milliseconds with which the runtime reloads changed libraries of the
+    /// package, if the package configures one with `reload-delay`
+    pub fn reload_delay(&self) -> Option<u64> {
+        self.reload_delay
+    }
+
     /// Returns the metadata information of the package
     pub fn metadata(&self) -> &ManifestMetadata {
         &self.metadata
     }
+
+    /// Returns the dependencies of the package, by name
+    pub fn dependencies(&self) -> &BTreeMap<String, Dependency> {
+        &self.dependencies
+    }
+
+    /// Returns the default compiler options of the package
+    pub fn build_defaults(&self) -> &BuildDefaults {
+        &self.build
+    }
+
+    /// Returns a copy of the manifest with its version replaced by `version`
+    pub fn with_version(mut self, version: semver::Version) -> Self {
+        self.package_id.version = version;
+        self
+    }
+}
+
+impl Dependency {
+    /// Constructs a dependency on the package in the directory `path`
+    pub fn with_path<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the directory of the package, relative to the root of the dependent package
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
 }
 
 impl PackageId {
@@ -85,7 +180,7 @@ impl std::str::FromStr for Manifest {
 
 #[cfg(test)]
 mod tests {
-    use crate::Manifest;
+    use crate::{BuildDefaults, Dependency, Manifest, ManifestMetadata};
     use std::str::FromStr;
 
     #[test]
@@ -106,6 +201,143 @@ mod tests {
             &semver::Version::from_str("0.2.0").unwrap()
         );
         assert_eq!(manifest.metadata().authors, vec!["Mun Team"]);
+        assert!(manifest.dependencies().is_empty());
+        assert!(manifest.build_defaults().is_empty());
+        assert_eq!(manifest.default_entry(), None);
         assert_eq!(format!("{}", manifest.package_id()), "test v0.2.0");
+
+        let manifest = manifest.with_version(semver::Version::from_str("1.2.3").unwrap());
+        assert_eq!(format!("{}", manifest.package_id()), "test v1.2.3");
+    }
+
+    #[test]
+    fn parse_dependencies() {
+        let manifest = Manifest::from_str(
+            r#"
+        [package]
+        name="test"
+        version="0.2.0"
+
+        [dependencies]
+        utils = { path = "../utils" }
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.dependencies().get("utils"),
+            Some(&Dependency::with_path("../utils"))
+        );
+        assert_eq!(
+            Manifest::from_str(&manifest.to_toml_string().unwrap()).unwrap(),
+            manifest
+        );
+    }
+
+    #[test]
+    fn parse_build_defaults() {
+        let manifest = Manifest::from_str(
+            r#"
+        [package]
+        name="test"
+        version="0.2.0"
+
+        [build]
+        opt-level = 3
+        target = "x86_64-unknown-linux-gnu"
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.build_defaults(),
+            &BuildDefaults {
+                opt_level: Some(3),
+                target: Some("x86_64-unknown-linux-gnu".to_owned()),
+            }
+        );
+        assert_eq!(
+            Manifest::from_str(&manifest.to_toml_string().unwrap()).unwrap(),
+            manifest
+        );
+    }
+
+    #[test]
+    fn parse_default_entry() {
+        let manifest = Manifest::from_str(
+            r#"
+        [package]
+        name="test"
+        version="0.2.0"
+        default-entry = "game_main"
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.default_entry(), Some("game_main"));
+        assert_eq!(
+            Manifest::from_str(&manifest.to_toml_string().unwrap()).unwrap(),
+            manifest
+        );
+
+        assert!(Manifest::from_str(
+            r#"
+        [package]
+        name="test"
+        version="0.2.0"
+        default-entry = " "
+        "#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn parse_reload_delay() {
+        let manifest = Manifest::from_str(
+            r#"
+        [package]
+        name="test"
+        version="0.2.0"
+        reload-delay = 250
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.reload_delay(), Some(250));
+        assert_eq!(
+            Manifest::from_str(&manifest.to_toml_string().unwrap()).unwrap(),
+            manifest
+        );
+
+        for delay in &["0", "-5", "\"fast\""] {
+            assert!(Manifest::from_str(&format!(
+                "[package]\nname=\"test\"\nversion=\"0.2.0\"\nreload-delay = {}\n",
+                delay
+            ))
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn to_toml_string() {
+        let manifest = Manifest::new(
+            "test",
+            semver::Version::from_str("0.1.0").unwrap(),
+            ManifestMetadata {
+                authors: vec!["Mun Team".to_owned()],
+                license: Some("MIT".to_owned()),
+            },
+        )
+        .unwrap();
+
+        let contents = manifest.to_toml_string().unwrap();
+        assert_eq!(Manifest::from_str(&contents).unwrap(), manifest);
+
+        assert!(Manifest::new(
+            " ",
+            semver::Version::from_str("0.1.0").unwrap(),
+            ManifestMetadata::default()
+        )
+        .is_err());
     }
 }
@@ -1,11 +1,17 @@
-use super::{Manifest, ManifestMetadata, PackageId};
+use super::{BuildDefaults, Dependency, Manifest, ManifestMetadata, PackageId};
 use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 /// A manifest as specified in a mun.toml file.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct TomlManifest {
     package: TomlProject,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    dependencies: BTreeMap<String, TomlDependency>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    build: Option<TomlBuild>,
 }
 
 /// Represents the `package` section of a mun.toml file.
@@ -14,9 +20,65 @@ pub struct TomlProject {
     name: String,
     version: semver::Version,
     authors: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    license: Option<String>,
+    #[serde(rename = "default-entry", skip_serializing_if = "Option::is_none")]
+    default_entry: Option<String>,
+    #[serde(rename = "reload-delay", skip_serializing_if = "Option::is_none")]
+    reload_delay: Option<u64>,
+}
+
+/// Represents the `build` section of a mun.toml file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct TomlBuild {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    opt_level: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target: Option<String>,
+}
+
+/// Represents an entry of the `dependencies` section of a mun.toml file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct TomlDependency {
+    path: PathBuf,
 }
 
 impl TomlManifest {
+    /// Convert a "real" manifest into its toml representation.
+    pub fn from_real_manifest(manifest: &Manifest) -> Self {
+        Self {
+            package: TomlProject {
+                name: manifest.name().to_owned(),
+                version: manifest.version().clone(),
+                authors: Some(manifest.metadata().authors.clone()),
+                license: manifest.metadata().license.clone(),
+                default_entry: manifest.default_entry().map(ToOwned::to_owned),
+                reload_delay: manifest.reload_delay(),
+            },
+            dependencies: manifest
+                .dependencies()
+                .iter()
+                .map(|(name, dependency)| {
+                    (
+                        name.clone(),
+                        TomlDependency {
+                            path: dependency.path().to_path_buf(),
+                        },
+                    )
+                })
+                .collect(),
+            build: if manifest.build_defaults().is_empty() {
+                None
+            } else {
+                Some(TomlBuild {
+                    opt_level: manifest.build_defaults().opt_level,
+                    target: manifest.build_defaults().target.clone(),
+                })
+            },
+        }
+    }
+
     /// Convert this toml manifest into a "real" manifest.
     pub fn into_real_manifest(self) -> Result<Manifest, anyhow::Error> {
         let name = self.package.name.trim();
@@ -24,14 +86,39 @@ impl TomlManifest {
             anyhow::bail!("package name cannot be an empty string");
         }
 
+        if let Some(entry) = &self.package.default_entry {
+            if entry.trim().is_empty() {
+                anyhow::bail!("default entry point cannot be an empty string");
+            }
+        }
+
+        if self.package.reload_delay == Some(0) {
+            anyhow::bail!("reload delay must be a positive number of milliseconds");
+        }
+
         Ok(Manifest {
             package_id: PackageId {
                 name: name.to_owned(),
                 version: self.package.version,
             },
+            default_entry: self.package.default_entry,
+            reload_delay: self.package.reload_delay,
             metadata: ManifestMetadata {
                 authors: self.package.authors.unwrap_or_default(),
+                license: self.package.license,
             },
+            dependencies: self
+                .dependencies
+                .into_iter()
+                .map(|(name, dependency)| (name, Dependency::with_path(dependency.path)))
+                .collect(),
+            build: self
+                .build
+                .map(|build| BuildDefaults {
+                    opt_level: build.opt_level,
+                    target: build.target,
+                })
+                .unwrap_or_default(),
         })
     }
 }
@@ -1,4 +1,5 @@
-use crate::{Manifest, PackageId};
+use crate::{Manifest, PackageId, MANIFEST_FILENAME};
+use anyhow::anyhow;
 use semver::Version;
 use std::fmt;
 use std::path::{Path, PathBuf};
@@ -27,6 +28,15 @@ impl Package {
         Ok(Self::new(manifest, path))
     }
 
+    /// Returns a copy of the package with its version replaced by `version`, e.g. to override the
+    /// version of the manifest for a single build
+    pub fn with_version(self, version: Version) -> Self {
+        Self {
+            manifest: self.manifest.with_version(version),
+            ..self
+        }
+    }
+
     /// Returns the manifest
     pub fn manifest(&self) -> &Manifest {
         &self.manifest
@@ -66,6 +76,76 @@ impl Package {
             None
         }
     }
+
+    /// Loads all path dependencies of the package, including transitive ones, in the order in which
+    /// they have to be compiled: every package comes after the packages it depends on. Returns an
+    /// error if a dependency cannot be loaded or if the dependencies contain a cycle.
+    pub fn resolve_dependencies(&self) -> anyhow::Result<Vec<Package>> {
+        let mut resolved = Vec::new();
+        self.visit_dependencies(&mut Vec::new(), &mut Vec::new(), &mut resolved)?;
+        Ok(resolved)
+    }
+
+    /// Appends the dependencies of the package to `resolved` in depth-first order. `stack` contains
+    /// the names and roots of the packages that are currently being visited and `visited` the roots
+    /// of the packages in `resolved`.
+    fn visit_dependencies(
+        &self,
+        stack: &mut Vec<(String, PathBuf)>,
+        visited: &mut Vec<PathBuf>,
+        resolved: &mut Vec<Package>,
+    ) -> anyhow::Result<()> {
+        stack.push((self.name().to_owned(), self.canonical_root()));
+        for (name, dependency) in self.manifest().dependencies() {
+            let manifest_path = self.root().join(dependency.path()).join(MANIFEST_FILENAME);
+            let package = Package::from_file(&manifest_path).map_err(|e| {
+                anyhow!(
+                    "could not load dependency '{}' of {} from '{}': {}",
+                    name,
+                    self,
+                    manifest_path.display(),
+                    e
+                )
+            })?;
+            if package.name() != name {
+                return Err(anyhow!(
+                    "dependency '{}' of {} refers to package '{}'",
+                    name,
+                    self,
+                    package.name()
+                ));
+            }
+
+            let root = package.canonical_root();
+            if let Some(index) = stack.iter().position(|(_, r)| *r == root) {
+                let cycle: Vec<&str> = stack[index..]
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .chain(std::iter::once(name.as_str()))
+                    .collect();
+                return Err(anyhow!("cyclic dependency: {}", cycle.join(" -> ")));
+            }
+            if visited.contains(&root) {
+                continue;
+            }
+
+            package.visit_dependencies(stack, visited, resolved)?;
+            visited.push(root);
+            resolved.push(package);
+        }
+        stack.pop();
+        Ok(())
+    }
+
+    /// Returns the absolute root folder of the package, to identify packages that are referred to
+    /// by different paths
+    fn canonical_root(&self) -> PathBuf {
+        let root = match self.root() {
+            root if root.as_os_str().is_empty() => Path::new("."),
+            root => root,
+        };
+        root.canonicalize().unwrap_or_else(|_| root.to_path_buf())
+    }
 }
 
 impl fmt::Display for Package {
@@ -73,3 +153,45 @@ impl fmt::Display for Package {
         write!(f, "{}", self.package_id())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::Package;
+    use std::path::Path;
+
+    fn create_package(root: &Path, name: &str, dependencies: &[&str]) {
+        let mut contents = format!("[package]\nname=\"{}\"\nversion=\"0.1.0\"\n", name);
+        if !dependencies.is_empty() {
+            contents.push_str("\n[dependencies]\n");
+            for dependency in dependencies {
+                contents.push_str(&format!("{0} = {{ path = \"../{0}\" }}\n", dependency));
+            }
+        }
+        std::fs::create_dir_all(root.join(name)).unwrap();
+        std::fs::write(root.join(name).join("mun.toml"), contents).unwrap();
+    }
+
+    #[test]
+    fn resolve_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        create_package(dir.path(), "game", &["physics", "utils"]);
+        create_package(dir.path(), "physics", &["utils"]);
+        create_package(dir.path(), "utils", &[]);
+
+        let package = Package::from_file(dir.path().join("game/mun.toml")).unwrap();
+        let names: Vec<String> = package
+            .resolve_dependencies()
+            .unwrap()
+            .iter()
+            .map(|package| package.name().to_owned())
+            .collect();
+        assert_eq!(names, vec!["utils", "physics"]);
+
+        create_package(dir.path(), "utils", &["game"]);
+        let error = package.resolve_dependencies().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "cyclic dependency: game -> physics -> utils -> game"
+        );
+    }
+}
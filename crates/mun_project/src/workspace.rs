@@ -0,0 +1,106 @@
+use serde_derive::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
+
+/// A collection of packages that are organized under a common root directory. Usually this
+/// information is read from a mun-workspace.toml file in the root directory.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Workspace {
+    members: Vec<String>,
+}
+
+/// A workspace as specified in a mun-workspace.toml file.
+#[derive(Debug, Deserialize, Serialize)]
+struct TomlWorkspaceManifest {
+    workspace: TomlWorkspace,
+}
+
+/// Represents the `workspace` section of a mun-workspace.toml file.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct TomlWorkspace {
+    #[serde(default)]
+    members: Vec<String>,
+}
+
+impl Workspace {
+    /// Constructs a new workspace with the packages in the specified member directories, relative
+    /// to the root of the workspace
+    pub fn new(members: Vec<String>) -> Self {
+        Self { members }
+    }
+
+    /// Try to read a workspace from a file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Workspace, anyhow::Error> {
+        let file_contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("could not read workspace file: {}", e))?;
+        Self::from_str(&file_contents)
+    }
+
+    /// Returns the directories of the member packages, relative to the root of the workspace
+    pub fn members(&self) -> &[String] {
+        &self.members
+    }
+
+    /// Adds the package in the directory `member`, relative to the root of the workspace. Returns
+    /// `false` if it already is a member.
+    pub fn add_member(&mut self, member: String) -> bool {
+        if self.members.contains(&member) {
+            return false;
+        }
+        self.members.push(member);
+        true
+    }
+
+    /// Serializes the workspace into the contents of a mun-workspace.toml file
+    pub fn to_toml_string(&self) -> Result<String, anyhow::Error> {
+        ::toml::to_string(&TomlWorkspaceManifest {
+            workspace: TomlWorkspace {
+                members: self.members.clone(),
+            },
+        })
+        .map_err(|e| anyhow::anyhow!("could not serialize workspace: {}", e))
+    }
+}
+
+impl FromStr for Workspace {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let workspace = ::toml::from_str::<TomlWorkspaceManifest>(s)
+            .map_err(|e| anyhow::anyhow!("could not parse workspace: {}", e))?;
+        Ok(Workspace {
+            members: workspace.workspace.members,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Workspace;
+    use std::str::FromStr;
+
+    #[test]
+    fn parse() {
+        let workspace = Workspace::from_str(
+            r#"
+        [workspace]
+        members = ["core", "game"]
+        "#,
+        )
+        .unwrap();
+        assert_eq!(workspace.members(), &["core", "game"]);
+
+        let contents = workspace.to_toml_string().unwrap();
+        assert_eq!(Workspace::from_str(&contents).unwrap(), workspace);
+
+        assert!(Workspace::from_str("[package]").is_err());
+    }
+
+    #[test]
+    fn add_member() {
+        let mut workspace = Workspace::new(vec!["core".to_owned()]);
+        assert!(workspace.add_member("packages/game".to_owned()));
+        assert!(!workspace.add_member("core".to_owned()));
+        assert_eq!(workspace.members(), &["core", "packages/game"]);
+    }
+}
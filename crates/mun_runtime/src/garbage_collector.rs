@@ -142,6 +142,44 @@ impl gc::TypeTrace for UnsafeTypeInfo {
     }
 }
 
+/// How the `Runtime` reclaims memory that is no longer referenced.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GcStrategy {
+    /// Unreferenced objects are freed by a mark-sweep collection
+    MarkSweep,
+    /// Memory is never reclaimed, e.g. to debug leaks or to avoid the latency of collections. Objects
+    /// are still tracked, so allocation statistics remain accurate. This strategy cannot be combined
+    /// with a memory limit.
+    None,
+}
+
+impl GcStrategy {
+    /// The names of all supported strategies, as parsed by `from_str`.
+    pub const NAMES: &'static [&'static str] = &["mark-sweep", "none"];
+}
+
+impl Default for GcStrategy {
+    fn default() -> Self {
+        GcStrategy::MarkSweep
+    }
+}
+
+impl std::str::FromStr for GcStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mark-sweep" => Ok(GcStrategy::MarkSweep),
+            "none" => Ok(GcStrategy::None),
+            _ => Err(anyhow::anyhow!(
+                "the garbage collection strategy '{}' is not supported, expected one of: {}",
+                s,
+                GcStrategy::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
 /// Defines the garbage collector used by the `Runtime`.
 pub type GarbageCollector = gc::MarkSweep<UnsafeTypeInfo, gc::NoopObserver<gc::Event>>;
 
@@ -10,6 +10,7 @@ mod macros;
 #[macro_use]
 mod garbage_collector;
 mod marshal;
+mod profile;
 mod reflection;
 mod struct_ref;
 
@@ -21,7 +22,7 @@ use rustc_hash::FxHashMap;
 use std::{
     cell::RefCell,
     collections::HashMap,
-    ffi, io, mem,
+    ffi, fmt, io, mem,
     path::{Path, PathBuf},
     ptr::NonNull,
     rc::Rc,
@@ -35,8 +36,9 @@ use std::{
 
 pub use crate::{
     assembly::Assembly,
-    garbage_collector::UnsafeTypeInfo,
+    garbage_collector::{GcStrategy, UnsafeTypeInfo},
     marshal::Marshal,
+    profile::{CallProfile, FunctionProfile},
     reflection::{ArgumentReflection, ReturnTypeReflection},
     struct_ref::StructRef,
 };
@@ -46,10 +48,85 @@ pub use abi::IntoFunctionDefinition;
 pub struct RuntimeOptions {
     /// Path to the entry point library
     pub library_path: PathBuf,
+    /// Paths to libraries that are loaded in addition to the entry point library. Libraries can
+    /// call the functions that other libraries export through `extern` functions.
+    pub additional_library_paths: Vec<PathBuf>,
     /// Delay during which filesystem events are collected, deduplicated, and after which emitted.
     pub delay: Duration,
+    /// Whether or not to watch the loaded libraries for changes, enabling hot reloading.
+    pub watch: bool,
+    /// The number of times attaching the watcher to the directory of a library is retried, with an
+    /// increasing delay, before giving up.
+    pub watch_retries: u32,
+    /// The maximum number of bytes that may be allocated by the garbage collector.
+    pub memory_limit: Option<usize>,
+    /// The number of bytes to preallocate for the heap of the garbage collector, if it supports a
+    /// fixed arena.
+    pub heap_size: Option<usize>,
+    /// Whether garbage is collected at fixed, predictable points: after every reload, in addition
+    /// to explicit calls to [`Runtime::gc_collect`].
+    pub deterministic_gc: bool,
+    /// How memory that is no longer referenced is reclaimed
+    pub gc_strategy: GcStrategy,
     /// Custom user injected functions
     pub user_functions: Vec<(abi::FunctionDefinition, abi::FunctionDefinitionStorage)>,
+    /// An optional callback that is invoked for every event of the hot reloading system
+    pub reload_observer: Option<Box<dyn FnMut(&ReloadEvent)>>,
+    /// Whether the number and duration of the invocations of every function are recorded
+    pub profiling: bool,
+}
+
+/// An event that occurred in the hot reloading system of a [`Runtime`].
+#[derive(Debug)]
+pub enum ReloadEvent<'a> {
+    /// A (debounced) filesystem event of the specified `kind` was received for `path`.
+    FileChanged {
+        /// The kind of filesystem event, e.g. `write` or `create`
+        kind: &'static str,
+        /// The path of the changed file
+        path: &'a Path,
+    },
+    /// A filesystem event was ignored because it does not refer to a loaded assembly.
+    Ignored {
+        /// The path of the changed file
+        path: &'a Path,
+    },
+    /// The assembly at `path` was reloaded.
+    Reloaded {
+        /// The path of the assembly
+        path: &'a Path,
+    },
+    /// Reloading the assembly at `path` failed.
+    ReloadFailed {
+        /// The path of the assembly
+        path: &'a Path,
+        /// The reason of the failure
+        error: &'a Error,
+    },
+}
+
+impl fmt::Display for ReloadEvent<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReloadEvent::FileChanged { kind, path } => {
+                write!(f, "filesystem event '{}': {}", kind, path.display())
+            }
+            ReloadEvent::Ignored { path } => {
+                write!(
+                    f,
+                    "ignored event, not a loaded assembly: {}",
+                    path.display()
+                )
+            }
+            ReloadEvent::Reloaded { path } => write!(f, "reloaded assembly: {}", path.display()),
+            ReloadEvent::ReloadFailed { path, error } => write!(
+                f,
+                "failed to reload assembly '{}': {}",
+                path.display(),
+                error
+            ),
+        }
+    }
 }
 
 /// A builder for the [`Runtime`].
@@ -63,18 +140,88 @@ impl RuntimeBuilder {
         Self {
             options: RuntimeOptions {
                 library_path: library_path.into(),
+                additional_library_paths: Vec::new(),
                 delay: Duration::from_millis(10),
+                watch: true,
+                watch_retries: DEFAULT_WATCH_RETRIES,
+                memory_limit: None,
+                heap_size: None,
+                deterministic_gc: false,
+                gc_strategy: GcStrategy::default(),
                 user_functions: Default::default(),
+                reload_observer: None,
+                profiling: false,
             },
         }
     }
 
+    /// Adds a library that is loaded in addition to the entry point library.
+    pub fn add_library<P: Into<PathBuf>>(mut self, library_path: P) -> Self {
+        self.options
+            .additional_library_paths
+            .push(library_path.into());
+        self
+    }
+
     /// Sets the `delay`.
     pub fn set_delay(mut self, delay: Duration) -> Self {
         self.options.delay = delay;
         self
     }
 
+    /// Sets whether the loaded libraries are watched for changes.
+    pub fn set_watch(mut self, watch: bool) -> Self {
+        self.options.watch = watch;
+        self
+    }
+
+    /// Sets the number of times attaching the watcher to the directory of a library is retried.
+    pub fn set_watch_retries(mut self, retries: u32) -> Self {
+        self.options.watch_retries = retries;
+        self
+    }
+
+    /// Sets the maximum number of bytes that may be allocated by the garbage collector. An
+    /// allocation that would exceed the limit aborts the invocation, which then returns an error.
+    /// Garbage cannot be collected while Mun code runs, so it is collected after every invocation.
+    pub fn set_memory_limit(mut self, memory_limit: usize) -> Self {
+        self.options.memory_limit = Some(memory_limit);
+        self
+    }
+
+    /// Sets the number of bytes to preallocate for the heap of the garbage collector. This is
+    /// ignored, with a warning, if the garbage collector cannot preallocate its heap.
+    pub fn set_heap_size(mut self, heap_size: usize) -> Self {
+        self.options.heap_size = Some(heap_size);
+        self
+    }
+
+    /// Sets whether garbage is collected at fixed, predictable points.
+    pub fn set_deterministic_gc(mut self, deterministic_gc: bool) -> Self {
+        self.options.deterministic_gc = deterministic_gc;
+        self
+    }
+
+    /// Sets how memory that is no longer referenced is reclaimed. [`GcStrategy::None`] cannot be
+    /// combined with a memory limit, because no memory would ever be reclaimed to stay below it.
+    pub fn set_gc_strategy(mut self, gc_strategy: GcStrategy) -> Self {
+        self.options.gc_strategy = gc_strategy;
+        self
+    }
+
+    /// Sets a callback that is invoked for every event of the hot reloading system.
+    pub fn set_reload_observer<F: FnMut(&ReloadEvent) + 'static>(mut self, observer: F) -> Self {
+        self.options.reload_observer = Some(Box::new(observer));
+        self
+    }
+
+    /// Sets whether the number and duration of the invocations of every function are recorded, see
+    /// [`Runtime::call_profile`]. Without profiling, invocations are not timed at all.
+    pub fn set_profiling(mut self, profiling: bool) -> Self {
+        self.options.profiling = profiling;
+        self
+    }
+
     /// Adds a custom user function to the dispatch table.
     pub fn insert_fn<S: AsRef<str>, F: abi::IntoFunctionDefinition>(
         mut self,
@@ -164,10 +311,15 @@ impl DispatchTable {
 pub struct Runtime {
     assemblies: HashMap<PathBuf, Assembly>,
     dispatch_table: DispatchTable,
-    watcher: RecommendedWatcher,
+    watcher: Option<RecommendedWatcher>,
     watcher_rx: Receiver<DebouncedEvent>,
+    watch_retries: u32,
     gc: Arc<GarbageCollector>,
     _user_functions: Vec<abi::FunctionDefinitionStorage>,
+    reload_observer: Option<Box<dyn FnMut(&ReloadEvent)>>,
+    deterministic_gc: bool,
+    gc_strategy: GcStrategy,
+    profile: Option<RefCell<CallProfile>>,
 }
 
 /// Retrieve the allocator using the provided handle.
@@ -190,6 +342,19 @@ extern "C" fn new(
     let allocator = unsafe { get_allocator(alloc_handle) };
     // Safety: the Mun Compiler guarantees that `new` is never called with `ptr::null()`.
     let type_info = UnsafeTypeInfo::new(unsafe { NonNull::new_unchecked(type_info as *mut _) });
+    let size = memory::TypeMemory::layout(&type_info).size();
+    if allocator.would_exceed_memory_limit(size) {
+        let exceeded = MemoryLimitExceeded {
+            memory_limit: allocator.memory_limit().unwrap_or_default(),
+            allocated_memory: allocator.stats().allocated_memory + size,
+        };
+        // Prevent destruction of the allocator
+        mem::forget(allocator);
+
+        // Allocations cannot fail from the perspective of Mun code, so the invocation is aborted.
+        // The payload is caught by `invoke_fn`, which returns it as an error.
+        std::panic::resume_unwind(Box::new(exceeded));
+    }
     let handle = allocator.alloc(type_info);
 
     // Prevent destruction of the allocator
@@ -198,6 +363,60 @@ extern "C" fn new(
     handle.into()
 }
 
+/// The payload with which an allocation that would exceed the memory limit unwinds out of an
+/// invocation.
+#[derive(Debug)]
+pub(crate) struct MemoryLimitExceeded {
+    memory_limit: usize,
+    allocated_memory: usize,
+}
+
+impl fmt::Display for MemoryLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "exceeded the memory limit of {} bytes ({} bytes allocated)",
+            self.memory_limit, self.allocated_memory
+        )
+    }
+}
+
+/// The number of times attaching the watcher is retried if no other value is specified.
+pub const DEFAULT_WATCH_RETRIES: u32 = 3;
+
+/// The delay before the first retry of attaching the watcher, which doubles with every retry.
+const WATCH_RETRY_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Attaches the `watcher` to `directory`, retrying up to `retries` times with an exponential
+/// backoff. Attaching can fail intermittently, e.g. while an editor replaces files.
+fn watch_with_retries(
+    watcher: &mut RecommendedWatcher,
+    directory: &Path,
+    retries: u32,
+) -> Result<(), Error> {
+    let mut backoff = WATCH_RETRY_BACKOFF;
+    for _ in 0..retries {
+        if watcher
+            .watch(directory, RecursiveMode::NonRecursive)
+            .is_ok()
+        {
+            return Ok(());
+        }
+        std::thread::sleep(backoff);
+        backoff *= 2;
+    }
+    watcher
+        .watch(directory, RecursiveMode::NonRecursive)
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "could not watch '{}' for changes after {} attempt(s): {}",
+                directory.display(),
+                retries + 1,
+                e
+            )
+        })
+}
+
 impl Runtime {
     /// Constructs a new `Runtime` that loads the library at `library_path` and its
     /// dependencies. The `Runtime` contains a file watcher that is triggered with an interval
@@ -205,6 +424,12 @@ impl Runtime {
     pub fn new(mut options: RuntimeOptions) -> Result<Runtime, Error> {
         let (tx, rx) = channel();
 
+        if let (GcStrategy::None, Some(_)) = (options.gc_strategy, options.memory_limit) {
+            return Err(anyhow::anyhow!(
+                "a memory limit cannot be combined with the 'none' garbage collection strategy, which never reclaims memory"
+            ));
+        }
+
         let mut dispatch_table = DispatchTable::default();
 
         // Add internal functions
@@ -212,6 +437,14 @@ impl Runtime {
             new as extern "C" fn(*const abi::TypeInfo, *mut ffi::c_void) -> *const *mut ffi::c_void,
             "new",
         ));
+        options.user_functions.push(IntoFunctionDefinition::into(
+            profile::enter_call as extern "C" fn(*const u8),
+            "enter_call",
+        ));
+        options.user_functions.push(IntoFunctionDefinition::into(
+            profile::exit_call as extern "C" fn(*const u8),
+            "exit_call",
+        ));
 
         let mut storages = Vec::with_capacity(options.user_functions.len());
         for (info, storage) in options.user_functions.into_iter() {
@@ -219,21 +452,78 @@ impl Runtime {
             storages.push(storage)
         }
 
-        let watcher: RecommendedWatcher = Watcher::new(tx, options.delay)?;
+        // The mark-sweep collector allocates every object separately, so there is no arena to
+        // preallocate.
+        if let Some(heap_size) = options.heap_size {
+            log::warn!(
+                "the garbage collector does not support preallocating its heap, ignoring a heap size of {} bytes",
+                heap_size
+            );
+        }
+
+        let watcher: Option<RecommendedWatcher> = if options.watch {
+            Some(Watcher::new(tx, options.delay)?)
+        } else {
+            None
+        };
         let mut runtime = Runtime {
             assemblies: HashMap::new(),
             dispatch_table,
             watcher,
             watcher_rx: rx,
-            gc: Arc::new(self::garbage_collector::GarbageCollector::default()),
+            watch_retries: options.watch_retries,
+            gc: Arc::new(
+                self::garbage_collector::GarbageCollector::default()
+                    .with_memory_limit(options.memory_limit),
+            ),
             _user_functions: storages,
+            reload_observer: options.reload_observer,
+            deterministic_gc: options.deterministic_gc,
+            gc_strategy: options.gc_strategy,
+            profile: if options.profiling {
+                Some(RefCell::new(CallProfile::default()))
+            } else {
+                None
+            },
         };
 
-        runtime.add_assembly(&options.library_path)?;
+        // A library that is specified more than once is only loaded once
+        let mut pending: Vec<PathBuf> = Vec::new();
+        for library_path in
+            std::iter::once(options.library_path).chain(options.additional_library_paths)
+        {
+            let library_path = library_path.canonicalize()?;
+            if !pending.contains(&library_path) {
+                pending.push(library_path);
+            }
+        }
+
+        // A library can depend on functions of a library that is listed after it, so libraries
+        // that cannot be linked yet are retried until no more libraries can be loaded.
+        while !pending.is_empty() {
+            let mut failed = Vec::new();
+            let mut first_error = None;
+            for library_path in pending.iter() {
+                // The library may have been loaded as a dependency of another library
+                if runtime.assemblies.contains_key(library_path) {
+                    continue;
+                }
+                if let Err(e) = runtime.add_assembly(library_path) {
+                    first_error.get_or_insert(e);
+                    failed.push(library_path.clone());
+                }
+            }
+            if failed.len() == pending.len() {
+                return Err(first_error.expect("failed libraries must have an error"));
+            }
+            pending = failed;
+        }
         Ok(runtime)
     }
 
-    /// Adds an assembly corresponding to the library at `library_path`.
+    /// Adds an assembly corresponding to the library at `library_path`, together with the
+    /// dependencies that are not loaded yet. The assembly is only linked once nothing else can
+    /// fail, so adding a library that failed can be retried.
     fn add_assembly(&mut self, library_path: &Path) -> Result<(), Error> {
         let library_path = library_path.canonicalize()?;
         if self.assemblies.contains_key(&library_path) {
@@ -246,17 +536,43 @@ impl Runtime {
 
         let mut assembly = Assembly::load(&library_path, self.gc.clone(), &self.dispatch_table)?;
         for dependency in assembly.info().dependencies() {
-            self.add_assembly(Path::new(dependency))?;
+            let dependency = Path::new(dependency).canonicalize()?;
+            if !self.assemblies.contains_key(&dependency) {
+                self.add_assembly(&dependency)?;
+            }
+        }
+        for function in assembly.info().symbols.functions() {
+            let name = function.prototype.name();
+            if let Some(other) = self.assemblies.values().find(|other| {
+                other
+                    .info()
+                    .symbols
+                    .functions()
+                    .iter()
+                    .any(|f| f.prototype.name() == name)
+            }) {
+                return Err(anyhow::anyhow!(
+                    "function '{}' is exported by both '{}' and '{}'",
+                    name,
+                    other.library_path().display(),
+                    library_path.display()
+                ));
+            }
+        }
+        if let Some(watcher) = self.watcher.as_mut() {
+            watch_with_retries(watcher, library_path.parent().unwrap(), self.watch_retries)?;
         }
-        assembly.link(&mut self.dispatch_table);
-
-        self.watcher
-            .watch(library_path.parent().unwrap(), RecursiveMode::NonRecursive)?;
 
+        assembly.link(&mut self.dispatch_table);
         self.assemblies.insert(library_path, assembly);
         Ok(())
     }
 
+    /// Returns an iterator over all assemblies that are currently loaded.
+    pub fn assemblies(&self) -> impl Iterator<Item = &Assembly> {
+        self.assemblies.values()
+    }
+
     /// Retrieves the function definition corresponding to `function_name`, if available.
     pub fn get_function_definition(&self, function_name: &str) -> Option<&abi::FunctionDefinition> {
         self.dispatch_table.get_fn(function_name)
@@ -267,30 +583,70 @@ impl Runtime {
     pub fn update(&mut self) -> bool {
         while let Ok(event) = self.watcher_rx.try_recv() {
             use notify::DebouncedEvent::*;
-            match event {
-                Write(ref path) | Rename(_, ref path) | Create(ref path) => {
-                    if let Some(assembly) = self.assemblies.get_mut(path) {
-                        if let Err(e) = assembly.swap(path, &mut self.dispatch_table) {
-                            println!(
-                                "An error occured while reloading assembly '{}': {:?}",
-                                path.to_string_lossy(),
-                                e
-                            );
-                        } else {
-                            println!(
-                                "Succesfully reloaded assembly: '{}'",
-                                path.to_string_lossy()
-                            );
-                            return true;
-                        }
+            let (kind, path) = match event {
+                Write(ref path) => ("write", path),
+                Rename(_, ref path) => ("rename", path),
+                Create(ref path) => ("create", path),
+                _ => continue,
+            };
+            self.notify_reload_observer(&ReloadEvent::FileChanged { kind, path });
+
+            if let Some(assembly) = self.assemblies.get_mut(path) {
+                if let Err(e) = assembly.swap(path, &mut self.dispatch_table) {
+                    println!(
+                        "An error occured while reloading assembly '{}': {:?}",
+                        path.to_string_lossy(),
+                        e
+                    );
+                    self.notify_reload_observer(&ReloadEvent::ReloadFailed { path, error: &e });
+                } else {
+                    println!(
+                        "Succesfully reloaded assembly: '{}'",
+                        path.to_string_lossy()
+                    );
+                    self.notify_reload_observer(&ReloadEvent::Reloaded { path });
+                    if self.deterministic_gc {
+                        self.gc_collect();
                     }
+                    return true;
                 }
-                _ => {}
+            } else {
+                self.notify_reload_observer(&ReloadEvent::Ignored { path });
             }
         }
         false
     }
 
+    /// Invokes the reload observer, if any, with the specified `event`.
+    fn notify_reload_observer(&mut self, event: &ReloadEvent) {
+        if let Some(observer) = self.reload_observer.as_mut() {
+            observer(event);
+        }
+    }
+
+    /// Reloads all assemblies from disk, regardless of whether a change was detected.
+    pub fn reload(&mut self) -> Result<(), Error> {
+        for (path, assembly) in self.assemblies.iter_mut() {
+            let result = assembly.swap(path, &mut self.dispatch_table);
+            if let Some(observer) = self.reload_observer.as_mut() {
+                match &result {
+                    Ok(()) => observer(&ReloadEvent::Reloaded { path }),
+                    Err(error) => observer(&ReloadEvent::ReloadFailed { path, error }),
+                }
+            }
+            result?;
+        }
+        if self.deterministic_gc {
+            self.gc_collect();
+        }
+        Ok(())
+    }
+
+    /// Returns whether garbage is collected at fixed, predictable points.
+    pub fn is_gc_deterministic(&self) -> bool {
+        self.deterministic_gc
+    }
+
     /// Returns a shared reference to the runtime's garbage collector.
     ///
     /// We cannot return an `Arc` here, because the lifetime of data contained in `GarbageCollector`
@@ -299,10 +655,52 @@ impl Runtime {
         self.gc.as_ref()
     }
 
+    /// Returns how memory that is no longer referenced is reclaimed.
+    pub fn gc_strategy(&self) -> GcStrategy {
+        self.gc_strategy
+    }
+
+    /// Returns the functions that were invoked so far, or `None` if profiling is not enabled.
+    pub fn call_profile(&self) -> Option<CallProfile> {
+        self.profile
+            .as_ref()
+            .map(|profile| profile.borrow().clone())
+    }
+
+    /// Starts a profiled invocation if profiling is enabled.
+    fn profile_start(&self) -> Option<profile::ProfileScope> {
+        self.profile
+            .as_ref()
+            .map(|_| profile::ProfileScope::start())
+    }
+
+    /// Records an invocation of `function_name` that started with `scope`, if profiling is
+    /// enabled.
+    fn profile_end(&self, function_name: &str, scope: Option<profile::ProfileScope>) {
+        if let (Some(profile), Some(scope)) = (&self.profile, scope) {
+            scope.end(function_name, &mut profile.borrow_mut());
+        }
+    }
+
+    /// Collects garbage after an invocation if a memory limit is set, so memory that is no longer
+    /// referenced does not count against the limit of later invocations. Objects that are only
+    /// referenced by running Mun code are not rooted, so garbage cannot be collected while an
+    /// invocation is still running.
+    fn collect_for_memory_limit(&self) {
+        if self.gc.memory_limit().is_some() {
+            self.gc_collect();
+        }
+    }
+
     /// Collects all memory that is no longer referenced by rooted objects. Returns `true` if memory
     /// was reclaimed, `false` otherwise. This behavior will likely change in the future.
+    ///
+    /// With [`GcStrategy::None`] nothing is ever collected.
     pub fn gc_collect(&self) -> bool {
-        self.gc.collect()
+        match self.gc_strategy {
+            GcStrategy::MarkSweep => self.gc.collect(),
+            GcStrategy::None => false,
+        }
     }
 
     /// Returns statistics about the garbage collector.
@@ -311,6 +709,19 @@ impl Runtime {
     }
 }
 
+/// The information shared by the errors of all `invoke_fn` functions, which allows a failed
+/// invocation to be reported without knowing the types of its arguments.
+pub trait InvokeError: std::error::Error {
+    /// Returns the name of the function that could not be invoked.
+    fn function_name(&self) -> &str;
+
+    /// Returns why the function could not be invoked.
+    fn message(&self) -> &str;
+
+    /// Returns whether the invocation was aborted because it would have exceeded the memory limit.
+    fn exceeded_memory_limit(&self) -> bool;
+}
+
 /// Extends a result object with functions that allow retrying of an action.
 pub trait RetryResultExt: Sized {
     /// Output type on success
@@ -16,11 +16,14 @@ macro_rules! invoke_fn_impl {
             /// An invocation error that contains the function name, a mutable reference to the
             /// runtime, passed arguments, and the output type. This allows the caller to retry
             /// the function invocation using the `Retriable` trait.
+            ///
+            /// If the invocation was aborted because it would have exceeded the memory limit, the
+            /// arguments were already passed to the function, so the invocation cannot be retried.
             pub struct $ErrName<'s, $($T: ArgumentReflection,)* Output: ReturnTypeReflection> {
                 msg: String,
                 runtime: std::rc::Rc<core::cell::RefCell<Runtime>>,
                 function_name: &'s str,
-                $($Arg: $T,)*
+                args: Option<($($T,)*)>,
                 output: core::marker::PhantomData<Output>,
             }
 
@@ -42,6 +45,20 @@ macro_rules! invoke_fn_impl {
                 }
             }
 
+            impl<'s, $($T: ArgumentReflection,)* Output: ReturnTypeReflection> $crate::InvokeError for $ErrName<'s, $($T,)* Output> {
+                fn function_name(&self) -> &str {
+                    self.function_name
+                }
+
+                fn message(&self) -> &str {
+                    &self.msg
+                }
+
+                fn exceeded_memory_limit(&self) -> bool {
+                    self.args.is_none()
+                }
+            }
+
             impl<'s, $($T: ArgumentReflection,)* Output: ReturnTypeReflection> $ErrName<'s, $($T,)* Output> {
                 /// Constructs a new invocation error.
                 #[allow(clippy::too_many_arguments)]
@@ -50,7 +67,19 @@ macro_rules! invoke_fn_impl {
                         msg: err_msg,
                         runtime,
                         function_name,
-                        $($Arg,)*
+                        args: Some(($($Arg,)*)),
+                        output: core::marker::PhantomData,
+                    }
+                }
+
+                /// Constructs an error for an invocation that was aborted because it would have
+                /// exceeded the memory limit.
+                fn memory_limit_exceeded(err_msg: String, runtime: std::rc::Rc<core::cell::RefCell<Runtime>>, function_name: &'s str) -> Self {
+                    Self {
+                        msg: err_msg,
+                        runtime,
+                        function_name,
+                        args: None,
                         output: core::marker::PhantomData,
                     }
                 }
@@ -67,7 +96,10 @@ macro_rules! invoke_fn_impl {
                             while !err.runtime.borrow_mut().update() {
                                 // Wait until there has been an update that might fix the error
                             }
-                            $crate::Runtime::$FnName(&err.runtime, err.function_name, $(err.$Arg,)*)
+                            match err.args {
+                                Some(($($Arg,)*)) => $crate::Runtime::$FnName(&err.runtime, err.function_name, $($Arg,)*),
+                                None => Err(err),
+                            }
                         }
                     }
                 }
@@ -147,10 +179,28 @@ macro_rules! invoke_fn_impl {
                             let function: fn($($T::Marshalled),*) -> Output::Marshalled = unsafe {
                                 core::mem::transmute(function_info.fn_ptr)
                             };
-                            let result = function($($Arg.marshal()),*);
+                            let start = runtime_ref.profile_start();
+                            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                                function($($Arg.marshal()),*)
+                            }));
+                            runtime_ref.profile_end(function_name, start);
+
+                            // An allocation that would exceed the memory limit aborts the invocation
+                            let result = match result {
+                                Ok(result) => result,
+                                Err(payload) => match payload.downcast::<$crate::MemoryLimitExceeded>() {
+                                    Ok(exceeded) => {
+                                        runtime_ref.collect_for_memory_limit();
+                                        return Err($ErrName::memory_limit_exceeded(exceeded.to_string(), runtime.clone(), function_name));
+                                    }
+                                    Err(payload) => std::panic::resume_unwind(payload),
+                                },
+                            };
 
-                            // Marshall the result
-                            return Ok(result.marshal_value(runtime.clone()))
+                            // Marshall the result, which roots it before garbage is collected
+                            let result = result.marshal_value(runtime.clone());
+                            runtime_ref.collect_for_memory_limit();
+                            return Ok(result)
                         }
                         Err(e) => Err($ErrName::new(e, runtime.clone(), function_name, $($Arg),*))
                     }
@@ -0,0 +1,137 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::time::{Duration, Instant};
+
+/// The number of calls to a single function and the time spent in them.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FunctionProfile {
+    /// The number of times the function was invoked
+    pub calls: u64,
+    /// The cumulative duration of all invocations, including the functions they call
+    pub total: Duration,
+}
+
+/// The functions that were invoked through a [`Runtime`](crate::Runtime) with profiling enabled.
+///
+/// Invocations through the runtime, e.g. with `invoke_fn!`, are always recorded. Mun functions
+/// call each other directly through the dispatch table, so these calls are only recorded if the
+/// calling library was compiled with call instrumentation; otherwise they are included in the
+/// duration of the invoked function but are not counted themselves.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CallProfile {
+    functions: BTreeMap<String, FunctionProfile>,
+}
+
+impl CallProfile {
+    /// Returns the profile of every function that was invoked at least once, ordered by name.
+    pub fn functions(&self) -> &BTreeMap<String, FunctionProfile> {
+        &self.functions
+    }
+
+    /// Records an invocation of `function_name` that took `duration`.
+    pub(crate) fn record(&mut self, function_name: &str, duration: Duration) {
+        let profile = self.functions.entry(function_name.to_owned()).or_default();
+        profile.calls += 1;
+        profile.total += duration;
+    }
+
+    /// Adds the invocations recorded in `other` to this profile.
+    fn merge(&mut self, other: CallProfile) {
+        for (function_name, other) in other.functions {
+            let profile = self.functions.entry(function_name).or_default();
+            profile.calls += other.calls;
+            profile.total += other.total;
+        }
+    }
+}
+
+/// The calls between Mun functions that are recorded on a thread while a profiled invocation is
+/// in progress.
+#[derive(Default)]
+struct CallRecorder {
+    profile: CallProfile,
+    /// The start of every call that has not returned yet, innermost last
+    started: Vec<Instant>,
+}
+
+thread_local! {
+    static CALL_RECORDER: RefCell<Option<CallRecorder>> = RefCell::new(None);
+}
+
+/// A profiled invocation through the runtime, started with [`ProfileScope::start`]. If the scope is
+/// dropped without calling [`ProfileScope::end`], e.g. because the invocation panicked, the
+/// recorded calls are discarded.
+pub(crate) struct ProfileScope {
+    start: Instant,
+    /// The recorder of an enclosing invocation on the same thread, which is restored afterwards
+    outer_recorder: Option<CallRecorder>,
+    /// Whether the recorder of the enclosing invocation was restored already
+    restored: bool,
+}
+
+impl ProfileScope {
+    /// Starts a profiled invocation, recording the calls between Mun functions on this thread
+    /// until [`ProfileScope::end`].
+    pub(crate) fn start() -> Self {
+        let outer_recorder =
+            CALL_RECORDER.with(|recorder| recorder.replace(Some(CallRecorder::default())));
+        ProfileScope {
+            start: Instant::now(),
+            outer_recorder,
+            restored: false,
+        }
+    }
+
+    /// Ends the invocation of `function_name` and adds it to `profile`, together with the calls
+    /// between Mun functions that it made.
+    pub(crate) fn end(mut self, function_name: &str, profile: &mut CallProfile) {
+        let duration = self.start.elapsed();
+        if let Some(recorder) = self.restore() {
+            profile.merge(recorder.profile);
+        }
+        profile.record(function_name, duration);
+    }
+
+    /// Restores the recorder of the enclosing invocation and returns the recorder of this one.
+    fn restore(&mut self) -> Option<CallRecorder> {
+        self.restored = true;
+        let outer_recorder = self.outer_recorder.take();
+        CALL_RECORDER.with(|recorder| recorder.replace(outer_recorder))
+    }
+}
+
+impl Drop for ProfileScope {
+    fn drop(&mut self) {
+        if !self.restored {
+            self.restore();
+        }
+    }
+}
+
+/// Reports that the function with the null-terminated `name` is about to be called. Called by Mun
+/// code that was compiled with call instrumentation.
+pub(crate) extern "C" fn enter_call(_name: *const u8) {
+    CALL_RECORDER.with(|recorder| {
+        if let Some(recorder) = recorder.borrow_mut().as_mut() {
+            recorder.started.push(Instant::now());
+        }
+    })
+}
+
+/// Reports that the function with the null-terminated `name` returned. Called by Mun code that
+/// was compiled with call instrumentation.
+pub(crate) extern "C" fn exit_call(name: *const u8) {
+    CALL_RECORDER.with(|recorder| {
+        if let Some(recorder) = recorder.borrow_mut().as_mut() {
+            if let Some(start) = recorder.started.pop() {
+                // Safety: the Mun Compiler always passes a pointer to a null-terminated string
+                // constant of the calling library.
+                let name = unsafe { CStr::from_ptr(name as *const std::os::raw::c_char) };
+                recorder
+                    .profile
+                    .record(&name.to_string_lossy(), start.elapsed());
+            }
+        }
+    })
+}
@@ -1,4 +1,4 @@
-use mun_runtime::{invoke_fn, StructRef};
+use mun_runtime::{invoke_fn, GcStrategy, InvokeError, StructRef};
 
 #[macro_use]
 mod util;
@@ -40,6 +40,45 @@ fn gc_trace() {
     assert_eq!(driver.runtime_mut().borrow().gc_stats().allocated_memory, 0);
 }
 
+#[test]
+fn gc_strategy_none() {
+    let mut driver = TestDriver::new(
+        r#"
+    pub struct Foo {
+        bar: i64,
+    }
+
+    pub fn new_foo() -> Foo {
+        Foo { bar: 1 }
+    }
+    "#,
+    )
+    .set_gc_strategy(GcStrategy::None);
+    assert_eq!(
+        driver.runtime_mut().borrow().gc_strategy(),
+        GcStrategy::None
+    );
+
+    let value: StructRef = invoke_fn!(driver.runtime_mut(), "new_foo").unwrap();
+    drop(value);
+
+    // The unreferenced struct is leaked
+    assert_eq!(driver.runtime_mut().borrow().gc_collect(), false);
+    assert!(driver.runtime_mut().borrow().gc_stats().allocated_memory > 0);
+}
+
+#[test]
+fn gc_strategy_none_rejects_memory_limit() {
+    let mut driver = TestDriver::new(
+        r#"
+    pub fn main() {}
+    "#,
+    )
+    .set_gc_strategy(GcStrategy::None)
+    .set_memory_limit(1024);
+    assert!(driver.spawn().is_err());
+}
+
 #[test]
 fn map_struct_insert_field1() {
     let mut driver = TestDriver::new(
@@ -779,3 +818,98 @@ fn insert_struct() {
     let d = foo.get::<StructRef>("d").unwrap();
     assert_eq!(d.get::<f64>("0"), Ok(0.0));
 }
+
+#[test]
+fn memory_limit_collects_garbage() {
+    let mut driver = TestDriver::new(
+        r#"
+    pub struct Foo {
+        a: i64,
+        b: i64,
+    }
+
+    pub fn garbage() {
+        let a = Foo { a: 1, b: 2 };
+    }
+
+    pub fn new_foo() -> Foo {
+        Foo { a: 1, b: 2 }
+    }
+    "#,
+    )
+    .set_memory_limit(16);
+
+    // The unreferenced object is collected after every invocation, so it does not count against
+    // the limit of the next one
+    let _: () = invoke_fn!(driver.runtime_mut(), "garbage").unwrap();
+    assert_eq!(driver.runtime_mut().borrow().gc_stats().allocated_memory, 0);
+    let _: () = invoke_fn!(driver.runtime_mut(), "garbage").unwrap();
+
+    // The returned object is rooted, so it survives the collection
+    let value: StructRef = invoke_fn!(driver.runtime_mut(), "new_foo").unwrap();
+    assert_eq!(value.get::<i64>("b").unwrap(), 2);
+}
+
+#[test]
+fn memory_limit_exceeded() {
+    let mut driver = TestDriver::new(
+        r#"
+    pub struct Foo {
+        a: i64,
+        b: i64,
+    }
+
+    pub struct Pair {
+        first: Foo,
+        second: Foo,
+    }
+
+    pub fn new_pair() -> Pair {
+        Pair {
+            first: Foo { a: 1, b: 2 },
+            second: Foo { a: 3, b: 4 },
+        }
+    }
+    "#,
+    )
+    .set_memory_limit(16);
+
+    let result: Result<StructRef, _> = invoke_fn!(driver.runtime_mut(), "new_pair");
+    let error = match result {
+        Ok(_) => panic!("the invocation should exceed the memory limit"),
+        Err(error) => error,
+    };
+    assert!(error.exceeded_memory_limit());
+    assert_eq!(
+        error.message(),
+        "exceeded the memory limit of 16 bytes (32 bytes allocated)"
+    );
+
+    // The objects of the aborted invocation are garbage
+    assert_eq!(driver.runtime_mut().borrow().gc_stats().allocated_memory, 0);
+}
+
+#[test]
+fn memory_limit_stops_runaway_allocation() {
+    let mut driver = TestDriver::new(
+        r#"
+    pub struct Foo {
+        a: i64,
+        b: i64,
+    }
+
+    pub fn runaway() {
+        loop {
+            let foo = Foo { a: 1, b: 2 };
+        }
+    }
+    "#,
+    )
+    .set_memory_limit(1024);
+
+    let result: Result<(), _> = invoke_fn!(driver.runtime_mut(), "runaway");
+    match result {
+        Ok(()) => panic!("the invocation should exceed the memory limit"),
+        Err(error) => assert!(error.exceeded_memory_limit()),
+    }
+}
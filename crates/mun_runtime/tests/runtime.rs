@@ -1,5 +1,7 @@
 mod util;
 
+use mun_compiler::Config;
+use mun_runtime::invoke_fn;
 use std::io;
 use util::*;
 
@@ -49,3 +51,86 @@ fn arg_missing_bug() {
 
     driver.spawn().unwrap()
 }
+
+#[test]
+fn call_profile() {
+    let mut driver = TestDriver::new(
+        r"
+    pub fn helper(n: i64) -> i64 { n * 2 }
+    pub fn main() -> i64 { helper(21) }
+    ",
+    )
+    .set_profiling(true);
+
+    for n in 0..5i64 {
+        let result: i64 = invoke_fn!(driver.runtime_mut(), "helper", n).unwrap();
+        assert_eq!(result, n * 2);
+    }
+    let _: i64 = invoke_fn!(driver.runtime_mut(), "main").unwrap();
+
+    let profile = driver.runtime_mut().borrow().call_profile().unwrap();
+    assert_eq!(profile.functions()["helper"].calls, 5);
+    // Without instrumentation, the call of `helper` from Mun code is only part of the duration
+    // of `main`
+    assert_eq!(profile.functions()["main"].calls, 1);
+
+    let mut driver = TestDriver::with_config(
+        r"
+    pub fn helper(n: i64) -> i64 { n * 2 }
+    pub fn main() -> i64 { helper(helper(helper(1))) }
+    ",
+        Config {
+            instrument_calls: true,
+            ..Config::default()
+        },
+    )
+    .set_profiling(true);
+    let result: i64 = invoke_fn!(driver.runtime_mut(), "main").unwrap();
+    assert_eq!(result, 8);
+    let result: i64 = invoke_fn!(driver.runtime_mut(), "helper", 1i64).unwrap();
+    assert_eq!(result, 2);
+
+    let profile = driver.runtime_mut().borrow().call_profile().unwrap();
+    assert_eq!(profile.functions()["helper"].calls, 4);
+    assert_eq!(profile.functions()["main"].calls, 1);
+
+    let mut driver = TestDriver::new("pub fn main() {}");
+    assert!(driver.runtime_mut().borrow().call_profile().is_none());
+}
+
+#[test]
+fn call_profile_after_panic() {
+    extern "C" fn check(n: i64) -> i64 {
+        if n == 0 {
+            panic!("check failed");
+        }
+        n
+    }
+
+    let mut driver = TestDriver::with_config(
+        r"
+    extern fn check(n: i64) -> i64;
+    pub fn helper(n: i64) -> i64 { check(n) * 2 }
+    pub fn main(n: i64) -> i64 { helper(n) }
+    ",
+        Config {
+            instrument_calls: true,
+            ..Config::default()
+        },
+    )
+    .insert_fn("check", check as extern "C" fn(i64) -> i64)
+    .set_profiling(true);
+
+    let runtime = driver.runtime_mut().clone();
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _: i64 = invoke_fn!(runtime, "main", 0i64).unwrap();
+    }))
+    .is_err());
+
+    // The calls that were in progress when the invocation panicked are not recorded by later
+    // invocations
+    let result: i64 = invoke_fn!(driver.runtime_mut(), "main", 1i64).unwrap();
+    assert_eq!(result, 2);
+    let profile = driver.runtime_mut().borrow().call_profile().unwrap();
+    assert_eq!(profile.functions()["helper"].calls, 1);
+}
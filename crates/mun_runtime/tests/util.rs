@@ -1,7 +1,7 @@
 #![allow(dead_code, unused_macros)]
 
 use mun_compiler::{Config, DisplayColor, Driver, FileId, PathOrInline, RelativePathBuf};
-use mun_runtime::{IntoFunctionDefinition, Runtime, RuntimeBuilder};
+use mun_runtime::{GcStrategy, IntoFunctionDefinition, Runtime, RuntimeBuilder};
 use std::io::Cursor;
 use std::{cell::RefCell, path::PathBuf, rc::Rc, thread::sleep, time::Duration};
 
@@ -37,11 +37,17 @@ impl RuntimeOrBuilder {
 impl TestDriver {
     /// Construct a new TestDriver from a single Mun source
     pub fn new(text: &str) -> Self {
+        Self::with_config(text, Config::default())
+    }
+
+    /// Construct a new TestDriver from a single Mun source that is compiled with `config`. The
+    /// output directory and colors of `config` are overridden.
+    pub fn with_config(text: &str, config: Config) -> Self {
         let temp_dir = tempfile::TempDir::new().unwrap();
         let config = Config {
             out_dir: Some(temp_dir.path().to_path_buf()),
             display_color: DisplayColor::Disable,
-            ..Config::default()
+            ..config
         };
         let input = PathOrInline::Inline {
             rel_path: RelativePathBuf::from("main.mun"),
@@ -120,6 +126,39 @@ impl TestDriver {
         self
     }
 
+    /// Sets how the runtime reclaims memory.
+    pub fn set_gc_strategy(mut self, gc_strategy: GcStrategy) -> Self {
+        self.runtime = match self.runtime {
+            RuntimeOrBuilder::Builder(builder) => {
+                RuntimeOrBuilder::Builder(builder.set_gc_strategy(gc_strategy))
+            }
+            _ => unreachable!(),
+        };
+        self
+    }
+
+    /// Sets the maximum number of bytes that the garbage collector may allocate.
+    pub fn set_memory_limit(mut self, memory_limit: usize) -> Self {
+        self.runtime = match self.runtime {
+            RuntimeOrBuilder::Builder(builder) => {
+                RuntimeOrBuilder::Builder(builder.set_memory_limit(memory_limit))
+            }
+            _ => unreachable!(),
+        };
+        self
+    }
+
+    /// Sets whether the runtime records the invocations of every function.
+    pub fn set_profiling(mut self, profiling: bool) -> Self {
+        self.runtime = match self.runtime {
+            RuntimeOrBuilder::Builder(builder) => {
+                RuntimeOrBuilder::Builder(builder.set_profiling(profiling))
+            }
+            _ => unreachable!(),
+        };
+        self
+    }
+
     /// Returns the `Runtime` used by this instance
     pub fn runtime_mut(&mut self) -> &mut Rc<RefCell<Runtime>> {
         self.runtime.spawn().unwrap();
@@ -139,8 +139,17 @@ pub unsafe extern "C" fn mun_runtime_create(
 
     let runtime_options = runtime::RuntimeOptions {
         library_path: library_path.into(),
+        additional_library_paths: Vec::new(),
         delay: Duration::from_millis(delay_ms.into()),
+        watch: true,
+        watch_retries: runtime::DEFAULT_WATCH_RETRIES,
+        memory_limit: None,
+        heap_size: None,
+        deterministic_gc: false,
+        gc_strategy: runtime::GcStrategy::default(),
         user_functions,
+        reload_observer: None,
+        profiling: false,
     };
 
     let runtime = match Runtime::new(runtime_options) {
@@ -9,6 +9,7 @@ pub enum LinkerFlavor {
     Ld,
     Ld64,
     Msvc,
+    WasmLd,
 }
 
 /// Everything Mun knows about a target.
@@ -67,6 +68,9 @@ pub struct TargetOptions {
 
     /// Whether the target toolchain is like Windows
     pub is_like_windows: bool,
+
+    /// Whether the target produces WebAssembly modules, which cannot be loaded by the runtime
+    pub is_like_wasm: bool,
 }
 
 impl Default for TargetOptions {
@@ -77,6 +81,7 @@ impl Default for TargetOptions {
             features: "".to_string(),
             dll_prefix: "lib".to_string(),
             is_like_windows: false,
+            is_like_wasm: false,
         }
     }
 }
@@ -129,6 +134,7 @@ supported_targets!(
     ("x86_64-apple-darwin", x86_64_apple_darwin),
     ("x86_64-pc-windows-msvc", x86_64_pc_windows_msvc),
     ("x86_64-unknown-linux-gnu", x86_64_unknown_linux_gnu),
+    ("wasm32-unknown-unknown", wasm32_unknown_unknown),
 );
 
 impl Target {